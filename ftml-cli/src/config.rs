@@ -0,0 +1,325 @@
+/*
+ * config.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use ftml::data::PageInfo;
+use sloggers::types::Severity;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+const DEFAULT_FORMAT: &str = "html";
+const DEFAULT_LOG_LEVEL: &str = "error";
+const DEFAULT_TEST_DIRECTORY: &str = "test";
+
+/// Which stage or rendering of the wikitext to output.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Rendered HTML.
+    Html,
+
+    /// The syntax tree, as JSON.
+    Json,
+
+    /// The page converted to Markdown.
+    Markdown,
+
+    /// The textual content, without formatting.
+    Text,
+
+    /// The tokens produced by the tokenizer.
+    Tokens,
+
+    /// The syntax tree, as an indented outline.
+    Tree,
+
+    /// The wikitext after preprocessing.
+    Wikitext,
+}
+
+impl OutputFormat {
+    const NAMES: [(&'static str, OutputFormat); 7] = [
+        ("html", OutputFormat::Html),
+        ("json", OutputFormat::Json),
+        ("markdown", OutputFormat::Markdown),
+        ("text", OutputFormat::Text),
+        ("tokens", OutputFormat::Tokens),
+        ("tree", OutputFormat::Tree),
+        ("wikitext", OutputFormat::Wikitext),
+    ];
+
+    fn parse(value: &str) -> Option<Self> {
+        for (name, format) in &Self::NAMES {
+            if name.eq_ignore_ascii_case(value) {
+                return Some(*format);
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub log_level: Severity,
+    pub command: Command,
+}
+
+/// The operation the executable was invoked to perform.
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Render wikitext from the input to stdout.
+    Render(RenderConfig),
+
+    /// Run the JSON test corpus.
+    Test(TestConfig),
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderConfig {
+    pub input: Option<PathBuf>,
+    pub format: OutputFormat,
+    pub page_info: PageInfo<'static>,
+    pub deterministic: bool,
+
+    /// A directory of pages to serve includes and links from.
+    pub pages: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TestConfig {
+    /// Whether to overwrite the expected output of failing tests.
+    pub bless: bool,
+
+    /// The directory containing the JSON test files.
+    pub directory: PathBuf,
+
+    /// Only run tests whose names contain this string.
+    pub filter: Option<String>,
+
+    /// Whether to color the output diffs.
+    pub color: bool,
+}
+
+impl Config {
+    #[cold]
+    pub fn parse_args() -> Self {
+        let format_names: Vec<&str> =
+            OutputFormat::NAMES.iter().map(|(name, _)| *name).collect();
+
+        let matches = App::new("ftml")
+            .version(env!("CARGO_PKG_VERSION"))
+            .author("Wikijump Team")
+            .about("Parse and render Wikidot text, reading from stdin and writing to stdout.")
+            .max_term_width(110)
+            .arg(
+                Arg::with_name("format")
+                    .short("f")
+                    .long("format")
+                    .value_name("FORMAT")
+                    .possible_values(&format_names)
+                    .default_value(DEFAULT_FORMAT)
+                    .help("The output format to write to stdout."),
+            )
+            .arg(
+                Arg::with_name("page_info")
+                    .short("p")
+                    .long("page-info")
+                    .value_name("JSON")
+                    .conflicts_with("page_info_file")
+                    .help("Information about the page being rendered, as a JSON object."),
+            )
+            .arg(
+                Arg::with_name("page_info_file")
+                    .short("P")
+                    .long("page-info-file")
+                    .value_name("FILE")
+                    .help("A file containing page information, as a JSON object."),
+            )
+            .arg(
+                Arg::with_name("deterministic")
+                    .long("deterministic")
+                    .help("Produce reproducible HTML, with attributes sorted by name."),
+            )
+            .arg(
+                Arg::with_name("pages")
+                    .long("pages")
+                    .value_name("DIRECTORY")
+                    .help("A directory of pages, such as 'component/acs.ftml', to include and link to."),
+            )
+            .arg(
+                Arg::with_name("log_level")
+                    .short("L")
+                    .long("log-level")
+                    .value_name("LEVEL")
+                    .default_value(DEFAULT_LOG_LEVEL)
+                    .help("Log level for messages written to stderr."),
+            )
+            .arg(
+                Arg::with_name("input")
+                    .value_name("FILE")
+                    .help("The file to read wikitext from. If absent or '-', stdin is used."),
+            )
+            .subcommand(
+                SubCommand::with_name("test")
+                    .about("Manage the JSON test corpus.")
+                    .setting(AppSettings::SubcommandRequiredElseHelp)
+                    .subcommand(test_arguments(
+                        SubCommand::with_name("run")
+                            .about("Run the tests, showing the differences for any which fail."),
+                    ))
+                    .subcommand(test_arguments(
+                        SubCommand::with_name("bless")
+                            .about("Run the tests, overwriting the expected output of any which fail."),
+                    )),
+            )
+            .get_matches();
+
+        let log_level = {
+            let value = matches
+                .value_of("log_level")
+                .expect("No log level argument set");
+
+            get_log_level(value).expect("Invalid log level value")
+        };
+
+        let command = match matches.subcommand() {
+            ("test", Some(matches)) => Command::Test(parse_test_args(matches)),
+            _ => Command::Render(parse_render_args(&matches)),
+        };
+
+        Config { log_level, command }
+    }
+}
+
+fn test_arguments<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+    app.arg(
+        Arg::with_name("directory")
+            .short("d")
+            .long("directory")
+            .value_name("DIR")
+            .default_value(DEFAULT_TEST_DIRECTORY)
+            .help("The directory containing the JSON test files."),
+    )
+    .arg(
+        Arg::with_name("no_color")
+            .long("no-color")
+            .help("Don't color the output."),
+    )
+    .arg(
+        Arg::with_name("filter")
+            .value_name("FILTER")
+            .help("Only run tests whose names contain this string."),
+    )
+}
+
+fn parse_test_args(matches: &ArgMatches) -> TestConfig {
+    let (bless, matches) = match matches.subcommand() {
+        ("run", Some(matches)) => (false, matches),
+        ("bless", Some(matches)) => (true, matches),
+        _ => unreachable!("Test subcommand is required"),
+    };
+
+    let directory = matches
+        .value_of_os("directory")
+        .expect("No directory argument set")
+        .into();
+
+    let filter = matches.value_of("filter").map(String::from);
+    let color = !matches.is_present("no_color");
+
+    TestConfig {
+        bless,
+        directory,
+        filter,
+        color,
+    }
+}
+
+fn parse_render_args(matches: &ArgMatches) -> RenderConfig {
+    let input = match matches.value_of_os("input") {
+        Some(path) if path != "-" => Some(PathBuf::from(path)),
+        _ => None,
+    };
+
+    let format = {
+        let value = matches.value_of("format").expect("No format argument set");
+
+        OutputFormat::parse(value).expect("Invalid format value")
+    };
+
+    let page_info = {
+        let json = match (
+            matches.value_of("page_info"),
+            matches.value_of_os("page_info_file"),
+        ) {
+            (Some(json), _) => Some(json.to_string()),
+            (None, Some(path)) => match fs::read_to_string(path) {
+                Ok(json) => Some(json),
+                Err(error) => {
+                    eprintln!("Unable to read page info file: {}", error);
+                    process::exit(1);
+                }
+            },
+            (None, None) => None,
+        };
+
+        match json {
+            Some(json) => match serde_json::from_str(&json) {
+                Ok(page_info) => page_info,
+                Err(error) => {
+                    eprintln!("Invalid page info JSON: {}", error);
+                    process::exit(1);
+                }
+            },
+            None => PageInfo::default(),
+        }
+    };
+
+    let deterministic = matches.is_present("deterministic");
+    let pages = matches.value_of_os("pages").map(PathBuf::from);
+
+    RenderConfig {
+        input,
+        format,
+        page_info,
+        deterministic,
+        pages,
+    }
+}
+
+fn get_log_level(value: &str) -> Option<Severity> {
+    const LOG_LEVELS: [(&str, Severity); 6] = [
+        ("trace", Severity::Trace),
+        ("debug", Severity::Debug),
+        ("info", Severity::Info),
+        ("warning", Severity::Warning),
+        ("error", Severity::Error),
+        ("critical", Severity::Critical),
+    ];
+
+    for (name, level) in &LOG_LEVELS {
+        if name.eq_ignore_ascii_case(value) {
+            return Some(*level);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,185 @@
+/*
+ * corpus.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Runs the JSON test corpus, and optionally updates its expected output.
+//!
+//! Each test case is run with the library's own harness (see `ftml::testing`).
+//! Files which don't match are reported with a diff, or rewritten if blessing.
+//! Rendered HTML which fails the tag balance check can't be blessed away.
+
+use crate::config::TestConfig;
+use crate::diff::diff;
+use ftml::testing::TestCase;
+use serde::Serialize;
+use serde_json::ser::{PrettyFormatter, Serializer};
+use std::fs::{self, File};
+use std::io;
+use std::path::PathBuf;
+
+/// The outcome of running the corpus.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub passed: usize,
+    pub failed: usize,
+    pub blessed: usize,
+}
+
+impl Summary {
+    #[inline]
+    pub fn success(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+pub fn run(log: &slog::Logger, config: &TestConfig) -> io::Result<Summary> {
+    info!(
+        log,
+        "Running test corpus";
+        "directory" => config.directory.display().to_string(),
+        "bless" => config.bless,
+    );
+
+    let mut summary = Summary::default();
+
+    for (name, path) in find_tests(config)? {
+        let file = File::open(&path)?;
+        let expected: TestCase = match serde_json::from_reader(file) {
+            Ok(test) => test,
+            Err(error) => {
+                println!("! {} [INVALID]: {}", name, error);
+                summary.failed += 1;
+                continue;
+            }
+        };
+
+        let actual = expected.run(log);
+
+        if let Err(error) = actual.check_html() {
+            println!("- {} [FAILED]: {}", name, error);
+            summary.failed += 1;
+            continue;
+        }
+
+        if actual.tree == expected.tree && actual.warnings == expected.warnings {
+            println!("+ {}", name);
+            summary.passed += 1;
+            continue;
+        }
+
+        let actual_json = to_json(&actual);
+
+        if config.bless {
+            println!("* {} [BLESSED]", name);
+            fs::write(&path, actual_json)?;
+            summary.blessed += 1;
+        } else {
+            println!("- {} [FAILED]", name);
+            print!("{}", diff(&to_json(&expected), &actual_json, config.color));
+            summary.failed += 1;
+        }
+    }
+
+    println!();
+    println!(
+        "{} passed, {} failed, {} blessed",
+        summary.passed, summary.failed, summary.blessed,
+    );
+
+    Ok(summary)
+}
+
+/// Gets the name and path of each JSON file in the test directory, sorted by name.
+fn find_tests(config: &TestConfig) -> io::Result<Vec<(String, PathBuf)>> {
+    let mut tests = Vec::new();
+
+    for entry in fs::read_dir(&config.directory)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if !entry.file_type()?.is_file() || path.extension() != Some("json".as_ref()) {
+            continue;
+        }
+
+        let name = match path.file_stem() {
+            Some(stem) => stem.to_string_lossy().into_owned(),
+            None => continue,
+        };
+
+        if let Some(ref filter) = config.filter {
+            if !name.contains(filter.as_str()) {
+                continue;
+            }
+        }
+
+        tests.push((name, path));
+    }
+
+    tests.sort();
+    Ok(tests)
+}
+
+/// Serializes the test in the same format as the files in the corpus.
+///
+/// This is serde_json's pretty output with four-space indentation,
+/// except that empty arrays are split across two lines, and spans
+/// are kept on one line.
+fn to_json(test: &TestCase) -> String {
+    let mut buffer = Vec::new();
+    let formatter = PrettyFormatter::with_indent(b"    ");
+    let mut serializer = Serializer::with_formatter(&mut buffer, formatter);
+
+    test.serialize(&mut serializer)
+        .expect("Unable to serialize test JSON");
+
+    let json = String::from_utf8(buffer).expect("Serialized JSON is not UTF-8");
+    let lines: Vec<&str> = json.lines().collect();
+    let mut output = String::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let indent = &line[..line.len() - line.trim_start().len()];
+
+        if let Some(prefix) = line.strip_suffix("[]").or_else(|| line.strip_suffix("[],"))
+        {
+            let comma = if line.ends_with(',') { "," } else { "" };
+
+            output.push_str(prefix);
+            output.push_str("[\n");
+            output.push_str(indent);
+            output.push(']');
+            output.push_str(comma);
+        } else if line.trim_start() == "\"span\": [" && i + 3 < lines.len() {
+            output.push_str(line);
+            output.push_str(lines[i + 1].trim());
+            output.push(' ');
+            output.push_str(lines[i + 2].trim());
+            output.push_str(lines[i + 3].trim());
+            i += 3;
+        } else {
+            output.push_str(line);
+        }
+
+        output.push('\n');
+        i += 1;
+    }
+
+    output
+}
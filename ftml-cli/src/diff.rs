@@ -0,0 +1,152 @@
+/*
+ * diff.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Line-based diffs, for displaying test failures.
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Produces a unified-style diff between the two strings.
+///
+/// Lines only in `expected` are prefixed with `-`, and lines only
+/// in `actual` are prefixed with `+`. Unchanged lines are only included
+/// if they are within `CONTEXT` lines of a change.
+pub fn diff(expected: &str, actual: &str, color: bool) -> String {
+    const CONTEXT: usize = 3;
+
+    let lines = diff_lines(expected, actual);
+    let mut output = String::new();
+    let mut skipped = false;
+
+    for (i, line) in lines.iter().enumerate() {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(lines.len());
+        let near_change = lines[start..end]
+            .iter()
+            .any(|line| !matches!(line, Line::Same(_)));
+
+        if !near_change {
+            skipped = true;
+            continue;
+        }
+
+        if skipped {
+            output.push_str("...\n");
+            skipped = false;
+        }
+
+        let (prefix, text, ansi) = match line {
+            Line::Same(text) => (' ', text, None),
+            Line::Removed(text) => ('-', text, Some(RED)),
+            Line::Added(text) => ('+', text, Some(GREEN)),
+        };
+
+        match ansi {
+            Some(ansi) if color => {
+                output.push_str(ansi);
+                output.push(prefix);
+                output.push_str(text);
+                output.push_str(RESET);
+            }
+            _ => {
+                output.push(prefix);
+                output.push_str(text);
+            }
+        }
+
+        output.push('\n');
+    }
+
+    if skipped {
+        output.push_str("...\n");
+    }
+
+    output
+}
+
+/// Computes the line edits between the two strings, via their longest common subsequence.
+fn diff_lines<'a>(expected: &'a str, actual: &'a str) -> Vec<Line<'a>> {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // lengths[i][j] is the LCS length of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(Line::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            lines.push(Line::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(Line::Added(new[j]));
+            j += 1;
+        }
+    }
+
+    lines.extend(old[i..].iter().map(|line| Line::Removed(line)));
+    lines.extend(new[j..].iter().map(|line| Line::Added(line)));
+    lines
+}
+
+#[test]
+fn lines() {
+    let lines = diff_lines("a\nb\nc\nd", "a\nc\nd\ne");
+
+    assert_eq!(
+        lines,
+        vec![
+            Line::Same("a"),
+            Line::Removed("b"),
+            Line::Same("c"),
+            Line::Same("d"),
+            Line::Added("e"),
+        ],
+        "Diff lines don't match expected",
+    );
+
+    assert_eq!(
+        diff("a\nb", "a\nc", false),
+        " a\n-b\n+c\n",
+        "Diff output doesn't match expected",
+    );
+}
@@ -1,5 +1,5 @@
 /*
- * parsing/boolean.rs
+ * logger.rs
  *
  * ftml - Library to parse Wikidot text
  * Copyright (C) 2019-2021 Wikijump Team
@@ -18,25 +18,17 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-/// Parse a boolean string into its corresponding value.
-pub fn parse_boolean<S: AsRef<str>>(s: S) -> Result<bool, ()> {
-    const NAMES: [(&str, bool); 8] = [
-        ("true", true),
-        ("false", false),
-        ("t", true),
-        ("f", false),
-        ("1", true),
-        ("0", false),
-        ("yes", true),
-        ("no", false),
-    ];
+use sloggers::terminal::{Destination, TerminalLoggerBuilder};
+use sloggers::types::Severity;
+use sloggers::Build;
 
-    let s = s.as_ref().trim();
-    for &(name, value) in &NAMES {
-        if name.eq_ignore_ascii_case(s) {
-            return Ok(value);
-        }
-    }
+/// Builds a logger which writes to stderr, so as not to interfere with the output.
+pub fn build(log_level: Severity) -> slog::Logger {
+    let log = TerminalLoggerBuilder::new()
+        .level(log_level)
+        .destination(Destination::Stderr)
+        .build()
+        .expect("Unable to initialize logger");
 
-    Err(())
+    log.new(o!("env" => "cli"))
 }
@@ -0,0 +1,134 @@
+/*
+ * main.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![deny(missing_debug_implementations)]
+#![forbid(unsafe_code)]
+
+//! Command-line tool to preprocess, parse, and render Wikidot text.
+//!
+//! This is a wrapper around the `ftml` crate, which reads wikitext from
+//! standard input (or a file), and writes the output in the requested
+//! format to standard output, so it can be used in shell pipelines.
+//!
+//! It also has a `test` subcommand, which runs the JSON test corpus and
+//! can update its expected output after an intentional change.
+
+extern crate clap;
+extern crate ftml;
+extern crate serde;
+extern crate serde_json;
+
+#[macro_use]
+extern crate slog;
+extern crate sloggers;
+
+mod config;
+mod corpus;
+mod diff;
+mod logger;
+mod pipeline;
+
+use self::config::{Command, Config, RenderConfig};
+use ftml::includes::{FileSystemHandle, IncludeSettings};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+
+fn main() {
+    let config = Config::parse_args();
+    let log = logger::build(config.log_level);
+
+    match config.command {
+        Command::Render(ref config) => render(&log, config),
+        Command::Test(ref config) => match corpus::run(&log, config) {
+            Ok(summary) if summary.success() => (),
+            Ok(_) => process::exit(1),
+            Err(error) => {
+                eprintln!("Unable to run tests: {}", error);
+                process::exit(1);
+            }
+        },
+    }
+}
+
+fn render(log: &slog::Logger, config: &RenderConfig) {
+    // Read the input wikitext
+    let input = match config.input {
+        Some(ref path) => fs::read_to_string(path),
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map(|_| input)
+        }
+    };
+
+    let input = match input {
+        Ok(input) => input,
+        Err(error) => {
+            eprintln!("Unable to read input: {}", error);
+            process::exit(1);
+        }
+    };
+
+    // Substitute includes, if there are pages to include
+    let input = match config.pages {
+        Some(ref directory) => {
+            let handle = FileSystemHandle::new(directory);
+            let settings = IncludeSettings::default();
+            let invalid =
+                || io::Error::new(io::ErrorKind::InvalidData, "invalid include");
+
+            match ftml::include(log, &input, &settings, handle, invalid) {
+                Ok((output, _)) => output,
+                Err(error) => {
+                    eprintln!("Unable to include pages: {}", error);
+                    process::exit(1);
+                }
+            }
+        }
+        None => input,
+    };
+
+    // Produce the output
+    let output = pipeline::run(log, config, input);
+
+    for warning in &output.parse_warnings {
+        let span = warning.span();
+
+        eprintln!(
+            "{}: {} at {}..{} (rule {}, token {})",
+            warning.severity().name().to_ascii_lowercase(),
+            warning.kind().name(),
+            span.start,
+            span.end,
+            warning.rule(),
+            warning.token().name(),
+        );
+    }
+
+    for warning in &output.render_warnings {
+        eprintln!("render: {} ({})", warning.kind().name(), warning.value());
+    }
+
+    let mut stdout = io::stdout();
+    if let Err(error) = stdout.write_all(output.output.as_bytes()) {
+        eprintln!("Unable to write output: {}", error);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,124 @@
+/*
+ * pipeline.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::config::{OutputFormat, RenderConfig};
+use ftml::includes::FileSystemHandle;
+use ftml::parsing::ParseWarning;
+use ftml::render::html::{Handle, HtmlOutput, HtmlRender};
+use ftml::render::json::JsonRender;
+use ftml::render::markdown::MarkdownRender;
+use ftml::render::text::TextRender;
+use ftml::render::tokens::render_tokens;
+use ftml::render::{Render, RenderWarning};
+use std::sync::Arc;
+
+/// The result of running the pipeline.
+#[derive(Debug)]
+pub struct PipelineOutput {
+    pub output: String,
+    pub parse_warnings: Vec<ParseWarning>,
+    pub render_warnings: Vec<RenderWarning>,
+}
+
+impl PipelineOutput {
+    #[inline]
+    fn new(output: String) -> Self {
+        PipelineOutput {
+            output,
+            parse_warnings: Vec::new(),
+            render_warnings: Vec::new(),
+        }
+    }
+}
+
+/// Runs the wikitext through ftml, as far as is needed for the output format.
+///
+/// Returns the output, and any warnings produced while parsing or rendering.
+pub fn run(
+    log: &slog::Logger,
+    config: &RenderConfig,
+    mut text: String,
+) -> PipelineOutput {
+    info!(log, "Running pipeline"; "format" => format!("{:?}", config.format));
+
+    ftml::preprocess(log, &mut text);
+
+    if config.format == OutputFormat::Wikitext {
+        return PipelineOutput::new(text);
+    }
+
+    let tokenization = ftml::tokenize(log, &text);
+
+    if config.format == OutputFormat::Tokens {
+        return PipelineOutput::new(render_tokens(&tokenization));
+    }
+
+    let (tree, parse_warnings) = ftml::parse(log, &tokenization).into();
+    let mut render_warnings = Vec::new();
+
+    let mut output = match config.format {
+        OutputFormat::Html => {
+            let handle = config.pages.as_ref().map(|directory| {
+                Arc::new(FileSystemHandle::new(directory))
+                    as Arc<dyn Handle + Send + Sync>
+            });
+
+            let renderer = HtmlRender {
+                page_info: config.page_info.clone(),
+                deterministic: config.deterministic,
+                handle,
+                log: Some(slog::Logger::clone(log)),
+                ..HtmlRender::default()
+            };
+
+            let HtmlOutput {
+                html,
+                style,
+                warnings,
+                ..
+            } = renderer.render(&tree);
+
+            render_warnings = warnings;
+
+            if style.is_empty() {
+                html
+            } else {
+                format!("<style>\n{}\n</style>\n{}", style, html)
+            }
+        }
+        OutputFormat::Json => JsonRender::pretty().render(&tree),
+        OutputFormat::Markdown => MarkdownRender.render(&tree),
+        OutputFormat::Text => TextRender.render(&tree),
+        OutputFormat::Tree => tree.to_string(),
+        OutputFormat::Tokens | OutputFormat::Wikitext => {
+            unreachable!("Format should have returned earlier")
+        }
+    };
+
+    if !output.ends_with('\n') {
+        output.push('\n');
+    }
+
+    PipelineOutput {
+        output,
+        parse_warnings,
+        render_warnings,
+    }
+}
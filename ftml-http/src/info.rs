@@ -31,6 +31,25 @@ pub use self::build::{
     GIT_COMMIT_HASH, PKG_LICENSE, PKG_NAME, PKG_REPOSITORY, PKG_VERSION, RUSTC_VERSION,
 };
 
+/// The version of the request and response formats served.
+///
+/// This is incremented whenever an existing route changes incompatibly,
+/// so that clients can refuse to talk to a server they don't understand.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// All the routes served, as listed in `ServerRoutes.md`.
+pub const ROUTES: [&str; 9] = [
+    "/ping",
+    "/version",
+    "/capabilities",
+    "/include",
+    "/preprocess",
+    "/tokenize",
+    "/parse",
+    "/render/html",
+    "/render/debug",
+];
+
 lazy_static! {
     pub static ref VERSION: String = {
         format!(
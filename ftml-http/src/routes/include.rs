@@ -46,7 +46,9 @@ pub fn run_include(
     let includer = HttpIncluder::new(&callback_url, &missing_include_template)?;
     let make_err = || Error::InvalidResponse;
 
-    match ftml::include(log, &text, includer, make_err) {
+    let settings = IncludeSettings::default();
+
+    match ftml::include(log, &text, &settings, includer, make_err) {
         Ok((output, pages)) => {
             info!(
                 log,
@@ -21,10 +21,29 @@
 use super::prelude::*;
 use crate::info;
 
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+struct Capabilities {
+    version: &'static str,
+    protocol_version: u32,
+    syntax_tree_version: u32,
+    routes: &'static [&'static str],
+    features: Vec<&'static str>,
+}
+
 pub fn route_misc() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     let ping = warp::path("ping").map(|| "Pong!");
     let version = warp::path("version").map(|| &**info::VERSION);
     let wikidot = warp::path("wikidot").map(|| ";-)");
+    let capabilities = warp::path("capabilities").map(|| {
+        warp::reply::json(&Capabilities {
+            version: &info::VERSION,
+            protocol_version: info::PROTOCOL_VERSION,
+            syntax_tree_version: ftml::info::SYNTAX_TREE_VERSION,
+            routes: &info::ROUTES,
+            features: ftml::info::features(),
+        })
+    });
 
-    ping.or(version).or(wikidot)
+    ping.or(version).or(capabilities).or(wikidot)
 }
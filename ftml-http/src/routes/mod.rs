@@ -23,7 +23,7 @@ mod prelude {
     pub use super::object::*;
     pub use crate::error::Error;
     pub use crate::includer::HttpIncluder;
-    pub use ftml::includes::PageRef;
+    pub use ftml::includes::{IncludeSettings, PageRef};
     pub use ftml::parsing::ExtractedToken;
     pub use ftml::prelude::*;
     pub use warp::{Filter, Rejection, Reply};
@@ -20,7 +20,7 @@
 
 use super::prelude::*;
 use ftml::parsing::ParseWarning;
-use ftml::tree::SyntaxTree;
+use ftml::tree::{SyntaxTree, MAX_SERIALIZED_DEPTH};
 
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "kebab-case")]
@@ -46,7 +46,10 @@ pub fn route_parse(
             ftml::preprocess(&log, &mut text);
 
             let tokenization = ftml::tokenize(&log, &text);
-            let (syntax_tree, warnings) = ftml::parse(&log, &tokenization).into();
+            let (mut syntax_tree, warnings) = ftml::parse(&log, &tokenization).into();
+
+            // Keep the response within what JSON readers will accept
+            syntax_tree.limit_depth(MAX_SERIALIZED_DEPTH);
 
             let resp = Response::ok(ParseOutput {
                 pages_included,
@@ -21,8 +21,8 @@
 use super::prelude::*;
 use ftml::render::debug::DebugRender;
 use ftml::render::html::{HtmlMeta, HtmlOutput, HtmlRender};
-use ftml::render::Render;
-use ftml::tree::SyntaxTree;
+use ftml::render::{Render, RenderWarning};
+use ftml::tree::{SyntaxTree, MAX_SERIALIZED_DEPTH};
 
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "kebab-case")]
@@ -35,6 +35,7 @@ struct HtmlRenderOutput<'a> {
     html: &'a str,
     style: &'a str,
     meta: &'a [HtmlMeta],
+    render_warnings: &'a [RenderWarning],
 }
 
 pub fn route_render_html(
@@ -51,8 +52,16 @@ pub fn route_render_html(
             ftml::preprocess(&log, &mut text);
 
             let tokenization = tokenize(&log, &text);
-            let (syntax_tree, warnings) = ftml::parse(&log, &tokenization).into();
-            let HtmlOutput { html, style, meta } = HtmlRender.render(&syntax_tree);
+            let (mut syntax_tree, warnings) = ftml::parse(&log, &tokenization).into();
+            let HtmlOutput {
+                html,
+                style,
+                meta,
+                warnings: render_warnings,
+            } = HtmlRender::default().render(&syntax_tree);
+
+            // Keep the response within what JSON readers will accept
+            syntax_tree.limit_depth(MAX_SERIALIZED_DEPTH);
 
             let resp = Response::ok(HtmlRenderOutput {
                 pages_included,
@@ -63,6 +72,7 @@ pub fn route_render_html(
                 html: &html,
                 style: &style,
                 meta: &meta,
+                render_warnings: &render_warnings,
             });
 
             warp::reply::json(&resp)
@@ -94,9 +104,12 @@ pub fn route_render_debug(
             ftml::preprocess(&log, &mut text);
 
             let tokenization = tokenize(&log, &text);
-            let (syntax_tree, warnings) = ftml::parse(&log, &tokenization).into();
+            let (mut syntax_tree, warnings) = ftml::parse(&log, &tokenization).into();
             let output = DebugRender.render(&syntax_tree);
 
+            // Keep the response within what JSON readers will accept
+            syntax_tree.limit_depth(MAX_SERIALIZED_DEPTH);
+
             let resp = Response::ok(DebugRenderOutput {
                 pages_included,
                 text: &text,
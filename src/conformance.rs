@@ -0,0 +1,189 @@
+/*
+ * conformance.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Reporting on wikitext constructs which the parser does not handle.
+//!
+//! This runs a corpus of raw Wikidot sources through the preprocessor, tokenizer,
+//! and parser, collecting each instance where no rule matched and the fallback
+//! was used instead. The resultant report lists these constructs by frequency,
+//! to help prioritize which rules to implement next.
+
+use crate::parsing::{ParseWarningKind, Token};
+use std::collections::HashMap;
+
+/// A construct which caused the parser to use the fallback rule.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct FallbackEntry {
+    pub token: Token,
+    pub slice: String,
+
+    /// Total number of times this construct hit the fallback.
+    pub count: usize,
+
+    /// Number of distinct pages where this construct hit the fallback.
+    pub pages: usize,
+}
+
+/// A rule which failed to match, with the reason why.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RuleFailureEntry {
+    pub rule: String,
+    pub kind: ParseWarningKind,
+    pub count: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct FallbackReport {
+    /// Number of pages processed.
+    pub pages: usize,
+
+    /// Number of pages which hit the fallback at least once.
+    pub pages_with_fallback: usize,
+
+    /// Constructs which hit the fallback, most frequent first.
+    pub fallbacks: Vec<FallbackEntry>,
+
+    /// Other warnings produced, most frequent first.
+    pub rule_failures: Vec<RuleFailureEntry>,
+}
+
+/// Parse each of the given sources, and report on which constructs hit the fallback.
+///
+/// Includes are not substituted, each source is processed as-is.
+pub fn fallback_report<'a, I>(log: &slog::Logger, sources: I) -> FallbackReport
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let log = &log.new(slog_o!(
+        "filename" => slog_filename!(),
+        "lineno" => slog_lineno!(),
+        "function" => "fallback_report",
+    ));
+
+    let mut report = FallbackReport::default();
+    let mut fallbacks = HashMap::<(&'static str, String), FallbackEntry>::new();
+    let mut failures = HashMap::<(String, &'static str), RuleFailureEntry>::new();
+
+    for source in sources {
+        info!(log, "Collecting fallbacks for page"; "page" => report.pages);
+
+        let mut text = str!(source);
+        crate::preprocess(log, &mut text);
+        let tokenization = crate::tokenize(log, &text);
+        let (_, warnings) = crate::parse(log, &tokenization).into();
+
+        let mut seen_on_page = Vec::new();
+        for warning in warnings {
+            let kind = warning.kind();
+
+            if kind.is_fallback() {
+                let token = warning.token();
+                let slice = str!(warning.span().slice(&text));
+                let key = (token.name(), slice);
+
+                if !seen_on_page.contains(&key) {
+                    seen_on_page.push(key.clone());
+                }
+
+                let entry =
+                    fallbacks
+                        .entry(key)
+                        .or_insert_with_key(|(_, slice)| FallbackEntry {
+                            token,
+                            slice: slice.clone(),
+                            count: 0,
+                            pages: 0,
+                        });
+
+                entry.count += 1;
+            } else {
+                let rule = str!(warning.rule());
+                let key = (rule, kind.name());
+
+                let entry = failures.entry(key).or_insert_with_key(|(rule, _)| {
+                    RuleFailureEntry {
+                        rule: rule.clone(),
+                        kind,
+                        count: 0,
+                    }
+                });
+
+                entry.count += 1;
+            }
+        }
+
+        for key in &seen_on_page {
+            if let Some(entry) = fallbacks.get_mut(key) {
+                entry.pages += 1;
+            }
+        }
+
+        if !seen_on_page.is_empty() {
+            report.pages_with_fallback += 1;
+        }
+
+        report.pages += 1;
+    }
+
+    // Sort by most frequent, then alphabetically for stable output
+    report.fallbacks = fallbacks.into_values().collect();
+    report.fallbacks.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.token.name().cmp(b.token.name()))
+            .then_with(|| a.slice.cmp(&b.slice))
+    });
+
+    report.rule_failures = failures.into_values().collect();
+    report.rule_failures.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.rule.cmp(&b.rule))
+            .then_with(|| a.kind.name().cmp(b.kind.name()))
+    });
+
+    report
+}
+
+#[test]
+fn report() {
+    let log = crate::build_logger();
+    let sources = vec!["apple ]] banana ]]", "cherry", "]] durian"];
+    let report = fallback_report(&log, sources);
+
+    assert_eq!(report.pages, 3, "Number of pages processed doesn't match");
+    assert_eq!(
+        report.pages_with_fallback, 2,
+        "Number of pages with fallbacks doesn't match",
+    );
+    assert_eq!(
+        report.fallbacks,
+        vec![FallbackEntry {
+            token: Token::RightBlock,
+            slice: str!("]]"),
+            count: 3,
+            pages: 2,
+        }],
+        "Fallback entries don't match",
+    );
+}
@@ -37,6 +37,18 @@ pub enum Alignment {
     Justify,
 }
 
+impl Alignment {
+    #[inline]
+    pub fn html_style(self) -> &'static str {
+        match self {
+            Alignment::Left => "text-align: left;",
+            Alignment::Right => "text-align: right;",
+            Alignment::Center => "text-align: center;",
+            Alignment::Justify => "text-align: justify;",
+        }
+    }
+}
+
 impl<'a> TryFrom<&'a str> for Alignment {
     type Error = ();
 
@@ -51,7 +63,7 @@ impl<'a> TryFrom<&'a str> for Alignment {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub struct ImageAlignment {
     pub align: Alignment,
@@ -71,4 +83,15 @@ impl ImageAlignment {
 
         Some(ImageAlignment { align, float })
     }
+
+    /// The class of the container wrapping an aligned image, as in Wikidot.
+    pub fn html_class(self) -> &'static str {
+        match (self.align, self.float) {
+            (Alignment::Left, false) => "alignleft",
+            (Alignment::Right, false) => "alignright",
+            (Alignment::Left, true) => "floatleft",
+            (Alignment::Right, true) => "floatright",
+            (Alignment::Center, _) | (Alignment::Justify, _) => "aligncenter",
+        }
+    }
 }
@@ -20,6 +20,10 @@
 
 mod alignment;
 mod page_info;
+mod site_settings;
+mod trust;
 
 pub use self::alignment::*;
 pub use self::page_info::*;
+pub use self::site_settings::*;
+pub use self::trust::*;
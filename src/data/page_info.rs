@@ -21,7 +21,7 @@
 use std::borrow::Cow;
 
 /// Metadata information on the article being rendered.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub struct PageInfo<'a> {
     /// The slug for this page.
@@ -0,0 +1,122 @@
+/*
+ * data/site_settings.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::BTreeMap;
+
+/// Conventions which vary between the wikis a host serves.
+///
+/// A single instance is meant to be shared between the parse and
+/// render settings for a site, so that both stages agree on things
+/// like which domains may be embedded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct SiteSettings {
+    /// The base URL for pages on this site, without a trailing slash.
+    ///
+    /// For instance, `https://scp-wiki.wikidot.com`.
+    pub site_url: String,
+
+    /// The base URL for files uploaded to pages on this site, without a trailing slash.
+    pub files_url: String,
+
+    /// Prefixes for links to other wikis, such as `[wikipedia:Apple]`.
+    ///
+    /// Maps each prefix to its URL template, where `$$` is replaced
+    /// with the linked page name.
+    pub interwiki: BTreeMap<String, String>,
+
+    /// The domains which may be the target of iframes and other embeds.
+    ///
+    /// Subdomains of a listed domain are also allowed.
+    pub embed_domains: Vec<String>,
+
+    /// The locale of the site, as a BCP 47 language tag.
+    pub locale: String,
+
+    /// The names of ftml extensions to Wikidot syntax which are enabled on this site.
+    pub extensions: Vec<String>,
+}
+
+impl SiteSettings {
+    /// Builds the URL for a link with the given interwiki prefix, if it exists.
+    pub fn interwiki_url(&self, prefix: &str, page: &str) -> Option<String> {
+        let (_, template) = self
+            .interwiki
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(prefix))?;
+
+        Some(template.replace("$$", page))
+    }
+
+    /// Determines if the given domain is permitted as the target of an embed.
+    pub fn is_embed_allowed(&self, domain: &str) -> bool {
+        let domain = domain.trim_end_matches('.');
+
+        self.embed_domains.iter().any(|allowed| {
+            if domain.eq_ignore_ascii_case(allowed) {
+                return true;
+            }
+
+            // Check if it's a subdomain of the allowed domain
+            domain.len() > allowed.len()
+                && domain.as_bytes()[domain.len() - allowed.len() - 1] == b'.'
+                && domain[domain.len() - allowed.len()..].eq_ignore_ascii_case(allowed)
+        })
+    }
+
+    /// Determines if the given ftml extension is enabled.
+    #[inline]
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.extensions.iter().any(|extension| extension == name)
+    }
+}
+
+impl Default for SiteSettings {
+    fn default() -> Self {
+        SiteSettings {
+            site_url: String::new(),
+            files_url: String::new(),
+            interwiki: BTreeMap::new(),
+            embed_domains: Vec::new(),
+            locale: str!("en"),
+            extensions: Vec::new(),
+        }
+    }
+}
+
+#[test]
+fn site_settings() {
+    let mut settings = SiteSettings::default();
+    settings
+        .interwiki
+        .insert(str!("wikipedia"), str!("https://en.wikipedia.org/wiki/$$"));
+    settings.embed_domains.push(str!("youtube.com"));
+
+    assert_eq!(
+        settings.interwiki_url("Wikipedia", "Apple"),
+        Some(str!("https://en.wikipedia.org/wiki/Apple")),
+    );
+    assert_eq!(settings.interwiki_url("banana", "Apple"), None);
+
+    assert!(settings.is_embed_allowed("youtube.com"));
+    assert!(settings.is_embed_allowed("www.YouTube.com"));
+    assert!(!settings.is_embed_allowed("notyoutube.com"));
+    assert!(!settings.is_embed_allowed("youtube.com.example.org"));
+}
@@ -0,0 +1,67 @@
+/*
+ * data/trust.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::SiteSettings;
+use std::sync::Arc;
+
+/// How far the author of a page is trusted.
+///
+/// Hosts often render both pages by staff and pages by anyone, such as
+/// anonymous previews. This selects every setting which depends on that
+/// together, see `ParseSettings::for_author()` and `HtmlRender::for_author()`.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthorTrust {
+    /// The author may use everything the site permits.
+    Trusted,
+
+    /// The author may only affect the page's own content.
+    ///
+    /// Nothing may be embedded, `style` arguments are dropped and `id`
+    /// arguments are prefixed, and page styles and modules are left out.
+    Untrusted,
+}
+
+impl AuthorTrust {
+    #[inline]
+    pub fn is_trusted(self) -> bool {
+        self == AuthorTrust::Trusted
+    }
+
+    /// Gets the site settings which apply to this author.
+    ///
+    /// Untrusted authors get a copy with no domains permitted for embeds.
+    pub fn site_settings(self, site: &Arc<SiteSettings>) -> Arc<SiteSettings> {
+        match self {
+            AuthorTrust::Trusted => Arc::clone(site),
+            AuthorTrust::Untrusted => Arc::new(SiteSettings {
+                embed_domains: Vec::new(),
+                ..SiteSettings::clone(site)
+            }),
+        }
+    }
+}
+
+impl Default for AuthorTrust {
+    #[inline]
+    fn default() -> Self {
+        AuthorTrust::Trusted
+    }
+}
@@ -18,8 +18,11 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use crate::parsing::parse_choice;
+use crate::tree::Element;
 use std::borrow::Cow;
 use std::convert::TryFrom;
+use std::str::FromStr;
 use strum_macros::IntoStaticStr;
 
 #[derive(
@@ -57,7 +60,7 @@ impl AnchorTarget {
             AnchorTarget::NewTab => "_blank",
             AnchorTarget::Parent => "_parent",
             AnchorTarget::Top => "_top",
-            AnchorTarget::Same => "_same",
+            AnchorTarget::Same => "_self",
         }
     }
 
@@ -70,6 +73,18 @@ impl AnchorTarget {
             AnchorTarget::Same => None,
         }
     }
+
+    /// The "rel" attribute used when a link doesn't specify one.
+    ///
+    /// Links opening a new tab shouldn't give the new page
+    /// access to this one through `window.opener`.
+    #[inline]
+    pub fn default_rel(self) -> Option<&'static str> {
+        match self {
+            AnchorTarget::NewTab => Some("noopener noreferrer"),
+            AnchorTarget::Parent | AnchorTarget::Top | AnchorTarget::Same => None,
+        }
+    }
 }
 
 impl<'a> TryFrom<&'a str> for AnchorTarget {
@@ -116,6 +131,13 @@ impl HeadingLevel {
         }
     }
 
+    /// Lowers the heading by the given number of levels, stopping at six.
+    pub fn shift(self, amount: u8) -> Self {
+        let value = self.value().saturating_add(amount).min(6);
+
+        HeadingLevel::try_from(value).expect("Clamped heading level is invalid")
+    }
+
     #[inline]
     pub fn html_tag(self) -> &'static str {
         match self {
@@ -178,6 +200,25 @@ pub enum LinkLabel<'a> {
     ///
     /// The label for this link is whatever the page's title is.
     Page,
+
+    /// Link label made of other elements.
+    ///
+    /// This is used when the label contains blocks, such as an image
+    /// in `[[[page | [[image x.png]] ]]]`.
+    Elements(Vec<Element<'a>>),
+}
+
+impl LinkLabel<'_> {
+    pub fn to_owned(&self) -> LinkLabel<'static> {
+        match self {
+            LinkLabel::Text(text) => LinkLabel::Text(Cow::Owned(text.to_string())),
+            LinkLabel::Url => LinkLabel::Url,
+            LinkLabel::Page => LinkLabel::Page,
+            LinkLabel::Elements(elements) => {
+                LinkLabel::Elements(elements.iter().map(Element::to_owned).collect())
+            }
+        }
+    }
 }
 
 #[derive(
@@ -194,6 +235,14 @@ impl ListStyle {
     pub fn name(self) -> &'static str {
         self.into()
     }
+
+    #[inline]
+    pub fn html_tag(self) -> &'static str {
+        match self {
+            ListStyle::Bullet => "ul",
+            ListStyle::Numbered => "ol",
+        }
+    }
 }
 
 #[derive(
@@ -212,3 +261,107 @@ impl InfoField {
         self.into()
     }
 }
+
+#[derive(
+    Serialize, Deserialize, IntoStaticStr, Debug, Copy, Clone, Hash, PartialEq, Eq,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum FloatAlignment {
+    Left,
+    Right,
+}
+
+impl FloatAlignment {
+    #[inline]
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
+
+    #[inline]
+    pub fn html_style(self) -> &'static str {
+        match self {
+            FloatAlignment::Left => "float: left;",
+            FloatAlignment::Right => "float: right;",
+        }
+    }
+}
+
+impl FromStr for FloatAlignment {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        const FLOAT_ALIGNMENT_VALUES: [(&str, FloatAlignment); 2] = [
+            ("left", FloatAlignment::Left),
+            ("right", FloatAlignment::Right),
+        ];
+
+        parse_choice(value, &FLOAT_ALIGNMENT_VALUES).ok_or(())
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, IntoStaticStr, Debug, Copy, Clone, Hash, PartialEq, Eq,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClearFloat {
+    Left,
+    Right,
+    Both,
+}
+
+impl ClearFloat {
+    #[inline]
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
+
+    #[inline]
+    pub fn html_style(self) -> &'static str {
+        match self {
+            ClearFloat::Left => "clear: left;",
+            ClearFloat::Right => "clear: right;",
+            ClearFloat::Both => "clear: both;",
+        }
+    }
+}
+
+impl FromStr for ClearFloat {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        const CLEAR_FLOAT_VALUES: [(&str, ClearFloat); 3] = [
+            ("left", ClearFloat::Left),
+            ("right", ClearFloat::Right),
+            ("both", ClearFloat::Both),
+        ];
+
+        parse_choice(value, &CLEAR_FLOAT_VALUES).ok_or(())
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, IntoStaticStr, Debug, Copy, Clone, Hash, PartialEq, Eq,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagramKind {
+    /// A chart, with its data and configuration in the body.
+    Chart,
+
+    /// A diagram written in the Mermaid language.
+    Mermaid,
+}
+
+impl DiagramKind {
+    #[inline]
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
+
+    #[inline]
+    pub fn html_class(self) -> &'static str {
+        match self {
+            DiagramKind::Chart => "chart",
+            DiagramKind::Mermaid => "mermaid",
+        }
+    }
+}
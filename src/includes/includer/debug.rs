@@ -18,6 +18,7 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use super::format_chain;
 use super::prelude::*;
 use crate::includes::IncludeVariables;
 use std::fmt::{self, Display};
@@ -69,6 +70,21 @@ impl<'t> Includer<'t> for DebugIncluder {
     fn no_such_include(&mut self, page_ref: &PageRef<'t>) -> Result<Cow<'t, str>, Void> {
         Ok(Cow::Owned(format!("<MISSING-PAGE {}>", page_ref)))
     }
+
+    #[inline]
+    fn include_too_large(&mut self, chain: &[PageRef<'t>]) -> Result<Cow<'t, str>, Void> {
+        Ok(Cow::Owned(format!("<TOO-LARGE {}>", format_chain(chain))))
+    }
+
+    #[inline]
+    fn include_cycle(&mut self, chain: &[PageRef<'t>]) -> Result<Cow<'t, str>, Void> {
+        Ok(Cow::Owned(format!("<CYCLE {}>", format_chain(chain))))
+    }
+
+    #[inline]
+    fn include_too_deep(&mut self, chain: &[PageRef<'t>]) -> Result<Cow<'t, str>, Void> {
+        Ok(Cow::Owned(format!("<TOO-DEEP {}>", format_chain(chain))))
+    }
 }
 
 /// Rendering a `HashMap` as a string, sorted alphabetically.
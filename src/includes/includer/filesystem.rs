@@ -0,0 +1,269 @@
+/*
+ * includes/includer/filesystem.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! A reference host implementation, backed by a directory of wikitext files.
+//!
+//! Each page is a file named after its slug, with categories as
+//! subdirectories. For instance `scp-001` is read from `scp-001.ftml`,
+//! and `component:acs` from `component/acs.ftml`. A page may have
+//! metadata beside it, as `scp-001.json`:
+//!
+//! ```json
+//! {
+//!     "title": "SCP-001",
+//!     "tags": ["scp", "keter"],
+//!     "rating": 100
+//! }
+//! ```
+//!
+//! Off-site pages are never found.
+
+use super::prelude::*;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "html")]
+use {
+    crate::data::PageInfo,
+    crate::render::html::{Handle, HandleError, PageMeta},
+    crate::tree::Module,
+};
+
+#[derive(Debug, Clone)]
+pub struct FileSystemHandle {
+    directory: PathBuf,
+}
+
+impl FileSystemHandle {
+    #[inline]
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        FileSystemHandle {
+            directory: directory.into(),
+        }
+    }
+
+    #[inline]
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Gets the path of a page's file with the given extension.
+    ///
+    /// Returns `None` for pages which cannot be in this directory,
+    /// such as off-site pages or those with path separators in their slug.
+    fn page_path(&self, page_ref: &PageRef, extension: &str) -> Option<PathBuf> {
+        if page_ref.site().is_some() {
+            return None;
+        }
+
+        let mut path = self.directory.clone();
+
+        for part in page_ref.page().split(':') {
+            if part.is_empty()
+                || part == "."
+                || part == ".."
+                || part.contains(&['/', '\\'][..])
+            {
+                return None;
+            }
+
+            path.push(part);
+        }
+
+        // Slugs may contain '.', so the extension is appended rather
+        // than replacing whatever follows the last one
+        let mut path = path.into_os_string();
+        path.push(".");
+        path.push(extension);
+
+        Some(PathBuf::from(path))
+    }
+
+    fn read_page(&self, page_ref: &PageRef) -> io::Result<Option<String>> {
+        let path = match self.page_path(page_ref, "ftml") {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    #[cfg(feature = "html")]
+    fn read_meta(&self, page_ref: &PageRef) -> Result<Option<PageMeta>, HandleError> {
+        let path = match self.page_path(page_ref, "ftml") {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        if !path.is_file() {
+            return Ok(Some(PageMeta::default()));
+        }
+
+        // This only replaces the extension added by page_path()
+        let file = match fs::read_to_string(path.with_extension("json")) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|error| HandleError::new(error.to_string()))?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => PageFile::default(),
+            Err(error) => return Err(HandleError::new(error.to_string())),
+        };
+
+        let PageFile {
+            title,
+            tags,
+            rating,
+        } = file;
+
+        Ok(Some(PageMeta {
+            exists: true,
+            title,
+            tags,
+            rating,
+        }))
+    }
+}
+
+impl<'t> Includer<'t> for FileSystemHandle {
+    type Error = io::Error;
+
+    fn include_pages(
+        &mut self,
+        includes: &[IncludeRef<'t>],
+    ) -> Result<Vec<FetchedPage<'t>>, io::Error> {
+        let mut pages = Vec::new();
+
+        for include in includes {
+            let page_ref = include.page_ref().clone();
+            let content = self.read_page(&page_ref)?.map(Cow::Owned);
+
+            pages.push(FetchedPage { page_ref, content });
+        }
+
+        Ok(pages)
+    }
+
+    #[inline]
+    fn no_such_include(
+        &mut self,
+        page_ref: &PageRef<'t>,
+    ) -> Result<Cow<'t, str>, io::Error> {
+        Ok(Cow::Owned(format!(
+            "[[div class=\"error-block\"]]\nPage to be included {} cannot be found!\n[[/div]]",
+            page_ref,
+        )))
+    }
+}
+
+#[cfg(feature = "html")]
+impl Handle for FileSystemHandle {
+    #[inline]
+    fn get_page_meta(&self, page: &PageRef) -> Result<Option<PageMeta>, HandleError> {
+        self.read_meta(page)
+    }
+
+    #[inline]
+    fn render_module(&self, _: &Module, _: &PageInfo) -> Result<String, HandleError> {
+        Ok(String::new())
+    }
+}
+
+/// The contents of a page's metadata file.
+#[cfg(feature = "html")]
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct PageFile {
+    title: Option<String>,
+    tags: Vec<String>,
+    rating: Option<f32>,
+}
+
+#[test]
+fn filesystem() {
+    // Unique to this process, so concurrent test runs don't interfere
+    let directory = std::env::temp_dir()
+        .join(format!("ftml-filesystem-handle-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(directory.join("component")).unwrap();
+    fs::write(directory.join("apple.ftml"), "Apple").unwrap();
+    fs::write(directory.join("apple.pie.ftml"), "Pie").unwrap();
+    fs::write(directory.join("component/acs.ftml"), "ACS").unwrap();
+
+    let mut handle = FileSystemHandle::new(&directory);
+    let includes = [
+        IncludeRef::page_only(PageRef::page_only("apple")),
+        IncludeRef::page_only(PageRef::page_only("apple.pie")),
+        IncludeRef::page_only(PageRef::page_only("component:acs")),
+        IncludeRef::page_only(PageRef::page_only("banana")),
+        IncludeRef::page_only(PageRef::page_only("../apple")),
+        IncludeRef::page_only(PageRef::page_and_site("scp-wiki", "apple")),
+    ];
+
+    let contents: Vec<_> = handle
+        .include_pages(&includes)
+        .expect("Unable to read pages")
+        .into_iter()
+        .map(|page| page.content)
+        .collect();
+
+    assert_eq!(
+        contents,
+        vec![
+            Some(cow!("Apple")),
+            Some(cow!("Pie")),
+            Some(cow!("ACS")),
+            None,
+            None,
+            None,
+        ],
+        "Included page contents don't match expected",
+    );
+
+    #[cfg(feature = "html")]
+    {
+        let meta = handle
+            .get_page_meta(&PageRef::page_only("apple"))
+            .expect("Unable to get page metadata");
+
+        assert_eq!(
+            meta,
+            Some(PageMeta {
+                exists: true,
+                ..PageMeta::default()
+            }),
+            "Page metadata doesn't match expected",
+        );
+
+        let meta = handle
+            .get_page_meta(&PageRef::page_only("banana"))
+            .expect("Unable to get page metadata");
+
+        assert_eq!(
+            meta,
+            Some(PageMeta::default()),
+            "Missing page metadata doesn't match expected",
+        );
+    }
+
+    fs::remove_dir_all(&directory).unwrap();
+}
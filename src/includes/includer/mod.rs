@@ -19,6 +19,7 @@
  */
 
 mod debug;
+mod filesystem;
 mod null;
 
 mod prelude {
@@ -31,6 +32,7 @@ use crate::includes::{IncludeRef, PageRef};
 use std::borrow::Cow;
 
 pub use self::debug::DebugIncluder;
+pub use self::filesystem::FileSystemHandle;
 pub use self::null::NullIncluder;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -52,4 +54,55 @@ pub trait Includer<'t> {
         &mut self,
         page_ref: &PageRef<'t>,
     ) -> Result<Cow<'t, str>, Self::Error>;
+
+    /// Produces the content used in place of an include which would make
+    /// the expanded text larger than `IncludeSettings::max_size`.
+    ///
+    /// The chain is the pages being included to reach this one, outermost first,
+    /// ending with the include which was cut off. Return an error to stop
+    /// including entirely instead.
+    fn include_too_large(
+        &mut self,
+        chain: &[PageRef<'t>],
+    ) -> Result<Cow<'t, str>, Self::Error> {
+        Ok(Cow::Owned(format!(
+            "[[div class=\"error-block\"]]\nIncluding {} would make the page too large!\n[[/div]]",
+            format_chain(chain),
+        )))
+    }
+
+    /// Produces the content used in place of an include of a page which
+    /// is already being included, which would otherwise never end.
+    ///
+    /// The chain is as in `include_too_large()`, ending with the repeated page.
+    /// This is only used if `IncludeSettings::max_depth` is set.
+    fn include_cycle(
+        &mut self,
+        chain: &[PageRef<'t>],
+    ) -> Result<Cow<'t, str>, Self::Error> {
+        Ok(Cow::Owned(format!(
+            "[[div class=\"error-block\"]]\nIncluding {} would repeat forever!\n[[/div]]",
+            format_chain(chain),
+        )))
+    }
+
+    /// Produces the content used in place of an include nested more deeply
+    /// than `IncludeSettings::max_depth`.
+    ///
+    /// The chain is as in `include_too_large()`, ending with the include which was cut off.
+    fn include_too_deep(
+        &mut self,
+        chain: &[PageRef<'t>],
+    ) -> Result<Cow<'t, str>, Self::Error> {
+        Ok(Cow::Owned(format!(
+            "[[div class=\"error-block\"]]\nIncluding {} would nest too deeply!\n[[/div]]",
+            format_chain(chain),
+        )))
+    }
+}
+
+/// Writes out a chain of included pages, such as `a -> b -> c`.
+pub fn format_chain(chain: &[PageRef]) -> String {
+    let pages: Vec<String> = chain.iter().map(PageRef::to_string).collect();
+    pages.join(" -> ")
 }
@@ -23,29 +23,41 @@ mod test;
 
 mod includer;
 mod object;
+mod pages;
 mod parse;
+mod scan;
+mod section;
+mod settings;
+mod variables;
 
-pub use self::includer::{DebugIncluder, FetchedPage, Includer, NullIncluder};
+pub use self::includer::{
+    DebugIncluder, FetchedPage, FileSystemHandle, Includer, NullIncluder,
+};
+pub(crate) use self::object::variables_to_owned;
 pub use self::object::{IncludeRef, IncludeVariables, PageRef};
+pub use self::pages::{IncludedPage, IncludedPages};
+pub use self::settings::IncludeSettings;
+pub use self::variables::{
+    escape_variable, find_variables, FoundVariable, VariableContext,
+};
 
-use self::parse::parse_include_block;
-use crate::span_wrap::SpanWrap;
-use regex::{Regex, RegexBuilder};
-
-lazy_static! {
-    static ref INCLUDE_REGEX: Regex = {
-        RegexBuilder::new(r"^\[\[\s*include\s+")
-            .case_insensitive(true)
-            .multi_line(true)
-            .dot_matches_new_line(true)
-            .build()
-            .unwrap()
-    };
-}
+use self::includer::format_chain;
+pub(crate) use self::parse::parse_include_block;
+use self::scan::next_include_start;
+use self::section::{extract_section, SECTION_ARGUMENT};
+use self::variables::substitute_variables;
+use crate::span::Span;
+use std::borrow::Cow;
 
+/// Substitutes the include blocks in the text with the contents of their pages.
+///
+/// Returns the expanded text, and each page which was included, in the order
+/// they appear. If `IncludeSettings::max_depth` is set, this also has the pages
+/// included within each, directly after the page which included them.
 pub fn include<'t, I, E, F>(
     log: &slog::Logger,
     input: &'t str,
+    settings: &IncludeSettings,
     mut includer: I,
     invalid_return: F,
 ) -> Result<(String, Vec<PageRef<'t>>), E>
@@ -65,89 +77,466 @@ where
         "Finding and replacing all instances of include blocks in text"
     );
 
-    let mut ranges = Vec::new();
-    let mut includes = Vec::new();
+    let found = find_includes(log, input, settings, &mut includer, invalid_return)?;
+
+    // Substitute inclusions
+    //
+    // We must iterate backwards for all the indices to be valid
+    //
+    // Borrowing from the original text and doing in-place insertions
+    // will not work here. We are trying to both return the page names
+    // (slices from the input string), and replace it with new content.
+    let mut output = String::from(input);
+    let mut pages = Vec::new();
+
+    for FoundInclude {
+        span,
+        include,
+        content,
+        nested,
+        ..
+    } in found.into_iter().rev()
+    {
+        let (page_ref, _) = include.into();
+
+        debug!(
+            log,
+            "Replacing range for included page";
+            "span" => span,
+            "site" => page_ref.site(),
+            "page" => page_ref.page(),
+        );
+
+        // Append page to final list, with those it included
+        //
+        // These are reversed along with the rest of the list below.
+        pages.extend(nested.into_iter().rev());
+        pages.push(page_ref);
+
+        // Perform the substitution
+        output.replace_range(span.range(), settings.trim(&content));
+    }
+
+    // Since we iterate in reverse order, the pages are reversed.
+    pages.reverse();
+
+    // Return
+    Ok((output, pages))
+}
+
+/// Finds and fetches include blocks, for inclusion at parse time.
+///
+/// Unlike `include()`, `[[include]]` blocks are left in the text, and their
+/// fetched contents are returned alongside it. When these are passed to the
+/// parser in `ParseOptions::includes`, each becomes an `Element::Include`,
+/// with its contents parsed separately.
+///
+/// `[[include-messy]]` blocks are still substituted textually, as in `include()`.
+/// If `IncludeSettings::max_depth` is set, the pages included within each
+/// included page are also returned, see `IncludedPages::nested()`.
+pub fn include_elements<'t, I, E, F>(
+    log: &slog::Logger,
+    input: &'t str,
+    settings: &IncludeSettings,
+    mut includer: I,
+    invalid_return: F,
+) -> Result<(String, IncludedPages<'t>), E>
+where
+    I: Includer<'t, Error = E>,
+    F: FnOnce() -> E,
+{
+    let log = &log.new(slog_o!(
+        "filename" => slog_filename!(),
+        "lineno" => slog_lineno!(),
+        "function" => "include_elements",
+        "text" => str!(input),
+    ));
+
+    info!(log, "Finding all instances of include blocks in text");
+
+    let found = find_includes(log, input, settings, &mut includer, invalid_return)?;
+
+    // Substitute messy inclusions, keeping the rest
+    //
+    // As above, we must iterate backwards for the indices to be valid
+    let mut output = String::from(input);
+    let mut pages = Vec::new();
+    let mut nested_pages = Vec::new();
+
+    for FoundInclude {
+        span,
+        messy,
+        include,
+        content,
+        nested,
+    } in found.into_iter().rev()
+    {
+        debug!(
+            log,
+            "Handling included page";
+            "span" => span,
+            "messy" => messy,
+            "site" => include.page_ref().site(),
+            "page" => include.page_ref().page(),
+        );
+
+        // As in include(), these are reversed along with the rest below
+        nested_pages.extend(nested.into_iter().rev());
+
+        if messy {
+            output.replace_range(span.range(), settings.trim(&content));
+        } else {
+            pages.push(IncludedPage { include, content });
+        }
+    }
+
+    pages.reverse();
+    nested_pages.reverse();
+
+    Ok((output, IncludedPages::with_nested(pages, nested_pages)))
+}
+
+/// An include block found in the text, along with its fetched contents.
+///
+/// The nested pages are those included within the contents, in order,
+/// if `IncludeSettings::max_depth` is set.
+#[derive(Debug)]
+struct FoundInclude<'t> {
+    span: Span,
+    messy: bool,
+    include: IncludeRef<'t>,
+    content: Cow<'t, str>,
+    nested: Vec<PageRef<'t>>,
+}
+
+/// The include blocks in some text, before their pages are fetched.
+#[derive(Debug, Default)]
+struct ScannedIncludes<'t> {
+    spans: Vec<Span>,
+    messy: Vec<bool>,
+    includes: Vec<IncludeRef<'t>>,
+}
+
+impl ScannedIncludes<'_> {
+    fn to_owned(&self) -> ScannedIncludes<'static> {
+        ScannedIncludes {
+            spans: self.spans.clone(),
+            messy: self.messy.clone(),
+            includes: self.includes.iter().map(IncludeRef::to_owned).collect(),
+        }
+    }
+}
+
+fn find_includes<'t, I, E, F>(
+    log: &slog::Logger,
+    input: &'t str,
+    settings: &IncludeSettings,
+    includer: &mut I,
+    invalid_return: F,
+) -> Result<Vec<FoundInclude<'t>>, E>
+where
+    I: Includer<'t, Error = E>,
+    F: FnOnce() -> E,
+{
+    let scanned = scan_includes(log, input, settings);
+    let mut invalid_return = Some(invalid_return);
+
+    fetch_includes(
+        log,
+        scanned,
+        input.len(),
+        settings,
+        includer,
+        &[],
+        &mut invalid_return,
+    )
+}
+
+fn scan_includes<'t>(
+    log: &slog::Logger,
+    input: &'t str,
+    settings: &IncludeSettings,
+) -> ScannedIncludes<'t> {
+    let mut scanned = ScannedIncludes::default();
 
     // Get include references
-    for mtch in INCLUDE_REGEX.find_iter(input) {
-        let start = mtch.start();
+    //
+    // Scanning resumes after the end of each include block,
+    // so blocks cannot overlap.
+    let mut index = 0;
 
+    while let Some((start, is_messy)) =
+        next_include_start(input, index, settings.allow_mid_line)
+    {
         debug!(
             log,
-            "Found include regex match";
+            "Found include block start";
             "start" => start,
-            "slice" => mtch.as_str(),
+            "messy" => is_messy,
         );
 
         match parse_include_block(log, &input[start..], start) {
-            None => debug!(log, "Unable to parse include regex match"),
+            None => {
+                debug!(log, "Unable to parse include block");
+                index = start + 2;
+            }
             Some((include, end)) => {
-                ranges.push(start..end);
-                includes.push(include);
+                scanned.spans.push(Span::new(start, end));
+                scanned.messy.push(is_messy);
+                scanned.includes.push(include);
+                index = end;
             }
         }
     }
 
+    scanned
+}
+
+/// Fetches the contents of each include block.
+///
+/// The chain is the pages being included to reach the text the blocks
+/// are in, outermost first, or empty for the page itself. The size is
+/// how large the whole text is at this point of expansion.
+fn fetch_includes<'t, I, E, F>(
+    log: &slog::Logger,
+    scanned: ScannedIncludes<'t>,
+    mut size: usize,
+    settings: &IncludeSettings,
+    includer: &mut I,
+    chain: &[PageRef<'t>],
+    invalid_return: &mut Option<F>,
+) -> Result<Vec<FoundInclude<'t>>, E>
+where
+    I: Includer<'t, Error = E>,
+    F: FnOnce() -> E,
+{
+    let ScannedIncludes {
+        spans,
+        messy,
+        includes,
+    } = scanned;
+
     // Retrieve included pages
     let fetched_pages = includer.include_pages(&includes)?;
 
     // Ensure it matches up with the request
     if includes.len() != fetched_pages.len() {
-        return Err(invalid_return());
+        return Err(invalid(invalid_return));
     }
 
-    // Substitute inclusions
-    //
-    // We must iterate backwards for all the indices to be valid
+    let mut found = Vec::new();
 
-    let ranges_iter = ranges.into_iter();
-    let includes_iter = includes.into_iter();
-    let fetched_iter = fetched_pages.into_iter();
+    for (((span, messy), include), fetched) in spans
+        .into_iter()
+        .zip(messy)
+        .zip(includes)
+        .zip(fetched_pages)
+    {
+        // Ensure the returned page reference matches
+        if include.page_ref() != &fetched.page_ref {
+            return Err(invalid(invalid_return));
+        }
 
-    // Borrowing from the original text and doing in-place insertions
-    // will not work here. We are trying to both return the page names
-    // (slices from the input string), and replace it with new content.
-    let mut output = String::from(input);
+        let page_chain = {
+            let mut page_chain = chain.to_vec();
+            page_chain.push(include.page_ref().clone());
+            page_chain
+        };
+
+        // Get replaced content, or error message
+        let content = match fetched.content {
+            Some(content) => content,
+            None => includer.no_such_include(include.page_ref())?,
+        };
+
+        // Narrow to the requested section, if enabled
+        let content = match include.variables().get(SECTION_ARGUMENT) {
+            Some(name) if settings.allow_sections => {
+                debug!(log, "Extracting section from included page"; "section" => name.as_ref());
+
+                match extract_section(content, name) {
+                    Some(section) => section,
+                    None => includer.no_such_include(include.page_ref())?,
+                }
+            }
+            _ => content,
+        };
+
+        // Fill in variables from the include block, if enabled
+        let content = if settings.substitute_variables {
+            substitute_variables(
+                content,
+                include.variables(),
+                settings.remove_unset_variables,
+            )
+        } else {
+            content
+        };
+
+        // Expand the includes within the included page, if enabled
+        let (content, nested) = match settings.max_depth {
+            Some(max_depth) => expand_nested(
+                log,
+                content,
+                size - span.len(),
+                settings,
+                includer,
+                &page_chain,
+                max_depth,
+                invalid_return,
+            )?,
+            None => (content, Vec::new()),
+        };
+
+        // Cut off expansion once the text would become too large
+        let expanded = size - span.len() + settings.trim(&content).len();
+        let content = match settings.max_size {
+            Some(max_size) if expanded > max_size => {
+                warn!(
+                    log,
+                    "Included page exceeds maximum expanded size";
+                    "size" => expanded,
+                    "max-size" => max_size,
+                );
+
+                includer.include_too_large(&page_chain)?
+            }
+            _ => content,
+        };
+
+        size = size - span.len() + settings.trim(&content).len();
+
+        found.push(FoundInclude {
+            span,
+            messy,
+            include,
+            content,
+            nested,
+        });
+    }
+
+    Ok(found)
+}
+
+/// Substitutes the include blocks within the contents of an included page.
+///
+/// Includes of a page already in the chain, or deeper than the maximum depth,
+/// are replaced with the includer's error content without being fetched.
+/// The size is how large the rest of the text is, outside this content.
+///
+/// Returns the expanded contents and the pages which were included, in order.
+/// Whether a nested block was `[[include-messy]]` is not kept, since its
+/// contents become part of the included page's text either way.
+#[allow(clippy::too_many_arguments)]
+fn expand_nested<'t, I, E, F>(
+    log: &slog::Logger,
+    content: Cow<'t, str>,
+    size: usize,
+    settings: &IncludeSettings,
+    includer: &mut I,
+    chain: &[PageRef<'t>],
+    max_depth: usize,
+    invalid_return: &mut Option<F>,
+) -> Result<(Cow<'t, str>, Vec<PageRef<'t>>), E>
+where
+    I: Includer<'t, Error = E>,
+    F: FnOnce() -> E,
+{
+    // Included pages are separate text, so their include blocks are copied out
+    let scanned = scan_includes(log, &content, settings).to_owned();
+    if scanned.includes.is_empty() {
+        return Ok((content, Vec::new()));
+    }
+
+    debug!(
+        log,
+        "Expanding includes within included page";
+        "chain" => format_chain(chain),
+        "includes" => scanned.includes.len(),
+    );
+
+    // Find which includes can't be expanded, and what to use instead
+    let mut fetch = ScannedIncludes::default();
+    let mut replacements = Vec::new();
     let mut pages = Vec::new();
 
-    for ((range, include), fetched) in
-        ranges_iter.zip(includes_iter).zip(fetched_iter).rev()
+    for ((span, messy), include) in scanned
+        .spans
+        .into_iter()
+        .zip(scanned.messy)
+        .zip(scanned.includes)
     {
-        let (page_ref, _) = include.into();
+        let page_ref = include.page_ref();
 
-        debug!(
-            log,
-            "Replacing range for included page";
-            "span" => SpanWrap::from(&range),
-            "site" => page_ref.site(),
-            "page" => page_ref.page(),
-        );
+        if chain.contains(page_ref) {
+            let mut cycle = chain.to_vec();
+            cycle.push(page_ref.clone());
 
-        // Ensure the returned page reference matches
-        if page_ref != fetched.page_ref {
-            return Err(invalid_return());
+            warn!(
+                log,
+                "Included page includes itself";
+                "chain" => format_chain(&cycle),
+            );
+
+            replacements.push((span, includer.include_cycle(&cycle)?));
+        } else if chain.len() >= max_depth {
+            let mut too_deep = chain.to_vec();
+            too_deep.push(page_ref.clone());
+
+            warn!(
+                log,
+                "Included page exceeds maximum depth";
+                "chain" => format_chain(&too_deep),
+                "max-depth" => max_depth,
+            );
+
+            replacements.push((span, includer.include_too_deep(&too_deep)?));
+        } else {
+            fetch.spans.push(span);
+            fetch.messy.push(messy);
+            fetch.includes.push(include);
         }
+    }
 
-        // Get replaced content, or error message
-        let message;
-        let replace_with = match fetched.content {
-            Some(ref content) => content,
-            None => {
-                message = includer.no_such_include(&page_ref)?;
-                &message
-            }
-        };
+    if !fetch.includes.is_empty() {
+        let size = size + content.len();
+        let found =
+            fetch_includes(log, fetch, size, settings, includer, chain, invalid_return)?;
 
-        // Append page to final list
-        pages.push(page_ref);
+        for FoundInclude {
+            span,
+            include,
+            content,
+            nested,
+            ..
+        } in found
+        {
+            let (page_ref, _) = include.into();
 
-        // Perform the substitution
-        output.replace_range(range, replace_with);
+            pages.push(page_ref);
+            pages.extend(nested);
+            replacements.push((span, content));
+        }
     }
 
-    // Since we iterate in reverse order, the pages are reversed.
-    pages.reverse();
+    // Substitute from the end, so the earlier spans stay valid
+    replacements.sort_by_key(|(span, _)| span.start);
 
-    // Return
-    Ok((output, pages))
+    let mut output = content.into_owned();
+    for (span, replacement) in replacements.into_iter().rev() {
+        output.replace_range(span.range(), settings.trim(&replacement));
+    }
+
+    Ok((Cow::Owned(output), pages))
+}
+
+/// Produces the error for an includer which returned the wrong pages.
+///
+/// Inclusion stops at this error, so it is only ever produced once.
+fn invalid<E, F: FnOnce() -> E>(invalid_return: &mut Option<F>) -> E {
+    let invalid_return = invalid_return
+        .take()
+        .expect("Invalid include error produced twice");
+
+    invalid_return()
 }
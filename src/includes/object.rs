@@ -170,6 +170,25 @@ impl<'t> IncludeRef<'t> {
     pub fn variables(&self) -> &IncludeVariables<'t> {
         &self.variables
     }
+
+    pub fn to_owned(&self) -> IncludeRef<'static> {
+        IncludeRef {
+            page_ref: self.page_ref.to_owned(),
+            variables: variables_to_owned(&self.variables),
+        }
+    }
+}
+
+pub fn variables_to_owned(variables: &IncludeVariables) -> IncludeVariables<'static> {
+    variables
+        .iter()
+        .map(|(key, value)| {
+            let key = Cow::Owned(key.as_ref().to_owned());
+            let value = Cow::Owned(value.as_ref().to_owned());
+
+            (key, value)
+        })
+        .collect()
 }
 
 impl<'t> From<IncludeRef<'t>> for (PageRef<'t>, IncludeVariables<'t>) {
@@ -0,0 +1,72 @@
+/*
+ * includes/pages.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::{IncludeRef, PageRef};
+use std::borrow::Cow;
+
+/// A page fetched for inclusion, along with the include block requesting it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncludedPage<'t> {
+    pub include: IncludeRef<'t>,
+    pub content: Cow<'t, str>,
+}
+
+/// The set of pages fetched for parse-time inclusion.
+///
+/// See `include_elements()`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IncludedPages<'t> {
+    pages: Vec<IncludedPage<'t>>,
+    nested: Vec<PageRef<'t>>,
+}
+
+impl<'t> IncludedPages<'t> {
+    #[inline]
+    pub fn new(pages: Vec<IncludedPage<'t>>) -> Self {
+        IncludedPages::with_nested(pages, Vec::new())
+    }
+
+    #[inline]
+    pub fn with_nested(pages: Vec<IncludedPage<'t>>, nested: Vec<PageRef<'t>>) -> Self {
+        IncludedPages { pages, nested }
+    }
+
+    #[inline]
+    pub fn pages(&self) -> &[IncludedPage<'t>] {
+        &self.pages
+    }
+
+    /// The pages included within the included pages, in order.
+    ///
+    /// Their contents are already part of the contents of the pages which
+    /// included them. This is only filled if `IncludeSettings::max_depth` is set.
+    #[inline]
+    pub fn nested(&self) -> &[PageRef<'t>] {
+        &self.nested
+    }
+
+    /// Gets the fetched contents for the given include block, if any.
+    pub fn get(&self, include: &IncludeRef) -> Option<&str> {
+        self.pages
+            .iter()
+            .find(|page| &page.include == include)
+            .map(|page| page.content.as_ref())
+    }
+}
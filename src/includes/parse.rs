@@ -19,7 +19,7 @@
  */
 
 use super::{IncludeRef, PageRef};
-use crate::span_wrap::SpanWrap;
+use crate::span::Span;
 use pest::iterators::Pairs;
 use pest::Parser;
 use std::borrow::Cow;
@@ -44,7 +44,7 @@ pub fn parse_include_block<'t>(
             debug!(
                 log,
                 "Parsed include block";
-                "span" => SpanWrap::from(span.start()..span.end()),
+                "span" => Span::new(span.start(), span.end()),
                 "slice" => text,
             );
 
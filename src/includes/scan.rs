@@ -0,0 +1,109 @@
+/*
+ * includes/scan.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Locating the start of each include block in the text.
+//!
+//! Only the opening `[[include` is matched here. The rest of the block is
+//! parsed by `parse_include_block()`, which determines where it ends, and
+//! scanning resumes after that point. This way text within an include's
+//! arguments is never mistaken for another include block.
+
+const INCLUDE_NAME: &str = "include";
+const MESSY_SUFFIX: &str = "-messy";
+
+/// Finds the next include block starting at or after `index`.
+///
+/// Returns its position, and whether it is an `[[include-messy]]` block.
+/// Unless `allow_mid_line` is set, only blocks at the start of a line are found.
+pub fn next_include_start(
+    text: &str,
+    mut index: usize,
+    allow_mid_line: bool,
+) -> Option<(usize, bool)> {
+    while let Some(offset) = text[index..].find("[[") {
+        let start = index + offset;
+        index = start + 2;
+
+        if !allow_mid_line && start > 0 && !text[..start].ends_with('\n') {
+            continue;
+        }
+
+        if let Some(messy) = match_include_head(&text[index..]) {
+            return Some((start, messy));
+        }
+    }
+
+    None
+}
+
+/// Checks if the text following `[[` is an include block's name.
+fn match_include_head(text: &str) -> Option<bool> {
+    let text = text.trim_start();
+    let name = text.get(..INCLUDE_NAME.len())?;
+
+    if !name.eq_ignore_ascii_case(INCLUDE_NAME) {
+        return None;
+    }
+
+    let mut text = &text[INCLUDE_NAME.len()..];
+    let messy = match text.get(..MESSY_SUFFIX.len()) {
+        Some(suffix) if suffix.eq_ignore_ascii_case(MESSY_SUFFIX) => {
+            text = &text[MESSY_SUFFIX.len()..];
+            true
+        }
+        _ => false,
+    };
+
+    // The name must be followed by whitespace, before the page
+    if text.starts_with(char::is_whitespace) {
+        Some(messy)
+    } else {
+        None
+    }
+}
+
+#[test]
+fn scan() {
+    macro_rules! test {
+        ($text:expr, $index:expr, $allow_mid_line:expr, $expected:expr $(,)?) => {
+            assert_eq!(
+                next_include_start($text, $index, $allow_mid_line),
+                $expected,
+                "Found include start doesn't match expected",
+            );
+        };
+    }
+
+    test!("[[include page]]", 0, false, Some((0, false)));
+    test!("[[ INCLUDE page]]", 0, false, Some((0, false)));
+    test!("[[include-messy page]]", 0, false, Some((0, true)));
+    test!("[[include\npage]]", 0, false, Some((0, false)));
+    test!("[[includepage]]", 0, false, None);
+    test!("[[include-messypage]]", 0, false, None);
+    test!("[[includes page]]", 0, false, None);
+    test!("[[include", 0, false, None);
+
+    test!("A [[include page]]", 0, false, None);
+    test!("A [[include page]]", 0, true, Some((2, false)));
+    test!("A\n[[include page]]", 0, false, Some((2, false)));
+    test!("[[include a]][[include b]]", 1, false, None);
+    test!("[[include a]][[include b]]", 1, true, Some((13, false)));
+    test!("[[span]] [[include page]]", 0, true, Some((9, false)));
+}
@@ -0,0 +1,128 @@
+/*
+ * includes/section.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Extraction of a single section from an included page.
+//!
+//! This is an ftml extension, where `[[include page section=Name]]` includes
+//! only the heading named `Name` and its contents, which continues until the
+//! next heading of the same or a higher level (fewer `+`s).
+
+use std::borrow::Cow;
+
+/// The include argument which selects a section.
+pub const SECTION_ARGUMENT: &str = "section";
+
+/// Returns the given section of the page's wikitext, if it has one with that name.
+pub fn extract_section<'t>(content: Cow<'t, str>, name: &str) -> Option<Cow<'t, str>> {
+    let name = name.trim();
+    let (start, end) = find_section(&content, name)?;
+
+    let section = match content {
+        Cow::Borrowed(content) => Cow::Borrowed(&content[start..end]),
+        Cow::Owned(content) => Cow::Owned(str!(content[start..end])),
+    };
+
+    Some(section)
+}
+
+fn find_section(content: &str, name: &str) -> Option<(usize, usize)> {
+    let mut section: Option<(usize, usize)> = None;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        if let Some((level, title)) = parse_heading(line) {
+            match section {
+                // Found the end of the section
+                Some((start, section_level)) if level <= section_level => {
+                    return Some((start, offset));
+                }
+
+                // Found the start of the section
+                None if title == name => section = Some((offset, level)),
+
+                _ => (),
+            }
+        }
+
+        offset += line.len();
+    }
+
+    // The section continues to the end of the page
+    section.map(|(start, _)| (start, content.len()))
+}
+
+/// Parses a heading line, returning its level and title.
+///
+/// Headings are of the form `++ Title`, or `++* Title` to be excluded
+/// from the table of contents.
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '+').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    let rest = &line[level..];
+    let rest = rest.strip_prefix('*').unwrap_or(rest);
+    if !rest.starts_with(' ') {
+        return None;
+    }
+
+    Some((level, rest.trim()))
+}
+
+#[test]
+fn section() {
+    const PAGE: &str = "\
+Introduction
++ Apple
+Apple text
+++ Banana
+Banana text
++ Cherry
+Cherry text
+++* Durian
+Durian text";
+
+    macro_rules! test {
+        ($name:expr, $expected:expr $(,)?) => {{
+            let actual = extract_section(Cow::Borrowed(PAGE), $name);
+            let expected: Option<&str> = $expected;
+
+            assert_eq!(
+                actual.as_deref(),
+                expected,
+                "Extracted section doesn't match expected",
+            );
+        }};
+    }
+
+    test!(
+        "Apple",
+        Some("+ Apple\nApple text\n++ Banana\nBanana text\n")
+    );
+    test!("Banana", Some("++ Banana\nBanana text\n"));
+    test!(
+        " Cherry ",
+        Some("+ Cherry\nCherry text\n++* Durian\nDurian text")
+    );
+    test!("Durian", Some("++* Durian\nDurian text"));
+    test!("Introduction", None);
+    test!("Eggplant", None);
+}
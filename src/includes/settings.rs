@@ -0,0 +1,86 @@
+/*
+ * includes/settings.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/// Settings which control how include blocks are substituted.
+///
+/// Wikidot's handling of whitespace around includes is inconsistent,
+/// so these flags permit matching whichever behavior a page expects.
+/// The default preserves included content exactly, and only recognizes
+/// include blocks at the start of a line.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct IncludeSettings {
+    /// Whether newlines at the start of included content are removed.
+    pub trim_leading_newlines: bool,
+
+    /// Whether newlines at the end of included content are removed.
+    pub trim_trailing_newlines: bool,
+
+    /// Whether include blocks are recognized anywhere in a line,
+    /// instead of only at its start.
+    pub allow_mid_line: bool,
+
+    /// Whether a `section` argument limits the included content to the
+    /// heading of that name and its contents. This is an ftml extension.
+    pub allow_sections: bool,
+
+    /// Whether `{$name}` variables in included content are replaced with the
+    /// values given in the include block, escaped for where each appears.
+    pub substitute_variables: bool,
+
+    /// Whether `{$name}` variables which the include block does not give
+    /// are replaced with empty values, instead of being left as they are.
+    ///
+    /// This only applies if `substitute_variables` is enabled.
+    pub remove_unset_variables: bool,
+
+    /// The largest the text may become in bytes as includes are expanded,
+    /// such as 2 MB, or `None` for no limit.
+    ///
+    /// This stops pages which include others many times over from growing
+    /// without bound. Includes past the limit are replaced with the content
+    /// given by `Includer::include_too_large()`.
+    pub max_size: Option<usize>,
+
+    /// How many levels of includes are expanded, such as 5, counting the
+    /// page's own include blocks as the first level, or `None` to leave
+    /// include blocks within included pages as they are.
+    ///
+    /// Deeper includes are replaced with the content given by
+    /// `Includer::include_too_deep()`. An include of a page which is already
+    /// being included is replaced with that of `Includer::include_cycle()`.
+    pub max_depth: Option<usize>,
+}
+
+impl IncludeSettings {
+    /// Applies the newline trimming settings to included content.
+    pub fn trim<'a>(&self, mut content: &'a str) -> &'a str {
+        let is_newline = |c| c == '\n' || c == '\r';
+
+        if self.trim_leading_newlines {
+            content = content.trim_start_matches(is_newline);
+        }
+
+        if self.trim_trailing_newlines {
+            content = content.trim_end_matches(is_newline);
+        }
+
+        content
+    }
+}
@@ -18,7 +18,9 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use super::{include, DebugIncluder, PageRef};
+use super::{include, include_elements, DebugIncluder, IncludeSettings, PageRef};
+use crate::parsing::ParseOptions;
+use crate::tree::{ContainerType, Element};
 
 #[test]
 fn includes() {
@@ -31,7 +33,8 @@ fn includes() {
 
         ($text:expr, $expected:expr) => {{
             let mut text = str!($text);
-            let result = include(&log, &mut text, DebugIncluder, || panic!());
+            let settings = IncludeSettings::default();
+            let result = include(&log, &mut text, &settings, DebugIncluder, || panic!());
             let (output, actual) = result.expect("Fetching pages failed");
             let expected = $expected;
 
@@ -238,6 +241,20 @@ fn includes() {
         "My other wonderful page!\n\n[[include component:info-ayers\n\t|lang=en\n\t|page=scp-xxxx\n\t|authorPage=http://scpwiki.com/main\n\t|comments=\n**SCP-XXXX:** My amazing skip \n**Author:** [[*user Username]] \n]]",
         vec![PageRef::page_only("component:info-ayers")],
     );
+    test!(
+        "[[include apple\n]]\n[[include banana\n| a = 1\n]]\n[[include cherry\n]]",
+        vec![
+            PageRef::page_only("apple"),
+            PageRef::page_only("banana"),
+            PageRef::page_only("cherry"),
+        ],
+    );
+
+    // Include-like text within arguments
+    test!(
+        "[[include apple a = \n[[include banana]] \n]]",
+        vec![PageRef::page_only("apple")],
+    );
 
     // Invalid cases
 
@@ -261,3 +278,382 @@ fn includes() {
         vec![],
     );
 }
+
+#[test]
+fn include_settings() {
+    let log = crate::build_logger();
+
+    macro_rules! test {
+        ($settings:expr, $text:expr, $expected:expr $(,)?) => {{
+            let result = include(&log, $text, &$settings, DebugIncluder, || panic!());
+            let (output, _) = result.expect("Fetching pages failed");
+
+            assert_eq!(output, $expected, "Output text doesn't match expected");
+        }};
+    }
+
+    let default = IncludeSettings::default();
+    let mid_line = IncludeSettings {
+        allow_mid_line: true,
+        ..IncludeSettings::default()
+    };
+
+    test!(default, "[[include page]]", "<INCLUDED-PAGE page {}>");
+    test!(default, "[[include-messy page]]", "<INCLUDED-PAGE page {}>");
+    test!(default, "A [[include page]]", "A [[include page]]");
+    test!(mid_line, "A [[include page]]", "A <INCLUDED-PAGE page {}>");
+
+    // Adjacent includes
+    //
+    // When several pages are included, the debug includer reports the first as missing
+    test!(
+        default,
+        "[[include apple]][[include banana]]",
+        "<INCLUDED-PAGE apple {}>[[include banana]]",
+    );
+    test!(
+        mid_line,
+        "[[include apple]][[include banana]]",
+        "<MISSING-PAGE apple><INCLUDED-PAGE banana {}>",
+    );
+    test!(
+        mid_line,
+        "[[include apple a = [[include banana]] ]]",
+        r#"<INCLUDED-PAGE apple {"a" => "[[include banana"}> ]]"#,
+    );
+
+    // The debug includer's pages have no headings
+    let sections = IncludeSettings {
+        allow_sections: true,
+        ..IncludeSettings::default()
+    };
+
+    test!(
+        default,
+        "[[include page section=Apple]]",
+        r#"<INCLUDED-PAGE page {"section" => "Apple"}>"#,
+    );
+    test!(
+        sections,
+        "[[include page section=Apple]]",
+        "<MISSING-PAGE page>",
+    );
+
+    // Includes which would make the text too large are cut off
+    let max_size = IncludeSettings {
+        allow_mid_line: true,
+        max_size: Some(40),
+        ..IncludeSettings::default()
+    };
+
+    test!(max_size, "[[include page]]", "<INCLUDED-PAGE page {}>");
+    test!(
+        max_size,
+        "[[include apple]][[include banana]]",
+        "<MISSING-PAGE apple><TOO-LARGE banana>",
+    );
+}
+
+#[test]
+fn include_trim() {
+    let settings = IncludeSettings {
+        trim_leading_newlines: true,
+        trim_trailing_newlines: false,
+        allow_mid_line: false,
+        allow_sections: false,
+        substitute_variables: false,
+        remove_unset_variables: false,
+        max_size: None,
+        max_depth: None,
+    };
+
+    assert_eq!(settings.trim("\n\napple\n"), "apple\n");
+    assert_eq!(settings.trim("banana"), "banana");
+
+    let settings = IncludeSettings {
+        trim_leading_newlines: true,
+        trim_trailing_newlines: true,
+        allow_mid_line: false,
+        allow_sections: false,
+        substitute_variables: false,
+        remove_unset_variables: false,
+        max_size: None,
+        max_depth: None,
+    };
+
+    assert_eq!(settings.trim("\r\ncherry\n\n"), "cherry");
+    assert_eq!(IncludeSettings::default().trim("\ndurian\n"), "\ndurian\n");
+}
+
+#[test]
+fn include_parse_elements() {
+    let log = crate::build_logger();
+    let settings = IncludeSettings::default();
+
+    // The debug includer treats the first page as missing when there are several
+    let input = "[[include apple]]\n[[include-messy banana]]";
+    let result = include_elements(&log, input, &settings, DebugIncluder, || panic!());
+    let (output, pages) = result.expect("Fetching pages failed");
+
+    assert_eq!(
+        output, "[[include apple]]\n<INCLUDED-PAGE banana {}>",
+        "Only messy includes should be substituted",
+    );
+    assert_eq!(pages.pages().len(), 1, "Only one page should be kept");
+    assert_eq!(
+        pages.pages()[0].content,
+        "<MISSING-PAGE apple>",
+        "Kept page has the wrong contents",
+    );
+
+    // Parse, with the include as an element
+    let tokens = crate::tokenize(&log, &output);
+    let options = ParseOptions {
+        includes: Some(&pages),
+        ..ParseOptions::default()
+    };
+    let (tree, _) = crate::parse_with_options(&log, &tokens, &options)
+        .outcome
+        .into();
+
+    // The included page is on its own line, so it's kept as a block
+    match tree.elements.first() {
+        Some(Element::Include {
+            page,
+            variables,
+            elements,
+        }) => {
+            assert_eq!(page, &PageRef::page_only("apple"), "Wrong page included");
+            assert!(variables.is_empty(), "Include variables were not empty");
+            assert!(!elements.is_empty(), "Included elements were empty");
+        }
+        _ => panic!("Expected include element"),
+    }
+}
+
+#[test]
+fn include_inline() {
+    let log = crate::build_logger();
+    let settings = IncludeSettings {
+        allow_mid_line: true,
+        ..IncludeSettings::default()
+    };
+
+    let input = "Apple [[include banana]] Cherry";
+    let result = include_elements(&log, input, &settings, DebugIncluder, || panic!());
+    let (output, pages) = result.expect("Fetching pages failed");
+
+    let tokens = crate::tokenize(&log, &output);
+    let options = ParseOptions {
+        includes: Some(&pages),
+        ..ParseOptions::default()
+    };
+    let (tree, _) = crate::parse_with_options(&log, &tokens, &options)
+        .outcome
+        .into();
+
+    let elements = match tree.elements.as_slice() {
+        [Element::Container(container)]
+            if container.ctype() == ContainerType::Paragraph =>
+        {
+            container.elements()
+        }
+        _ => panic!("Expected a single paragraph at top level"),
+    };
+
+    let included = elements
+        .iter()
+        .find_map(|element| match element {
+            Element::Include { elements, .. } => Some(elements),
+            _ => None,
+        })
+        .expect("No include element in paragraph");
+
+    assert!(!included.is_empty(), "Included elements were empty");
+    assert!(
+        included.iter().all(|element| !element.is_block()),
+        "Inline include was not unwrapped from its paragraph",
+    );
+}
+
+#[test]
+fn include_heading_shift() {
+    use super::{IncludeRef, IncludedPage, IncludedPages};
+    use crate::enums::HeadingLevel;
+    use crate::parsing::ParseSettings;
+
+    let log = crate::build_logger();
+    let pages = IncludedPages::new(vec![IncludedPage {
+        include: IncludeRef::page_only(PageRef::page_only("apple")),
+        content: cow!("+ Banana"),
+    }]);
+
+    let tokens = crate::tokenize(&log, "+ Apple\n[[include apple]]");
+    let options = ParseOptions {
+        settings: ParseSettings {
+            heading_shift: 1,
+            ..ParseSettings::default()
+        },
+        includes: Some(&pages),
+    };
+    let (tree, _) = crate::parse_with_options(&log, &tokens, &options)
+        .outcome
+        .into();
+
+    // Headings in the included page are shifted like those of the page itself
+    let levels: Vec<_> = tree
+        .elements
+        .iter()
+        .flat_map(|element| match element {
+            Element::Include { elements, .. } => elements.as_slice(),
+            _ => std::slice::from_ref(element),
+        })
+        .filter_map(|element| match element {
+            Element::Container(container) => match container.ctype() {
+                ContainerType::Header(level) => Some(level),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(levels, vec![HeadingLevel::Two, HeadingLevel::Two]);
+}
+
+#[test]
+fn include_nested() {
+    use super::{FetchedPage, IncludeRef, Includer};
+    use std::borrow::Cow;
+    use void::Void;
+
+    /// Includer for a few pages which include each other.
+    #[derive(Debug)]
+    struct NestedIncluder;
+
+    impl<'t> Includer<'t> for NestedIncluder {
+        type Error = Void;
+
+        fn include_pages(
+            &mut self,
+            includes: &[IncludeRef<'t>],
+        ) -> Result<Vec<FetchedPage<'t>>, Void> {
+            let pages = includes
+                .iter()
+                .map(|include| {
+                    let page_ref = include.page_ref().clone();
+                    let content = match page_ref.page() {
+                        "apple" => Some("A [[include banana]]"),
+                        "banana" => Some("B [[include cherry]]"),
+                        "cherry" => Some("C"),
+                        "durian" => Some("D [[include durian]]"),
+                        _ => None,
+                    };
+
+                    FetchedPage {
+                        page_ref,
+                        content: content.map(Cow::Borrowed),
+                    }
+                })
+                .collect();
+
+            Ok(pages)
+        }
+
+        fn no_such_include(
+            &mut self,
+            page_ref: &PageRef<'t>,
+        ) -> Result<Cow<'t, str>, Void> {
+            DebugIncluder.no_such_include(page_ref)
+        }
+
+        fn include_cycle(&mut self, chain: &[PageRef<'t>]) -> Result<Cow<'t, str>, Void> {
+            DebugIncluder.include_cycle(chain)
+        }
+
+        fn include_too_deep(
+            &mut self,
+            chain: &[PageRef<'t>],
+        ) -> Result<Cow<'t, str>, Void> {
+            DebugIncluder.include_too_deep(chain)
+        }
+    }
+
+    let log = crate::build_logger();
+
+    macro_rules! test {
+        ($max_depth:expr, $text:expr, $expected:expr, $pages:expr $(,)?) => {{
+            let settings = IncludeSettings {
+                allow_mid_line: true,
+                max_depth: $max_depth,
+                ..IncludeSettings::default()
+            };
+
+            let result = include(&log, $text, &settings, NestedIncluder, || panic!());
+            let (output, pages) = result.expect("Fetching pages failed");
+            let expected_pages: Vec<_> =
+                $pages.iter().copied().map(PageRef::page_only).collect();
+
+            assert_eq!(output, $expected, "Output text doesn't match expected");
+            assert_eq!(pages, expected_pages, "Included pages don't match expected");
+        }};
+    }
+
+    test!(None, "[[include apple]]", "A [[include banana]]", ["apple"]);
+    test!(
+        Some(1),
+        "[[include apple]]",
+        "A <TOO-DEEP apple -> banana>",
+        ["apple"],
+    );
+    test!(
+        Some(5),
+        "[[include apple]]",
+        "A B C",
+        ["apple", "banana", "cherry"],
+    );
+    test!(
+        Some(2),
+        "[[include apple]] [[include banana]]",
+        "A B <TOO-DEEP apple -> banana -> cherry> B C",
+        ["apple", "banana", "banana", "cherry"],
+    );
+    test!(
+        Some(5),
+        "[[include durian]]",
+        "D <CYCLE durian -> durian>",
+        ["durian"],
+    );
+    test!(
+        Some(5),
+        "[[include apple]] [[include fig]]",
+        "A B C <MISSING-PAGE fig>",
+        ["apple", "banana", "cherry", "fig"],
+    );
+
+    // Including at parse time also gives the nested pages
+    let settings = IncludeSettings {
+        allow_mid_line: true,
+        max_depth: Some(5),
+        ..IncludeSettings::default()
+    };
+
+    let result = include_elements(
+        &log,
+        "[[include apple]]",
+        &settings,
+        NestedIncluder,
+        || panic!(),
+    );
+    let (_, pages) = result.expect("Fetching pages failed");
+
+    assert_eq!(
+        pages.pages().len(),
+        1,
+        "Included pages don't match expected"
+    );
+    assert_eq!(
+        pages.nested(),
+        [PageRef::page_only("banana"), PageRef::page_only("cherry")],
+        "Nested pages don't match expected",
+    );
+}
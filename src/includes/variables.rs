@@ -0,0 +1,623 @@
+/*
+ * includes/variables.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Context-aware escaping of include variables.
+//!
+//! Wikidot replaces each `{$name}` in an included page with the value given
+//! in the include block, as plain text. This breaks whenever the value has
+//! characters which are meaningful where the variable appears, for instance
+//! a `"` within a quoted argument, or a newline within a block head.
+//!
+//! So here the value is escaped according to its context:
+//! * In regular text, it is inserted as-is, since values may contain wikitext.
+//! * Within a quoted argument, it is escaped as a string.
+//! * As an unquoted argument value, as in `key={$name}`, it is written as a string.
+//!   If other text follows directly, as in `key={$name}pie`, the value is not
+//!   the whole argument, so it is escaped as in the rest of the block head.
+//! * Elsewhere in a block head, newlines become spaces and `]]` becomes `]&#93;`.
+//! * Within an `[[include]]` block, where arguments are never quoted, the same
+//!   is done, and `|` also becomes `&#124;` so the value can't end its argument.
+//! * Within the body of a raw block, like `[[code]]`, any `[[/code` which would
+//!   close the block is broken with a zero-width space. This is the one case
+//!   which changes the value, as these bodies have no escape syntax.
+//!
+//! Variables which are not given are left as they are, unless `remove_unset`
+//! is set, in which case they are substituted as though they were empty.
+
+use super::IncludeVariables;
+use crate::span::Span;
+use std::borrow::Cow;
+
+/// Blocks whose body is read as raw text, and so cannot contain their end block.
+const RAW_BODY_BLOCKS: [&str; 4] = ["code", "css", "chart", "mermaid"];
+
+/// Where in the wikitext a variable appears.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VariableContext<'t> {
+    /// Regular wikitext.
+    Text,
+
+    /// Within a double-quoted string in a block head.
+    String,
+
+    /// Immediately following the `=` of an argument, where a string is expected.
+    Argument,
+
+    /// Elsewhere in a block head, such as `[[tab {$label}]]`.
+    BlockHead,
+
+    /// Within the head of an `[[include]]` block, which has no string syntax.
+    IncludeHead,
+
+    /// Within the body of the given raw block, such as `[[code]]`.
+    RawBody(&'t str),
+}
+
+/// A `{$name}` variable found in wikitext.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FoundVariable<'t> {
+    /// The name of the variable, without the surrounding `{$` and `}`.
+    pub name: &'t str,
+
+    /// Where the whole `{$name}` is in the wikitext.
+    pub span: Span,
+
+    /// Where the variable appears, which determines how its value is escaped.
+    pub context: VariableContext<'t>,
+}
+
+/// Finds each `{$name}` variable in the wikitext, along with its context.
+pub fn find_variables(content: &str) -> Vec<FoundVariable> {
+    let mut variables = Vec::new();
+    let mut scanner = Scanner::default();
+    let mut index = 0;
+
+    while let Some(ch) = content[index..].chars().next() {
+        let rest = &content[index..];
+
+        if let Some((name, length)) = parse_variable(rest) {
+            let mut context = scanner.context();
+
+            // Only quote a value which is the whole argument
+            if context == VariableContext::Argument && !ends_value(&rest[length..]) {
+                context = VariableContext::BlockHead;
+            }
+
+            variables.push(FoundVariable {
+                name,
+                span: Span::new(index, index + length),
+                context,
+            });
+
+            scanner.end_value();
+            index += length;
+            continue;
+        }
+
+        index += scanner.step(rest).unwrap_or_else(|| ch.len_utf8());
+    }
+
+    variables
+}
+
+/// Replaces each `{$name}` in the content with its value, escaped for its context.
+pub fn substitute_variables<'t>(
+    content: Cow<'t, str>,
+    variables: &IncludeVariables,
+    remove_unset: bool,
+) -> Cow<'t, str> {
+    if (variables.is_empty() && !remove_unset) || !content.contains("{$") {
+        return content;
+    }
+
+    let mut output = String::with_capacity(content.len());
+    let mut last = 0;
+
+    for variable in find_variables(&content) {
+        let value = match variables.get(variable.name) {
+            Some(value) => value.as_ref(),
+            None if remove_unset => "",
+            None => continue,
+        };
+
+        output.push_str(&content[last..variable.span.start]);
+        output.push_str(&escape_variable(value, variable.context));
+        last = variable.span.end;
+    }
+
+    output.push_str(&content[last..]);
+    Cow::Owned(output)
+}
+
+/// Escapes a variable's value for the context it appears in.
+pub fn escape_variable(value: &str, context: VariableContext) -> String {
+    let mut output = String::with_capacity(value.len());
+
+    match context {
+        VariableContext::Text => output.push_str(value),
+        VariableContext::String => escape_string(&mut output, value),
+        VariableContext::Argument => {
+            output.push('"');
+            escape_string(&mut output, value);
+            output.push('"');
+        }
+        VariableContext::BlockHead => escape_head(&mut output, value, false),
+        VariableContext::IncludeHead => escape_head(&mut output, value, true),
+        VariableContext::RawBody(name) => {
+            let mut rest = value;
+
+            while let Some(index) = rest.find("[[/") {
+                let (before, after) = rest.split_at(index);
+                output.push_str(before);
+
+                if starts_with_ignore_case(&after[3..], name) {
+                    output.push_str("[\u{200b}[/");
+                } else {
+                    output.push_str("[[/");
+                }
+
+                rest = &after[3..];
+            }
+
+            output.push_str(rest);
+        }
+    }
+
+    output
+}
+
+/// Escapes the value as the inside of a double-quoted string.
+///
+/// This is the inverse of `parsing::parse_string()`.
+fn escape_string(output: &mut String, value: &str) {
+    for ch in value.chars() {
+        match ch {
+            '\\' => output.push_str("\\\\"),
+            '"' => output.push_str("\\\""),
+            '\r' => output.push_str("\\r"),
+            '\n' => output.push_str("\\n"),
+            '\t' => output.push_str("\\t"),
+            _ => output.push(ch),
+        }
+    }
+}
+
+/// Escapes the value as part of a block head, keeping it on one line.
+///
+/// In include heads, pipes are also escaped, since they separate arguments.
+fn escape_head(output: &mut String, value: &str, escape_pipes: bool) {
+    let mut chars = value.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\r' if chars.peek() == Some(&'\n') => (),
+            '\r' | '\n' => output.push(' '),
+            '|' if escape_pipes => output.push_str("&#124;"),
+            ']' if chars.peek() == Some(&']') => {
+                chars.next();
+                output.push_str("]&#93;");
+            }
+            _ => output.push(ch),
+        }
+    }
+}
+
+/// Parses a `{$name}` at the start of the text, returning the name and its length.
+fn parse_variable(text: &str) -> Option<(&str, usize)> {
+    let rest = text.strip_prefix("{$")?;
+    let length = rest
+        .find(|ch: char| !is_name_char(ch))
+        .unwrap_or(rest.len());
+
+    if length == 0 || !rest[length..].starts_with('}') {
+        return None;
+    }
+
+    Some((&rest[..length], length + 3))
+}
+
+/// Whether the text after an argument's value ends that value.
+#[inline]
+fn ends_value(text: &str) -> bool {
+    text.is_empty() || text.starts_with("]]") || text.starts_with(char::is_whitespace)
+}
+
+#[inline]
+fn is_name_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '-' || ch == '_'
+}
+
+#[inline]
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    text.len() >= prefix.len()
+        && text.is_char_boundary(prefix.len())
+        && text[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+/// Tracks enough of the wikitext's structure to determine each variable's context.
+#[derive(Debug, Default)]
+struct Scanner<'t> {
+    head: Option<BlockHead<'t>>,
+    raw_body: Option<&'t str>,
+}
+
+#[derive(Debug)]
+struct BlockHead<'t> {
+    name: &'t str,
+    end: bool,
+    in_string: bool,
+    after_equals: bool,
+}
+
+impl BlockHead<'_> {
+    fn is_include(&self) -> bool {
+        self.name.eq_ignore_ascii_case("include")
+            || self.name.eq_ignore_ascii_case("include-messy")
+    }
+}
+
+impl<'t> Scanner<'t> {
+    fn context(&self) -> VariableContext<'t> {
+        if let Some(name) = self.raw_body {
+            return VariableContext::RawBody(name);
+        }
+
+        match self.head {
+            None => VariableContext::Text,
+            Some(ref head) if head.is_include() => VariableContext::IncludeHead,
+            Some(BlockHead {
+                in_string: true, ..
+            }) => VariableContext::String,
+            Some(BlockHead {
+                after_equals: true, ..
+            }) => VariableContext::Argument,
+            Some(_) => VariableContext::BlockHead,
+        }
+    }
+
+    /// Updates the state after a variable's value, which is complete as written.
+    fn end_value(&mut self) {
+        if let Some(ref mut head) = self.head {
+            head.after_equals = false;
+        }
+    }
+
+    /// Updates the state for the start of the text.
+    ///
+    /// Returns the length consumed, if more than a single character.
+    fn step(&mut self, text: &'t str) -> Option<usize> {
+        // Within a raw body, only look for its end block
+        if let Some(name) = self.raw_body {
+            if let Some(rest) = text.strip_prefix("[[/") {
+                if starts_with_ignore_case(rest, name) {
+                    self.raw_body = None;
+                }
+            }
+
+            return None;
+        }
+
+        match self.head {
+            Some(ref mut head) if head.in_string => {
+                if text.starts_with('\\') {
+                    return text.chars().nth(1).map(|ch| 1 + ch.len_utf8());
+                }
+
+                match text.chars().next() {
+                    Some('"') => head.in_string = false,
+                    Some('\n') => self.head = None,
+                    _ => (),
+                }
+
+                None
+            }
+            Some(ref mut head) => {
+                if text.starts_with("]]") {
+                    let head = self.head.take().unwrap();
+                    let is_raw = RAW_BODY_BLOCKS
+                        .iter()
+                        .any(|raw| raw.eq_ignore_ascii_case(head.name));
+
+                    if is_raw && !head.end {
+                        self.raw_body = Some(head.name);
+                    }
+
+                    return Some(2);
+                }
+
+                // Include heads have no strings, and may span several lines
+                match text.chars().next() {
+                    Some('"') if !head.is_include() => {
+                        head.in_string = true;
+                        head.after_equals = false;
+                    }
+                    Some('=') => head.after_equals = true,
+                    Some('\n') if !head.is_include() => self.head = None,
+                    Some(ch) if ch.is_whitespace() => (),
+                    _ => head.after_equals = false,
+                }
+
+                None
+            }
+            None => {
+                // Links, as in "[[[page]]]", are not blocks
+                if text.starts_with("[[[") {
+                    return Some(3);
+                }
+
+                let rest = text.strip_prefix("[[")?;
+                let (end, rest) = match rest.strip_prefix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, rest.strip_prefix('*').unwrap_or(rest)),
+                };
+
+                let length = rest
+                    .find(|ch: char| !is_name_char(ch))
+                    .unwrap_or(rest.len());
+
+                self.head = Some(BlockHead {
+                    name: &rest[..length],
+                    end,
+                    in_string: false,
+                    after_equals: false,
+                });
+
+                Some(text.len() - rest.len() + length)
+            }
+        }
+    }
+}
+
+#[test]
+fn variable_contexts() {
+    use self::VariableContext::*;
+
+    macro_rules! test {
+        ($content:expr, $expected:expr $(,)?) => {{
+            let contexts: Vec<_> = find_variables($content)
+                .iter()
+                .map(|variable| (variable.name, variable.context))
+                .collect();
+
+            assert_eq!(
+                contexts, $expected,
+                "Variable contexts don't match expected",
+            );
+        }};
+    }
+
+    // Regular text
+    test!("", Vec::<(&str, VariableContext)>::new());
+    test!("{$apple} pie {$banana", vec![("apple", Text)]);
+    test!(
+        "[[[{$apple}]]] {$banana}",
+        vec![("apple", Text), ("banana", Text)]
+    );
+
+    // Block heads
+    test!(
+        r#"[[span class="{$apple}" title = {$banana}]]"#,
+        vec![("apple", VariableContext::String), ("banana", Argument)],
+    );
+    test!(
+        r#"[[span title="\"{$apple}\""]]"#,
+        vec![("apple", VariableContext::String)],
+    );
+    test!(
+        "[[tab {$apple}]]\n{$banana}",
+        vec![("apple", BlockHead), ("banana", Text)],
+    );
+    test!(
+        "[[span title={$apple}{$banana} class={$cherry}pie]]",
+        vec![
+            ("apple", BlockHead),
+            ("banana", BlockHead),
+            ("cherry", BlockHead)
+        ],
+    );
+    test!(
+        "[[span title={$apple-pie} class={$banana_split}]]",
+        vec![("apple-pie", Argument), ("banana_split", Argument)],
+    );
+
+    // Include heads
+    test!(
+        "[[include page | a={$apple} | b=\"{$banana}\"\n| c = {$cherry}]] {$durian}",
+        vec![
+            ("apple", IncludeHead),
+            ("banana", IncludeHead),
+            ("cherry", IncludeHead),
+            ("durian", Text),
+        ],
+    );
+
+    // Raw bodies
+    test!(
+        "[[code]]\n{$apple}\n[[/code]]\n{$banana}",
+        vec![("apple", RawBody("code")), ("banana", Text)],
+    );
+    test!(
+        "[[CODE type=\"{$apple}\"]]\n{$banana}\n[[/CODE]]",
+        vec![
+            ("apple", VariableContext::String),
+            ("banana", RawBody("CODE")),
+        ],
+    );
+
+    assert_eq!(
+        find_variables("Apple {$banana}")[0].span,
+        Span::new(6, 15),
+        "Variable span doesn't match expected",
+    );
+}
+
+#[test]
+fn escape() {
+    use self::VariableContext::*;
+
+    macro_rules! test {
+        ($value:expr, $context:expr, $expected:expr $(,)?) => {
+            assert_eq!(
+                escape_variable($value, $context),
+                $expected,
+                "Escaped value doesn't match expected",
+            );
+        };
+    }
+
+    test!("Banana]] [[span", Text, "Banana]] [[span");
+    test!("Cherry\nDurian", Text, "Cherry\nDurian");
+    test!(
+        r#"Say "hi" \o/"#,
+        VariableContext::String,
+        r#"Say \"hi\" \\o/"#,
+    );
+    test!(
+        "Cherry\nDurian",
+        VariableContext::String,
+        r#"Cherry\nDurian"#
+    );
+    test!("Banana]] [[span", Argument, r#""Banana]] [[span""#);
+    test!("Banana]] [[span", BlockHead, "Banana]&#93; [[span");
+    test!("Cherry\r\nDurian\nFig", BlockHead, "Cherry Durian Fig");
+    test!("Banana | Cherry", BlockHead, "Banana | Cherry");
+    test!(
+        "Banana]] | Cherry\nDurian",
+        IncludeHead,
+        "Banana]&#93; &#124; Cherry Durian",
+    );
+    test!(
+        "x\n[[/code]]\ny",
+        RawBody("code"),
+        "x\n[\u{200b}[/code]]\ny"
+    );
+    test!(
+        "x\n[[/CODE]]\ny",
+        RawBody("code"),
+        "x\n[\u{200b}[/CODE]]\ny"
+    );
+    test!("x\n[[/code]]\ny", RawBody("css"), "x\n[[/code]]\ny");
+}
+#[test]
+fn variables() {
+    macro_rules! test {
+        ($content:expr, $expected:expr $(,)?) => {
+            test!($content, $expected, false)
+        };
+        ($content:expr, $expected:expr, $remove_unset:expr $(,)?) => {{
+            let mut variables = IncludeVariables::new();
+            variables.insert(cow!("plain"), cow!("Apple"));
+            variables.insert(cow!("quote"), cow!(r#"Say "hi" \o/"#));
+            variables.insert(cow!("block"), cow!("Banana]] [[span"));
+            variables.insert(cow!("lines"), cow!("Cherry\nDurian"));
+            variables.insert(cow!("code"), cow!("x\n[[/code]]\ny"));
+
+            let actual =
+                substitute_variables(Cow::Borrowed($content), &variables, $remove_unset);
+
+            assert_eq!(
+                actual, $expected,
+                "Substituted content doesn't match expected"
+            );
+        }};
+    }
+
+    // Regular text
+    test!("", "");
+    test!("{$plain} pie", "Apple pie");
+    test!("{$missing} {$plain", "{$missing} {$plain");
+    test!("{$block}", "Banana]] [[span");
+    test!("{$lines}", "Cherry\nDurian");
+
+    // Unset variables
+    test!("{$missing} {$plain}", " Apple", true);
+    test!(
+        r#"[[span class={$missing} title="{$missing}"]]"#,
+        r#"[[span class="" title=""]]"#,
+        true,
+    );
+
+    // Quoted arguments
+    test!(r#"[[span class="{$plain}"]]"#, r#"[[span class="Apple"]]"#,);
+    test!(
+        r#"[[span title="A {$quote}"]]"#,
+        r#"[[span title="A Say \"hi\" \\o/"]]"#,
+    );
+    test!(
+        r#"[[span title="{$block}"]]"#,
+        r#"[[span title="Banana]] [[span"]]"#,
+    );
+    test!(
+        r#"[[span title="{$lines}"]] {$lines}"#,
+        r#"[[span title="Cherry\nDurian"]] Cherry
+Durian"#,
+    );
+    test!(
+        r#"[[span title="\"{$plain}\""]]"#,
+        r#"[[span title="\"Apple\""]]"#,
+    );
+
+    // Unquoted arguments
+    test!(
+        "[[span class={$plain} title = {$quote}]]",
+        r#"[[span class="Apple" title = "Say \"hi\" \\o/"]]"#,
+    );
+    test!(
+        "[[span title={$plain}{$plain} class={$plain}pie]]",
+        "[[span title=AppleApple class=Applepie]]",
+    );
+    test!(
+        "[[span title={$block}]]",
+        r#"[[span title="Banana]] [[span"]]"#,
+    );
+
+    // Block heads
+    test!("[[tab {$plain}]]", "[[tab Apple]]");
+    test!("[[tab {$block}]]", "[[tab Banana]&#93; [[span]]");
+    test!(
+        "[[tab {$lines}]]\n{$lines}",
+        "[[tab Cherry Durian]]\nCherry\nDurian"
+    );
+    test!("[[[{$plain}]]] {$block}", "[[[Apple]]] Banana]] [[span");
+
+    // Include heads
+    test!(
+        "[[include page | a={$block} | b = {$lines}]]",
+        "[[include page | a=Banana]&#93; [[span | b = Cherry Durian]]",
+    );
+    test!(
+        "[[include page\n| a={$plain}\n]] {$block}",
+        "[[include page\n| a=Apple\n]] Banana]] [[span",
+    );
+
+    // Raw bodies
+    test!(
+        "[[code]]\n{$code}\n[[/code]]\n{$code}",
+        "[[code]]\nx\n[\u{200b}[/code]]\ny\n[[/code]]\nx\n[[/code]]\ny",
+    );
+    test!(
+        "[[CODE type=\"{$plain}\"]]\n{$code}\n[[/CODE]]",
+        "[[CODE type=\"Apple\"]]\nx\n[\u{200b}[/code]]\ny\n[[/CODE]]",
+    );
+    test!(
+        "[[css]]\n{$code}\n[[/css]]",
+        "[[css]]\nx\n[[/code]]\ny\n[[/css]]",
+    );
+}
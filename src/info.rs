@@ -0,0 +1,63 @@
+/*
+ * info.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Information on this build of ftml, for compatibility checks by consumers.
+
+/// The version of the serialized `SyntaxTree` format.
+///
+/// This is incremented whenever a change to the tree would break consumers
+/// reading its JSON form, such as renaming or removing fields.
+/// Additions, such as new element types, do not change it.
+pub const SYNTAX_TREE_VERSION: u32 = 2;
+
+/// Lists the optional crate features this build was compiled with.
+pub fn features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+
+    if cfg!(feature = "html") {
+        features.push("html");
+    }
+
+    if cfg!(feature = "json") {
+        features.push("json");
+    }
+
+    if cfg!(feature = "markdown") {
+        features.push("markdown");
+    }
+
+    if cfg!(feature = "text") {
+        features.push("text");
+    }
+
+    if cfg!(feature = "debug") {
+        features.push("debug");
+    }
+
+    if cfg!(feature = "profile") {
+        features.push("profile");
+    }
+
+    if cfg!(feature = "manual-lexer") {
+        features.push("manual-lexer");
+    }
+
+    features
+}
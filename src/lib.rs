@@ -46,6 +46,7 @@ extern crate regex;
 
 #[macro_use]
 extern crate serde;
+#[cfg(any(test, feature = "html", feature = "json", feature = "profile"))]
 extern crate serde_json;
 
 #[macro_use]
@@ -77,23 +78,28 @@ mod log;
 mod macros;
 
 mod enums;
-mod preproc;
-mod span_wrap;
+mod span;
 mod text;
 
+pub mod conformance;
 pub mod data;
 pub mod includes;
+pub mod info;
 pub mod parsing;
+pub mod preproc;
 pub mod render;
+pub mod testing;
 pub mod tokenizer;
 pub mod tree;
 
 #[cfg(test)]
 pub use self::log::{build_console_logger, build_logger, build_null_logger};
 
-pub use self::includes::include;
-pub use self::parsing::parse;
-pub use self::preproc::preprocess;
+pub use self::includes::{include, include_elements};
+pub use self::parsing::{parse, parse_events, parse_with_options};
+
+pub use self::preproc::{preprocess, preprocess_with_edits, preprocess_with_format};
+pub use self::span::Span;
 pub use self::tokenizer::{tokenize, Tokenization};
 
 pub mod prelude {
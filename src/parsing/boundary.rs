@@ -0,0 +1,167 @@
+/*
+ * parsing/boundary.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Locations of blocks within the source text, and how they were written.
+//!
+//! These are intended for editors, to implement folding and highlighting
+//! of matching block heads and tails. They also record details which the
+//! syntax tree discards, for instance `[[DIV class="a"]]` and
+//! `[[div  class = "a" ]]` produce the same element. So a wikitext generator
+//! can reproduce the author's formatting by reusing the source of unchanged blocks.
+
+use crate::span::Span;
+use std::borrow::Cow;
+
+/// The location of a block, such as `[[div]]` ... `[[/div]]`.
+///
+/// Only blocks which were successfully parsed are included, in the order
+/// their heads appear in the source text. Elements do not record where
+/// they came from, so a block is identified by the span of its head.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct BlockBoundary<'t> {
+    /// The name of the block, as written in its head.
+    ///
+    /// This keeps its original casing and any `_` suffix.
+    name: Cow<'t, str>,
+
+    /// Whether the block was invoked with `[[*`.
+    special: bool,
+
+    /// The span of the block's head, such as `[[div class="x"]]`.
+    head: Span,
+
+    /// The span of the block's tail, such as `[[/div]]`, if it has one.
+    ///
+    /// Blocks without a body, such as `[[image]]`, have no tail.
+    tail: Option<Span>,
+}
+
+impl<'t> BlockBoundary<'t> {
+    #[inline]
+    pub fn new(name: &'t str, special: bool, head: Span, tail: Option<Span>) -> Self {
+        BlockBoundary {
+            name: Cow::Borrowed(name),
+            special,
+            head,
+            tail,
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn special(&self) -> bool {
+        self.special
+    }
+
+    #[inline]
+    pub fn head(&self) -> Span {
+        self.head
+    }
+
+    #[inline]
+    pub fn tail(&self) -> Option<Span> {
+        self.tail
+    }
+
+    /// The span of the entire block, from the start of its head to the end of its tail.
+    #[inline]
+    pub fn span(&self) -> Span {
+        match self.tail {
+            Some(tail) => self.head.merge(tail),
+            None => self.head,
+        }
+    }
+
+    /// The text of the block's head, including its whitespace and argument order.
+    #[inline]
+    pub fn head_source<'a>(&self, text: &'a str) -> &'a str {
+        self.head.slice(text)
+    }
+
+    /// The text of the block's tail, if it has one.
+    #[inline]
+    pub fn tail_source<'a>(&self, text: &'a str) -> Option<&'a str> {
+        self.tail.map(|tail| tail.slice(text))
+    }
+
+    pub fn to_owned(&self) -> BlockBoundary<'static> {
+        BlockBoundary {
+            name: Cow::Owned(self.name.to_string()),
+            special: self.special,
+            head: self.head,
+            tail: self.tail,
+        }
+    }
+}
+
+#[test]
+fn boundaries() {
+    use crate::parsing::ParseOptions;
+
+    let log = crate::build_logger();
+    let text = "[[div]]\n[[span]]apple[[/span]]\n[[/div]]\n[[span]]banana";
+    let tokenization = crate::tokenize(&log, text);
+    let options = ParseOptions::default();
+    let blocks = crate::parse_with_options(&log, &tokenization, &options).blocks;
+
+    // The final span is unclosed, so it is not a block
+    assert_eq!(
+        blocks,
+        vec![
+            BlockBoundary::new("div", false, Span::new(0, 7), Some(Span::new(31, 39))),
+            BlockBoundary::new("span", false, Span::new(8, 16), Some(Span::new(21, 30))),
+        ],
+    );
+
+    assert_eq!(
+        blocks[0].span().slice(text),
+        "[[div]]\n[[span]]apple[[/span]]\n[[/div]]"
+    );
+}
+
+#[test]
+fn formatting() {
+    use crate::parsing::ParseOptions;
+
+    let log = crate::build_logger();
+    let text = "[[DIV class=\"apple\"]]\n[[Image banana.png]]\n[[/div ]]";
+    let tokenization = crate::tokenize(&log, text);
+    let options = ParseOptions::default();
+    let output = crate::parse_with_options(&log, &tokenization, &options);
+
+    assert_eq!(output.blocks.len(), 2);
+
+    let div = output.block(Span::new(0, 21)).expect("No block for div");
+    assert_eq!(div.name(), "DIV");
+    assert!(!div.special());
+    assert_eq!(div.head_source(text), "[[DIV class=\"apple\"]]");
+    assert_eq!(div.tail_source(text), Some("[[/div ]]"));
+
+    let image = output.block(Span::new(22, 42)).expect("No block for image");
+    assert_eq!(image.name(), "Image");
+    assert!(!image.special());
+    assert_eq!(image.head_source(text), "[[Image banana.png]]");
+    assert_eq!(image.tail(), None);
+}
@@ -59,7 +59,7 @@ pub fn collect_container<'p, 'r, 't>(
     );
 
     // Iterate and consume all the tokens
-    let (elements, exceptions) = collect_consume(
+    let (elements, mut exceptions) = collect_consume(
         log,
         parser,
         rule,
@@ -70,8 +70,8 @@ pub fn collect_container<'p, 'r, 't>(
     .into();
 
     // Package into a container
-    ok!(
-        Element::Container(Container::new(container_type, elements)),
-        exceptions,
-    )
+    let element = Element::Container(Container::new(container_type, elements));
+    parser.check_children(&element, &mut exceptions);
+
+    ok!(element, exceptions)
 }
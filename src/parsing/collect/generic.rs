@@ -19,7 +19,6 @@
  */
 
 use super::prelude::*;
-use crate::span_wrap::SpanWrap;
 
 /// Generic function to parse upcoming tokens until conditions are met.
 ///
@@ -81,7 +80,7 @@ where
             "rule" => str!(rule.name()),
             "token" => str!(token.name()),
             "slice" => str!(slice),
-            "span" => SpanWrap::from(span),
+            "span" => *span,
             "remaining-len" => parser.remaining().len(),
             "close-conditions" => format!("{:?}", close_conditions),
             "invalid-conditions" => format!("{:?}", invalid_conditions),
@@ -29,7 +29,6 @@
 use super::prelude::*;
 use super::rule::{get_rules_for_token, impls::RULE_FALLBACK};
 use super::Parser;
-use crate::span_wrap::SpanWrap;
 use std::mem;
 
 /// Main function that consumes tokens to produce a single element, then returns.
@@ -43,7 +42,7 @@ pub fn consume<'p, 'r, 't>(
     let log = &log.new(slog_o!(
         "token" => parser.current().token,
         "slice" => str!(parser.current().slice),
-        "span" => SpanWrap::from(&parser.current().span),
+        "span" => parser.current().span,
         "remaining-len" => parser.remaining().len(),
     ));
 
@@ -58,6 +57,8 @@ pub fn consume<'p, 'r, 't>(
     for &rule in get_rules_for_token(current) {
         info!(log, "Trying rule consumption for tokens"; "rule" => rule);
 
+        parser.record_rule_attempt();
+
         let old_remaining = parser.remaining();
         match rule.try_consume(log, parser) {
             Ok(output) => {
@@ -87,6 +88,7 @@ pub fn consume<'p, 'r, 't>(
     }
 
     debug!(log, "All rules exhausted, using generic text fallback");
+    parser.record_fallback();
     let element = text!(current.slice);
     parser.step()?;
 
@@ -103,8 +105,32 @@ pub fn consume<'p, 'r, 't>(
     }
 
     // Add fallback warning to exceptions list
+    //
+    // Closing delimiters with nothing to close get their own kind,
+    // so the author can be told specifically what is wrong.
+    //
+    // If the head before it also fell back, such as a block which failed
+    // to parse, then the delimiter is part of that and isn't orphaned.
+    let kind = match current.token {
+        Token::LeftBlock | Token::LeftBlockEnd | Token::LeftBlockSpecial => {
+            parser.set_fallback_head(Token::RightBlock);
+            ParseWarningKind::NoRulesMatch
+        }
+        Token::LeftComment => {
+            parser.set_fallback_head(Token::RightComment);
+            ParseWarningKind::NoRulesMatch
+        }
+        Token::RightBlock if !parser.take_fallback_head(Token::RightBlock) => {
+            ParseWarningKind::OrphanedCloseBlock
+        }
+        Token::RightComment if !parser.take_fallback_head(Token::RightComment) => {
+            ParseWarningKind::OrphanedCloseComment
+        }
+        _ => ParseWarningKind::NoRulesMatch,
+    };
+
     all_exceptions.push(ParseException::Warning(ParseWarning::new(
-        ParseWarningKind::NoRulesMatch,
+        kind,
         RULE_FALLBACK,
         current,
     )));
@@ -0,0 +1,150 @@
+/*
+ * parsing/context.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Flags describing what kind of content the parser is currently within.
+//!
+//! Rules set these while parsing nested content, so that rules
+//! deeper in the tree can adjust their behavior. For instance,
+//! block constructs are not permitted within link labels.
+
+use strum_macros::IntoStaticStr;
+
+/// A single kind of context the parser can be within.
+#[derive(IntoStaticStr, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum ParseContextFlag {
+    /// Within an item of a list.
+    List,
+
+    /// Within a cell of a simple (`||`) table.
+    Table,
+
+    /// Within text which is rendered inside a link, such as a collapsible's show text.
+    LinkLabel,
+
+    /// Within the contents of a footnote.
+    Footnote,
+
+    /// Within the value of a block's argument, such as an image's caption.
+    Argument,
+}
+
+impl ParseContextFlag {
+    const LIST: [ParseContextFlag; 5] = [
+        ParseContextFlag::List,
+        ParseContextFlag::Table,
+        ParseContextFlag::LinkLabel,
+        ParseContextFlag::Footnote,
+        ParseContextFlag::Argument,
+    ];
+
+    #[inline]
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
+
+    /// Whether block elements may not be produced within this context.
+    #[inline]
+    pub fn forbids_blocks(self) -> bool {
+        matches!(
+            self,
+            ParseContextFlag::LinkLabel | ParseContextFlag::Argument
+        )
+    }
+
+    #[inline]
+    fn bit(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+/// The set of context flags currently active in the parser.
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct ParseContext {
+    bits: u8,
+}
+
+impl ParseContext {
+    #[inline]
+    pub fn contains(self, flag: ParseContextFlag) -> bool {
+        self.bits & flag.bit() != 0
+    }
+
+    #[inline]
+    pub fn insert(&mut self, flag: ParseContextFlag) {
+        self.bits |= flag.bit();
+    }
+
+    #[inline]
+    pub fn remove(&mut self, flag: ParseContextFlag) {
+        self.bits &= !flag.bit();
+    }
+
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    /// Whether any active flag prohibits block elements.
+    pub fn forbids_blocks(self) -> bool {
+        ParseContextFlag::LIST
+            .iter()
+            .any(|&flag| self.contains(flag) && flag.forbids_blocks())
+    }
+}
+
+impl slog::Value for ParseContext {
+    fn serialize(
+        &self,
+        _: &slog::Record,
+        key: slog::Key,
+        serializer: &mut dyn slog::Serializer,
+    ) -> slog::Result {
+        let names = ParseContextFlag::LIST
+            .iter()
+            .filter(|&&flag| self.contains(flag))
+            .map(|flag| flag.name())
+            .collect::<Vec<_>>();
+
+        serializer.emit_str(key, &names.join(","))
+    }
+}
+
+#[test]
+fn context() {
+    let mut context = ParseContext::default();
+    assert!(context.is_empty());
+    assert!(!context.forbids_blocks());
+
+    context.insert(ParseContextFlag::Table);
+    assert!(context.contains(ParseContextFlag::Table));
+    assert!(!context.contains(ParseContextFlag::List));
+    assert!(!context.forbids_blocks());
+
+    context.insert(ParseContextFlag::LinkLabel);
+    assert!(context.contains(ParseContextFlag::LinkLabel));
+    assert!(context.forbids_blocks());
+
+    context.remove(ParseContextFlag::LinkLabel);
+    context.remove(ParseContextFlag::Table);
+    assert!(context.is_empty());
+
+    context.insert(ParseContextFlag::Argument);
+    assert!(context.forbids_blocks());
+}
@@ -0,0 +1,173 @@
+/*
+ * parsing/event.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Events produced by the streaming parser, see `parse_events()`.
+//!
+//! Each element is flattened into a sequence of events, so consumers may
+//! walk the page without a `SyntaxTree` ever being built.
+
+use super::ParseWarning;
+use crate::tree::{for_each_child_list, Element};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ParseEvent<'e, 't> {
+    /// The start of an element with children, such as a paragraph or link.
+    ///
+    /// The events of its children follow, in order, then an `EndContainer`.
+    StartContainer(&'e Element<'t>),
+
+    /// The end of an element with children.
+    EndContainer(&'e Element<'t>),
+
+    /// Text from the page.
+    Text(&'e str),
+
+    /// Any other element, which has no children.
+    Element(&'e Element<'t>),
+
+    /// A warning produced while parsing.
+    Warning(&'e ParseWarning),
+}
+
+/// Passes the events for this element, and everything within it, to the callback.
+pub fn emit_element<'e, 't, F>(element: &'e Element<'t>, f: &mut F)
+where
+    F: FnMut(ParseEvent<'e, 't>),
+{
+    if let Element::Text(text) = element {
+        f(ParseEvent::Text(text));
+        return;
+    }
+
+    let mut started = false;
+    for_each_child_list(element, &mut |children| {
+        if !started {
+            f(ParseEvent::StartContainer(element));
+            started = true;
+        }
+
+        for child in children {
+            emit_element(child, f);
+        }
+    });
+
+    if started {
+        f(ParseEvent::EndContainer(element));
+    } else {
+        f(ParseEvent::Element(element));
+    }
+}
+
+#[test]
+fn parse_events() {
+    use super::ParseOptions;
+
+    let log = crate::build_logger();
+    let text = "**apple** banana\n\n[[char apple]]";
+    let tokens = crate::tokenize(&log, text);
+
+    let mut events = Vec::new();
+    let options = ParseOptions::default();
+    super::parse_events(&log, &tokens, &options, |event| {
+        let event = match event {
+            ParseEvent::StartContainer(element) => format!("start {}", element.name()),
+            ParseEvent::EndContainer(element) => format!("end {}", element.name()),
+            ParseEvent::Text(text) => format!("text {}", text),
+            ParseEvent::Element(element) => element.name().to_string(),
+            ParseEvent::Warning(warning) => format!("warning {:?}", warning.kind()),
+        };
+
+        events.push(event);
+    });
+
+    assert_eq!(
+        events,
+        vec![
+            "start Paragraph",
+            "start Strong",
+            "text apple",
+            "end Strong",
+            "text  ",
+            "text banana",
+            "end Paragraph",
+            "warning InvalidEntity",
+            "warning NoRulesMatch",
+            "warning NoRulesMatch",
+            "start Paragraph",
+            "text [[",
+            "text char",
+            "text  ",
+            "text apple",
+            "text ]]",
+            "end Paragraph",
+        ],
+        "Streamed parse events don't match expected",
+    );
+}
+
+#[test]
+fn parse_events_failure() {
+    use super::ParseOptions;
+
+    let log = crate::build_logger();
+
+    // Paragraphs before the failure are passed on as usual
+    let mut text = str!("apple\n\n");
+
+    for _ in 0..101 {
+        text.push_str("[[div]]\n");
+    }
+
+    for _ in 0..101 {
+        text.push_str("[[/div]]\n");
+    }
+
+    text.pop();
+
+    let tokens = crate::tokenize(&log, &text);
+    let mut events = Vec::new();
+    let options = ParseOptions::default();
+    super::parse_events(&log, &tokens, &options, |event| {
+        let event = match event {
+            ParseEvent::StartContainer(element) => format!("start {}", element.name()),
+            ParseEvent::EndContainer(element) => format!("end {}", element.name()),
+            ParseEvent::Text(text) => format!("text {}", text),
+            ParseEvent::Element(element) => element.name().to_string(),
+            ParseEvent::Warning(warning) => format!("warning {:?}", warning.kind()),
+        };
+
+        events.push(event);
+    });
+
+    // Only the rest of the page is then source text
+    assert_eq!(
+        events,
+        vec![
+            str!("start Paragraph"),
+            str!("text apple"),
+            str!("end Paragraph"),
+            str!("start Paragraph"),
+            format!("text {}", &text[7..]),
+            str!("end Paragraph"),
+            str!("warning RecursionDepthExceeded"),
+        ],
+        "Streamed parse events after failure don't match expected",
+    );
+}
@@ -18,9 +18,10 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use super::{rule::Rule, ExtractedToken, Token};
+use super::{rule::Rule, BlockBoundary, ExtractedToken, ParseFix, Token};
+use crate::enums::HeadingLevel;
+use crate::span::Span;
 use std::borrow::Cow;
-use std::ops::Range;
 use strum_macros::IntoStaticStr;
 
 /// Exceptions that occurred during parsing
@@ -32,11 +33,21 @@ use strum_macros::IntoStaticStr;
 /// These are primarily parser warnings, but are not necessarily such.
 /// For instance, CSS styles are not present in the syntax tree
 /// like regular elements, and instead must be bubbled up
-/// to the top level.
+/// to the top level. The same goes for the locations of blocks,
+/// and details of how they were written.
+///
+/// Headings outside of any block are also recorded with their span,
+/// since they divide the page into sections. See `SyntaxTree::section()`.
+///
+/// Citations are recorded with the warning to produce if no bibliography
+/// entry has their label, which is only known once the page is parsed.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParseException<'t> {
     Warning(ParseWarning),
     Style(Cow<'t, str>),
+    Block(BlockBoundary<'t>),
+    Heading(Span, HeadingLevel),
+    Citation(Cow<'t, str>, ParseWarning),
 }
 
 /// An issue that occurred during parsing.
@@ -51,15 +62,24 @@ pub enum ParseException<'t> {
 pub struct ParseWarning {
     token: Token,
     rule: Cow<'static, str>,
-    span: Range<usize>,
+    span: Span,
     kind: ParseWarningKind,
+    severity: ParseWarningSeverity,
+
+    /// The argument value which caused this warning, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+
+    /// Suggested edits to the source which would resolve this warning.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<ParseFix>,
 }
 
 impl ParseWarning {
     #[inline]
     pub fn new(kind: ParseWarningKind, rule: Rule, current: &ExtractedToken) -> Self {
         let token = current.token;
-        let span = Range::clone(&current.span);
+        let span = current.span;
         let rule = cow!(rule.name());
 
         ParseWarning {
@@ -67,9 +87,26 @@ impl ParseWarning {
             rule,
             span,
             kind,
+            severity: kind.severity(),
+            value: None,
+            fixes: Vec::new(),
         }
     }
 
+    /// Attaches the offending argument value to this warning.
+    #[inline]
+    pub fn with_value<S: Into<String>>(mut self, value: S) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    /// Attaches a suggested edit which would resolve this warning.
+    #[inline]
+    pub fn with_fix(mut self, fix: ParseFix) -> Self {
+        self.fixes.push(fix);
+        self
+    }
+
     #[inline]
     pub fn token(&self) -> Token {
         self.token
@@ -81,14 +118,133 @@ impl ParseWarning {
     }
 
     #[inline]
-    pub fn span(&self) -> Range<usize> {
-        Range::clone(&self.span)
+    pub fn span(&self) -> Span {
+        self.span
     }
 
     #[inline]
     pub fn kind(&self) -> ParseWarningKind {
         self.kind
     }
+
+    #[inline]
+    pub fn severity(&self) -> ParseWarningSeverity {
+        self.severity
+    }
+
+    #[inline]
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    /// Suggested edits to resolve this warning, which can be applied with `apply_fixes()`.
+    #[inline]
+    pub fn fixes(&self) -> &[ParseFix] {
+        &self.fixes
+    }
+
+    /// Attributes this warning to the given token, keeping its value and fixes.
+    ///
+    /// This is for warnings from text which was parsed separately, such as an
+    /// argument value. Its fixes are moved by `offset`, the position of that text
+    /// within the page. If it has none, such as for an included page, the fixes
+    /// cannot be applied to the page, and so are discarded.
+    pub(crate) fn relocate(
+        self,
+        rule: Rule,
+        current: &ExtractedToken,
+        offset: Option<usize>,
+    ) -> Self {
+        let fixes = match offset {
+            Some(offset) => self
+                .fixes
+                .into_iter()
+                .map(|fix| ParseFix::new(fix.span.offset(offset), fix.replacement))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        ParseWarning {
+            value: self.value,
+            fixes,
+            ..ParseWarning::new(self.kind, rule, current)
+        }
+    }
+}
+
+/// A set of warnings sharing the same kind and rule.
+///
+/// Pages which repeat a malformed construct may emit many identical warnings,
+/// so this collapses them into a count, keeping only the first few spans.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ParseWarningGroup {
+    kind: ParseWarningKind,
+    rule: Cow<'static, str>,
+    spans: Vec<Span>,
+    count: usize,
+}
+
+impl ParseWarningGroup {
+    /// Groups the given warnings by kind and rule, in order of first appearance.
+    ///
+    /// At most `max_spans` spans are retained per group,
+    /// though the count reflects every warning.
+    pub fn aggregate(warnings: &[ParseWarning], max_spans: usize) -> Vec<Self> {
+        let mut groups: Vec<ParseWarningGroup> = Vec::new();
+
+        for warning in warnings {
+            let group = match groups
+                .iter_mut()
+                .find(|group| group.kind == warning.kind && group.rule == warning.rule)
+            {
+                Some(group) => group,
+                None => {
+                    groups.push(ParseWarningGroup {
+                        kind: warning.kind,
+                        rule: Cow::clone(&warning.rule),
+                        spans: Vec::new(),
+                        count: 0,
+                    });
+
+                    groups.last_mut().unwrap()
+                }
+            };
+
+            if group.spans.len() < max_spans {
+                group.spans.push(warning.span());
+            }
+
+            group.count += 1;
+        }
+
+        groups
+    }
+
+    #[inline]
+    pub fn kind(&self) -> ParseWarningKind {
+        self.kind
+    }
+
+    #[inline]
+    pub fn severity(&self) -> ParseWarningSeverity {
+        self.kind.severity()
+    }
+
+    #[inline]
+    pub fn rule(&self) -> &str {
+        &self.rule
+    }
+
+    #[inline]
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.count
+    }
 }
 
 #[derive(Serialize, Deserialize, IntoStaticStr, Debug, Copy, Clone, PartialEq, Eq)]
@@ -106,6 +262,12 @@ pub enum ParseWarningKind {
     /// No rules match for these tokens, returning as plain text.
     NoRulesMatch,
 
+    /// A `]]` was found with no block open, returning as plain text.
+    OrphanedCloseBlock,
+
+    /// A `--]` was found with no comment open, returning as plain text.
+    OrphanedCloseComment,
+
     /// Attempting to match this rule failed, falling back to try an alternate.
     RuleFailed,
 
@@ -130,6 +292,22 @@ pub enum ParseWarningKind {
     /// Some required arguments where missing when parsing the block.
     BlockMissingArguments,
 
+    /// The same argument was given more than once in a block's head.
+    ///
+    /// As in Wikidot, the last value is used. This warning is emitted for
+    /// both the first occurrence of the argument and the repeat.
+    BlockDuplicateArgument,
+
+    /// This block argument's value was too long, so it was cut short.
+    ///
+    /// See `ParseSettings::max_argument_length`.
+    BlockArgumentTruncated,
+
+    /// This block has too many arguments, so this one was dropped.
+    ///
+    /// See `ParseSettings::max_argument_count`.
+    BlockTooManyArguments,
+
     /// This block expects a line break here.
     BlockExpectedLineBreak,
 
@@ -139,14 +317,68 @@ pub enum ParseWarningKind {
     /// An end block was found, but of the incorrect type.
     BlockEndMismatch,
 
+    /// This block was never closed, so it was ended at the next blank line or heading.
+    BlockAutoClosed,
+
     /// This no rule for the module name specified.
     NoSuchModule,
 
+    /// This block has been disabled by the host, see `ParseSettings::disabled_blocks`.
+    BlockDisabled,
+
+    /// This module has been disabled by the host, see `ParseSettings::disabled_modules`.
+    ModuleDisabled,
+
     /// This module does not specify a name.
     ModuleMissingName,
 
     /// The URL passed here was invalid.
     InvalidUrl,
+
+    /// This tab view has no tabs.
+    TabViewEmpty,
+
+    /// This tab view contains elements other than tabs.
+    TabViewContainsNonTab,
+
+    /// This tab is not contained within a tab view.
+    TabOutsideTabView,
+
+    /// The rows of this table do not all have the same number of cells.
+    TableRaggedRows,
+
+    /// This table block contains elements other than rows.
+    TableContainsNonRow,
+
+    /// This table row contains elements other than cells.
+    TableRowContainsNonCell,
+
+    /// This table row is not contained within a table block.
+    TableRowOutsideTable,
+
+    /// This table cell is not contained within a table row.
+    TableCellOutsideRow,
+
+    /// This footnote is within another footnote.
+    NestedFootnote,
+
+    /// This citation refers to a label which no bibliography entry has.
+    NoSuchBibliographyEntry,
+
+    /// This heading is deeper than six levels, so it was made an `h6`.
+    HeadingLevelClamped,
+
+    /// This heading is deeper than six levels, so it was left as text.
+    HeadingLevelExceeded,
+
+    /// This character entity is not recognized.
+    InvalidEntity,
+
+    /// This element is not permitted within its container, so the container was split around it.
+    InvalidNesting,
+
+    /// Block elements are not permitted here, such as within a link label.
+    BlockNotPermitted,
 }
 
 impl ParseWarningKind {
@@ -154,6 +386,68 @@ impl ParseWarningKind {
     pub fn name(self) -> &'static str {
         self.into()
     }
+
+    /// Whether this warning is from a token being returned as plain text.
+    #[inline]
+    pub fn is_fallback(self) -> bool {
+        matches!(
+            self,
+            ParseWarningKind::NoRulesMatch
+                | ParseWarningKind::OrphanedCloseBlock
+                | ParseWarningKind::OrphanedCloseComment,
+        )
+    }
+
+    pub fn severity(self) -> ParseWarningSeverity {
+        use self::ParseWarningKind::*;
+
+        match self {
+            // Internal parser details, which authors don't need to see
+            EndOfInput | NoRulesMatch | RuleFailed => ParseWarningSeverity::Info,
+
+            // Recoverable issues, where the output is likely still close to intended
+            NotImplemented
+            | InvalidSpecialBlock
+            | BlockExpectedLineBreak
+            | InvalidUrl
+            | TableRaggedRows
+            | HeadingLevelClamped
+            | HeadingLevelExceeded
+            | InvalidEntity
+            | InvalidNesting
+            | NoSuchBibliographyEntry
+            | BlockDuplicateArgument
+            | BlockArgumentTruncated
+            | BlockTooManyArguments
+            | BlockAutoClosed
+            | OrphanedCloseBlock
+            | OrphanedCloseComment => ParseWarningSeverity::Warning,
+
+            // Constructs which are broken, and so were rendered as text or dropped
+            RecursionDepthExceeded
+            | InvalidInclude
+            | NoSuchBlock
+            | BlockMissingName
+            | BlockMissingCloseBrackets
+            | BlockMalformedArguments
+            | BlockMissingArguments
+            | BlockExpectedEnd
+            | BlockEndMismatch
+            | NoSuchModule
+            | BlockDisabled
+            | ModuleDisabled
+            | ModuleMissingName
+            | TabOutsideTabView
+            | TableRowOutsideTable
+            | TableCellOutsideRow
+            | TabViewEmpty
+            | TabViewContainsNonTab
+            | TableContainsNonRow
+            | TableRowContainsNonCell
+            | NestedFootnote
+            | BlockNotPermitted => ParseWarningSeverity::Error,
+        }
+    }
 }
 
 impl slog::Value for ParseWarningKind {
@@ -166,3 +460,134 @@ impl slog::Value for ParseWarningKind {
         serializer.emit_str(key, self.name())
     }
 }
+
+/// How significant a parser warning is.
+///
+/// This allows hosts to decide which warnings to surface to authors,
+/// and which to only log.
+#[derive(
+    Serialize,
+    Deserialize,
+    IntoStaticStr,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ParseWarningSeverity {
+    /// Diagnostic information about the parse, not indicative of a problem.
+    Info,
+
+    /// An issue with the wikitext, but one with a reasonable interpretation.
+    Warning,
+
+    /// Malformed wikitext, which did not produce the intended construct.
+    Error,
+}
+
+impl ParseWarningSeverity {
+    #[inline]
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
+}
+
+impl slog::Value for ParseWarningSeverity {
+    fn serialize(
+        &self,
+        _: &slog::Record,
+        key: slog::Key,
+        serializer: &mut dyn slog::Serializer,
+    ) -> slog::Result {
+        serializer.emit_str(key, self.name())
+    }
+}
+
+#[test]
+fn aggregate() {
+    use super::rule::impls::{RULE_FALLBACK, RULE_TEXT};
+
+    let token = |start| ExtractedToken {
+        token: Token::RightBlock,
+        slice: "]]",
+        span: Span::new(start, start + 2),
+    };
+
+    let warnings = vec![
+        ParseWarning::new(ParseWarningKind::NoRulesMatch, RULE_FALLBACK, &token(0)),
+        ParseWarning::new(ParseWarningKind::RuleFailed, RULE_TEXT, &token(2)),
+        ParseWarning::new(ParseWarningKind::NoRulesMatch, RULE_FALLBACK, &token(4)),
+        ParseWarning::new(ParseWarningKind::NoRulesMatch, RULE_FALLBACK, &token(6)),
+    ];
+
+    let groups = ParseWarningGroup::aggregate(&warnings, 2);
+    assert_eq!(groups.len(), 2, "Number of warning groups doesn't match");
+
+    assert_eq!(groups[0].kind(), ParseWarningKind::NoRulesMatch);
+    assert_eq!(groups[0].rule(), "fallback");
+    assert_eq!(groups[0].spans(), &[Span::new(0, 2), Span::new(4, 6)]);
+    assert_eq!(groups[0].count(), 3);
+
+    assert_eq!(groups[1].kind(), ParseWarningKind::RuleFailed);
+    assert_eq!(groups[1].rule(), "text");
+    assert_eq!(groups[1].spans().len(), 1);
+    assert_eq!(groups[1].spans()[0], Span::new(2, 4));
+    assert_eq!(groups[1].count(), 1);
+}
+
+#[test]
+fn severity() {
+    use super::rule::impls::RULE_FALLBACK;
+
+    let token = ExtractedToken {
+        token: Token::RightBlock,
+        slice: "]]",
+        span: Span::new(0, 2),
+    };
+
+    let warning =
+        ParseWarning::new(ParseWarningKind::NoRulesMatch, RULE_FALLBACK, &token);
+    assert_eq!(warning.severity(), ParseWarningSeverity::Info);
+
+    let warning = ParseWarning::new(ParseWarningKind::NoSuchBlock, RULE_FALLBACK, &token);
+    assert_eq!(warning.severity(), ParseWarningSeverity::Error);
+
+    let warning =
+        ParseWarning::new(ParseWarningKind::OrphanedCloseBlock, RULE_FALLBACK, &token);
+    assert_eq!(warning.severity(), ParseWarningSeverity::Warning);
+    assert!(warning.kind().is_fallback());
+
+    assert_eq!(
+        ParseWarningKind::TableContainsNonRow.severity(),
+        ParseWarningSeverity::Error,
+    );
+
+    assert!(ParseWarningSeverity::Info < ParseWarningSeverity::Warning);
+    assert!(ParseWarningSeverity::Warning < ParseWarningSeverity::Error);
+}
+
+#[test]
+fn relocate() {
+    use super::apply_fixes;
+
+    // Warnings within argument values keep their fixes, moved to the page's text
+    let log = crate::build_logger();
+    let text = "[[collapsible show=\"##red|Apple\"]]\nBanana\n[[/collapsible]]";
+    let tokenization = crate::tokenize(&log, text);
+    let (_, warnings) = crate::parse(&log, &tokenization).into();
+
+    let warning = warnings
+        .iter()
+        .find(|warning| !warning.fixes().is_empty())
+        .expect("No warning with fixes produced");
+
+    assert_eq!(warning.rule(), "block-collapsible");
+    assert_eq!(
+        apply_fixes(text, warning.fixes()),
+        "[[collapsible show=\"##red|Apple##\"]]\nBanana\n[[/collapsible]]",
+    );
+}
@@ -0,0 +1,137 @@
+/*
+ * parsing/fix.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Suggested edits which resolve parser warnings.
+//!
+//! Editors can offer these as "quick fixes", see `ParseWarning::fixes()`.
+
+use crate::span::Span;
+
+/// A machine-applicable edit to the source text, replacing a span with new text.
+///
+/// An empty span is an insertion. As with warnings, the span refers to
+/// the text which was parsed, that is, after preprocessing.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ParseFix {
+    pub span: Span,
+    pub replacement: String,
+}
+
+impl ParseFix {
+    #[inline]
+    pub fn new<S: Into<String>>(span: Span, replacement: S) -> Self {
+        ParseFix {
+            span,
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Creates a fix which inserts text at the given position.
+    #[inline]
+    pub fn insert<S: Into<String>>(position: usize, text: S) -> Self {
+        ParseFix::new(Span::new(position, position), text)
+    }
+}
+
+/// Applies the given fixes to the text, producing the edited text.
+///
+/// Fixes are applied in order of position. If a fix overlaps one
+/// which was already applied, it is skipped, since its span would
+/// no longer refer to the same text. Insertions at the same position
+/// are applied in the order given.
+///
+/// # Panics
+/// Like string slicing, this panics if a span is out of bounds
+/// or does not fall on character boundaries.
+pub fn apply_fixes<'a, I>(text: &str, fixes: I) -> String
+where
+    I: IntoIterator<Item = &'a ParseFix>,
+{
+    let mut fixes: Vec<&ParseFix> = fixes.into_iter().collect();
+    fixes.sort_by_key(|fix| fix.span.start);
+
+    let mut output = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for fix in fixes {
+        if fix.span.start < last {
+            continue;
+        }
+
+        output.push_str(&text[last..fix.span.start]);
+        output.push_str(&fix.replacement);
+        last = fix.span.end;
+    }
+
+    output.push_str(&text[last..]);
+    output
+}
+
+#[test]
+fn fixes() {
+    macro_rules! check {
+        ($text:expr, $fixes:expr, $expected:expr $(,)?) => {{
+            let fixes: Vec<ParseFix> = $fixes;
+            let output = apply_fixes($text, &fixes);
+
+            assert_eq!(output, $expected, "Fixed text doesn't match expected");
+        }};
+    }
+
+    check!("apple", vec![], "apple");
+    check!(
+        "[[div class=apple]]",
+        vec![ParseFix::new(Span::new(12, 17), "\"apple\"")],
+        "[[div class=\"apple\"]]",
+    );
+    check!(
+        "##blue|apple",
+        vec![ParseFix::insert(12, "##")],
+        "##blue|apple##",
+    );
+
+    // Applied in order of position, regardless of the order given
+    check!(
+        "a b c",
+        vec![
+            ParseFix::insert(5, "!"),
+            ParseFix::new(Span::new(0, 1), "A")
+        ],
+        "A b c!",
+    );
+
+    // Insertions at the same position are kept in order
+    check!(
+        "[[div]]",
+        vec![ParseFix::insert(7, "\n"), ParseFix::insert(7, "[[/div]]")],
+        "[[div]]\n[[/div]]",
+    );
+
+    // Overlapping fixes are skipped
+    check!(
+        "apple",
+        vec![
+            ParseFix::new(Span::new(0, 3), "b"),
+            ParseFix::new(Span::new(2, 5), "c"),
+        ],
+        "ble",
+    );
+}
@@ -0,0 +1,77 @@
+/*
+ * parsing/metrics.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Counters collected over the course of a parse.
+//!
+//! These are intended for hosts to log and alert on pathological pages,
+//! for instance ones that cause heavy backtracking or deep nesting.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ParseMetrics {
+    /// How many times a rule was tried against the token stream.
+    pub rules_attempted: usize,
+
+    /// How many times no rule matched and the token was emitted as text.
+    pub fallbacks_taken: usize,
+
+    /// The deepest level of recursion reached.
+    pub max_depth: usize,
+
+    /// How many token steps were made, including those later backtracked.
+    pub tokens_consumed: usize,
+}
+
+/// Shared handle to the metrics, so that all clones of a parser count together.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsHandle(Rc<RefCell<ParseMetrics>>);
+
+impl MetricsHandle {
+    #[inline]
+    pub fn get(&self) -> ParseMetrics {
+        *self.0.borrow()
+    }
+
+    #[inline]
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut ParseMetrics),
+    {
+        f(&mut self.0.borrow_mut());
+    }
+}
+
+#[test]
+fn metrics() {
+    use crate::parsing::ParseOptions;
+
+    let log = crate::build_logger();
+    let tokenization = crate::tokenize(&log, "**apple** [[ banana");
+    let options = ParseOptions::default();
+    let metrics = crate::parse_with_options(&log, &tokenization, &options).metrics;
+
+    assert!(metrics.rules_attempted > 0, "No rules were attempted");
+    assert!(metrics.fallbacks_taken > 0, "No fallbacks were recorded");
+    assert!(metrics.max_depth > 0, "No recursion depth was recorded");
+    assert!(metrics.tokens_consumed > 0, "No token steps were recorded");
+}
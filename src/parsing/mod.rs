@@ -21,19 +21,31 @@
 #[macro_use]
 mod macros;
 
-mod boolean;
+mod boundary;
 mod check_step;
 mod collect;
 mod condition;
 mod consume;
+mod context;
+mod entity;
+mod event;
 mod exception;
+mod fix;
+mod metrics;
+mod options;
 mod outcome;
 mod paragraph;
 mod parser;
+
+#[cfg(feature = "profile")]
+mod profile;
+
 mod result;
 mod rule;
+mod settings;
 mod string;
 mod token;
+mod value;
 
 mod prelude {
     pub use crate::parsing::{
@@ -44,19 +56,46 @@ mod prelude {
     pub use crate::tree::Element;
 }
 
-use self::boolean::parse_boolean;
-use self::paragraph::{gather_paragraphs, NO_CLOSE_CONDITION};
+use self::entity::parse_entity;
+use self::event::emit_element;
+use self::paragraph::{
+    gather_paragraphs, gather_paragraphs_recovering, group_paragraphs, stream_paragraphs,
+    wrap_paragraphs,
+    NO_CLOSE_CONDITION,
+};
 use self::parser::Parser;
 use self::rule::impls::RULE_PAGE;
-use self::string::parse_string;
+use self::string::{parse_string, parse_string_limited};
+use crate::enums::HeadingLevel;
+use crate::span::Span;
 use crate::tokenizer::Tokenization;
-use crate::tree::SyntaxTree;
+use crate::tree::{
+    collect_bibliography, normalize_elements, resolve_bibliography, resolve_counters,
+    section_spans, Container, ContainerType, Element, SyntaxTree,
+};
 use std::borrow::Cow;
 
-pub use self::exception::{ParseException, ParseWarning, ParseWarningKind};
+pub use self::boundary::BlockBoundary;
+pub use self::context::{ParseContext, ParseContextFlag};
+pub use self::event::ParseEvent;
+pub use self::exception::{
+    ParseException, ParseWarning, ParseWarningGroup, ParseWarningKind,
+    ParseWarningSeverity,
+};
+pub use self::fix::{apply_fixes, ParseFix};
+pub use self::metrics::ParseMetrics;
+pub use self::options::{ParseOptions, ParseOutput};
 pub use self::outcome::ParseOutcome;
+
+#[cfg(feature = "profile")]
+pub use self::profile::{ParseProfile, RuleProfile};
+
 pub use self::result::{ParseResult, ParseSuccess};
+pub use self::settings::{HeadingOverflow, ParseSettings};
 pub use self::token::{ExtractedToken, Token};
+pub use self::value::{
+    parse_boolean, parse_choice, parse_dimension, parse_integer, Dimension, DimensionUnit,
+};
 
 /// Parse through the given tokens and produce an AST.
 ///
@@ -68,8 +107,112 @@ pub fn parse<'r, 't>(
 where
     'r: 't,
 {
-    let mut parser = Parser::new(log, tokenization);
+    let parser = Parser::new(log, tokenization);
+
+    parse_internal(log, parser, tokenization).outcome
+}
+
+/// Parse through the given tokens, using the given options.
+///
+/// Like `parse()`, but allows customizing parser behavior, such as with
+/// `ParseSettings` or pages for parse-time inclusion. See `ParseOptions`.
+///
+/// Along with the syntax tree, this produces everything else collected
+/// during the parse, such as the location of each block and counters
+/// about the parse run. See `ParseOutput`.
+pub fn parse_with_options<'r, 't>(
+    log: &slog::Logger,
+    tokenization: &'r Tokenization<'t>,
+    options: &'r ParseOptions<'r, 't>,
+) -> ParseOutput<'t>
+where
+    'r: 't,
+{
+    let parser = Parser::with_options(log, tokenization, options);
+
+    parse_internal(log, parser, tokenization)
+}
 
+/// Parse through the given tokens, passing each part of the page to a callback.
+///
+/// Rather than building a `SyntaxTree`, each top-level paragraph is streamed
+/// as `ParseEvent`s as soon as it is finished, then dropped. This keeps memory
+/// use low for consumers which only need to see the page once, such as text
+/// extraction or collecting statistics.
+///
+/// Since the whole page is never held, the processing which `parse()` does
+/// afterwards is skipped: element nesting isn't normalized, counters and
+/// bibliography citations are left unresolved, and no warnings are produced
+/// for citations of missing entries. Page styles aren't emitted either.
+///
+/// Warnings are passed on as soon as they are produced, so they may come
+/// before the events of the paragraph they were found in.
+///
+/// If parsing fails, the source text from the paragraph which failed onwards
+/// is passed on as a plain text paragraph, followed by the fatal warning.
+/// Paragraphs which were already passed on are not repeated.
+///
+/// The other outputs of the parse, such as block locations, are returned
+/// once the page is finished, in a `ParseOutput` with no syntax tree.
+pub fn parse_events<'r, 't, F>(
+    log: &slog::Logger,
+    tokenization: &'r Tokenization<'t>,
+    options: &'r ParseOptions<'r, 't>,
+    mut f: F,
+) -> ParseOutput<'t, ()>
+where
+    'r: 't,
+    F: FnMut(ParseEvent<'_, 't>),
+{
+    let log = &log.new(slog_o!(
+        "filename" => slog_filename!(),
+        "lineno" => slog_lineno!(),
+        "function" => "parse_events",
+        "tokens-len" => tokenization.tokens().len(),
+    ));
+
+    info!(log, "Running streaming parser on tokens");
+
+    let mut parser = Parser::with_options(log, tokenization, options);
+    let mut blocks = Vec::new();
+
+    let result =
+        stream_paragraphs(log, &mut parser, RULE_PAGE, |elements, exceptions| {
+            for element in &elements {
+                emit_element(element, &mut f);
+            }
+
+            for exception in exceptions {
+                match exception {
+                    ParseException::Warning(warning) => f(ParseEvent::Warning(&warning)),
+                    ParseException::Block(block) => blocks.push(block),
+                    _ => (),
+                }
+            }
+        });
+
+    if let Err(warning) = result {
+        // As with parse(), the rest of the page is then only its source text,
+        // which has already been passed on as a paragraph
+        warn!(
+            log,
+            "Fatal error occurred at highest-level parsing: {:#?}", warning,
+        );
+
+        f(ParseEvent::Warning(&warning));
+    }
+
+    make_output(&parser, (), blocks)
+}
+
+fn parse_internal<'r, 't>(
+    log: &slog::Logger,
+    mut parser: Parser<'r, 't>,
+    tokenization: &'r Tokenization<'t>,
+) -> ParseOutput<'t>
+where
+    'r: 't,
+{
     // Logging setup
     let log = &log.new(slog_o!(
         "filename" => slog_filename!(),
@@ -85,53 +228,126 @@ where
     debug!(log, "Finished paragraph gathering, matching on consumption");
     match result {
         Ok(ParseSuccess {
-            item: elements,
+            item: mut elements,
             exceptions,
             ..
         }) => {
-            let (warnings, styles) = extract_exceptions(exceptions);
+            let ExtractedExceptions {
+                mut warnings,
+                styles,
+                blocks,
+                headings,
+                citations,
+            } = extract_exceptions(exceptions);
+
+            // Ensure the output can be rendered with valid nesting
+            debug!(log, "Normalizing element nesting");
+            normalize_elements(&mut elements);
+
+            // Counters can be referred to before they appear, so need the whole page
+            debug!(log, "Resolving counter values");
+            resolve_counters(&mut elements);
+
+            // Likewise for citations, which usually come before the bibliography
+            debug!(log, "Resolving bibliography citations");
+            resolve_bibliography(&mut elements);
+
+            let entries = collect_bibliography(&elements);
+            for (label, warning) in citations {
+                if !entries.iter().any(|entry| entry.label() == label) {
+                    warnings.push(warning);
+                }
+            }
 
             info!(
                 log,
                 "Finished parsing, producing final syntax tree";
                 "warnings-len" => warnings.len(),
                 "styles-len" => styles.len(),
+                "blocks-len" => blocks.len(),
             );
 
-            SyntaxTree::from_element_result(elements, warnings, styles)
+            // Sections run up to the next heading, or the end of the page
+            let end = tokenization.full_text().inner().len();
+            let sections = section_spans(&headings, end);
+
+            let tree = SyntaxTree {
+                elements,
+                styles,
+                sections,
+            };
+
+            let outcome = ParseOutcome::new(tree, warnings);
+            make_output(&parser, outcome, blocks)
         }
         Err(warning) => {
             // This path is only reachable if a very bad error occurs.
             //
             // If this happens, then just return the input source as the output
-            // and the warning.
+            // and the warning. Like any other top-level text, it is in a paragraph.
 
             warn!(
                 log,
                 "Fatal error occurred at highest-level parsing: {:#?}", warning,
             );
 
-            let elements = vec![text!(tokenization.full_text().inner())];
+            let elements = vec![Element::Container(Container::new(
+                ContainerType::Paragraph,
+                vec![text!(tokenization.full_text().inner())],
+            ))];
             let warnings = vec![warning];
             let styles = vec![];
 
-            SyntaxTree::from_element_result(elements, warnings, styles)
+            let outcome = SyntaxTree::from_element_result(elements, warnings, styles);
+            make_output(&parser, outcome, vec![])
         }
     }
 }
 
-fn extract_exceptions(
-    exceptions: Vec<ParseException>,
-) -> (Vec<ParseWarning>, Vec<Cow<str>>) {
-    let mut warnings = Vec::new();
-    let mut styles = Vec::new();
+fn make_output<'t, T>(
+    parser: &Parser<'_, 't>,
+    outcome: T,
+    mut blocks: Vec<BlockBoundary<'t>>,
+) -> ParseOutput<'t, T> {
+    // Nested blocks finish first, so put them back in source order
+    blocks.sort_by_key(|block| block.head().start);
+
+    ParseOutput {
+        outcome,
+        blocks,
+        metrics: parser.metrics_handle().get(),
+
+        #[cfg(feature = "profile")]
+        profile: parser.profile_handle().get(),
+    }
+}
+
+/// The exceptions produced while parsing, separated by kind.
+#[derive(Debug, Default)]
+struct ExtractedExceptions<'t> {
+    warnings: Vec<ParseWarning>,
+    styles: Vec<Cow<'t, str>>,
+    blocks: Vec<BlockBoundary<'t>>,
+    headings: Vec<(Span, HeadingLevel)>,
+    citations: Vec<(Cow<'t, str>, ParseWarning)>,
+}
+
+fn extract_exceptions(exceptions: Vec<ParseException>) -> ExtractedExceptions {
+    let mut extracted = ExtractedExceptions::default();
 
     for exception in exceptions {
         match exception {
-            ParseException::Warning(warning) => warnings.push(warning),
-            ParseException::Style(style) => styles.push(style),
+            ParseException::Warning(warning) => extracted.warnings.push(warning),
+            ParseException::Style(style) => extracted.styles.push(style),
+            ParseException::Block(block) => extracted.blocks.push(block),
+            ParseException::Heading(span, level) => {
+                extracted.headings.push((span, level))
+            }
+            ParseException::Citation(label, warning) => {
+                extracted.citations.push((label, warning))
+            }
         }
     }
 
-    (warnings, styles)
+    extracted
 }
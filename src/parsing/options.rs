@@ -0,0 +1,94 @@
+/*
+ * parsing/options.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! The inputs and outputs of a parse run, beyond the tokens and syntax tree.
+//!
+//! These are gathered into structures so that they can be freely combined,
+//! for instance parsing with both custom settings and included pages, while
+//! also collecting block locations.
+
+use super::{BlockBoundary, ParseMetrics, ParseOutcome, ParseSettings};
+use crate::includes::IncludedPages;
+use crate::span::Span;
+use crate::tree::SyntaxTree;
+
+#[cfg(feature = "profile")]
+use super::ParseProfile;
+
+/// Options for a parse run, see `parse_with_options()`.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions<'r, 't> {
+    /// Settings which change the parser's behavior, see `ParseSettings`.
+    pub settings: ParseSettings,
+
+    /// The included pages fetched by `include_elements()`, if any.
+    ///
+    /// If present, each `[[include]]` block becomes an `Element::Include`
+    /// containing the parsed contents of its page.
+    pub includes: Option<&'r IncludedPages<'t>>,
+}
+
+impl<'r, 't> ParseOptions<'r, 't> {
+    #[inline]
+    pub fn new(settings: ParseSettings) -> Self {
+        ParseOptions {
+            settings,
+            includes: None,
+        }
+    }
+}
+
+/// Everything produced by a parse run, see `parse_with_options()`.
+///
+/// For `parse_events()`, where no syntax tree is built, `outcome` is `()`.
+#[derive(Debug)]
+pub struct ParseOutput<'t, T = ParseOutcome<SyntaxTree<'t>>> {
+    /// The syntax tree and its warnings.
+    pub outcome: T,
+
+    /// The location of each block, and how it was written, in source order.
+    ///
+    /// These are intended for editors, to implement folding and highlighting
+    /// of matching blocks, and for tools which rewrite a page while preserving
+    /// its formatting. See `BlockBoundary`.
+    pub blocks: Vec<BlockBoundary<'t>>,
+
+    /// Counters about the parse run, see `ParseMetrics`.
+    ///
+    /// Hosts can use these to log or alert on pages which are expensive to parse.
+    pub metrics: ParseMetrics,
+
+    /// The time spent in and number of matches for each rule.
+    ///
+    /// This is intended for finding which parts of the grammar are slow.
+    #[cfg(feature = "profile")]
+    pub profile: ParseProfile,
+}
+
+impl<'t, T> ParseOutput<'t, T> {
+    /// Gets the block whose head has the given span.
+    pub fn block(&self, head: Span) -> Option<&BlockBoundary<'t>> {
+        self.blocks
+            .binary_search_by_key(&head.start, |block| block.head().start)
+            .ok()
+            .map(|index| &self.blocks[index])
+            .filter(|block| block.head() == head)
+    }
+}
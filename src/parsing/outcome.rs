@@ -18,7 +18,7 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use super::ParseWarning;
+use super::{ParseWarning, ParseWarningGroup};
 use std::borrow::{Borrow, BorrowMut};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -49,6 +49,14 @@ impl<T> ParseOutcome<T> {
     pub fn warnings(&self) -> &[ParseWarning] {
         &self.warnings
     }
+
+    /// Gets the warnings grouped by kind and rule, keeping at most `max_spans` spans each.
+    ///
+    /// See `warnings()` for the raw, ungrouped form.
+    #[inline]
+    pub fn aggregated_warnings(&self, max_spans: usize) -> Vec<ParseWarningGroup> {
+        ParseWarningGroup::aggregate(&self.warnings, max_spans)
+    }
 }
 
 impl<U> ParseOutcome<Vec<U>> {
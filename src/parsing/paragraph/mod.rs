@@ -26,6 +26,7 @@ use super::parser::Parser;
 use super::prelude::*;
 use super::rule::Rule;
 use super::token::Token;
+use crate::tree::{Container, ContainerType};
 
 /// Wrapper type to satisfy the issue with generic closure types.
 ///
@@ -132,16 +133,257 @@ where
     stack.into_result()
 }
 
+/// Like `gather_paragraphs()`, but for the body of a block which may never be closed.
+///
+/// The first point where `boundary_fn` holds is remembered, along with the
+/// paragraphs gathered before it. If the input ends before the close condition
+/// is satisfied, the parser is moved back to that point and those paragraphs
+/// are produced instead, with `true` to indicate the body was ended early.
+///
+/// This way the body is only parsed once, even if blocks nested within it
+/// are also unclosed.
+pub fn gather_paragraphs_recovering<'r, 't, F, B>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    rule: Rule,
+    mut close_condition_fn: F,
+    boundary_fn: B,
+) -> ParseResult<'r, 't, (Vec<Element<'t>>, bool)>
+where
+    'r: 't,
+    F: FnMut(&mut Parser<'r, 't>) -> Result<bool, ParseWarning>,
+    B: Fn(&Parser<'r, 't>) -> bool,
+{
+    info!(
+        log,
+        "Gathering paragraphs until ending, or the first boundary"
+    );
+
+    // Update parser rule
+    parser.set_rule(rule);
+
+    // Build paragraph stack
+    let mut stack = ParagraphStack::new(log);
+    let mut boundary = None;
+
+    loop {
+        if boundary.is_none() && boundary_fn(parser) {
+            debug!(log, "Hit the first boundary, remembering it");
+            boundary = Some((parser.clone(), stack.clone()));
+        }
+
+        let result = match parser.current().token {
+            Token::InputEnd => Err(parser.make_warn(ParseWarningKind::EndOfInput)),
+
+            // If we've hit a paragraph break, then finish the current paragraph
+            Token::ParagraphBreak => {
+                stack.end_paragraph();
+                parser.step()?;
+                continue;
+            }
+
+            _ => {
+                if close_condition_fn(parser).unwrap_or(false) {
+                    debug!(
+                        log,
+                        "Hit closing condition for paragraphs, terminating token iteration",
+                    );
+
+                    break;
+                }
+
+                consume(log, parser)
+            }
+        };
+
+        match result {
+            Ok(success) => {
+                let (element, mut exceptions) = success.into();
+                push_element(&mut stack, element);
+                stack.push_exceptions(&mut exceptions);
+            }
+
+            // The close condition was never satisfied, so end at the first boundary instead
+            Err(warning) if warning.kind() == ParseWarningKind::EndOfInput => {
+                debug!(log, "Hit the end of input, returning to the first boundary");
+
+                return match boundary {
+                    Some((boundary_parser, boundary_stack)) => {
+                        parser.update(&boundary_parser);
+                        boundary_stack
+                            .into_result()
+                            .map(|success| success.map(|elements| (elements, true)))
+                    }
+                    None => Err(warning),
+                };
+            }
+
+            Err(warning) => return Err(warning),
+        }
+    }
+
+    stack
+        .into_result()
+        .map(|success| success.map(|elements| (elements, false)))
+}
+
+/// Iterates over tokens to produce paragraphs, as they are finished.
+///
+/// Like `gather_paragraphs()` at the top level of the page, but rather than
+/// collecting every element, each finished paragraph is passed to the callback
+/// along with the exceptions produced for it, and then dropped.
+///
+/// If parsing fails, the source text which wasn't yet passed on is given to
+/// the callback as a paragraph of plain text, before returning the error.
+pub fn stream_paragraphs<'r, 't, F>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    rule: Rule,
+    mut f: F,
+) -> Result<(), ParseWarning>
+where
+    'r: 't,
+    F: FnMut(Vec<Element<'t>>, Vec<ParseException<'t>>),
+{
+    info!(log, "Streaming paragraphs until ending");
+
+    parser.set_rule(rule);
+
+    let mut stack = ParagraphStack::new(log);
+
+    // Where the source text not yet passed on as elements begins
+    let mut unstreamed = parser.current().span.start;
+
+    loop {
+        let result = match parser.current().token {
+            Token::InputEnd => {
+                debug!(log, "Hit the end of input, terminating token iteration");
+                break;
+            }
+            Token::ParagraphBreak => {
+                debug!(log, "Hit a paragraph break, passing on finished paragraph");
+
+                stack.end_paragraph();
+                parser.step().map(|_| ())
+            }
+            _ => {
+                debug!(log, "Trying to consume tokens to produce element");
+
+                consume(log, parser).map(|success| {
+                    let (element, mut exceptions) = success.into();
+                    push_element(&mut stack, element);
+                    stack.push_exceptions(&mut exceptions);
+                })
+            }
+        };
+
+        if let Err(warning) = result {
+            debug!(
+                log,
+                "Failed to stream paragraphs, passing on remaining text";
+                "start" => unstreamed,
+            );
+
+            let text = &parser.full_text().inner()[unstreamed..];
+            let element = Element::Container(Container::new(
+                ContainerType::Paragraph,
+                vec![text!(text)],
+            ));
+
+            f(vec![element], vec![]);
+            return Err(warning);
+        }
+
+        let (elements, exceptions) = stack.take_finished();
+        if !elements.is_empty() || !exceptions.is_empty() {
+            f(elements, exceptions);
+        }
+
+        if stack.current_empty() {
+            unstreamed = parser.current().span.start;
+        }
+    }
+
+    stack.end_paragraph();
+
+    let (elements, exceptions) = stack.take_finished();
+    f(elements, exceptions);
+
+    Ok(())
+}
+
+/// Groups elements into paragraphs around any block-level elements.
+///
+/// This is for containers whose contents are normally inline, such as
+/// table cells. If there are no block elements the contents are returned
+/// as-is, otherwise each run of inline elements is wrapped in a paragraph,
+/// with the block elements as siblings between them.
+pub fn group_paragraphs<'t>(
+    log: &slog::Logger,
+    elements: Vec<Element<'t>>,
+) -> Vec<Element<'t>> {
+    if !elements.iter().any(Element::is_block) {
+        return elements;
+    }
+
+    wrap_paragraphs(log, elements)
+}
+
+/// Wraps each run of inline elements in a paragraph, keeping block elements between them.
+///
+/// Unlike `group_paragraphs()`, this always produces paragraphs, for containers
+/// whose contents are separated into paragraphs regardless, such as the items
+/// of a loose list.
+pub fn wrap_paragraphs<'t>(
+    log: &slog::Logger,
+    elements: Vec<Element<'t>>,
+) -> Vec<Element<'t>> {
+    debug!(
+        log,
+        "Grouping inline elements into paragraphs around blocks"
+    );
+
+    let mut stack = ParagraphStack::new(log);
+
+    // Runs of inline elements are kept as-is, aside from leading whitespace
+    for element in elements {
+        if element.is_block() {
+            stack.push_block(element);
+        } else {
+            push_element(&mut stack, element);
+        }
+    }
+
+    stack.into_elements()
+}
+
 fn push_element<'t>(stack: &mut ParagraphStack<'t>, element: Element<'t>) {
     // Don't add null elements
     if element == Element::Null {
         return;
     }
 
-    // Don't add line break if the element is otherwise empty
-    if stack.current_empty() && element == Element::LineBreak {
+    // Block elements may not be within a paragraph, so they end it,
+    // without the whitespace which separated them
+    if element.is_block() {
+        stack.trim_end();
+        stack.push_block(element);
+        return;
+    }
+
+    // Don't start a paragraph with a line break or whitespace
+    if stack.current_empty() && is_whitespace(&element) {
         return;
     }
 
     stack.push_element(element);
 }
+
+/// Whether this element is only whitespace, such as a space or line break.
+fn is_whitespace(element: &Element) -> bool {
+    match element {
+        Element::Text(text) => text.trim().is_empty(),
+        Element::LineBreak => true,
+        _ => false,
+    }
+}
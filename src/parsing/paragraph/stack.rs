@@ -18,11 +18,12 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use super::is_whitespace;
 use crate::parsing::prelude::*;
 use crate::tree::{Container, ContainerType};
 use std::mem;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParagraphStack<'t> {
     /// The `slog::Logger` instance used for logging stack operations.
     log: slog::Logger,
@@ -64,6 +65,25 @@ impl<'t> ParagraphStack<'t> {
         self.current.push(element);
     }
 
+    /// Pushes a block-level element, which is kept outside of any paragraph.
+    pub fn push_block(&mut self, element: Element<'t>) {
+        debug!(
+            self.log,
+            "Pushing block element to stack";
+            "element" => element.name(),
+        );
+
+        self.end_paragraph();
+        self.finished.push(element);
+    }
+
+    /// Removes any trailing whitespace or line breaks from the current paragraph.
+    pub fn trim_end(&mut self) {
+        while self.current.last().map(is_whitespace) == Some(true) {
+            self.current.pop();
+        }
+    }
+
     #[inline]
     pub fn push_exceptions(&mut self, exceptions: &mut Vec<ParseException<'t>>) {
         debug!(
@@ -111,6 +131,30 @@ impl<'t> ParagraphStack<'t> {
         }
     }
 
+    /// Takes the elements and exceptions gathered so far.
+    ///
+    /// The paragraph currently being built is left on the stack.
+    pub fn take_finished(&mut self) -> (Vec<Element<'t>>, Vec<ParseException<'t>>) {
+        debug!(
+            self.log,
+            "Taking finished elements from stack";
+            "finished-len" => self.finished.len(),
+            "exceptions-len" => self.exceptions.len(),
+        );
+
+        let elements = mem::replace(&mut self.finished, Vec::new());
+        let exceptions = mem::replace(&mut self.exceptions, Vec::new());
+        (elements, exceptions)
+    }
+
+    pub fn into_elements(mut self) -> Vec<Element<'t>> {
+        debug!(self.log, "Converting paragraph stack into elements");
+        debug_assert!(self.exceptions.is_empty());
+
+        self.end_paragraph();
+        self.finished
+    }
+
     pub fn into_result<'r>(mut self) -> ParseResult<'r, 't, Vec<Element<'t>>> {
         debug!(
             self.log,
@@ -19,15 +19,28 @@
  */
 
 use super::condition::ParseCondition;
+use super::context::{ParseContext, ParseContextFlag};
+use super::metrics::MetricsHandle;
+
+#[cfg(feature = "profile")]
+use super::profile::ProfileHandle;
+
 use super::prelude::*;
 use super::rule::Rule;
-use super::RULE_PAGE;
-use crate::span_wrap::SpanWrap;
+use super::{ParseOptions, ParseSettings, RULE_PAGE};
+use crate::includes::IncludedPages;
 use crate::tokenizer::Tokenization;
+use crate::tree::AcceptsPartial;
+use std::cell::RefCell;
 use std::ptr;
+use std::rc::Rc;
 
 const MAX_RECURSION_DEPTH: usize = 100;
 
+lazy_static! {
+    static ref DEFAULT_SETTINGS: ParseSettings = ParseSettings::default();
+}
+
 #[derive(Debug, Clone)]
 pub struct Parser<'r, 't> {
     log: slog::Logger,
@@ -36,6 +49,17 @@ pub struct Parser<'r, 't> {
     full_text: FullText<'t>,
     rule: Rule,
     depth: usize,
+    accepts_partial: AcceptsPartial,
+    context: ParseContext,
+    includes: Option<&'r IncludedPages<'t>>,
+    settings: &'r ParseSettings,
+    metrics: MetricsHandle,
+
+    #[cfg(feature = "profile")]
+    profile: ProfileHandle,
+
+    deferred: Rc<RefCell<Vec<ParseWarning>>>,
+    fallback_head: Option<Token>,
 }
 
 impl<'r, 't> Parser<'r, 't> {
@@ -58,9 +82,32 @@ impl<'r, 't> Parser<'r, 't> {
             full_text,
             rule: RULE_PAGE,
             depth: 0,
+            accepts_partial: AcceptsPartial::None,
+            context: ParseContext::default(),
+            includes: None,
+            settings: &DEFAULT_SETTINGS,
+            metrics: MetricsHandle::default(),
+
+            #[cfg(feature = "profile")]
+            profile: ProfileHandle::default(),
+
+            deferred: Rc::default(),
+            fallback_head: None,
         }
     }
 
+    /// Like `new()`, but with the settings and included pages of the given options.
+    pub(crate) fn with_options(
+        log: &slog::Logger,
+        tokenization: &'r Tokenization<'t>,
+        options: &'r ParseOptions<'r, 't>,
+    ) -> Self {
+        let mut parser = Parser::new(log, tokenization);
+        parser.settings = &options.settings;
+        parser.includes = options.includes;
+        parser
+    }
+
     // Getters
     #[inline]
     pub fn log(&self) -> slog::Logger {
@@ -77,23 +124,132 @@ impl<'r, 't> Parser<'r, 't> {
         self.rule
     }
 
+    /// How many rules deep the parser currently is.
+    ///
+    /// Rules consumed directly by the page are at depth one.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    #[inline]
+    pub fn accepts_partial(&self) -> AcceptsPartial {
+        self.accepts_partial
+    }
+
+    #[inline]
+    pub fn context(&self) -> ParseContext {
+        self.context
+    }
+
+    #[inline]
+    pub fn in_context(&self, flag: ParseContextFlag) -> bool {
+        self.context.contains(flag)
+    }
+
+    /// The pages fetched for parse-time inclusion, if that mode is in use.
+    #[inline]
+    pub fn includes(&self) -> Option<&'r IncludedPages<'t>> {
+        self.includes
+    }
+
+    #[inline]
+    pub fn settings(&self) -> &'r ParseSettings {
+        self.settings
+    }
+
+    /// A handle to the metrics counters, which is shared between clones.
+    #[inline]
+    pub fn metrics_handle(&self) -> MetricsHandle {
+        MetricsHandle::clone(&self.metrics)
+    }
+
+    /// A handle to the rule profile, which is shared between clones.
+    #[cfg(feature = "profile")]
+    #[inline]
+    pub fn profile_handle(&self) -> ProfileHandle {
+        ProfileHandle::clone(&self.profile)
+    }
+
     // Setters
     #[inline]
     pub fn set_rule(&mut self, rule: Rule) {
         self.rule = rule;
     }
 
+    #[inline]
+    pub fn set_accepts_partial(&mut self, value: AcceptsPartial) {
+        self.accepts_partial = value;
+    }
+
+    /// Runs the given function with the context flag set, restoring the prior context after.
+    ///
+    /// This is how rules should mark nested content, so that
+    /// the flag does not leak out if the function fails.
+    pub fn with_context<F, T>(&mut self, flag: ParseContextFlag, f: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        debug!(
+            &self.log,
+            "Entering parser context";
+            "flag" => flag.name(),
+            "context" => self.context,
+        );
+
+        let previous = self.context;
+        self.context.insert(flag);
+        let result = f(self);
+        self.context = previous;
+        result
+    }
+
     pub fn clone_with_rule(&self, rule: Rule) -> Self {
         let mut clone = self.clone();
         clone.set_rule(rule);
         clone
     }
 
+    /// Creates a parser over a separate tokenization, such as for a nested string.
+    ///
+    /// The recursion depth is carried over from this instance, so nested
+    /// parsing cannot be used to circumvent the recursion limit.
+    /// The context flags are also carried over.
+    pub fn clone_with_tokenization<'r2, 't2>(
+        &self,
+        tokenization: &'r2 Tokenization<'t2>,
+    ) -> Parser<'r2, 't2>
+    where
+        'r: 'r2,
+        't: 't2,
+    {
+        let mut parser = Parser::new(&self.log, tokenization);
+        parser.depth = self.depth;
+        parser.context = self.context;
+        parser.includes = self.includes;
+        parser.settings = self.settings;
+        parser.metrics = self.metrics_handle();
+
+        #[cfg(feature = "profile")]
+        {
+            parser.profile = self.profile_handle();
+        }
+
+        parser
+    }
+
     pub fn depth_increment(&mut self) -> Result<(), ParseWarning> {
         debug!(self.log, "Incrementing recursion depth"; "depth" => self.depth);
 
         self.depth += 1;
 
+        let depth = self.depth;
+        self.metrics.update(|metrics| {
+            if depth > metrics.max_depth {
+                metrics.max_depth = depth;
+            }
+        });
+
         if self.depth > MAX_RECURSION_DEPTH {
             return Err(self.make_warn(ParseWarningKind::RecursionDepthExceeded));
         }
@@ -108,6 +264,31 @@ impl<'r, 't> Parser<'r, 't> {
         self.depth -= 1;
     }
 
+    /// Whether the current token begins a line.
+    ///
+    /// Argument values are inline text, rather than lines of their own,
+    /// so nothing within them begins a line.
+    pub fn start_of_line(&self) -> bool {
+        if self.context.contains(ParseContextFlag::Argument) {
+            return false;
+        }
+
+        let start = self.current.span.start;
+
+        start == 0 || self.full_text.inner()[..start].ends_with('\n')
+    }
+
+    // Metrics
+    #[inline]
+    pub fn record_rule_attempt(&self) {
+        self.metrics.update(|metrics| metrics.rules_attempted += 1);
+    }
+
+    #[inline]
+    pub fn record_fallback(&self) {
+        self.metrics.update(|metrics| metrics.fallbacks_taken += 1);
+    }
+
     // State evaluation
     pub fn evaluate(&self, condition: ParseCondition) -> bool {
         debug!(
@@ -116,7 +297,7 @@ impl<'r, 't> Parser<'r, 't> {
             "condition" => format!("{:?}", condition),
             "current-token" => self.current.token,
             "current-slice" => self.current.slice,
-            "current-span" => SpanWrap::from(&self.current.span),
+            "current-span" => self.current.span,
         );
 
         match condition {
@@ -217,6 +398,7 @@ impl<'r, 't> Parser<'r, 't> {
     pub fn update(&mut self, parser: &Parser<'r, 't>) {
         self.current = parser.current;
         self.remaining = parser.remaining;
+        self.fallback_head = parser.fallback_head;
     }
 
     #[inline]
@@ -233,6 +415,7 @@ impl<'r, 't> Parser<'r, 't> {
             Some((current, remaining)) => {
                 self.current = current;
                 self.remaining = remaining;
+                self.metrics.update(|metrics| metrics.tokens_consumed += 1);
                 Ok(current)
             }
 
@@ -273,6 +456,80 @@ impl<'r, 't> Parser<'r, 't> {
             .ok_or_else(|| self.make_warn(ParseWarningKind::EndOfInput))
     }
 
+    /// Checks the children of a parsed element against its content model.
+    ///
+    /// A warning is produced for each child which is not permitted, such as a
+    /// `[[div]]` within a `[[span]]`. The element is kept as-is, since the
+    /// tree's normalization pass closes the container before such a child
+    /// and reopens it afterwards.
+    pub fn check_children(
+        &self,
+        element: &Element<'t>,
+        exceptions: &mut Vec<ParseException<'t>>,
+    ) {
+        for child in element.children() {
+            if !element.accepts(child) {
+                debug!(
+                    &self.log,
+                    "Element not permitted within its container";
+                    "element" => element.name(),
+                    "child" => child.name(),
+                );
+
+                let warning = self.make_warn(ParseWarningKind::InvalidNesting);
+                exceptions.push(ParseException::Warning(warning));
+            }
+        }
+    }
+
+    // Deferred warnings
+
+    /// Records a non-fatal warning from a helper which can only return fatal ones.
+    ///
+    /// These are collected into the block's exceptions once it finishes,
+    /// see `take_deferred_warnings()`.
+    pub fn defer_warning(&self, warning: ParseWarning) {
+        self.deferred.borrow_mut().push(warning);
+    }
+
+    /// How many deferred warnings are currently pending.
+    ///
+    /// Used as a marker, so that only warnings recorded after it are taken.
+    #[inline]
+    pub fn deferred_warnings_len(&self) -> usize {
+        self.deferred.borrow().len()
+    }
+
+    /// Removes all the deferred warnings recorded after the given marker.
+    pub fn take_deferred_warnings(&self, marker: usize) -> Vec<ParseWarning> {
+        let mut deferred = self.deferred.borrow_mut();
+        let marker = marker.min(deferred.len());
+
+        deferred.split_off(marker)
+    }
+
+    /// Records that the head of a block or comment fell back to text.
+    ///
+    /// The given token is the delimiter which would have closed it,
+    /// see `take_fallback_head()`.
+    #[inline]
+    pub fn set_fallback_head(&mut self, close: Token) {
+        self.fallback_head = Some(close);
+    }
+
+    /// Checks whether the given closing delimiter ends a head which fell back to text.
+    ///
+    /// If so, the head is cleared, as the delimiter belongs to it rather
+    /// than having nothing to close.
+    pub fn take_fallback_head(&mut self, close: Token) -> bool {
+        if self.fallback_head == Some(close) {
+            self.fallback_head = None;
+            true
+        } else {
+            false
+        }
+    }
+
     // Utilities
     #[cold]
     #[inline]
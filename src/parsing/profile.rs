@@ -0,0 +1,117 @@
+/*
+ * parsing/profile.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Per-rule timing and match counts, for optimizing the grammar.
+//!
+//! This is only compiled with the `profile` feature, since timing
+//! each rule attempt has a cost which most users should not pay.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParseProfile {
+    /// The statistics for each rule which was attempted, by rule name.
+    pub rules: BTreeMap<&'static str, RuleProfile>,
+}
+
+impl ParseProfile {
+    /// Produces a pretty-printed JSON report of this profile.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Unable to serialize JSON")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RuleProfile {
+    /// How many times the rule was tried.
+    pub attempts: usize,
+
+    /// How many of those attempts produced an element.
+    pub matches: usize,
+
+    /// How many of those attempts failed, causing a backtrack.
+    pub failures: usize,
+
+    /// The total time spent in the rule, in nanoseconds.
+    ///
+    /// This includes the time spent in any rules nested within it.
+    pub total_nanoseconds: u64,
+}
+
+/// Shared handle to the profile, so that all clones of a parser record together.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileHandle(Rc<RefCell<ParseProfile>>);
+
+impl ProfileHandle {
+    #[inline]
+    pub fn get(&self) -> ParseProfile {
+        self.0.borrow().clone()
+    }
+
+    pub fn record(&self, rule: &'static str, elapsed: Duration, matched: bool) {
+        let mut profile = self.0.borrow_mut();
+        let entry = profile.rules.entry(rule).or_default();
+
+        entry.attempts += 1;
+        entry.total_nanoseconds += elapsed.as_nanos() as u64;
+
+        if matched {
+            entry.matches += 1;
+        } else {
+            entry.failures += 1;
+        }
+    }
+}
+
+#[test]
+fn profile() {
+    use crate::parsing::ParseOptions;
+
+    let log = crate::build_logger();
+    let tokenization = crate::tokenize(&log, "**apple** [[ banana");
+    let options = ParseOptions::default();
+    let profile = crate::parse_with_options(&log, &tokenization, &options).profile;
+
+    let strong = profile
+        .rules
+        .get("bold")
+        .expect("Bold rule was not profiled");
+    assert!(strong.attempts > 0, "No attempts were recorded");
+    assert!(strong.matches > 0, "No matches were recorded");
+
+    let failures = profile
+        .rules
+        .values()
+        .map(|rule| rule.failures)
+        .sum::<usize>();
+    assert!(failures > 0, "No failures were recorded");
+
+    for rule in profile.rules.values() {
+        assert_eq!(
+            rule.attempts,
+            rule.matches + rule.failures,
+            "Attempts don't add up to matches and failures",
+        );
+    }
+}
@@ -0,0 +1,86 @@
+/*
+ * parsing/rule/impls/bibcite.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Rule for citations of bibliography entries, such as `((bibcite label))`.
+//!
+//! The citation's number is assigned after parsing, see `resolve_bibliography()`.
+
+use super::prelude::*;
+
+pub const RULE_BIBCITE: Rule = Rule {
+    name: "bibcite",
+    try_consume_fn,
+};
+
+fn try_consume_fn<'p, 'r, 't>(
+    log: &slog::Logger,
+    parser: &'p mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Trying to create a bibliography citation");
+
+    // Reported after parsing if the label turns out not to exist
+    let missing = parser.make_warn(ParseWarningKind::NoSuchBibliographyEntry);
+
+    check_step(parser, Token::LeftParentheses)?;
+
+    if parser.current().token == Token::Whitespace {
+        parser.step()?;
+    }
+
+    let ExtractedToken { token, slice, .. } = parser.current();
+    if *token != Token::Identifier || !slice.eq_ignore_ascii_case("bibcite") {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    parser.step()?;
+
+    if parser.current().token != Token::Whitespace {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    // Gather the label of the entry being cited
+    let label = collect_text(
+        log,
+        parser,
+        RULE_BIBCITE,
+        &[ParseCondition::current(Token::RightParentheses)],
+        &[
+            ParseCondition::current(Token::ParagraphBreak),
+            ParseCondition::current(Token::LineBreak),
+        ],
+        None,
+    )?
+    .trim();
+
+    if label.is_empty() {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    debug!(log, "Retrieved label for citation"; "label" => label);
+
+    let element = Element::BibliographyCite {
+        label: cow!(label),
+        number: None,
+    };
+
+    let exceptions = vec![ParseException::Citation(cow!(label), missing)];
+
+    ok!(element, exceptions)
+}
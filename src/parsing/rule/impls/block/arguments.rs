@@ -18,9 +18,11 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use crate::parsing::{parse_boolean, ParseWarning, ParseWarningKind, Parser};
+use crate::parsing::{
+    parse_boolean, parse_dimension, ParseResult, ParseWarning, ParseWarningKind, Parser,
+};
+use crate::tree::Element;
 use std::borrow::Cow;
-use std::collections::HashMap;
 use std::str::FromStr;
 use unicase::UniCase;
 
@@ -30,9 +32,14 @@ macro_rules! make_warn {
     };
 }
 
+/// The arguments passed in a block's head, in the order they were written.
+///
+/// Keys are compared case-insensitively. Blocks have few arguments,
+/// so a linear scan is used rather than a hash map, which would lose
+/// the author's ordering.
 #[derive(Debug, Clone, Default)]
 pub struct Arguments<'t> {
-    inner: HashMap<UniCase<&'t str>, Cow<'t, str>>,
+    inner: Vec<(UniCase<&'t str>, Cow<'t, str>)>,
 }
 
 impl<'t> Arguments<'t> {
@@ -41,43 +48,207 @@ impl<'t> Arguments<'t> {
         Arguments::default()
     }
 
+    /// Adds an argument, replacing the value of any existing one with this key.
+    ///
+    /// A replaced argument keeps its original position.
     pub fn insert(&mut self, key: &'t str, value: Cow<'t, str>) {
         let key = UniCase::ascii(key);
 
-        self.inner.insert(key, value);
+        match self.position(&key) {
+            Some(index) => self.inner[index].1 = value,
+            None => self.inner.push((key, value)),
+        }
     }
 
     pub fn get(&mut self, key: &'t str) -> Option<Cow<'t, str>> {
         let key = UniCase::ascii(key);
+        let index = self.position(&key)?;
+        let (_, value) = self.inner.remove(index);
 
-        self.inner.remove(&key)
+        Some(value)
     }
 
-    pub fn get_bool(
+    #[inline]
+    fn position(&self, key: &UniCase<&'t str>) -> Option<usize> {
+        self.inner.iter().position(|(other, _)| other == key)
+    }
+
+    /// Iterates over the remaining arguments, in the order they were written.
+    pub fn iter(&self) -> impl Iterator<Item = (&'t str, &Cow<'t, str>)> {
+        self.inner
+            .iter()
+            .map(|(key, value)| (key.into_inner(), value))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Gets the argument, converting it with the given function.
+    ///
+    /// This is intended for use with the functions in `parsing::value`.
+    /// If conversion fails, the warning carries the offending value.
+    pub fn get_with<T, F>(
         &mut self,
         parser: &Parser<'_, 't>,
         key: &'t str,
-    ) -> Result<Option<bool>, ParseWarning> {
+        convert: F,
+    ) -> Result<Option<T>, ParseWarning>
+    where
+        F: FnOnce(&str) -> Option<T>,
+    {
         match self.get(key) {
-            Some(argument) => match parse_boolean(argument) {
-                Ok(value) => Ok(Some(value)),
-                Err(_) => Err(make_warn!(parser)),
+            Some(argument) => match convert(&argument) {
+                Some(value) => Ok(Some(value)),
+                None => Err(make_warn!(parser).with_value(argument)),
             },
             None => Ok(None),
         }
     }
 
+    #[inline]
+    pub fn get_bool(
+        &mut self,
+        parser: &Parser<'_, 't>,
+        key: &'t str,
+    ) -> Result<Option<bool>, ParseWarning> {
+        self.get_with(parser, key, |value| parse_boolean(value))
+    }
+
+    #[inline]
     pub fn get_value<T: FromStr>(
         &mut self,
         parser: &Parser<'_, 't>,
         key: &'t str,
     ) -> Result<Option<T>, ParseWarning> {
+        self.get_with(parser, key, |value| value.trim().parse().ok())
+    }
+
+    /// Gets a size argument, such as a width or height.
+    ///
+    /// The value is kept as written, but must be a valid dimension,
+    /// such as `300`, `300px`, or `50%` (see `parse_dimension()`).
+    pub fn get_dimension(
+        &mut self,
+        parser: &Parser<'_, 't>,
+        key: &'t str,
+    ) -> Result<Option<Cow<'t, str>>, ParseWarning> {
         match self.get(key) {
-            Some(argument) => match argument.parse() {
-                Ok(value) => Ok(Some(value)),
-                Err(_) => Err(make_warn!(parser)),
-            },
-            None => Ok(None),
+            Some(argument) if parse_dimension(&argument).is_none() => {
+                Err(make_warn!(parser).with_value(argument))
+            }
+            argument => Ok(argument),
+        }
+    }
+
+    /// Gets the argument, parsing its value as wikitext.
+    ///
+    /// See `Parser::get_argument_elements()`.
+    pub fn get_elements<'r>(
+        &mut self,
+        parser: &Parser<'r, 't>,
+        key: &'t str,
+    ) -> ParseResult<'r, 't, Option<Vec<Element<'t>>>>
+    where
+        'r: 't,
+    {
+        match self.get(key) {
+            Some(argument) => parser.get_argument_elements(&argument)?.map_ok(Some),
+            None => ok!(None),
         }
     }
 }
+
+/// Restricts an argument given by an untrusted author.
+///
+/// Returns `None` if the argument is dropped.
+/// See `ParseSettings::sanitize_arguments`.
+pub fn sanitize_argument<'t>(key: &str, value: Cow<'t, str>) -> Option<Cow<'t, str>> {
+    if key.eq_ignore_ascii_case("style") {
+        return None;
+    }
+
+    if key.eq_ignore_ascii_case("id") && !value.starts_with("u-") {
+        return Some(Cow::Owned(format!("u-{}", value)));
+    }
+
+    Some(value)
+}
+
+/// Cuts an argument's value down to at most the given number of characters.
+///
+/// Returns the value and whether it was shortened.
+/// See `ParseSettings::max_argument_length`.
+pub fn truncate_argument(value: &str, max_length: usize) -> (&str, bool) {
+    match value.char_indices().nth(max_length) {
+        Some((index, _)) => (&value[..index], true),
+        None => (value, false),
+    }
+}
+
+#[test]
+fn arguments() {
+    let mut arguments = Arguments::new();
+    arguments.insert("style", cow!("color: red;"));
+    arguments.insert("ID", cow!("apple"));
+    arguments.insert("class", cow!("fruit"));
+    arguments.insert("id", cow!("banana"));
+
+    assert_eq!(
+        arguments.iter().collect::<Vec<_>>(),
+        vec![
+            ("style", &cow!("color: red;")),
+            ("ID", &cow!("banana")),
+            ("class", &cow!("fruit")),
+        ],
+        "Arguments not in insertion order",
+    );
+
+    assert_eq!(arguments.get("Id"), Some(cow!("banana")));
+    assert_eq!(arguments.get("id"), None);
+    assert_eq!(arguments.len(), 2);
+}
+
+#[test]
+fn sanitize() {
+    assert_eq!(sanitize_argument("Style", cow!("position: fixed;")), None);
+    assert_eq!(
+        sanitize_argument("id", cow!("apple")),
+        Some(cow!("u-apple"))
+    );
+    assert_eq!(
+        sanitize_argument("ID", cow!("u-banana")),
+        Some(cow!("u-banana"))
+    );
+    assert_eq!(
+        sanitize_argument("class", cow!("cherry")),
+        Some(cow!("cherry"))
+    );
+}
+
+#[test]
+fn truncate() {
+    macro_rules! check {
+        ($value:expr, $max_length:expr, $expected:expr, $truncated:expr $(,)?) => {{
+            let (value, truncated) = truncate_argument($value, $max_length);
+
+            assert_eq!(value, $expected, "Truncated value doesn't match expected");
+            assert_eq!(
+                truncated, $truncated,
+                "Truncation result doesn't match expected"
+            );
+        }};
+    }
+
+    check!("apple", 10, "apple", false);
+    check!("apple", 5, "apple", false);
+    check!("banana", 3, "ban", true);
+    check!("cherry", 0, "", true);
+    check!("dürüm", 2, "dü", true);
+}
@@ -0,0 +1,63 @@
+/*
+ * parsing/rule/impls/block/blocks/abbr.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+
+pub const BLOCK_ABBR: BlockRule = BlockRule {
+    name: "block-abbr",
+    accepts_names: &["abbr", "abbreviation"],
+    accepts_special: false,
+    newline_separator: false,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(
+        log,
+        "Parsing abbreviation block";
+        "in-head" => in_head,
+        "name" => name,
+    );
+
+    assert_eq!(special, false, "Abbreviation doesn't allow special variant");
+    assert_block_name(&BLOCK_ABBR, name);
+
+    let mut arguments = parser.get_head_map(&BLOCK_ABBR, in_head)?;
+
+    // Get the expansion, which is required
+    let title = match arguments.get("title") {
+        Some(title) => title,
+        None => return Err(parser.make_warn(ParseWarningKind::BlockMissingArguments)),
+    };
+
+    // Get body content, without paragraphs
+    let (elements, exceptions) = parser.get_body_elements(&BLOCK_ABBR, false)?.into();
+
+    // Build and return element
+    let element = Element::Abbreviation { title, elements };
+
+    ok!(element, exceptions)
+}
@@ -0,0 +1,102 @@
+/*
+ * parsing/rule/impls/block/blocks/anchor.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Block for links wrapping arbitrary content, with control over the
+//! "target" and "rel" attributes, such as `[[a href="/page" target="_top"]]`.
+
+use super::prelude::*;
+use crate::enums::AnchorTarget;
+use crate::parsing::rule::impls::link_single::url_valid;
+use std::convert::TryFrom;
+
+pub const BLOCK_ANCHOR: BlockRule = BlockRule {
+    name: "block-anchor",
+    accepts_names: &["a", "a_"],
+    accepts_special: false,
+    newline_separator: false,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(
+        log,
+        "Parsing anchor block";
+        "in-head" => in_head,
+        "name" => name,
+    );
+
+    assert_eq!(special, false, "Anchor doesn't allow special variant");
+    assert_block_name(&BLOCK_ANCHOR, name);
+
+    let mut arguments = parser.get_head_map(&BLOCK_ANCHOR, in_head)?;
+
+    // Like "span_", "a_" strips surrounding line breaks
+    let strip_line_breaks = name.ends_with('_');
+
+    // Get link arguments
+    let url = match arguments.get("href") {
+        Some(url) if url_valid(&url) => url,
+        Some(url) => {
+            return Err(parser
+                .make_warn(ParseWarningKind::InvalidUrl)
+                .with_value(url))
+        }
+        None => return Err(parser.make_warn(ParseWarningKind::BlockMissingArguments)),
+    };
+
+    let anchor = arguments
+        .get_with(parser, "target", |value| {
+            AnchorTarget::try_from(value.trim()).ok()
+        })?
+        .unwrap_or(AnchorTarget::Same);
+
+    let rel = arguments.get("rel");
+
+    // Get styling arguments
+    let id = arguments.get("id");
+    let class = arguments.get("class");
+    let style = arguments.get("style");
+
+    // Get body content, without paragraphs
+    let (mut elements, exceptions) =
+        parser.get_body_elements(&BLOCK_ANCHOR, false)?.into();
+
+    if strip_line_breaks {
+        trim_line_breaks(&mut elements);
+    }
+
+    let element = Element::Anchor {
+        elements,
+        url,
+        anchor,
+        rel,
+        id,
+        class,
+        style,
+    };
+
+    ok!(element, exceptions)
+}
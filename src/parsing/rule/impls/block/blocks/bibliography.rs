@@ -0,0 +1,118 @@
+/*
+ * parsing/rule/impls/block/blocks/bibliography.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Block for a list of entries which can be cited with `((bibcite label))`.
+//!
+//! Each entry is a line of the form `: label : contents`,
+//! and any other lines in the body are ignored:
+//!
+//! ```text
+//! [[bibliography title="References"]]
+//! : smith : Smith, J. //On Anomalies//. 1998.
+//! : jones : Jones, K. //Containment Procedures//. 2004.
+//! [[/bibliography]]
+//! ```
+
+use super::prelude::*;
+use crate::tree::BibliographyEntry;
+
+pub const BLOCK_BIBLIOGRAPHY: BlockRule = BlockRule {
+    name: "block-bibliography",
+    accepts_names: &["bibliography"],
+    accepts_special: false,
+    newline_separator: true,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing bibliography block"; "in-head" => in_head);
+
+    assert_eq!(special, false, "Bibliography doesn't allow special variant");
+    assert_block_name(&BLOCK_BIBLIOGRAPHY, name);
+
+    let mut arguments = parser.get_head_map(&BLOCK_BIBLIOGRAPHY, in_head)?;
+    let title = arguments.get("title");
+    let hide = arguments.get_bool(parser, "hide")?.unwrap_or(false);
+
+    let body = parser.get_body_text(&BLOCK_BIBLIOGRAPHY)?;
+    let mut entries = Vec::new();
+    let mut exceptions = Vec::new();
+
+    for (label, contents) in split_entries(body) {
+        let elements = parser
+            .get_argument_elements(contents)?
+            .chain(&mut exceptions);
+
+        entries.push(BibliographyEntry::new(cow!(label), elements));
+    }
+
+    let element = Element::Bibliography {
+        title,
+        hide,
+        entries,
+    };
+
+    ok!(element, exceptions)
+}
+
+/// Gets the label and contents of each entry line in the body.
+fn split_entries(body: &str) -> Vec<(&str, &str)> {
+    body.lines()
+        .filter_map(|line| {
+            let line = line.trim().strip_prefix(':')?;
+            let index = line.find(':')?;
+            let label = line[..index].trim();
+            let contents = line[index + 1..].trim();
+
+            if label.is_empty() {
+                None
+            } else {
+                Some((label, contents))
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn entries() {
+    let body = concat!(
+        ": apple : Apple pie\n",
+        "not an entry\n",
+        " :banana:Banana: split\n",
+        ": : No label\n",
+        ": cherry :",
+    );
+
+    assert_eq!(
+        split_entries(body),
+        vec![
+            ("apple", "Apple pie"),
+            ("banana", "Banana: split"),
+            ("cherry", ""),
+        ],
+        "Bibliography entries don't match expected",
+    );
+}
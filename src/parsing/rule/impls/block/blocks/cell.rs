@@ -0,0 +1,75 @@
+/*
+ * parsing/rule/impls/block/blocks/cell.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::parsing::parse_integer;
+use crate::tree::{AcceptsPartial, PartialElement, TableCell};
+use std::num::NonZeroU32;
+
+pub const BLOCK_TABLE_CELL: BlockRule = BlockRule {
+    name: "block-table-cell",
+    accepts_names: &["cell", "hcell"],
+    accepts_special: false,
+    newline_separator: true,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing table cell block"; "in-head" => in_head, "name" => name);
+
+    assert_eq!(special, false, "Table cell doesn't allow special variant");
+    assert_block_name(&BLOCK_TABLE_CELL, name);
+
+    // Cells are only valid within a table row
+    if parser.accepts_partial() != AcceptsPartial::TableCell {
+        return Err(parser.make_warn(ParseWarningKind::TableCellOutsideRow));
+    }
+
+    let header = name.eq_ignore_ascii_case("hcell");
+
+    let mut arguments = parser.get_head_map(&BLOCK_TABLE_CELL, in_head)?;
+    let id = arguments.get("id");
+    let class = arguments.get("class");
+    let style = arguments.get("style");
+    let column_span = arguments
+        .get_with(parser, "colspan", parse_integer::<NonZeroU32>)?
+        .unwrap_or_else(|| NonZeroU32::new(1).unwrap());
+
+    // Get body content, with paragraphs
+    //
+    // Tables may be nested within a cell, but not bare rows or cells
+    parser.set_accepts_partial(AcceptsPartial::None);
+
+    let (elements, exceptions) =
+        parser.get_body_elements(&BLOCK_TABLE_CELL, true)?.into();
+
+    // Build partial element and return
+    let cell = TableCell::new(header, column_span, None, elements)
+        .with_attributes(id, class, style);
+    let element = Element::Partial(PartialElement::TableCell(cell));
+
+    ok!(element, exceptions)
+}
@@ -0,0 +1,64 @@
+/*
+ * parsing/rule/impls/block/blocks/char.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::parsing::parse_entity;
+use std::borrow::Cow;
+
+pub const BLOCK_CHAR: BlockRule = BlockRule {
+    name: "block-char",
+    accepts_names: &["char", "character"],
+    accepts_special: false,
+    newline_separator: false,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing character block"; "in-head" => in_head);
+
+    assert_eq!(special, false, "Character doesn't allow special variant");
+    assert_block_name(&BLOCK_CHAR, name);
+
+    let ch = parser.get_head_value(&BLOCK_CHAR, in_head, parse_char)?;
+
+    ok!(Element::Text(Cow::Owned(ch.to_string())))
+}
+
+fn parse_char<'r, 't>(
+    parser: &Parser<'r, 't>,
+    argument: Option<&'t str>,
+) -> Result<char, ParseWarning> {
+    let argument = match argument {
+        Some(arg) => arg.trim(),
+        None => return Err(parser.make_warn(ParseWarningKind::BlockMissingArguments)),
+    };
+
+    parse_entity(argument).map_err(|_| {
+        debug!(&parser.log(), "Unknown character entity"; "entity" => argument);
+
+        parser.make_warn(ParseWarningKind::InvalidEntity)
+    })
+}
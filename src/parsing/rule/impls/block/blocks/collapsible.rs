@@ -19,7 +19,7 @@
  */
 
 use super::prelude::*;
-use crate::parsing::{ParseWarning, ParseWarningKind};
+use crate::parsing::{parse_choice, ParseWarning, ParseWarningKind};
 
 pub const BLOCK_COLLAPSIBLE: BlockRule = BlockRule {
     name: "block-collapsible",
@@ -52,9 +52,20 @@ fn parse_fn<'r, 't>(
     let class = arguments.get("class");
     let style = arguments.get("style");
 
-    // Get display arguments
-    let show_text = arguments.get("show");
-    let hide_text = arguments.get("hide");
+    // Get display arguments, which may contain formatting
+    //
+    // These are rendered within the toggle links
+    let mut exceptions = Vec::new();
+    let show_text = parser
+        .with_context(ParseContextFlag::LinkLabel, |parser| {
+            arguments.get_elements(parser, "show")
+        })?
+        .chain(&mut exceptions);
+    let hide_text = parser
+        .with_context(ParseContextFlag::LinkLabel, |parser| {
+            arguments.get_elements(parser, "hide")
+        })?
+        .chain(&mut exceptions);
 
     // Get folding arguments
     //
@@ -66,8 +77,9 @@ fn parse_fn<'r, 't>(
     };
 
     // Get body content, with paragraphs
-    let (elements, exceptions) =
-        parser.get_body_elements(&BLOCK_COLLAPSIBLE, true)?.into();
+    let elements = parser
+        .get_body_elements(&BLOCK_COLLAPSIBLE, true)?
+        .chain(&mut exceptions);
 
     // Build element and return
     let element = Element::Collapsible {
@@ -94,14 +106,11 @@ fn parse_hide_location(s: &str, parser: &Parser) -> Result<(bool, bool), ParseWa
         ("none", (false, false)),
     ];
 
-    let s = s.trim();
-    for &(name, value) in &NAMES {
-        if name.eq_ignore_ascii_case(s) {
-            return Ok(value);
-        }
-    }
-
-    debug!(&parser.log(), "Unknown hideLocation argument"; "value" => s);
+    parse_choice(s, &NAMES).ok_or_else(|| {
+        debug!(&parser.log(), "Unknown hideLocation argument"; "value" => s);
 
-    Err(parser.make_warn(ParseWarningKind::BlockMalformedArguments))
+        parser
+            .make_warn(ParseWarningKind::BlockMalformedArguments)
+            .with_value(s)
+    })
 }
@@ -0,0 +1,108 @@
+/*
+ * parsing/rule/impls/block/blocks/counter.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Blocks for numbering things on a page, such as figures.
+//!
+//! `[[counter figure]]` increments the counter named "figure" and displays
+//! its value. The amount can be changed with `increment`, or the counter set
+//! with `reset`. Giving it an `id` allows `[[counter-ref id]]` elsewhere on
+//! the page to display the same value.
+
+use super::prelude::*;
+use crate::tree::{Counter, CounterAction};
+
+pub const BLOCK_COUNTER: BlockRule = BlockRule {
+    name: "block-counter",
+    accepts_names: &["counter"],
+    accepts_special: false,
+    newline_separator: false,
+    parse_fn: parse_counter,
+};
+
+pub const BLOCK_COUNTER_REF: BlockRule = BlockRule {
+    name: "block-counter-ref",
+    accepts_names: &["counter-ref"],
+    accepts_special: false,
+    newline_separator: false,
+    parse_fn: parse_counter_ref,
+};
+
+fn parse_counter<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing counter block"; "in-head" => in_head);
+
+    assert_eq!(special, false, "Counter doesn't allow special variant");
+    assert_block_name(&BLOCK_COUNTER, name);
+
+    let (counter_name, mut arguments) =
+        parser.get_head_name_map(&BLOCK_COUNTER, in_head)?;
+
+    let id = arguments.get("id");
+    let increment = arguments.get_value(parser, "increment")?;
+    let reset = arguments.get_value(parser, "reset")?;
+
+    let action = match reset {
+        Some(start) => CounterAction::Reset(start),
+        None => CounterAction::Increment(increment.unwrap_or(1)),
+    };
+
+    // The value is assigned once the whole page has been parsed
+    let counter = Counter::new(cow!(counter_name), id, action);
+
+    ok!(Element::Counter(counter))
+}
+
+fn parse_counter_ref<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing counter reference block"; "in-head" => in_head);
+
+    assert_eq!(
+        special, false,
+        "Counter reference doesn't allow special variant"
+    );
+    assert_block_name(&BLOCK_COUNTER_REF, name);
+
+    let id =
+        parser.get_head_value(
+            &BLOCK_COUNTER_REF,
+            in_head,
+            |parser, value| match value.map(str::trim) {
+                Some(id) if !id.is_empty() => Ok(id),
+                _ => Err(parser.make_warn(ParseWarningKind::BlockMissingArguments)),
+            },
+        )?;
+
+    let element = Element::CounterRef {
+        id: cow!(id),
+        value: None,
+    };
+
+    ok!(element)
+}
@@ -0,0 +1,59 @@
+/*
+ * parsing/rule/impls/block/blocks/date.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+
+pub const BLOCK_DATE: BlockRule = BlockRule {
+    name: "block-date",
+    accepts_names: &["date"],
+    accepts_special: false,
+    newline_separator: false,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing date block"; "in-head" => in_head);
+
+    assert_eq!(special, false, "Date doesn't allow special variant");
+    assert_block_name(&BLOCK_DATE, name);
+
+    let (value, mut arguments) = parser.get_head_name_map(&BLOCK_DATE, in_head)?;
+
+    let timestamp = value.trim().parse().map_err(|_| {
+        parser
+            .make_warn(ParseWarningKind::BlockMalformedArguments)
+            .with_value(value)
+    })?;
+
+    // The time is formatted when rendering,
+    // since it depends on the reader's timezone.
+    let element = Element::Date {
+        timestamp,
+        format: arguments.get("format"),
+    };
+
+    ok!(element)
+}
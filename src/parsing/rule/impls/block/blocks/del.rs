@@ -62,6 +62,8 @@ fn parse_fn<'r, 't>(
         id,
         class,
         style,
+        None,
+        None,
     ));
 
     ok!(element, exceptions)
@@ -0,0 +1,64 @@
+/*
+ * parsing/rule/impls/block/blocks/diagram.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::enums::DiagramKind;
+
+pub const BLOCK_DIAGRAM: BlockRule = BlockRule {
+    name: "block-diagram",
+    accepts_names: &["chart", "mermaid"],
+    accepts_special: false,
+    newline_separator: true,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(
+        log,
+        "Parsing diagram block";
+        "in-head" => in_head,
+        "name" => name,
+    );
+
+    assert_eq!(special, false, "Diagram doesn't allow special variant");
+    assert_block_name(&BLOCK_DIAGRAM, name);
+
+    let kind = if name.eq_ignore_ascii_case("mermaid") {
+        DiagramKind::Mermaid
+    } else {
+        DiagramKind::Chart
+    };
+
+    parser.get_head_none(&BLOCK_DIAGRAM, in_head)?;
+
+    let contents = parser.get_body_text(&BLOCK_DIAGRAM)?;
+    let element = Element::Diagram {
+        kind,
+        contents: cow!(contents),
+    };
+
+    ok!(element)
+}
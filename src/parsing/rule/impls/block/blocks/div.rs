@@ -56,6 +56,10 @@ fn parse_fn<'r, 't>(
     let class = arguments.get("class");
     let style = arguments.get("style");
 
+    // Get layout arguments, these are validated rather than raw CSS
+    let float = arguments.get_value(parser, "float")?;
+    let clear = arguments.get_value(parser, "clear")?;
+
     // Get body content, based on whether we want paragraphs or not
     let (elements, exceptions) = parser
         .get_body_elements(&BLOCK_DIV, wrap_paragraphs)?
@@ -68,6 +72,8 @@ fn parse_fn<'r, 't>(
         id,
         class,
         style,
+        float,
+        clear,
     ));
 
     ok!(element, exceptions)
@@ -0,0 +1,95 @@
+/*
+ * parsing/rule/impls/block/blocks/footnote.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Blocks for footnotes, and where the page lists them.
+//!
+//! `[[footnote]]` is replaced by a numbered reference to its contents,
+//! which are listed at `[[footnoteblock]]`, or the end of the page if
+//! there is none. The block's heading can be changed with `title`,
+//! or the list left out entirely with `hide="true"`.
+
+use super::prelude::*;
+
+pub const BLOCK_FOOTNOTE: BlockRule = BlockRule {
+    name: "block-footnote",
+    accepts_names: &["footnote"],
+    accepts_special: false,
+    newline_separator: false,
+    parse_fn: parse_footnote,
+};
+
+pub const BLOCK_FOOTNOTE_BLOCK: BlockRule = BlockRule {
+    name: "block-footnote-block",
+    accepts_names: &["footnoteblock"],
+    accepts_special: false,
+    newline_separator: false,
+    parse_fn: parse_footnote_block,
+};
+
+fn parse_footnote<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing footnote block"; "in-head" => in_head);
+
+    assert_eq!(special, false, "Footnote doesn't allow special variant");
+    assert_block_name(&BLOCK_FOOTNOTE, name);
+
+    // The contents are listed in the footnote block, not where this is
+    if parser.in_context(ParseContextFlag::Footnote) {
+        return Err(parser.make_warn(ParseWarningKind::NestedFootnote));
+    }
+
+    parser.get_head_none(&BLOCK_FOOTNOTE, in_head)?;
+
+    // Get body content, without paragraphs
+    let (elements, exceptions) = parser
+        .with_context(ParseContextFlag::Footnote, |parser| {
+            parser.get_body_elements(&BLOCK_FOOTNOTE, false)
+        })?
+        .into();
+
+    ok!(Element::Footnote { elements }, exceptions)
+}
+
+fn parse_footnote_block<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing footnote list block"; "in-head" => in_head);
+
+    assert_eq!(
+        special, false,
+        "Footnote block doesn't allow special variant"
+    );
+    assert_block_name(&BLOCK_FOOTNOTE_BLOCK, name);
+
+    let mut arguments = parser.get_head_map(&BLOCK_FOOTNOTE_BLOCK, in_head)?;
+    let title = arguments.get("title");
+    let hide = arguments.get_bool(parser, "hide")?.unwrap_or(false);
+
+    ok!(Element::FootnoteBlock { title, hide })
+}
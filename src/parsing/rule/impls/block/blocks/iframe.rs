@@ -0,0 +1,56 @@
+/*
+ * parsing/rule/impls/block/blocks/iframe.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+
+pub const BLOCK_IFRAME: BlockRule = BlockRule {
+    name: "block-iframe",
+    accepts_names: &["iframe"],
+    accepts_special: false,
+    newline_separator: false,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing iframe block"; "in-head" => in_head);
+
+    assert_eq!(special, false, "Iframe doesn't allow special variant");
+    assert_block_name(&BLOCK_IFRAME, name);
+
+    let (url, mut arguments) = parser.get_head_name_map(&BLOCK_IFRAME, in_head)?;
+
+    // Whether the URL may be embedded depends on the site,
+    // so it is checked when rendering instead.
+    let element = Element::Iframe {
+        url: cow!(url),
+        width: arguments.get_dimension(parser, "width")?,
+        height: arguments.get_dimension(parser, "height")?,
+        class: arguments.get("class"),
+        style: arguments.get("style"),
+    };
+
+    ok!(element)
+}
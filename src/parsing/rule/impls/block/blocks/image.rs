@@ -0,0 +1,83 @@
+/*
+ * parsing/rule/impls/block/blocks/image.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Block for images, which may be aligned by a prefix on the block's name.
+//!
+//! For instance, `[[=image apple.png]]` is centered, and `[[f<image apple.png]]`
+//! floats to the left. See `ImageSource` for what the image may come from.
+
+use super::prelude::*;
+use crate::data::ImageAlignment;
+use crate::tree::{Image, ImageSource};
+
+pub const BLOCK_IMAGE: BlockRule = BlockRule {
+    name: "block-image",
+    accepts_names: &["image", "=image", "<image", ">image", "f<image", "f>image"],
+    accepts_special: false,
+    newline_separator: false,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing image block"; "in-head" => in_head, "name" => name);
+
+    assert_eq!(special, false, "Image doesn't allow special variant");
+    assert_block_name(&BLOCK_IMAGE, name);
+
+    // Get alignment from the block name's prefix, if any
+    let prefix = name[..name.len() - "image".len()].to_ascii_lowercase();
+    let alignment = ImageAlignment::parse(&prefix);
+
+    let (source, mut arguments) = parser.get_head_name_map(&BLOCK_IMAGE, in_head)?;
+    let source = match ImageSource::parse(source) {
+        Some(source) => source,
+        None => return Err(parser.make_warn(ParseWarningKind::BlockMissingName)),
+    };
+
+    // Get layout arguments
+    let float = arguments.get_value(parser, "float")?;
+    let clear = arguments.get_value(parser, "clear")?;
+
+    // Parse the caption as wikitext, if present
+    let (caption, exceptions) = arguments.get_elements(parser, "caption")?.into();
+
+    // Build and return element
+    let element = Element::Image(Box::new(Image {
+        source,
+        link: arguments.get("link"),
+        alignment,
+        alt: arguments.get("alt"),
+        width: arguments.get_dimension(parser, "width")?,
+        height: arguments.get_dimension(parser, "height")?,
+        class: arguments.get("class"),
+        style: arguments.get("style"),
+        caption,
+        float,
+        clear,
+    }));
+
+    ok!(element, exceptions)
+}
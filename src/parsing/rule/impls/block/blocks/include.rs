@@ -19,19 +19,25 @@
  */
 
 use super::prelude::*;
+use crate::includes::{parse_include_block, IncludeVariables};
+use std::borrow::Cow;
 
-/// Psuedo block rule for include.
+/// Block rule for include.
 ///
-/// Because includes are performed first, before preprocessing,
-/// tokenizing, or any other steps, no `[[include]]` blocks
+/// Normally includes are performed first, before preprocessing,
+/// tokenizing, or any other steps, so no `[[include]]` blocks
 /// should actually be present in the wikitext.
 ///
 /// If they are, this indicates that an error occurred parsing
 /// them. As such, we return a particular warning instead of
 /// interpreting the block.
+///
+/// The exception is parse-time inclusion (see `ParseOptions::includes`),
+/// where `[[include]]` blocks are left in place. Here the block is matched
+/// with its fetched page, which is parsed separately as its own elements.
 pub const BLOCK_INCLUDE: BlockRule = BlockRule {
     name: "block-include",
-    accepts_names: &["include"],
+    accepts_names: &["include", "include-messy"],
     accepts_special: false,
     newline_separator: true,
     parse_fn,
@@ -44,13 +50,123 @@ fn parse_fn<'r, 't>(
     special: bool,
     in_head: bool,
 ) -> ParseResult<'r, 't, Element<'t>> {
-    debug!(log, "Found invalid include block"; "in-head" => in_head);
+    debug!(log, "Found include block"; "in-head" => in_head);
 
     assert_eq!(special, false, "Include doesn't allow special variant");
     assert_block_name(&BLOCK_INCLUDE, name);
 
-    // Includes are handled specially, so we should never actually be
+    // Unless we're including at parse time, we should never actually be
     // parsing a block here. So, we return a warning.
+    //
+    // Messy includes are always substituted textually.
+    let includes = match parser.includes() {
+        Some(includes) if in_head && name.eq_ignore_ascii_case("include") => includes,
+        _ => return Err(parser.make_warn(ParseWarningKind::InvalidInclude)),
+    };
+
+    // Whether the include is preceded by other content on its line
+    let after_content = {
+        let text = parser.full_text().inner();
+        let before = &text[..parser.current().span.start];
+        let before = &before[..before.rfind("[[").unwrap_or(0)];
+
+        !before.is_empty() && !before.ends_with('\n')
+    };
+
+    // Reconstruct the block, so it can be read by the include parser
+    let head_token = parser.current();
+    let head = collect_text(
+        log,
+        parser,
+        BLOCK_INCLUDE.rule(),
+        &[ParseCondition::current(Token::RightBlock)],
+        &[ParseCondition::current(Token::ParagraphBreak)],
+        Some(ParseWarningKind::InvalidInclude),
+    )?;
+
+    let block = format!("[[include {}]]", head);
+    let include = match parse_include_block(log, &block, 0) {
+        Some((include, _)) => include,
+        None => return Err(parser.make_warn(ParseWarningKind::InvalidInclude)),
+    };
+
+    // Get the fetched page and parse it
+    let content = match includes.get(&include) {
+        Some(content) => content,
+        None => return Err(parser.make_warn(ParseWarningKind::InvalidInclude)),
+    };
+
+    let (mut elements, exceptions) = parser.get_include_elements(content)?.into();
+
+    // If the include shares its line with other content, such as
+    // "Apple [[include page]] Banana", then it's within a paragraph.
+    //
+    // A page which is only a single paragraph is unwrapped, so its contents
+    // join that paragraph instead of nesting another one inside of it.
+    let inline = after_content
+        || !matches!(
+            parser.current().token,
+            Token::LineBreak | Token::ParagraphBreak | Token::InputEnd,
+        );
+
+    if inline {
+        elements = unwrap_paragraph(elements);
+    }
+
+    // Build and return element
+    let element = Element::Include {
+        page: include.page_ref().to_owned(),
+        variables: limit_variables(parser, include.variables(), head_token),
+        elements,
+    };
+
+    ok!(element, exceptions)
+}
+
+/// Copies the include's variables into the tree, within the argument limits.
+///
+/// Variables aren't kept in order, so any past the count limit are dropped
+/// by name, so that the same ones are dropped each time.
+fn limit_variables<'r, 't>(
+    parser: &Parser<'r, 't>,
+    variables: &IncludeVariables,
+    token: &'r ExtractedToken<'t>,
+) -> IncludeVariables<'static>
+where
+    'r: 't,
+{
+    let mut keys: Vec<_> = variables.keys().collect();
+    keys.sort();
+
+    let mut limited = IncludeVariables::new();
+
+    for key in keys {
+        if parser.argument_limit_reached(limited.len(), token) {
+            continue;
+        }
+
+        let value = parser.limit_head_value(&variables[key], token);
+        let key = Cow::Owned(key.as_ref().to_owned());
+        let value = Cow::Owned(value.to_owned());
+
+        limited.insert(key, value);
+    }
+
+    limited
+}
+
+fn unwrap_paragraph(mut elements: Vec<Element>) -> Vec<Element> {
+    let single_paragraph = matches!(
+        elements.as_slice(),
+        [Element::Container(container)] if container.ctype() == ContainerType::Paragraph,
+    );
+
+    if !single_paragraph {
+        return elements;
+    }
 
-    Err(parser.make_warn(ParseWarningKind::InvalidInclude))
+    match elements.pop() {
+        Some(Element::Container(container)) => container.into(),
+        _ => unreachable!(),
+    }
 }
@@ -19,8 +19,14 @@
  */
 
 use super::prelude::*;
+use crate::parsing::parse_integer;
 use std::num::NonZeroU32;
 
+/// The largest number of line breaks a single block may produce.
+///
+/// This prevents a short block from generating an enormous output.
+const MAX_LINE_BREAKS: u32 = 100;
+
 pub const BLOCK_LINES: BlockRule = BlockRule {
     name: "block-lines",
     accepts_names: &["lines", "newlines"],
@@ -55,9 +61,26 @@ fn parse_count<'r, 't>(
         None => return Err(parser.make_warn(ParseWarningKind::BlockMissingArguments)),
     };
 
-    argument.parse().map_err(|error| {
-        debug!(&parser.log(), "Invalid numeric expression: {}", error);
+    let count: NonZeroU32 = parse_integer(argument).ok_or_else(|| {
+        debug!(&parser.log(), "Invalid numeric expression"; "value" => argument);
+
+        parser
+            .make_warn(ParseWarningKind::BlockMalformedArguments)
+            .with_value(argument)
+    })?;
+
+    if count.get() > MAX_LINE_BREAKS {
+        debug!(
+            &parser.log(),
+            "Too many line breaks requested";
+            "count" => count.get(),
+            "max" => MAX_LINE_BREAKS,
+        );
+
+        return Err(parser
+            .make_warn(ParseWarningKind::BlockMalformedArguments)
+            .with_value(argument));
+    }
 
-        parser.make_warn(ParseWarningKind::BlockMalformedArguments)
-    })
+    Ok(count)
 }
@@ -22,6 +22,7 @@ mod prelude {
     pub use super::super::{Arguments, BlockRule};
     pub use crate::parsing::collect::*;
     pub use crate::parsing::condition::ParseCondition;
+    pub use crate::parsing::context::ParseContextFlag;
     pub use crate::parsing::parser::Parser;
     pub use crate::parsing::prelude::*;
     pub use crate::parsing::{ParseWarning, Token};
@@ -54,28 +55,100 @@ mod prelude {
     pub fn assert_block_name(block_rule: &BlockRule, actual_name: &str) {
         assert_generic_name(block_rule.accepts_names, actual_name, "block")
     }
+
+    /// Removes leading and trailing line breaks, for the `_` variants of inline blocks.
+    pub fn trim_line_breaks(elements: &mut Vec<Element>) {
+        let is_line_break = |element: &Element| {
+            matches!(element, Element::LineBreak | Element::LineBreaks(_))
+        };
+
+        let start = elements
+            .iter()
+            .position(|element| !is_line_break(element))
+            .unwrap_or(elements.len());
+
+        elements.drain(..start);
+
+        while let Some(element) = elements.last() {
+            if !is_line_break(element) {
+                break;
+            }
+
+            elements.pop();
+        }
+    }
 }
 
+mod abbr;
+mod anchor;
+mod bibliography;
+mod blockquote;
+mod cell;
+mod char;
 mod code;
 mod collapsible;
+mod counter;
 mod css;
+mod date;
 mod del;
+mod diagram;
 mod div;
+mod footnote;
+mod iframe;
+mod image;
 mod include;
 mod ins;
+mod kbd;
 mod lines;
 mod mark;
+mod math;
 mod module;
+mod notypography;
+mod poem;
+mod row;
+mod samp;
 mod span;
+mod strikethrough;
+mod tab;
+mod table;
+mod tabview;
+mod toc;
+mod underline;
+mod var;
 
+pub use self::abbr::BLOCK_ABBR;
+pub use self::anchor::BLOCK_ANCHOR;
+pub use self::bibliography::BLOCK_BIBLIOGRAPHY;
+pub use self::blockquote::BLOCK_BLOCKQUOTE;
+pub use self::cell::BLOCK_TABLE_CELL;
+pub use self::char::BLOCK_CHAR;
 pub use self::code::BLOCK_CODE;
 pub use self::collapsible::BLOCK_COLLAPSIBLE;
+pub use self::counter::{BLOCK_COUNTER, BLOCK_COUNTER_REF};
 pub use self::css::BLOCK_CSS;
+pub use self::date::BLOCK_DATE;
 pub use self::del::BLOCK_DEL;
+pub use self::diagram::BLOCK_DIAGRAM;
 pub use self::div::BLOCK_DIV;
+pub use self::footnote::{BLOCK_FOOTNOTE, BLOCK_FOOTNOTE_BLOCK};
+pub use self::iframe::BLOCK_IFRAME;
+pub use self::image::BLOCK_IMAGE;
 pub use self::include::BLOCK_INCLUDE;
 pub use self::ins::BLOCK_INS;
+pub use self::kbd::BLOCK_KBD;
 pub use self::lines::BLOCK_LINES;
 pub use self::mark::BLOCK_MARK;
+pub use self::math::BLOCK_MATH;
 pub use self::module::BLOCK_MODULE;
+pub use self::notypography::BLOCK_NO_TYPOGRAPHY;
+pub use self::poem::BLOCK_POEM;
+pub use self::row::BLOCK_TABLE_ROW;
+pub use self::samp::BLOCK_SAMP;
 pub use self::span::BLOCK_SPAN;
+pub use self::strikethrough::BLOCK_STRIKETHROUGH;
+pub use self::tab::BLOCK_TAB;
+pub use self::table::BLOCK_TABLE;
+pub use self::tabview::BLOCK_TABVIEW;
+pub use self::toc::BLOCK_TOC;
+pub use self::underline::BLOCK_UNDERLINE;
+pub use self::var::BLOCK_VAR;
@@ -22,11 +22,14 @@ use super::{modules::*, ModuleRule};
 use std::collections::HashMap;
 use unicase::UniCase;
 
-pub const MODULE_RULES: [ModuleRule; 6] = [
+pub const MODULE_RULES: [ModuleRule; 9] = [
     MODULE_BACKLINKS,
     MODULE_CATEGORIES,
     MODULE_CSS,
+    MODULE_FEED,
     MODULE_JOIN,
+    MODULE_LIST_PAGES,
+    MODULE_MAIL_FORM,
     MODULE_PAGE_TREE,
     MODULE_RATE,
 ];
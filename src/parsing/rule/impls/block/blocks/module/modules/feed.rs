@@ -0,0 +1,58 @@
+/*
+ * parsing/rule/impls/block/blocks/module/modules/feed.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+
+pub const MODULE_FEED: ModuleRule = ModuleRule {
+    name: "module-feed",
+    accepts_names: &["Feed"],
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    mut arguments: Arguments<'t>,
+) -> ParseResult<'r, 't, Module<'t>> {
+    debug!(log, "Parsing feed module");
+    assert_module_name(&MODULE_FEED, name);
+
+    let src = match arguments.get("src") {
+        Some(src) => src,
+        None => return Err(parser.make_warn(ParseWarningKind::BlockMissingArguments)),
+    };
+
+    let limit = arguments.get_value(parser, "limit")?;
+
+    // The item template is optional
+    let template = if parser.module_has_body() {
+        let template = parser.get_body_text(&BLOCK_MODULE)?;
+        Some(cow!(template))
+    } else {
+        None
+    };
+
+    ok!(Module::Feed {
+        src,
+        limit,
+        template,
+    })
+}
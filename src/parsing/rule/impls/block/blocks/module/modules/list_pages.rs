@@ -0,0 +1,140 @@
+/*
+ * parsing/rule/impls/block/blocks/module/modules/list_pages.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::{ListPagesOrder, ListPagesQuery};
+use std::borrow::Cow;
+
+pub const MODULE_LIST_PAGES: ModuleRule = ModuleRule {
+    name: "module-list-pages",
+    accepts_names: &["ListPages"],
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    mut arguments: Arguments<'t>,
+) -> ParseResult<'r, 't, Module<'t>> {
+    debug!(log, "Parsing ListPages module");
+    assert_module_name(&MODULE_LIST_PAGES, name);
+
+    let (categories, excluded_categories) = parse_categories(arguments.get("category"));
+    let (required_tags, any_tags, excluded_tags) = parse_tags(arguments.get("tags"));
+    let order = arguments
+        .get_with(parser, "order", ListPagesOrder::parse)?
+        .unwrap_or_default();
+
+    let limit = arguments.get_value(parser, "limit")?;
+    let per_page = arguments.get_value(parser, "perPage")?;
+    let offset = arguments.get_value(parser, "offset")?.unwrap_or(0);
+
+    // The page template is optional
+    let template = if parser.module_has_body() {
+        let template = parser.get_body_text(&BLOCK_MODULE)?;
+        Some(cow!(template))
+    } else {
+        None
+    };
+
+    let query = ListPagesQuery {
+        categories,
+        excluded_categories,
+        required_tags,
+        any_tags,
+        excluded_tags,
+        order,
+        limit,
+        per_page,
+        offset,
+    };
+
+    ok!(Module::ListPages { query, template })
+}
+
+type Words<'t> = Vec<Cow<'t, str>>;
+
+/// Reads the categories to list from and to exclude, such as `* -fragment`.
+fn parse_categories(value: Option<Cow<str>>) -> (Words, Words) {
+    let mut categories = Vec::new();
+    let mut excluded = Vec::new();
+    let mut all = false;
+
+    for word in split_words(value) {
+        if word == "*" {
+            all = true;
+        } else if word.starts_with('-') {
+            excluded.push(skip_prefix(word));
+        } else {
+            categories.push(word);
+        }
+    }
+
+    // Without any categories, the current page's category is used
+    if all {
+        categories.clear();
+    } else if categories.is_empty() {
+        categories.push(cow!("."));
+    }
+
+    (categories, excluded)
+}
+
+/// Reads the tags a page must have, may have, and must not have, such as `+fruit -rotten`.
+fn parse_tags(value: Option<Cow<str>>) -> (Words, Words, Words) {
+    let mut required = Vec::new();
+    let mut any = Vec::new();
+    let mut excluded = Vec::new();
+
+    for word in split_words(value) {
+        if word.starts_with('+') {
+            required.push(skip_prefix(word));
+        } else if word.starts_with('-') {
+            excluded.push(skip_prefix(word));
+        } else {
+            any.push(word);
+        }
+    }
+
+    (required, any, excluded)
+}
+
+/// Splits an argument into words, borrowing from the source where possible.
+fn split_words(value: Option<Cow<str>>) -> Words {
+    match value {
+        None => Vec::new(),
+        Some(Cow::Borrowed(value)) => {
+            value.split_whitespace().map(Cow::Borrowed).collect()
+        }
+        Some(Cow::Owned(value)) => value
+            .split_whitespace()
+            .map(|word| Cow::Owned(str!(word)))
+            .collect(),
+    }
+}
+
+/// Removes the `+` or `-` before a category or tag.
+fn skip_prefix(word: Cow<str>) -> Cow<str> {
+    match word {
+        Cow::Borrowed(word) => Cow::Borrowed(&word[1..]),
+        Cow::Owned(word) => Cow::Owned(str!(&word[1..])),
+    }
+}
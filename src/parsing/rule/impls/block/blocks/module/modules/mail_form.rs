@@ -0,0 +1,207 @@
+/*
+ * parsing/rule/impls/block/blocks/module/modules/mail_form.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Module for a form which sends its submissions by email.
+//!
+//! The body lists the fields, each a heading with its properties below:
+//!
+//! ```text
+//! [[module MailForm to="someone@example.com"]]
+//! # name
+//!  * title: Your name
+//!  * rules:
+//!   * required: true
+//!   * minLength: 3
+//! # topic
+//!  * type: select
+//!  * options:
+//!   * question: A question
+//!   * feedback: Feedback
+//! [[/module]]
+//! ```
+//!
+//! As in Wikidot, unrecognized properties and rules are ignored.
+
+use super::prelude::*;
+use crate::parsing::parse_boolean;
+use crate::tree::{FormField, FormFieldType, FormRule};
+
+pub const MODULE_MAIL_FORM: ModuleRule = ModuleRule {
+    name: "module-mail-form",
+    accepts_names: &["MailForm"],
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    mut arguments: Arguments<'t>,
+) -> ParseResult<'r, 't, Module<'t>> {
+    debug!(log, "Parsing mail form module");
+    assert_module_name(&MODULE_MAIL_FORM, name);
+
+    let to = match arguments.get("to") {
+        Some(to) => to,
+        None => return Err(parser.make_warn(ParseWarningKind::BlockMissingArguments)),
+    };
+
+    let title = arguments.get("title");
+    let button_text = arguments.get("button");
+    let success_page = arguments.get("successPage");
+
+    let body = parser.get_body_text(&BLOCK_MODULE)?;
+    let fields = parse_fields(body);
+
+    ok!(Module::MailForm {
+        to,
+        title,
+        button_text,
+        success_page,
+        fields,
+    })
+}
+
+/// The property whose sub-items are being read.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Section {
+    Options,
+    Rules,
+    Other,
+}
+
+fn parse_fields(body: &str) -> Vec<FormField> {
+    let mut fields = Vec::new();
+    let mut section = Section::Other;
+    let mut property_indent = None;
+
+    for line in body.lines() {
+        let trimmed = line.trim();
+
+        // Each field begins with its name as a heading
+        if let Some(name) = trimmed.strip_prefix('#') {
+            let name = name.trim();
+
+            if !name.is_empty() {
+                fields.push(FormField::new(cow!(name)));
+                section = Section::Other;
+                property_indent = None;
+            }
+
+            continue;
+        }
+
+        let field = match fields.last_mut() {
+            Some(field) => field,
+            None => continue,
+        };
+
+        let item = match trimmed.strip_prefix('*') {
+            Some(item) => item,
+            None => continue,
+        };
+
+        let (key, value) = match item.find(':') {
+            Some(index) => (item[..index].trim(), item[index + 1..].trim()),
+            None => continue,
+        };
+
+        // Sub-items are indented further than the field's properties
+        let indent = line.len() - line.trim_start().len();
+        let base_indent = *property_indent.get_or_insert(indent);
+
+        if indent > base_indent {
+            match section {
+                Section::Options => field.options.push((cow!(key), cow!(value))),
+                Section::Rules => field.rules.extend(parse_rule(key, value)),
+                Section::Other => (),
+            }
+
+            continue;
+        }
+
+        section = Section::Other;
+
+        match key.to_ascii_lowercase().as_str() {
+            "title" => field.title = Some(cow!(value)),
+            "hint" => field.hint = Some(cow!(value)),
+            "default" => field.default = Some(cow!(value)),
+            "type" => {
+                if let Some(field_type) = FormFieldType::from_name(value) {
+                    field.field_type = field_type;
+                }
+            }
+            "options" => section = Section::Options,
+            "rules" => section = Section::Rules,
+            _ => (),
+        }
+    }
+
+    fields
+}
+
+fn parse_rule<'t>(key: &str, value: &'t str) -> Option<FormRule<'t>> {
+    let enabled = || parse_boolean(value) == Some(true);
+
+    match key.to_ascii_lowercase().as_str() {
+        "required" if enabled() => Some(FormRule::Required),
+        "email" if enabled() => Some(FormRule::Email),
+        "number" if enabled() => Some(FormRule::Number),
+        "minlength" => value.parse().ok().map(FormRule::MinLength),
+        "maxlength" => value.parse().ok().map(FormRule::MaxLength),
+        "match" if !value.is_empty() => Some(FormRule::Match(cow!(value))),
+        _ => None,
+    }
+}
+
+#[test]
+fn fields() {
+    let body = concat!(
+        "# name\n",
+        " * title: Your name\n",
+        " * rules:\n",
+        "  * required: true\n",
+        "  * minLength: 3\n",
+        "  * unknown: 1\n",
+        "# topic\n",
+        " * type: select\n",
+        " * options:\n",
+        "  * a: Apple\n",
+        "  * b: Banana\n",
+        " * hint: Pick one",
+    );
+
+    let fields = parse_fields(body);
+
+    let mut name = FormField::new(cow!("name"));
+    name.title = Some(cow!("Your name"));
+    name.rules = vec![FormRule::Required, FormRule::MinLength(3)];
+
+    let mut topic = FormField::new(cow!("topic"));
+    topic.field_type = FormFieldType::Select;
+    topic.options = vec![(cow!("a"), cow!("Apple")), (cow!("b"), cow!("Banana"))];
+    topic.hint = Some(cow!("Pick one"));
+
+    assert_eq!(
+        fields,
+        vec![name, topic],
+        "Form fields don't match expected"
+    );
+}
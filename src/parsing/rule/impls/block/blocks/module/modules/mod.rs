@@ -31,13 +31,19 @@ mod prelude {
 mod backlinks;
 mod categories;
 mod css;
+mod feed;
 mod join;
+mod list_pages;
+mod mail_form;
 mod page_tree;
 mod rate;
 
 pub use self::backlinks::MODULE_BACKLINKS;
 pub use self::categories::MODULE_CATEGORIES;
 pub use self::css::MODULE_CSS;
+pub use self::feed::MODULE_FEED;
 pub use self::join::MODULE_JOIN;
+pub use self::list_pages::MODULE_LIST_PAGES;
+pub use self::mail_form::MODULE_MAIL_FORM;
 pub use self::page_tree::MODULE_PAGE_TREE;
 pub use self::rate::MODULE_RATE;
@@ -18,8 +18,8 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use super::ModuleRule;
-use crate::parsing::Parser;
+use super::{ModuleRule, BLOCK_MODULE};
+use crate::parsing::{Parser, Token};
 
 impl<'r, 't> Parser<'r, 't>
 where
@@ -35,4 +35,45 @@ where
 
         self.set_rule(module_rule.rule());
     }
+
+    /// Determines whether the module being parsed has a body.
+    ///
+    /// For modules where the body is optional, the head alone doesn't say.
+    /// So this looks ahead for a `[[/module]]`, unless another module
+    /// starts first, in which case that end block belongs to it instead.
+    pub fn module_has_body(&self) -> bool {
+        let is_module = |name: &str| {
+            BLOCK_MODULE
+                .accepts_names
+                .iter()
+                .any(|module_name| name.eq_ignore_ascii_case(module_name))
+        };
+
+        self.evaluate_fn(|parser| loop {
+            let old_remaining = parser.remaining();
+
+            match parser.current().token {
+                Token::LeftBlockEnd => {
+                    if let Ok(name) = parser.get_end_block() {
+                        if is_module(name) {
+                            return Ok(true);
+                        }
+                    }
+                }
+                Token::LeftBlock => {
+                    if let Ok((name, _)) = parser.get_block_name(false) {
+                        if is_module(name) {
+                            return Ok(false);
+                        }
+                    }
+                }
+                Token::InputEnd => return Ok(false),
+                _ => (),
+            }
+
+            if parser.same_pointer(old_remaining) {
+                parser.step()?;
+            }
+        })
+    }
 }
@@ -54,6 +54,14 @@ fn parse_fn<'r, 't>(
     // Prepare to run the module's parsing function
     parser.set_module(module_rule);
 
+    // Check if the host has disabled this module
+    if parser
+        .settings()
+        .is_module_disabled(module_rule.accepts_names)
+    {
+        return Err(parser.make_warn(ParseWarningKind::ModuleDisabled));
+    }
+
     // Run the parse function until the end.
     // This starts after the head and its newline.
     //
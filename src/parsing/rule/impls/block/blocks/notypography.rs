@@ -0,0 +1,59 @@
+/*
+ * parsing/rule/impls/block/blocks/notypography.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Block marking text which typography substitutions should not change.
+//!
+//! The substitutions themselves happen in the preprocessor,
+//! which skips these blocks. See `preproc::exclusion`.
+
+use super::prelude::*;
+
+pub const BLOCK_NO_TYPOGRAPHY: BlockRule = BlockRule {
+    name: "block-no-typography",
+    accepts_names: &["notypography"],
+    accepts_special: false,
+    newline_separator: false,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing no typography block"; "in-head" => in_head);
+
+    assert_eq!(
+        special, false,
+        "No typography doesn't allow special variant"
+    );
+    assert_block_name(&BLOCK_NO_TYPOGRAPHY, name);
+
+    parser.get_head_none(&BLOCK_NO_TYPOGRAPHY, in_head)?;
+
+    // Get body content, without paragraphs
+    let (elements, exceptions) = parser
+        .get_body_elements(&BLOCK_NO_TYPOGRAPHY, false)?
+        .into();
+
+    ok!(Element::NoTypography { elements }, exceptions)
+}
@@ -0,0 +1,54 @@
+/*
+ * parsing/rule/impls/block/blocks/poem.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+
+pub const BLOCK_POEM: BlockRule = BlockRule {
+    name: "block-poem",
+    accepts_names: &["poem", "pre"],
+    accepts_special: false,
+    newline_separator: true,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing poem block"; "in-head" => in_head, "name" => name);
+
+    assert_eq!(special, false, "Poem doesn't allow special variant");
+    assert_block_name(&BLOCK_POEM, name);
+
+    parser.get_head_none(&BLOCK_POEM, in_head)?;
+
+    // Get body content, without paragraphs.
+    //
+    // Each newline becomes a line break, and leading spaces
+    // are kept as text, so the layout of the verse is preserved.
+    let (mut elements, exceptions) = parser.get_body_elements(&BLOCK_POEM, false)?.into();
+
+    trim_line_breaks(&mut elements);
+
+    ok!(Element::Poem { elements }, exceptions)
+}
@@ -0,0 +1,85 @@
+/*
+ * parsing/rule/impls/block/blocks/row.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::{AcceptsPartial, PartialElement, TableRow};
+
+pub const BLOCK_TABLE_ROW: BlockRule = BlockRule {
+    name: "block-table-row",
+    accepts_names: &["row"],
+    accepts_special: false,
+    newline_separator: true,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing table row block"; "in-head" => in_head);
+
+    assert_eq!(special, false, "Table row doesn't allow special variant");
+    assert_block_name(&BLOCK_TABLE_ROW, name);
+
+    // Rows are only valid within a table
+    if parser.accepts_partial() != AcceptsPartial::TableRow {
+        return Err(parser.make_warn(ParseWarningKind::TableRowOutsideTable));
+    }
+
+    let mut arguments = parser.get_head_map(&BLOCK_TABLE_ROW, in_head)?;
+    let id = arguments.get("id");
+    let class = arguments.get("class");
+    let style = arguments.get("style");
+
+    // Get body, permitting cells within it
+    parser.set_accepts_partial(AcceptsPartial::TableCell);
+
+    let (elements, exceptions) =
+        parser.get_body_elements(&BLOCK_TABLE_ROW, false)?.into();
+
+    // Collect the cells, ignoring whitespace between them
+    let mut cells = Vec::new();
+
+    for element in elements {
+        match element {
+            Element::Partial(PartialElement::TableCell(cell)) => cells.push(cell),
+            Element::LineBreak | Element::LineBreaks(_) => (),
+            Element::Text(ref text) if text.trim().is_empty() => (),
+            _ => {
+                debug!(
+                    log,
+                    "Table row contains non-cell element";
+                    "element" => element,
+                );
+
+                return Err(parser.make_warn(ParseWarningKind::TableRowContainsNonCell));
+            }
+        }
+    }
+
+    // Build partial element and return
+    let row = TableRow::new(cells).with_attributes(id, class, style);
+    let element = Element::Partial(PartialElement::TableRow(row));
+
+    ok!(element, exceptions)
+}
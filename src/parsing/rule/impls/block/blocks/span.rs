@@ -60,23 +60,7 @@ fn parse_fn<'r, 't>(
     let (mut elements, exceptions) = parser.get_body_elements(&BLOCK_SPAN, false)?.into();
 
     if strip_line_breaks {
-        // Remove leading line breaks
-        while let Some(element) = elements.first() {
-            if !matches!(element, Element::LineBreak | Element::LineBreaks(_)) {
-                break;
-            }
-
-            elements.remove(0);
-        }
-
-        // Remove trailing line breaks
-        while let Some(element) = elements.last() {
-            if !matches!(element, Element::LineBreak | Element::LineBreaks(_)) {
-                break;
-            }
-
-            elements.pop();
-        }
+        trim_line_breaks(&mut elements);
     }
 
     let element = Element::StyledContainer(StyledContainer::new(
@@ -85,6 +69,8 @@ fn parse_fn<'r, 't>(
         id,
         class,
         style,
+        None,
+        None,
     ));
 
     ok!(element, exceptions)
@@ -0,0 +1,120 @@
+/*
+ * parsing/rule/impls/block/blocks/tab.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::{AcceptsPartial, PartialElement, Tab};
+
+pub const BLOCK_TAB: BlockRule = BlockRule {
+    name: "block-tab",
+    accepts_names: &["tab"],
+    accepts_special: false,
+    newline_separator: true,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing tab block"; "in-head" => in_head);
+
+    assert_eq!(special, false, "Tab doesn't allow special variant");
+    assert_block_name(&BLOCK_TAB, name);
+
+    // Tabs are only valid within a tabview
+    if parser.accepts_partial() != AcceptsPartial::Tab {
+        return Err(parser.make_warn(ParseWarningKind::TabOutsideTabView));
+    }
+
+    // Get the label and attributes.
+    //
+    // This is either the Wikidot form, "[[tab Label]]",
+    // or with arguments, "[[tab label="Label" class="..."]]".
+    let (label, id, class) = if has_arguments(parser, in_head) {
+        let mut arguments = parser.get_head_map(&BLOCK_TAB, in_head)?;
+        let label = match arguments.get("label") {
+            Some(label) => label,
+            None => return Err(parser.make_warn(ParseWarningKind::BlockMissingArguments)),
+        };
+
+        let id = arguments.get("id");
+        let class = arguments.get("class");
+
+        (label, id, class)
+    } else {
+        let label =
+            parser.get_head_value(&BLOCK_TAB, in_head, |parser, value| match value {
+                Some(value) => Ok(value.trim()),
+                None => Err(parser.make_warn(ParseWarningKind::BlockMissingArguments)),
+            })?;
+
+        (cow!(label), None, None)
+    };
+
+    // Parse the label, which may contain formatting
+    //
+    // This is rendered within the tab's link
+    let mut exceptions = Vec::new();
+    let label = parser
+        .with_context(ParseContextFlag::LinkLabel, |parser| {
+            parser.get_argument_elements(&label)
+        })?
+        .chain(&mut exceptions);
+
+    // Get body content, with paragraphs
+    //
+    // Tabs cannot be nested directly, only within another tabview
+    parser.set_accepts_partial(AcceptsPartial::None);
+
+    let elements = parser
+        .get_body_elements(&BLOCK_TAB, true)?
+        .chain(&mut exceptions);
+
+    // Build partial element and return
+    let tab = Tab::new(label, elements, id, class);
+    let element = Element::Partial(PartialElement::Tab(tab));
+
+    ok!(element, exceptions)
+}
+
+/// Determines if the head uses arguments, or is a plain label.
+///
+/// That is, if the head starts with `key=`.
+fn has_arguments(parser: &Parser, in_head: bool) -> bool {
+    if !in_head {
+        return false;
+    }
+
+    parser.evaluate_fn(|parser| {
+        parser.get_optional_space()?;
+
+        if parser.current().token != Token::Identifier {
+            return Ok(false);
+        }
+
+        parser.step()?;
+        parser.get_optional_space()?;
+
+        Ok(parser.current().token == Token::Equals)
+    })
+}
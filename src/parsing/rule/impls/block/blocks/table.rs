@@ -0,0 +1,254 @@
+/*
+ * parsing/rule/impls/block/blocks/table.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Block for tables written with explicit blocks, or loaded from delimited data.
+//!
+//! As in Wikidot, the body of `[[table]]` contains `[[row]]` blocks, each of which
+//! contains `[[cell]]` or `[[hcell]]` blocks. Unlike `||` tables, each of these
+//! may have its own id, class, and style, and cells may contain blocks.
+//!
+//! As an ftml extension, giving a `type` such as `csv` instead reads the body
+//! as delimited data. Both produce the same table element as the `||` syntax.
+
+use super::prelude::*;
+use crate::parsing::parse_choice;
+use crate::tree::{AcceptsPartial, PartialElement, Table, TableCell, TableRow};
+use std::borrow::Cow;
+use std::num::NonZeroU32;
+
+pub const BLOCK_TABLE: BlockRule = BlockRule {
+    name: "block-table",
+    accepts_names: &["table"],
+    accepts_special: false,
+    newline_separator: true,
+    parse_fn,
+};
+
+const DELIMITERS: [(&str, char); 2] = [("csv", ','), ("tsv", '\t')];
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing table block"; "in-head" => in_head);
+
+    assert_eq!(special, false, "Table doesn't allow special variant");
+    assert_block_name(&BLOCK_TABLE, name);
+
+    let mut arguments = parser.get_head_map(&BLOCK_TABLE, in_head)?;
+
+    match arguments.get_with(parser, "type", |value| parse_choice(value, &DELIMITERS))? {
+        Some(delimiter) => parse_delimited(parser, arguments, delimiter),
+        None => parse_rows(log, parser, arguments),
+    }
+}
+
+/// Parses a table made of `[[row]]` blocks.
+fn parse_rows<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    mut arguments: Arguments<'t>,
+) -> ParseResult<'r, 't, Element<'t>> {
+    let id = arguments.get("id");
+    let class = arguments.get("class");
+    let style = arguments.get("style");
+
+    // Get body, permitting rows within it
+    parser.set_accepts_partial(AcceptsPartial::TableRow);
+
+    let (elements, exceptions) = parser.get_body_elements(&BLOCK_TABLE, false)?.into();
+
+    // Collect the rows, ignoring whitespace between them
+    let mut rows = Vec::new();
+
+    for element in elements {
+        match element {
+            Element::Partial(PartialElement::TableRow(row)) => rows.push(row),
+            Element::LineBreak | Element::LineBreaks(_) => (),
+            Element::Text(ref text) if text.trim().is_empty() => (),
+            _ => {
+                debug!(
+                    log,
+                    "Table contains non-row element";
+                    "element" => element,
+                );
+
+                return Err(parser.make_warn(ParseWarningKind::TableContainsNonRow));
+            }
+        }
+    }
+
+    let table = Table::new(rows).with_attributes(id, class, style);
+
+    ok!(Element::Table(table), exceptions)
+}
+
+/// Parses a table from a body of delimited data.
+fn parse_delimited<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    mut arguments: Arguments<'t>,
+    delimiter: char,
+) -> ParseResult<'r, 't, Element<'t>> {
+    let header = arguments.get_bool(parser, "header")?.unwrap_or(false);
+
+    // Parse body as delimited data
+    let body = parser.get_body_text(&BLOCK_TABLE)?;
+    let records = parse_records(body, delimiter);
+    let mut exceptions = Vec::new();
+
+    // Rows of uneven length are still rendered, but may not be intended
+    if let Some(first) = records.first() {
+        if records.iter().any(|record| record.len() != first.len()) {
+            let warning = parser.make_warn(ParseWarningKind::TableRaggedRows);
+            exceptions.push(ParseException::Warning(warning));
+        }
+    }
+
+    // Build table element
+    let column_span = NonZeroU32::new(1).unwrap();
+    let rows = records
+        .into_iter()
+        .enumerate()
+        .map(|(index, record)| {
+            let is_header = header && index == 0;
+            let cells = record
+                .into_iter()
+                .map(|field| {
+                    let elements = if field.is_empty() {
+                        vec![]
+                    } else {
+                        vec![Element::Text(field)]
+                    };
+
+                    TableCell::new(is_header, column_span, None, elements)
+                })
+                .collect();
+
+            TableRow::new(cells)
+        })
+        .collect();
+
+    ok!(Element::Table(Table::new(rows)), exceptions)
+}
+
+/// Splits delimited text into records of fields.
+///
+/// Fields may be quoted with `"`, in which case they can contain
+/// the delimiter or newlines, and `""` is an escaped quote.
+/// Blank lines are skipped.
+fn parse_records<'t>(text: &'t str, delimiter: char) -> Vec<Vec<Cow<'t, str>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    loop {
+        let field = match chars.peek() {
+            Some(&(_, '"')) => {
+                chars.next();
+
+                let mut value = String::new();
+                while let Some((_, ch)) = chars.next() {
+                    if ch != '"' {
+                        value.push(ch);
+                    } else if let Some(&(_, '"')) = chars.peek() {
+                        chars.next();
+                        value.push('"');
+                    } else {
+                        break;
+                    }
+                }
+
+                // Discard anything between the closing quote and the next field
+                while let Some(&(_, ch)) = chars.peek() {
+                    if ch == delimiter || ch == '\n' {
+                        break;
+                    }
+
+                    chars.next();
+                }
+
+                Cow::Owned(value)
+            }
+            _ => {
+                let start = chars.peek().map(|&(index, _)| index).unwrap_or(text.len());
+                let mut end = text.len();
+
+                while let Some(&(index, ch)) = chars.peek() {
+                    if ch == delimiter || ch == '\n' {
+                        end = index;
+                        break;
+                    }
+
+                    chars.next();
+                }
+
+                Cow::Borrowed(text[start..end].trim_end_matches('\r'))
+            }
+        };
+
+        record.push(field);
+
+        match chars.next() {
+            Some((_, ch)) if ch == delimiter => continue,
+            next => {
+                let blank = record.len() == 1 && record[0].is_empty();
+                if !blank {
+                    records.push(record);
+                }
+
+                if next.is_none() {
+                    break;
+                }
+
+                record = Vec::new();
+            }
+        }
+    }
+
+    records
+}
+
+#[test]
+fn records() {
+    macro_rules! check {
+        ($text:expr, $delimiter:expr, $expected:expr $(,)?) => {{
+            let actual = parse_records($text, $delimiter);
+            let expected: Vec<Vec<&str>> = $expected;
+
+            assert_eq!(actual, expected, "Parsed records don't match expected");
+        }};
+    }
+
+    check!("", ',', vec![]);
+    check!("a,b,c", ',', vec![vec!["a", "b", "c"]]);
+    check!("a,b\n\nc,d\n", ',', vec![vec!["a", "b"], vec!["c", "d"]]);
+    check!("a,,c", ',', vec![vec!["a", "", "c"]]);
+    check!("a\tb,c", '\t', vec![vec!["a", "b,c"]]);
+    check!("a\r\nb", ',', vec![vec!["a"], vec!["b"]]);
+    check!(
+        "\"a,b\",\"say \"\"hi\"\"\"\n\"multi\nline\"",
+        ',',
+        vec![vec!["a,b", "say \"hi\""], vec!["multi\nline"]],
+    );
+    check!("a,b\nc", ',', vec![vec!["a", "b"], vec!["c"]]);
+}
@@ -0,0 +1,76 @@
+/*
+ * parsing/rule/impls/block/blocks/tabview.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::{AcceptsPartial, PartialElement};
+
+pub const BLOCK_TABVIEW: BlockRule = BlockRule {
+    name: "block-tabview",
+    accepts_names: &["tabview", "tabs"],
+    accepts_special: false,
+    newline_separator: true,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing tabview block"; "in-head" => in_head);
+
+    assert_eq!(special, false, "Tabview doesn't allow special variant");
+    assert_block_name(&BLOCK_TABVIEW, name);
+
+    parser.get_head_none(&BLOCK_TABVIEW, in_head)?;
+
+    // Get body, permitting tabs within it
+    parser.set_accepts_partial(AcceptsPartial::Tab);
+
+    let (elements, exceptions) = parser.get_body_elements(&BLOCK_TABVIEW, false)?.into();
+
+    // Collect the tabs, ignoring whitespace between them
+    let mut tabs = Vec::new();
+
+    for element in elements {
+        match element {
+            Element::Partial(PartialElement::Tab(tab)) => tabs.push(tab),
+            Element::LineBreak | Element::LineBreaks(_) => (),
+            Element::Text(ref text) if text.trim().is_empty() => (),
+            _ => {
+                debug!(
+                    log,
+                    "Tabview contains non-tab element";
+                    "element" => element,
+                );
+
+                return Err(parser.make_warn(ParseWarningKind::TabViewContainsNonTab));
+            }
+        }
+    }
+
+    if tabs.is_empty() {
+        return Err(parser.make_warn(ParseWarningKind::TabViewEmpty));
+    }
+
+    ok!(Element::TabView(tabs), exceptions)
+}
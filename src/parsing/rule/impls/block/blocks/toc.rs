@@ -0,0 +1,67 @@
+/*
+ * parsing/rule/impls/block/blocks/toc.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Block for a table of contents, listing the page's headings.
+//!
+//! The `f<toc` and `f>toc` variants float the table to the left or right.
+//! Passing `depth` limits the headings listed to those at that level or above,
+//! so `[[toc depth="2"]]` lists only `+` and `++` headings.
+
+use super::prelude::*;
+use crate::enums::{FloatAlignment, HeadingLevel};
+use std::convert::TryFrom;
+
+pub const BLOCK_TOC: BlockRule = BlockRule {
+    name: "block-toc",
+    accepts_names: &["toc", "f<toc", "f>toc"],
+    accepts_special: false,
+    newline_separator: false,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Parsing table of contents block"; "in-head" => in_head);
+
+    assert_eq!(special, false, "Table of contents doesn't allow special variant");
+    assert_block_name(&BLOCK_TOC, name);
+
+    let mut arguments = parser.get_head_map(&BLOCK_TOC, in_head)?;
+
+    let depth = arguments.get_with(parser, "depth", |value| {
+        let depth = value.trim().parse::<u8>().ok()?;
+        HeadingLevel::try_from(depth).ok()
+    })?;
+
+    let float = if name.eq_ignore_ascii_case("f<toc") {
+        Some(FloatAlignment::Left)
+    } else if name.eq_ignore_ascii_case("f>toc") {
+        Some(FloatAlignment::Right)
+    } else {
+        None
+    };
+
+    ok!(Element::TableOfContents { depth, float })
+}
@@ -22,18 +22,45 @@ use super::{blocks::*, BlockRule};
 use std::collections::HashMap;
 use unicase::UniCase;
 
-pub const BLOCK_RULES: [BlockRule; 11] = [
+pub const BLOCK_RULES: [BlockRule; 38] = [
+    BLOCK_ABBR,
+    BLOCK_ANCHOR,
+    BLOCK_BIBLIOGRAPHY,
+    BLOCK_BLOCKQUOTE,
+    BLOCK_CHAR,
     BLOCK_CODE,
     BLOCK_COLLAPSIBLE,
+    BLOCK_COUNTER,
+    BLOCK_COUNTER_REF,
     BLOCK_CSS,
+    BLOCK_DATE,
     BLOCK_DEL,
+    BLOCK_DIAGRAM,
     BLOCK_DIV,
+    BLOCK_FOOTNOTE,
+    BLOCK_FOOTNOTE_BLOCK,
+    BLOCK_IFRAME,
+    BLOCK_IMAGE,
     BLOCK_INCLUDE,
     BLOCK_INS,
+    BLOCK_KBD,
     BLOCK_LINES,
     BLOCK_MARK,
+    BLOCK_MATH,
     BLOCK_MODULE,
+    BLOCK_NO_TYPOGRAPHY,
+    BLOCK_POEM,
+    BLOCK_SAMP,
     BLOCK_SPAN,
+    BLOCK_STRIKETHROUGH,
+    BLOCK_TAB,
+    BLOCK_TABLE,
+    BLOCK_TABLE_CELL,
+    BLOCK_TABLE_ROW,
+    BLOCK_TABVIEW,
+    BLOCK_TOC,
+    BLOCK_UNDERLINE,
+    BLOCK_VAR,
 ];
 
 pub type BlockRuleMap = HashMap<UniCase<&'static str>, &'static BlockRule>;
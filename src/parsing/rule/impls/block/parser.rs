@@ -18,16 +18,20 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use super::arguments::Arguments;
+use super::arguments::{sanitize_argument, truncate_argument, Arguments};
 use super::BlockRule;
 use crate::parsing::collect::{collect_text, collect_text_keep};
 use crate::parsing::condition::ParseCondition;
 use crate::parsing::consume::consume;
 use crate::parsing::{
-    gather_paragraphs, parse_string, ExtractedToken, ParseResult, ParseWarning,
-    ParseWarningKind, Parser, Token,
+    gather_paragraphs, gather_paragraphs_recovering, parse_string, parse_string_limited,
+    ExtractedToken, ParseContextFlag, ParseException, ParseFix, ParseResult,
+    ParseWarning, ParseWarningKind, Parser, Token, NO_CLOSE_CONDITION,
 };
+use crate::tokenize;
 use crate::tree::Element;
+use std::borrow::Cow;
+use unicase::UniCase;
 
 impl<'r, 't> Parser<'r, 't>
 where
@@ -266,10 +270,24 @@ where
             "as_paragraphs" => as_paragraphs,
         );
 
-        if as_paragraphs {
+        if self.settings().recover_unclosed_blocks {
+            return self.get_body_elements_recovering(block_rule, as_paragraphs);
+        }
+
+        let result = if as_paragraphs {
             self.get_body_elements_paragraphs(block_rule)
         } else {
             self.get_body_elements_no_paragraphs(block_rule)
+        };
+
+        // If the block was never closed, suggest closing it at the end
+        match result {
+            Err(warning) if warning.kind() == ParseWarningKind::EndOfInput => {
+                let position = warning.span().start;
+                let fix = close_block_fix(block_rule, position, false);
+                Err(warning.with_fix(fix))
+            }
+            _ => result,
         }
     }
 
@@ -321,6 +339,234 @@ where
         }
     }
 
+    /// Collects the body of a block which may have no end, closing it at the first safe point.
+    ///
+    /// This is the first blank line, heading, or end block (which presumably
+    /// belongs to a block containing this one), whichever comes first.
+    ///
+    /// Rather than parsing the body to the end of input and then again up to
+    /// this point, the point is remembered as the body is parsed. Otherwise,
+    /// each nested unclosed block would double the work done.
+    fn get_body_elements_recovering(
+        &mut self,
+        block_rule: &BlockRule,
+        as_paragraphs: bool,
+    ) -> ParseResult<'r, 't, Vec<Element<'t>>> {
+        let success = if as_paragraphs {
+            let mut first = true;
+
+            gather_paragraphs_recovering(
+                &self.log(),
+                self,
+                self.rule(),
+                move |parser: &mut Parser<'r, 't>| {
+                    let result = parser.verify_end_block(first, block_rule);
+                    first = false;
+
+                    Ok(result.is_some())
+                },
+                Parser::at_unclosed_boundary,
+            )?
+        } else {
+            self.get_body_elements_no_paragraphs_recovering(block_rule)?
+        };
+
+        let ((elements, recovered), mut exceptions) = success.into();
+
+        // Suggest closing the block where it was ended
+        if recovered {
+            debug!(
+                &self.log(),
+                "Block is unclosed, ended it at the first boundary"
+            );
+
+            let current = self.current();
+            let at_line_start = current.token == Token::Heading;
+            let fix = close_block_fix(block_rule, current.span.start, at_line_start);
+            let warning = self
+                .make_warn(ParseWarningKind::BlockAutoClosed)
+                .with_fix(fix);
+
+            exceptions.push(ParseException::Warning(warning));
+        }
+
+        ok!(elements, exceptions)
+    }
+
+    /// Like `get_body_elements_no_paragraphs()`, but remembering the first boundary.
+    ///
+    /// See `get_body_elements_recovering()`.
+    fn get_body_elements_no_paragraphs_recovering(
+        &mut self,
+        block_rule: &BlockRule,
+    ) -> ParseResult<'r, 't, (Vec<Element<'t>>, bool)> {
+        let mut elements = Vec::new();
+        let mut exceptions = Vec::new();
+        let mut boundary = None;
+        let mut first = true;
+
+        loop {
+            if boundary.is_none() && self.at_unclosed_boundary() {
+                boundary = Some((self.clone(), elements.len(), exceptions.len()));
+            }
+
+            let result = self.verify_end_block(first, block_rule);
+
+            if result.is_some() {
+                return ok!((elements, false), exceptions);
+            }
+
+            first = false;
+            let old_remaining = self.remaining();
+            let result = consume(&self.log(), self).and_then(|success| {
+                let element = success.chain(&mut exceptions);
+                if element != Element::Null {
+                    elements.push(element);
+                }
+
+                // Step if the rule hasn't moved the pointer itself
+                if self.same_pointer(old_remaining) {
+                    self.step()?;
+                }
+
+                Ok(())
+            });
+
+            match result {
+                Ok(()) => (),
+
+                // The block was never closed, so end it at the first boundary instead
+                Err(warning) if warning.kind() == ParseWarningKind::EndOfInput => {
+                    return match boundary {
+                        Some((parser, elements_len, exceptions_len)) => {
+                            self.update(&parser);
+                            elements.truncate(elements_len);
+                            exceptions.truncate(exceptions_len);
+
+                            ok!((elements, true), exceptions)
+                        }
+                        None => Err(warning),
+                    };
+                }
+
+                Err(warning) => return Err(warning),
+            }
+        }
+    }
+
+    /// Whether an unclosed block may be ended here.
+    ///
+    /// The line break before a heading or end block is left for it,
+    /// since an end block may need to be on its own line.
+    fn at_unclosed_boundary(&self) -> bool {
+        match self.current().token {
+            Token::InputEnd | Token::ParagraphBreak | Token::LeftBlockEnd => true,
+            Token::LineBreak => matches!(
+                self.look_ahead(0).map(|next| next.token),
+                Some(Token::Heading) | Some(Token::LeftBlockEnd),
+            ),
+            Token::Heading => self.start_of_line(),
+            _ => false,
+        }
+    }
+
+    /// Parses the given argument value as wikitext, producing inline elements.
+    ///
+    /// This is used for arguments which may contain formatting,
+    /// such as image captions or the text of a collapsible's links.
+    ///
+    /// Because the value may contain escapes, it does not necessarily
+    /// borrow from the original text. So it is tokenized and parsed separately,
+    /// the produced elements are converted to owned, and any warnings are
+    /// attributed to the current block.
+    pub fn get_argument_elements(
+        &self,
+        value: &str,
+    ) -> ParseResult<'r, 't, Vec<Element<'t>>> {
+        debug!(
+            &self.log(),
+            "Parsing argument value as elements";
+            "value" => value,
+        );
+
+        self.get_nested_elements(value, false)
+    }
+
+    /// Parses the contents of an included page as elements.
+    ///
+    /// Like `get_argument_elements()`, except that the elements are
+    /// gathered into paragraphs, as with a full page.
+    pub fn get_include_elements(
+        &self,
+        content: &str,
+    ) -> ParseResult<'r, 't, Vec<Element<'t>>> {
+        debug!(
+            &self.log(),
+            "Parsing included page as elements";
+            "content" => content,
+        );
+
+        self.get_nested_elements(content, true)
+    }
+
+    fn get_nested_elements(
+        &self,
+        text: &str,
+        paragraphs: bool,
+    ) -> ParseResult<'r, 't, Vec<Element<'t>>> {
+        let tokenization = tokenize(&self.log(), text);
+        let mut parser = self.clone_with_tokenization(&tokenization);
+        let mut elements = Vec::new();
+        let mut nested_exceptions = Vec::new();
+
+        if paragraphs {
+            let nested_elements = gather_paragraphs(
+                &self.log(),
+                &mut parser,
+                self.rule(),
+                NO_CLOSE_CONDITION,
+            )?
+            .chain(&mut nested_exceptions);
+
+            elements.extend(nested_elements.iter().map(Element::to_owned));
+        } else {
+            // Argument values are inline, so block constructs aren't permitted
+            parser.with_context(ParseContextFlag::Argument, |parser| {
+                while parser.current().token != Token::InputEnd {
+                    let element =
+                        consume(&self.log(), parser)?.chain(&mut nested_exceptions);
+
+                    if element != Element::Null {
+                        elements.push(element.to_owned());
+                    }
+                }
+
+                Ok(())
+            })?;
+        }
+
+        // Block locations, headings, and citations refer to the nested text,
+        // so they are discarded
+        let offset = self.full_text().position(text);
+        let exceptions = nested_exceptions
+            .into_iter()
+            .filter_map(|exception| match exception {
+                ParseException::Warning(warning) => {
+                    let warning = warning.relocate(self.rule(), self.current(), offset);
+                    Some(ParseException::Warning(warning))
+                }
+                ParseException::Style(style) => {
+                    Some(ParseException::Style(Cow::Owned(style.into_owned())))
+                }
+                ParseException::Block(_)
+                | ParseException::Heading(..)
+                | ParseException::Citation(..) => None,
+            })
+            .collect();
+
+        ok!(elements, exceptions)
+    }
+
     // Block head / argument parsing
     pub fn get_head_map(
         &mut self,
@@ -330,6 +576,8 @@ where
         debug!(&self.log(), "Looking for key value arguments, then ']]'");
 
         let mut map = Arguments::new();
+        let mut keys: Vec<(UniCase<&'t str>, &'r ExtractedToken<'t>, bool)> = Vec::new();
+
         if in_head {
             // Only process if the block isn't done yet
             loop {
@@ -338,6 +586,7 @@ where
                 // Try to get the argument key
                 // Determines if we stop or keep parsing
                 let current = self.current();
+                let key_token = current;
                 let key = match current.token {
                     Token::Identifier => current.slice,
                     Token::RightBlock => break,
@@ -355,16 +604,92 @@ where
 
                 // Get the argument value
                 self.get_optional_space()?;
-                let value_raw = self.get_token(
-                    Token::String,
-                    ParseWarningKind::BlockMalformedArguments,
-                )?;
+                let value_token = self.current();
+                let value_raw = match value_token {
+                    ExtractedToken {
+                        token: Token::String,
+                        slice,
+                        ..
+                    } => {
+                        self.step()?;
+                        slice
+                    }
+                    current => {
+                        let mut warning =
+                            self.make_warn(ParseWarningKind::BlockMalformedArguments);
+
+                        // An unquoted value, like class=apple, can be quoted
+                        let next = self.look_ahead(0).map(|next| next.token);
+                        if current.token == Token::Identifier
+                            && matches!(
+                                next,
+                                Some(Token::Whitespace) | Some(Token::RightBlock),
+                            )
+                        {
+                            let quoted = format!("\"{}\"", current.slice);
+                            warning =
+                                warning.with_fix(ParseFix::new(current.span, quoted));
+                        }
+
+                        return Err(warning);
+                    }
+                };
+
+                // Warn on repeated arguments, the last value is kept
+                let unicase_key = UniCase::ascii(key);
+                match keys.iter_mut().find(|(other, _, _)| *other == unicase_key) {
+                    Some((_, first_token, warned)) => {
+                        let kind = ParseWarningKind::BlockDuplicateArgument;
+                        let rule = self.rule();
+
+                        if !*warned {
+                            self.defer_warning(ParseWarning::new(
+                                kind,
+                                rule,
+                                first_token,
+                            ));
+                            *warned = true;
+                        }
+
+                        self.defer_warning(ParseWarning::new(kind, rule, key_token));
+                    }
+                    None => {
+                        // Drop arguments past the limit, if there is one
+                        if self.argument_limit_reached(keys.len(), key_token) {
+                            continue;
+                        }
+
+                        keys.push((unicase_key, key_token, false));
+                    }
+                }
+
+                // Parse the string, cutting overly long values short
+                //
+                // This is checked while parsing, so that a huge value
+                // is never copied in full.
+                let (value, truncated) = match self.settings().max_argument_length {
+                    Some(max_length) => parse_string_limited(value_raw, max_length),
+                    None => (parse_string(value_raw), false),
+                };
 
-                // Parse the string
-                let value = parse_string(value_raw);
+                if truncated {
+                    self.defer_warning(ParseWarning::new(
+                        ParseWarningKind::BlockArgumentTruncated,
+                        self.rule(),
+                        value_token,
+                    ));
+                }
+
+                // Add to argument map, unless it was dropped
+                let value = if self.settings().sanitize_arguments {
+                    sanitize_argument(key, value)
+                } else {
+                    Some(value)
+                };
 
-                // Add to argument map
-                map.insert(key, value);
+                if let Some(value) = value {
+                    map.insert(key, value);
+                }
             }
         }
 
@@ -392,8 +717,10 @@ where
         }
 
         // Get module's name
+        let name_token = self.current();
         let (subname, in_head) =
             self.get_block_name_internal(ParseWarningKind::ModuleMissingName)?;
+        let subname = self.limit_head_value(subname, name_token);
 
         // Get arguments and end of block
         let arguments = self.get_head_map(block_rule, in_head)?;
@@ -418,6 +745,7 @@ where
 
         let argument = if in_head {
             // Gather slice of tokens in value
+            let value_token = self.current();
             let slice = collect_text(
                 &self.log(),
                 self,
@@ -430,7 +758,7 @@ where
                 Some(ParseWarningKind::BlockMalformedArguments),
             )?;
 
-            Some(slice)
+            Some(self.limit_head_value(slice, value_token))
         } else {
             None
         };
@@ -443,6 +771,56 @@ where
         Ok(value)
     }
 
+    /// Cuts a value from a block's head short, if it's over the length limit.
+    ///
+    /// This is for values which aren't quoted strings, such as the source of
+    /// an image. The warning is for the given token, where the value begins.
+    /// See `ParseSettings::max_argument_length`.
+    pub fn limit_head_value<'a>(
+        &self,
+        value: &'a str,
+        token: &'r ExtractedToken<'t>,
+    ) -> &'a str {
+        let max_length = match self.settings().max_argument_length {
+            Some(max_length) => max_length,
+            None => return value,
+        };
+
+        let (value, truncated) = truncate_argument(value, max_length);
+        if truncated {
+            self.defer_warning(ParseWarning::new(
+                ParseWarningKind::BlockArgumentTruncated,
+                self.rule(),
+                token,
+            ));
+        }
+
+        value
+    }
+
+    /// Checks whether a block already has as many arguments as it may.
+    ///
+    /// If so, the argument at the given token is to be dropped, and a warning
+    /// is produced for it. See `ParseSettings::max_argument_count`.
+    pub fn argument_limit_reached(
+        &self,
+        count: usize,
+        token: &'r ExtractedToken<'t>,
+    ) -> bool {
+        match self.settings().max_argument_count {
+            Some(max_count) if count >= max_count => {
+                self.defer_warning(ParseWarning::new(
+                    ParseWarningKind::BlockTooManyArguments,
+                    self.rule(),
+                    token,
+                ));
+
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn get_head_none(
         &mut self,
         block_rule: &BlockRule,
@@ -494,3 +872,21 @@ where
         self.set_rule(block_rule.rule());
     }
 }
+
+/// Produces a fix inserting the end block for the given block at this position.
+///
+/// Blocks separated by newlines have their end block put on its own line.
+fn close_block_fix(
+    block_rule: &BlockRule,
+    position: usize,
+    at_line_start: bool,
+) -> ParseFix {
+    let name = block_rule.accepts_names[0];
+    let text = match (block_rule.newline_separator, at_line_start) {
+        (false, _) => format!("[[/{}]]", name),
+        (true, false) => format!("\n[[/{}]]", name),
+        (true, true) => format!("[[/{}]]\n", name),
+    };
+
+    ParseFix::insert(position, text)
+}
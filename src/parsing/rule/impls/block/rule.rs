@@ -20,6 +20,9 @@
 
 use super::super::prelude::*;
 use super::mapping::get_block_rule_with_name;
+use super::BlockRule;
+use crate::parsing::BlockBoundary;
+use crate::span::Span;
 
 pub const RULE_BLOCK: Rule = Rule {
     name: "block",
@@ -85,8 +88,10 @@ fn block_skip<'r, 't>(
 
         // Get the associated block rule
         let block = match get_block_rule_with_name(name) {
-            Some(block) => block,
-            None => return Ok(false),
+            Some(block) if !parser.settings().is_block_disabled(block.accepts_names) => {
+                block
+            }
+            _ => return Ok(false),
         };
 
         // Now, if it wants newlines, ignore this newline.
@@ -119,6 +124,10 @@ where
         "special" => special,
     );
 
+    // Save the start of the head, to locate the block if it succeeds
+    let start = parser.current();
+    let start_remaining = parser.remaining();
+
     // Set general rule based on presence of special
     parser.set_rule(if special {
         RULE_BLOCK_SPECIAL
@@ -141,6 +150,11 @@ where
     // Set block rule for better warnings
     parser.set_block(block);
 
+    // Check if the host has disabled this block
+    if parser.settings().is_block_disabled(block.accepts_names) {
+        return Err(parser.make_warn(ParseWarningKind::BlockDisabled));
+    }
+
     // Check if this block allows special invocation (the '[[*' token)
     if !block.accepts_special && special {
         return Err(parser.make_warn(ParseWarningKind::InvalidSpecialBlock));
@@ -148,10 +162,83 @@ where
 
     parser.get_optional_space()?;
 
+    // Warnings about the block's arguments are deferred until it succeeds,
+    // since they are meaningless if the block is rendered as text instead.
+    let deferred = parser.deferred_warnings_len();
+    let result = parse_block_body(log, parser, block, name, special, in_head);
+    let deferred = parser.take_deferred_warnings(deferred);
+
+    let (element, mut exceptions) = result?.into();
+    exceptions.extend(deferred.into_iter().map(ParseException::Warning));
+
+    // Record where this block is, and how it was written
+    let consumed = start_remaining.len() - parser.remaining().len();
+    let consumed = &start_remaining[..consumed.saturating_sub(1)];
+
+    if let Some((head, tail)) = find_head_tail(start, consumed) {
+        let boundary = BlockBoundary::new(name, special, head, tail);
+        exceptions.push(ParseException::Block(boundary));
+    }
+
+    ok!(element, exceptions)
+}
+
+/// Finds the head and tail of a block from the tokens it consumed.
+///
+/// The head runs from the opening `[[` to the first `]]`, and the tail
+/// is the final `[[/` ... `]]` (any earlier ones belong to nested blocks).
+/// Blocks without a body, such as `[[image]]`, have no tail.
+fn find_head_tail(
+    start: &ExtractedToken,
+    consumed: &[ExtractedToken],
+) -> Option<(Span, Option<Span>)> {
+    let is_right_block = |token: &&ExtractedToken| token.token == Token::RightBlock;
+
+    let head_end = consumed.iter().find(is_right_block)?;
+    let head = Span::new(start.span.start, head_end.span.end);
+
+    let tail = consumed
+        .iter()
+        .rposition(|token| token.token == Token::LeftBlockEnd)
+        .and_then(|index| {
+            let tail_start = &consumed[index];
+            let tail_end = consumed[index..].iter().find(is_right_block)?;
+
+            Some(Span::new(tail_start.span.start, tail_end.span.end))
+        })
+        .filter(|tail| head.end <= tail.start);
+
+    Some((head, tail))
+}
+
+fn parse_block_body<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    block: &BlockRule,
+    name: &'t str,
+    special: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Element<'t>>
+where
+    'r: 't,
+{
     // Run the parse function until the end.
     //
     // This is responsible for parsing any arguments,
     // and terminating the block (the ']]' token),
     // then processing the body (if any) and tail block.
-    (block.parse_fn)(log, parser, name, special, in_head)
+    let (element, mut exceptions) =
+        (block.parse_fn)(log, parser, name, special, in_head)?.into();
+
+    // Nested blocks will have changed the rule, so restore it for warnings
+    parser.set_block(block);
+
+    // Some contexts, such as link labels, only allow inline content
+    if element.is_block() && parser.context().forbids_blocks() {
+        return Err(parser.make_warn(ParseWarningKind::BlockNotPermitted));
+    }
+
+    parser.check_children(&element, &mut exceptions);
+
+    ok!(element, exceptions)
 }
@@ -19,6 +19,8 @@
  */
 
 use super::prelude::*;
+use crate::parsing::ParseFix;
+use crate::tree::{StyledContainer, StyledContainerType};
 
 pub const RULE_COLOR: Rule = Rule {
     name: "color",
@@ -56,6 +58,8 @@ fn try_consume_fn<'p, 'r, 't>(
     );
 
     // Build color container
+    //
+    // If it isn't closed, suggest closing it where the contents end
     let (elements, exceptions) = collect_consume(
         log,
         parser,
@@ -63,13 +67,38 @@ fn try_consume_fn<'p, 'r, 't>(
         &[ParseCondition::current(Token::Color)],
         &[ParseCondition::current(Token::ParagraphBreak)],
         None,
-    )?
+    )
+    .map_err(|warning| match warning.kind() {
+        ParseWarningKind::EndOfInput | ParseWarningKind::RuleFailed => {
+            let position = warning.span().start;
+            warning.with_fix(ParseFix::insert(position, "##"))
+        }
+        _ => warning,
+    })?
     .into();
 
     // Return result
-    let element = Element::Color {
-        color: cow!(color),
-        elements,
+    //
+    // If enabled, the special color "highlight" marks the text instead
+    let element = if parser.settings().highlight_shorthand
+        && color.trim().eq_ignore_ascii_case("highlight")
+    {
+        debug!(log, "Using highlight shorthand, building mark container");
+
+        Element::StyledContainer(StyledContainer::new(
+            StyledContainerType::Mark,
+            elements,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ))
+    } else {
+        Element::Color {
+            color: cow!(color),
+            elements,
+        }
     };
 
     ok!(element, exceptions)
@@ -19,7 +19,6 @@
  */
 
 use super::prelude::*;
-use crate::span_wrap::SpanWrap;
 
 pub const RULE_COMMENT: Rule = Rule {
     name: "comment",
@@ -42,7 +41,7 @@ fn try_consume_fn<'p, 'r, 't>(
             "Received token inside comment";
             "token" => token,
             "slice" => slice,
-            "span" => SpanWrap::from(span),
+            "span" => *span,
         );
 
         match token {
@@ -0,0 +1,161 @@
+/*
+ * parsing/rule/impls/definition_list.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Rule for definition lists, in the `: term : definition` syntax.
+//!
+//! Each line starting with a colon followed by a space is an item,
+//! whose term is separated from its definition by the next ` : `.
+//! Consecutive items form a single list.
+//!
+//! A line without the separator is not an item, and is left as text.
+
+use super::prelude::*;
+use super::table::{trim_end, trim_start};
+use crate::tree::DefinitionListItem;
+
+pub const RULE_DEFINITION_LIST: Rule = Rule {
+    name: "definition-list",
+    try_consume_fn,
+};
+
+fn try_consume_fn<'p, 'r, 't>(
+    log: &slog::Logger,
+    parser: &'p mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Trying to create a definition list");
+
+    // Definition lists must begin a line
+    if !parser.start_of_line() {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    let mut exceptions = Vec::new();
+    let mut items = vec![parse_item(log, parser)?.chain(&mut exceptions)];
+
+    // Continue the list while the next line is another item.
+    // A line which only looks like one ends the list, and is left as text.
+    while parser.current().token == Token::LineBreak {
+        let mut sub_parser = parser.clone_with_rule(RULE_DEFINITION_LIST);
+        sub_parser.step()?;
+
+        match parse_item(log, &mut sub_parser) {
+            Ok(success) => {
+                parser.update(&sub_parser);
+                items.push(success.chain(&mut exceptions));
+            }
+            Err(_) => break,
+        }
+    }
+
+    // The list is a block, so it consumes its final newline
+    if parser.current().token == Token::LineBreak {
+        parser.step()?;
+    }
+
+    debug!(log, "Built definition list"; "items" => items.len());
+
+    ok!(Element::DefinitionList(items), exceptions)
+}
+
+/// Parses a single item, from the `: ` beginning it to the end of the line.
+fn parse_item<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, DefinitionListItem<'t>> {
+    debug!(log, "Parsing definition list item");
+
+    if !is_marker(parser) {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    parser.step_n(2)?;
+
+    let mut exceptions = Vec::new();
+    let key = parse_elements(log, parser, is_separator)?.chain(&mut exceptions);
+
+    // The term must be followed by its definition on the same line
+    if !is_separator(parser) {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    parser.step_n(3)?;
+
+    let value = parse_elements(log, parser, |_| false)?.chain(&mut exceptions);
+
+    ok!(DefinitionListItem::new(key, value), exceptions)
+}
+
+/// Parses elements until the end of the line, or until `should_stop` is true.
+fn parse_elements<'r, 't, F>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+    should_stop: F,
+) -> ParseResult<'r, 't, Vec<Element<'t>>>
+where
+    F: Fn(&Parser) -> bool,
+{
+    let mut elements = Vec::new();
+    let mut exceptions = Vec::new();
+
+    parser.with_context(ParseContextFlag::List, |parser| {
+        while !is_line_end(parser.current().token) && !should_stop(parser) {
+            let old_remaining = parser.remaining();
+            let element = consume(log, parser)?.chain(&mut exceptions);
+
+            if element != Element::Null {
+                elements.push(element);
+            }
+
+            if parser.same_pointer(old_remaining) {
+                parser.step()?;
+            }
+        }
+
+        Ok(())
+    })?;
+
+    trim_start(&mut elements);
+    trim_end(&mut elements);
+
+    ok!(elements, exceptions)
+}
+
+/// Determines if the parser is at the `: ` beginning an item.
+fn is_marker(parser: &Parser) -> bool {
+    parser.current().token == Token::Colon
+        && parser.look_ahead(0).map(|token| token.token) == Some(Token::Whitespace)
+}
+
+/// Determines if the parser is at the ` : ` between a term and its definition.
+fn is_separator(parser: &Parser) -> bool {
+    let next = |offset| parser.look_ahead(offset).map(|token| token.token);
+
+    parser.current().token == Token::Whitespace
+        && next(0) == Some(Token::Colon)
+        && next(1) == Some(Token::Whitespace)
+}
+
+#[inline]
+fn is_line_end(token: Token) -> bool {
+    matches!(
+        token,
+        Token::LineBreak | Token::ParagraphBreak | Token::InputEnd
+    )
+}
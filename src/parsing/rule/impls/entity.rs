@@ -0,0 +1,57 @@
+/*
+ * parsing/rule/impls/entity.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Rule for HTML character entities, such as `&copy;` or `&#x2603;`.
+//!
+//! Recognized entities are replaced with the character they represent.
+//! Anything else is left as text, via the next rule.
+
+use super::prelude::*;
+use crate::parsing::parse_entity;
+use std::borrow::Cow;
+
+pub const RULE_ENTITY: Rule = Rule {
+    name: "entity",
+    try_consume_fn,
+};
+
+fn try_consume_fn<'p, 'r, 't>(
+    log: &slog::Logger,
+    parser: &'p mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, Element<'t>> {
+    let ExtractedToken { token, slice, .. } = parser.current();
+
+    debug!(log, "Trying to convert character entity"; "slice" => slice);
+
+    assert_eq!(
+        *token,
+        Token::Entity,
+        "Current token isn't a character entity",
+    );
+
+    match parse_entity(slice) {
+        Ok(ch) => {
+            parser.step()?;
+
+            ok!(Element::Text(Cow::Owned(ch.to_string())))
+        }
+        Err(_) => Err(parser.make_warn(ParseWarningKind::InvalidEntity)),
+    }
+}
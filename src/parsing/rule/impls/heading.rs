@@ -0,0 +1,146 @@
+/*
+ * parsing/rule/impls/heading.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Rule for headings, in the `+ Title` syntax.
+//!
+//! The number of `+` characters gives the heading level, from one to six.
+//! Deeper headings are handled according to `ParseSettings::heading_overflow`.
+//!
+//! Headings written as `+* Title` are left out of the table of contents.
+
+use super::prelude::*;
+use crate::enums::HeadingLevel;
+use crate::parsing::HeadingOverflow;
+use crate::span::Span;
+use crate::tree::Container;
+use std::convert::TryFrom;
+
+pub const RULE_HEADING: Rule = Rule {
+    name: "heading",
+    try_consume_fn,
+};
+
+fn try_consume_fn<'p, 'r, 't>(
+    log: &slog::Logger,
+    parser: &'p mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Trying to create heading");
+
+    // Headings must begin a line, and be followed by a space,
+    // or by a "*" and then a space for unlisted headings.
+    if !parser.start_of_line() {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    let next = parser.look_ahead(0).map(|next| next.token);
+    let after = parser.look_ahead(1).map(|after| after.token);
+    let listed = match (next, after) {
+        (Some(Token::Whitespace), _) => true,
+        (Some(Token::BulletItem), Some(Token::Whitespace)) => false,
+        _ => return Err(parser.make_warn(ParseWarningKind::RuleFailed)),
+    };
+
+    // Determine heading level
+    let current = parser.current();
+    let mut exceptions = Vec::new();
+
+    let level = match HeadingLevel::try_from(current.slice.len()) {
+        Ok(level) => level,
+        Err(_) => {
+            debug!(
+                log,
+                "Heading level too deep";
+                "depth" => current.slice.len(),
+                "overflow" => format!("{:?}", parser.settings().heading_overflow),
+            );
+
+            match parser.settings().heading_overflow {
+                HeadingOverflow::Clamp => {
+                    let warning = parser.make_warn(ParseWarningKind::HeadingLevelClamped);
+                    exceptions.push(ParseException::Warning(warning));
+
+                    HeadingLevel::Six
+                }
+                HeadingOverflow::Text => {
+                    let warning =
+                        parser.make_warn(ParseWarningKind::HeadingLevelExceeded);
+                    parser.step()?;
+
+                    return ok!(
+                        text!(current.slice),
+                        vec![ParseException::Warning(warning)]
+                    );
+                }
+            }
+        }
+    };
+
+    let level = level.shift(parser.settings().heading_shift);
+    let start = current.span.start;
+
+    parser.step_n(if listed { 2 } else { 3 })?;
+
+    // Gather heading contents, up to the end of the line
+    let mut elements = Vec::new();
+
+    loop {
+        match parser.current().token {
+            Token::LineBreak | Token::ParagraphBreak | Token::InputEnd => break,
+            _ => {
+                let old_remaining = parser.remaining();
+                let element = consume(log, parser)?.chain(&mut exceptions);
+
+                if element != Element::Null {
+                    elements.push(element);
+                }
+
+                if parser.same_pointer(old_remaining) {
+                    parser.step()?;
+                }
+            }
+        }
+    }
+
+    // A heading must have contents
+    if elements.is_empty() {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    // The heading is a block, so it consumes its final newline
+    if parser.current().token == Token::LineBreak {
+        parser.step()?;
+    }
+
+    let ctype = if listed {
+        ContainerType::Header(level)
+    } else {
+        ContainerType::UnlistedHeader(level)
+    };
+
+    // Headings directly on the page begin sections, record where
+    if parser.depth() == 1 {
+        let span = Span::new(start, parser.current().span.start);
+        exceptions.push(ParseException::Heading(span, level));
+    }
+
+    let container = Container::new(ctype, elements);
+
+    ok!(Element::Container(container), exceptions)
+}
@@ -125,7 +125,7 @@ fn try_consume_link<'p, 'r, 't>(
     ok!(element)
 }
 
-fn url_valid(url: &str) -> bool {
+pub fn url_valid(url: &str) -> bool {
     const SCHEMES: [&str; 20] = [
         "blob:",
         "chrome-extension://",
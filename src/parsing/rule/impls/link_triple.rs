@@ -153,6 +153,11 @@ fn build_separate<'p, 'r, 't>(
         "url" => url,
     );
 
+    // Labels with blocks, such as images, are parsed as elements
+    if label_has_blocks(parser) {
+        return build_elements(log, parser, rule, url, anchor);
+    }
+
     // Gather label for link
     let label = collect_text(
         log,
@@ -193,3 +198,76 @@ fn build_separate<'p, 'r, 't>(
     // Return result
     ok!(element)
 }
+
+/// Helper to build link with a label made of elements.
+/// e.g. `[[[page| [[image x.png]] ]]]`
+fn build_elements<'p, 'r, 't>(
+    log: &slog::Logger,
+    parser: &'p mut Parser<'r, 't>,
+    rule: Rule,
+    url: &'t str,
+    anchor: AnchorTarget,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(
+        log,
+        "Building link with element label";
+        "url" => url,
+    );
+
+    // Gather label for link, which may only contain inline elements
+    let (mut elements, exceptions) = parser
+        .with_context(ParseContextFlag::LinkLabel, |parser| {
+            collect_consume(
+                log,
+                parser,
+                rule,
+                &[ParseCondition::current(Token::RightLink)],
+                &[
+                    ParseCondition::current(Token::ParagraphBreak),
+                    ParseCondition::current(Token::LineBreak),
+                ],
+                None,
+            )
+        })?
+        .into();
+
+    // Trim label, like text labels
+    while let Some(Element::Text(text)) = elements.first() {
+        if !text.trim().is_empty() {
+            break;
+        }
+
+        elements.remove(0);
+    }
+
+    while let Some(Element::Text(text)) = elements.last() {
+        if !text.trim().is_empty() {
+            break;
+        }
+
+        elements.pop();
+    }
+
+    // Build link element
+    let element = Element::Link {
+        url: cow!(url),
+        label: LinkLabel::Elements(elements),
+        anchor,
+    };
+
+    // Return result
+    ok!(element, exceptions)
+}
+
+/// Determines if the label, up to the end of the link, contains a block.
+fn label_has_blocks(parser: &Parser) -> bool {
+    for extracted in parser.remaining() {
+        match extracted.token {
+            Token::LeftBlock | Token::LeftBlockSpecial => return true,
+            Token::RightLink | Token::ParagraphBreak | Token::LineBreak => return false,
+            _ => (),
+        }
+    }
+
+    false
+}
@@ -0,0 +1,305 @@
+/*
+ * parsing/rule/impls/list.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Rule for lists, in the `*` (bulleted) and `#` (numbered) syntax.
+//!
+//! Each line starting with a marker followed by a space is an item.
+//! Lines are nested by indenting them further than the line above.
+//! Like Wikidot, this distinguishes between two kinds of lists:
+//!
+//! * Tight lists, with items on consecutive lines, have their
+//!   contents placed directly in each item.
+//! * Loose lists, with a blank line between any of their items,
+//!   have each item's contents wrapped in paragraphs.
+//!
+//! An indented line without a marker continues the item above it,
+//! separated from it by a line break.
+//!
+//! Switching between `*` and `#` at the same depth ends the list,
+//! and begins a new one of the other style.
+
+use super::prelude::*;
+use super::table::{trim_end, trim_start};
+use crate::enums::ListStyle;
+use crate::parsing::wrap_paragraphs;
+use crate::tree::{List, ListItem};
+use std::iter::{self, Peekable};
+
+pub const RULE_LIST: Rule = Rule {
+    name: "list",
+    try_consume_fn,
+};
+
+fn try_consume_fn<'p, 'r, 't>(
+    log: &slog::Logger,
+    parser: &'p mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Trying to create a list");
+
+    // Lists must begin a line
+    if !parser.start_of_line() {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    let mut lines = Vec::new();
+    let mut exceptions = Vec::new();
+    let mut loose = false;
+    let mut first_marker = None;
+
+    loop {
+        let marker =
+            match read_marker(iter::once(parser.current()).chain(parser.remaining())) {
+                Some(marker) => marker,
+                None => return Err(parser.make_warn(ParseWarningKind::RuleFailed)),
+            };
+
+        let first = *first_marker.get_or_insert(marker);
+
+        parser.step_n(marker.tokens)?;
+
+        let mut elements = parse_item(log, parser)?.chain(&mut exceptions);
+
+        // Gather any continuation lines for this item
+        while parser.current().token == Token::LineBreak && is_continuation(parser) {
+            parser.step_n(2)?;
+
+            let mut continued = parse_item(log, parser)?.chain(&mut exceptions);
+            elements.push(Element::LineBreak);
+            elements.append(&mut continued);
+        }
+
+        lines.push(ListLine {
+            depth: marker.depth,
+            style: marker.style,
+            elements,
+        });
+
+        // Continue the list only if the next line is another item,
+        // not one at the top level in the other style.
+        // A blank line between items makes the list loose.
+        let next_is_item = match read_marker(parser.remaining().iter()) {
+            Some(next) => next.depth > first.depth || next.style == first.style,
+            None => false,
+        };
+
+        match parser.current().token {
+            Token::LineBreak if next_is_item => (),
+            Token::ParagraphBreak if next_is_item => loose = true,
+            _ => break,
+        }
+
+        parser.step()?;
+    }
+
+    // The list is a block, so it consumes its final newline
+    if parser.current().token == Token::LineBreak {
+        parser.step()?;
+    }
+
+    debug!(
+        log,
+        "Building list from lines";
+        "lines" => lines.len(),
+        "loose" => loose,
+    );
+
+    let mut lines = lines.into_iter().peekable();
+    let list = build_list(log, &mut lines, true, loose);
+    debug_assert!(lines.next().is_none(), "Not all list lines were used");
+
+    ok!(Element::List(list), exceptions)
+}
+
+/// The marker at the start of a list line, such as `  * `.
+#[derive(Debug, Copy, Clone)]
+struct ListMarker {
+    /// The number of spaces before the marker.
+    depth: usize,
+    style: ListStyle,
+
+    /// The number of tokens making up the marker.
+    tokens: usize,
+}
+
+/// A single item line, with any continuation lines appended.
+#[derive(Debug)]
+struct ListLine<'t> {
+    depth: usize,
+    style: ListStyle,
+    elements: Vec<Element<'t>>,
+}
+
+/// Reads a list marker from the tokens beginning a line, if there is one.
+///
+/// A marker is optional indentation, then `*` or `#`, then a space,
+/// and must be followed by some contents on the same line.
+fn read_marker<'r, 't: 'r, I>(mut tokens: I) -> Option<ListMarker>
+where
+    I: Iterator<Item = &'r ExtractedToken<'t>>,
+{
+    let mut current = tokens.next()?;
+    let mut depth = 0;
+    let mut count = 2;
+
+    if current.token == Token::Whitespace {
+        depth = current.slice.len();
+        count += 1;
+        current = tokens.next()?;
+    }
+
+    let style = match current.token {
+        Token::BulletItem => ListStyle::Bullet,
+        Token::NumberedItem => ListStyle::Numbered,
+        _ => return None,
+    };
+
+    if tokens.next()?.token != Token::Whitespace || is_line_end(tokens.next()?.token) {
+        return None;
+    }
+
+    Some(ListMarker {
+        depth,
+        style,
+        tokens: count,
+    })
+}
+
+/// Determines if the line after the current line break continues the previous item.
+///
+/// This is any indented line with contents which is not itself an item.
+fn is_continuation(parser: &Parser) -> bool {
+    let remaining = parser.remaining();
+
+    match remaining {
+        [indent, next, ..] => {
+            indent.token == Token::Whitespace
+                && !is_line_end(next.token)
+                && read_marker(remaining.iter()).is_none()
+        }
+        _ => false,
+    }
+}
+
+fn parse_item<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, Vec<Element<'t>>> {
+    debug!(log, "Parsing list item");
+
+    let mut elements = Vec::new();
+    let mut exceptions = Vec::new();
+
+    parser.with_context(ParseContextFlag::List, |parser| {
+        while !is_line_end(parser.current().token) {
+            let old_remaining = parser.remaining();
+            let element = consume(log, parser)?.chain(&mut exceptions);
+
+            if element != Element::Null {
+                elements.push(element);
+            }
+
+            if parser.same_pointer(old_remaining) {
+                parser.step()?;
+            }
+        }
+
+        Ok(())
+    })?;
+
+    trim_start(&mut elements);
+    trim_end(&mut elements);
+
+    ok!(elements, exceptions)
+}
+
+/// Builds a list from item lines, starting from the next line's depth.
+///
+/// Deeper lines form a list nested within the preceding item, and the list
+/// ends at the first shallower line, which belongs to a parent list. The
+/// outermost list has no parent, so any shallower lines are added to it.
+///
+/// A nested list also ends at a line of the same depth in another style,
+/// which then begins the next list nested within the same item.
+///
+/// The list's style is that of its first item.
+fn build_list<'t, I>(
+    log: &slog::Logger,
+    lines: &mut Peekable<I>,
+    root: bool,
+    loose: bool,
+) -> List<'t>
+where
+    I: Iterator<Item = ListLine<'t>>,
+{
+    let (depth, style) = match lines.peek() {
+        Some(line) => (line.depth, line.style),
+        None => panic!("No lines to build list from"),
+    };
+
+    let mut items: Vec<Vec<Element>> = Vec::new();
+
+    while let Some(line) = lines.peek() {
+        if !root && (line.depth < depth || (line.depth == depth && line.style != style)) {
+            break;
+        }
+
+        if line.depth > depth {
+            let nested = build_list(log, lines, false, loose);
+
+            // A list nested without a parent item gets an empty one
+            if items.is_empty() {
+                items.push(Vec::new());
+            }
+
+            if let Some(item) = items.last_mut() {
+                item.push(Element::List(nested));
+            }
+
+            continue;
+        }
+
+        if let Some(line) = lines.next() {
+            items.push(line.elements);
+        }
+    }
+
+    let items = items
+        .into_iter()
+        .map(|elements| {
+            let elements = if loose {
+                wrap_paragraphs(log, elements)
+            } else {
+                elements
+            };
+
+            ListItem::new(elements)
+        })
+        .collect();
+
+    List::new(style, items)
+}
+
+#[inline]
+fn is_line_end(token: Token) -> bool {
+    matches!(
+        token,
+        Token::LineBreak | Token::ParagraphBreak | Token::InputEnd
+    )
+}
@@ -0,0 +1,86 @@
+/*
+ * parsing/rule/impls/math.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Rule for inline math, such as `[[$ e^{i \pi} + 1 = 0 $]]`.
+//!
+//! The LaTeX within is kept as-is, since it is typeset while rendering.
+//! Block math uses `[[math]]` instead, see `BLOCK_MATH`.
+
+use super::prelude::*;
+
+pub const RULE_MATH_INLINE: Rule = Rule {
+    name: "math-inline",
+    try_consume_fn,
+};
+
+fn try_consume_fn<'p, 'r, 't>(
+    log: &slog::Logger,
+    parser: &'p mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Trying to create inline math");
+
+    check_step(parser, Token::LeftMath)?;
+    let start = parser.current();
+
+    loop {
+        let current = parser.current();
+
+        match current.token {
+            // Reached the end, the LaTeX is everything in between
+            Token::RightMath => {
+                trace!(log, "Reached end of inline math");
+
+                let latex = parser.full_text().slice_partial(log, start, current);
+                let latex = latex.trim();
+
+                if latex.is_empty() {
+                    return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+                }
+
+                parser.step()?;
+
+                let element = Element::Math {
+                    latex: cow!(latex),
+                    inline: true,
+                };
+
+                return ok!(element);
+            }
+
+            // Hit a newline, abort
+            Token::LineBreak | Token::ParagraphBreak => {
+                trace!(log, "Reached newline, aborting");
+
+                return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+            }
+
+            // Hit the end of the input, abort
+            Token::InputEnd => {
+                trace!(log, "Reached end of input, aborting");
+
+                return Err(parser.make_warn(ParseWarningKind::EndOfInput));
+            }
+
+            _ => (),
+        }
+
+        parser.step()?;
+    }
+}
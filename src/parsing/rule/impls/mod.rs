@@ -23,6 +23,7 @@ mod prelude {
     pub use crate::parsing::collect::*;
     pub use crate::parsing::condition::ParseCondition;
     pub use crate::parsing::consume::consume;
+    pub use crate::parsing::context::ParseContextFlag;
     pub use crate::parsing::exception::{ParseException, ParseWarning, ParseWarningKind};
     pub use crate::parsing::parser::Parser;
     pub use crate::parsing::result::{ParseResult, ParseSuccess};
@@ -32,18 +33,24 @@ mod prelude {
     pub use crate::tree::{Container, ContainerType, Element};
 }
 
+mod bibcite;
 mod block;
 mod color;
 mod comment;
 mod dash;
+mod definition_list;
 mod email;
 mod emphasis;
+mod entity;
 mod fallback;
+mod heading;
 mod horizontal_rule;
 mod line_break;
 mod link_anchor;
 mod link_single;
 mod link_triple;
+mod list;
+mod math;
 mod monospace;
 mod null;
 mod page;
@@ -52,23 +59,30 @@ mod strikethrough;
 mod strong;
 mod subscript;
 mod superscript;
+mod table;
 mod text;
 mod todo;
 mod underline;
 mod url;
 
+pub use self::bibcite::RULE_BIBCITE;
 pub use self::block::{RULE_BLOCK, RULE_BLOCK_SKIP, RULE_BLOCK_SPECIAL};
 pub use self::color::RULE_COLOR;
 pub use self::comment::RULE_COMMENT;
 pub use self::dash::RULE_DASH;
+pub use self::definition_list::RULE_DEFINITION_LIST;
 pub use self::email::RULE_EMAIL;
 pub use self::emphasis::RULE_EMPHASIS;
+pub use self::entity::RULE_ENTITY;
 pub use self::fallback::RULE_FALLBACK;
+pub use self::heading::RULE_HEADING;
 pub use self::horizontal_rule::RULE_HORIZONTAL_RULE;
 pub use self::line_break::{RULE_LINE_BREAK, RULE_LINE_BREAK_PARAGRAPH};
 pub use self::link_anchor::RULE_LINK_ANCHOR;
 pub use self::link_single::{RULE_LINK_SINGLE, RULE_LINK_SINGLE_NEW_TAB};
 pub use self::link_triple::{RULE_LINK_TRIPLE, RULE_LINK_TRIPLE_NEW_TAB};
+pub use self::list::RULE_LIST;
+pub use self::math::RULE_MATH_INLINE;
 pub use self::monospace::RULE_MONOSPACE;
 pub use self::null::RULE_NULL;
 pub use self::page::RULE_PAGE;
@@ -77,6 +91,7 @@ pub use self::strikethrough::RULE_STRIKETHROUGH;
 pub use self::strong::RULE_STRONG;
 pub use self::subscript::RULE_SUBSCRIPT;
 pub use self::superscript::RULE_SUPERSCRIPT;
+pub use self::table::RULE_TABLE;
 pub use self::text::RULE_TEXT;
 pub use self::todo::RULE_TODO;
 pub use self::underline::RULE_UNDERLINE;
@@ -19,7 +19,6 @@
  */
 
 use super::prelude::*;
-use crate::span_wrap::SpanWrap;
 
 macro_rules! raw {
     ($value:expr) => {
@@ -128,7 +127,7 @@ fn try_consume_fn<'p, 'r, 't>(
             "Received token inside raw";
             "token" => token,
             "slice" => slice,
-            "span" => SpanWrap::from(span),
+            "span" => *span,
         );
 
         // Check token
@@ -0,0 +1,230 @@
+/*
+ * parsing/rule/impls/table.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Rule for simple tables, in the `||` syntax.
+//!
+//! Each line starting with `||` is a row, with each `||` separating cells.
+//! This supports the Wikidot conventions for cell formatting:
+//!
+//! * `||~` makes the cell a header cell.
+//! * Repeated separators, such as `||||`, make the cell span multiple columns.
+//! * A `<`, `=`, or `>` directly after the separator, followed by a space,
+//!   aligns the cell's contents.
+//! * A `_` at the end of a line continues the cell onto the next line.
+//!
+//! Cells containing block elements, such as code blocks, have their inline
+//! contents grouped into paragraphs around them (see `group_paragraphs()`).
+
+use super::prelude::*;
+use crate::data::Alignment;
+use crate::parsing::group_paragraphs;
+use crate::tree::{Table, TableCell, TableRow};
+use std::num::NonZeroU32;
+
+pub const RULE_TABLE: Rule = Rule {
+    name: "table",
+    try_consume_fn,
+};
+
+fn try_consume_fn<'p, 'r, 't>(
+    log: &slog::Logger,
+    parser: &'p mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, Element<'t>> {
+    debug!(log, "Trying to create a simple table");
+
+    // Simple tables cannot be nested within each other
+    if parser.in_context(ParseContextFlag::Table) {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    let mut rows = Vec::new();
+    let mut exceptions = Vec::new();
+
+    loop {
+        let row = parse_row(log, parser)?.chain(&mut exceptions);
+        rows.push(row);
+
+        // Continue the table only if the next line is another row
+        let next_is_row = parser.current().token == Token::LineBreak
+            && parser.look_ahead(0).map(|next| is_column(next.token)) == Some(true);
+
+        if !next_is_row {
+            break;
+        }
+
+        parser.step()?;
+    }
+
+    // The table is a block, so it consumes its final newline
+    if parser.current().token == Token::LineBreak {
+        parser.step()?;
+    }
+
+    ok!(Element::Table(Table::new(rows)), exceptions)
+}
+
+fn parse_row<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, TableRow<'t>> {
+    debug!(log, "Parsing table row");
+
+    let mut cells = Vec::new();
+    let mut exceptions = Vec::new();
+
+    loop {
+        // Each repeated separator increases the column span
+        let mut column_span = 0;
+        let mut header = false;
+
+        while is_column(parser.current().token) {
+            header = parser.current().token == Token::TableColumnTitle;
+            column_span += 1;
+            parser.step()?;
+        }
+
+        // If the separator ends the line, then the row is finished
+        match parser.current().token {
+            Token::LineBreak | Token::ParagraphBreak | Token::InputEnd => break,
+            _ => (),
+        }
+
+        // Cells must begin with a separator
+        let column_span = match NonZeroU32::new(column_span) {
+            Some(column_span) => column_span,
+            None => return Err(parser.make_warn(ParseWarningKind::RuleFailed)),
+        };
+
+        let align = parse_alignment(parser)?;
+        let elements = parser
+            .with_context(ParseContextFlag::Table, |parser| parse_cell(log, parser))?
+            .chain(&mut exceptions);
+
+        cells.push(TableCell::new(header, column_span, align, elements));
+    }
+
+    // A row must have at least one cell
+    if cells.is_empty() {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    ok!(TableRow::new(cells), exceptions)
+}
+
+fn parse_alignment(parser: &mut Parser) -> Result<Option<Alignment>, ParseWarning> {
+    // The alignment marker must be separated from the cell's contents,
+    // so "||<-- text" or "||== text" are not treated as aligned.
+    //
+    // The lexer includes one trailing space in a quote token, so ">"
+    // is only a marker if it's exactly "> ".
+    let ExtractedToken { token, slice, .. } = parser.current();
+    let (align, needs_space) = match (token, *slice) {
+        (Token::Other, "<") => (Alignment::Left, true),
+        (Token::Equals, "=") => (Alignment::Center, true),
+        (Token::Quote, "> ") => (Alignment::Right, false),
+        _ => return Ok(None),
+    };
+
+    if needs_space {
+        let next = parser.look_ahead(0).map(|next| next.token);
+        if next != Some(Token::Whitespace) {
+            return Ok(None);
+        }
+    }
+
+    parser.step()?;
+    Ok(Some(align))
+}
+
+fn parse_cell<'r, 't>(
+    log: &slog::Logger,
+    parser: &mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, Vec<Element<'t>>> {
+    debug!(log, "Parsing table cell");
+
+    let mut elements = Vec::new();
+    let mut exceptions = Vec::new();
+
+    loop {
+        match parser.current().token {
+            // End of this cell
+            Token::TableColumn | Token::TableColumnTitle => break,
+
+            // Rows must be terminated by a separator
+            Token::LineBreak | Token::ParagraphBreak | Token::InputEnd => {
+                return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+            }
+
+            // Line continuation
+            Token::Underscore
+                if parser.look_ahead(0).map(|next| next.token)
+                    == Some(Token::LineBreak) =>
+            {
+                trim_end(&mut elements);
+                elements.push(Element::LineBreak);
+                parser.step_n(2)?;
+            }
+
+            // Cell contents
+            _ => {
+                let old_remaining = parser.remaining();
+                let element = consume(log, parser)?.chain(&mut exceptions);
+
+                if element != Element::Null {
+                    elements.push(element);
+                }
+
+                if parser.same_pointer(old_remaining) {
+                    parser.step()?;
+                }
+            }
+        }
+    }
+
+    trim_start(&mut elements);
+    trim_end(&mut elements);
+
+    let elements = group_paragraphs(log, elements);
+    ok!(elements, exceptions)
+}
+
+#[inline]
+fn is_column(token: Token) -> bool {
+    matches!(token, Token::TableColumn | Token::TableColumnTitle)
+}
+
+#[inline]
+fn is_whitespace(element: &Element) -> bool {
+    match element {
+        Element::Text(text) => text.trim().is_empty(),
+        _ => false,
+    }
+}
+
+pub(super) fn trim_start(elements: &mut Vec<Element>) {
+    let count = elements.iter().take_while(|e| is_whitespace(e)).count();
+    elements.drain(..count);
+}
+
+pub(super) fn trim_end(elements: &mut Vec<Element>) {
+    while elements.last().map(is_whitespace) == Some(true) {
+        elements.pop();
+    }
+}
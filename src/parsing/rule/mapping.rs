@@ -42,6 +42,10 @@ lazy_static! {
             Token::LeftBlockEnd => vec![],
             Token::LeftBlockSpecial => vec![RULE_BLOCK_SPECIAL],
             Token::RightBlock => vec![],
+            Token::LeftMath => vec![RULE_MATH_INLINE],
+            Token::RightMath => vec![],
+            Token::LeftParentheses => vec![RULE_BIBCITE, RULE_TEXT],
+            Token::RightParentheses => vec![RULE_TEXT],
             Token::DoubleDash => vec![RULE_STRIKETHROUGH, RULE_DASH],
             Token::TripleDash => vec![RULE_HORIZONTAL_RULE],
             Token::ClearFloatNeutral => vec![RULE_TODO], // TODO
@@ -52,10 +56,13 @@ lazy_static! {
             Token::Equals => vec![RULE_TODO, RULE_TEXT], // TODO
             Token::Underscore => vec![RULE_TEXT],
             Token::Quote => vec![RULE_TODO, RULE_TEXT], // TODO
-            Token::Heading => vec![RULE_TODO, RULE_TEXT], // TODO
+            Token::Heading => vec![RULE_HEADING, RULE_TEXT],
+            Token::BulletItem => vec![RULE_LIST, RULE_TEXT],
+            Token::NumberedItem => vec![RULE_LIST, RULE_TEXT],
+            Token::Colon => vec![RULE_DEFINITION_LIST, RULE_TEXT],
             Token::LineBreak => vec![RULE_BLOCK_SKIP, RULE_LINE_BREAK],
             Token::ParagraphBreak => vec![RULE_LINE_BREAK_PARAGRAPH],
-            Token::Whitespace => vec![RULE_TEXT],
+            Token::Whitespace => vec![RULE_LIST, RULE_TEXT],
 
             // Formatting
             Token::Strong => vec![RULE_STRONG],
@@ -76,8 +83,8 @@ lazy_static! {
             Token::RightLink => vec![],
 
             // Tables
-            Token::TableColumn => vec![RULE_TODO], // TODO
-            Token::TableColumnTitle => vec![RULE_TODO], // TODO
+            Token::TableColumn => vec![RULE_TABLE, RULE_TEXT],
+            Token::TableColumnTitle => vec![RULE_TABLE, RULE_TEXT],
 
             // Alignment
             Token::RightAlignOpen => vec![RULE_TODO], // TODO
@@ -95,14 +102,19 @@ lazy_static! {
             Token::Url => vec![RULE_URL],
             Token::Variable => vec![RULE_TEXT],
             Token::String => vec![RULE_TEXT],
+            Token::Entity => vec![RULE_ENTITY, RULE_TEXT],
 
             // Miscellaneous
             Token::LeftComment => vec![RULE_COMMENT],
             Token::RightComment => vec![],
             Token::InputEnd => vec![RULE_NULL],
 
+            // Punctuation
+            Token::Punctuation => vec![RULE_TEXT],
+
             // Fallback
             Token::Other => vec![RULE_TEXT],
+            Token::LexerError => vec![],
         }
     };
 }
@@ -54,9 +54,28 @@ impl Rule {
     ) -> ParseResult<'r, 't, Element<'t>> {
         info!(log, "Trying to consume for parse rule"; "name" => self.name);
 
+        #[cfg(feature = "profile")]
+        let start = std::time::Instant::now();
+
         let mut sub_parser = parser.clone_with_rule(self);
         let result = (self.try_consume_fn)(log, &mut sub_parser);
 
+        #[cfg(feature = "profile")]
+        parser
+            .profile_handle()
+            .record(self.name, start.elapsed(), result.is_ok());
+
+        // Some contexts, such as argument values, only allow inline content
+        let result = match result {
+            Ok(success)
+                if success.item.is_block() && parser.context().forbids_blocks() =>
+            {
+                let kind = ParseWarningKind::BlockNotPermitted;
+                Err(ParseWarning::new(kind, self, parser.current()))
+            }
+            result => result,
+        };
+
         // Run in a separate parser instance,
         // only keeping the parser state if it succeeded
         if result.is_ok() {
@@ -0,0 +1,147 @@
+/*
+ * parsing/settings.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::data::{AuthorTrust, SiteSettings};
+use std::sync::Arc;
+
+/// Settings which affect how wikitext is parsed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ParseSettings {
+    /// What to do with headings deeper than `h6`, such as `+++++++`.
+    #[serde(default)]
+    pub heading_overflow: HeadingOverflow,
+
+    /// How many levels to lower every heading by, clamped to `h6`.
+    ///
+    /// This is for pages rendered within another, such as ListPages bodies,
+    /// so their headings fall beneath those of the enclosing page.
+    /// For instance, with a shift of 1 then `+ Title` produces an `h2`.
+    #[serde(default)]
+    pub heading_shift: u8,
+
+    /// Whether `##highlight|text##` produces a mark container, rather than a color.
+    #[serde(default)]
+    pub highlight_shorthand: bool,
+
+    /// Whether blocks which are never closed, such as a `[[div]]` missing its `[[/div]]`,
+    /// are ended at the next blank line, heading, or end block.
+    ///
+    /// Otherwise, as in Wikidot, the block is left as text.
+    #[serde(default)]
+    pub recover_unclosed_blocks: bool,
+
+    /// Whether block arguments are restricted, for pages by untrusted authors.
+    ///
+    /// Values of `style` arguments are dropped, and values of `id` arguments
+    /// are prefixed with `u-` as in Wikidot, so they can't collide with the
+    /// IDs of the site's own elements.
+    #[serde(default)]
+    pub sanitize_arguments: bool,
+
+    /// The most characters the value of a block argument may have.
+    ///
+    /// This also applies to other values in a block's head, such as the
+    /// source of an image, and to the variables of an include.
+    /// Longer values, such as a huge `style` argument, are cut short with
+    /// a `BlockArgumentTruncated` warning. There is no limit if `None`.
+    #[serde(default)]
+    pub max_argument_length: Option<usize>,
+
+    /// The most arguments a single block may have.
+    ///
+    /// Arguments past this are dropped with a `BlockTooManyArguments` warning.
+    /// Repeats of an earlier argument don't count towards the limit.
+    /// This also applies to the variables of an include.
+    /// There is no limit if `None`.
+    #[serde(default)]
+    pub max_argument_count: Option<usize>,
+
+    /// Blocks which may not be used, such as `iframe`.
+    ///
+    /// Naming any of a block's names disables all of them, so `span`
+    /// also disables `span_`. Disabled blocks are left as text,
+    /// with a `BlockDisabled` warning.
+    #[serde(default)]
+    pub disabled_blocks: Vec<String>,
+
+    /// Modules which may not be used, such as `CSS`.
+    ///
+    /// As with blocks, naming any of a module's names disables all of them.
+    /// Disabled modules are left as text, with a `ModuleDisabled` warning.
+    #[serde(default)]
+    pub disabled_modules: Vec<String>,
+
+    /// Conventions for the site the page belongs to.
+    ///
+    /// This is shared with the render settings, see `HtmlRender::site`.
+    #[serde(default)]
+    pub site: Arc<SiteSettings>,
+}
+
+impl ParseSettings {
+    /// Creates the settings for a page by an author with the given trust.
+    ///
+    /// See `HtmlRender::for_author()` for the corresponding render settings.
+    pub fn for_author(trust: AuthorTrust, site: &Arc<SiteSettings>) -> Self {
+        ParseSettings {
+            sanitize_arguments: !trust.is_trusted(),
+            site: trust.site_settings(site),
+            ..ParseSettings::default()
+        }
+    }
+
+    /// Determines if the block with the given names has been disabled.
+    #[inline]
+    pub fn is_block_disabled(&self, names: &[&str]) -> bool {
+        is_listed(&self.disabled_blocks, names)
+    }
+
+    /// Determines if the module with the given names has been disabled.
+    #[inline]
+    pub fn is_module_disabled(&self, names: &[&str]) -> bool {
+        is_listed(&self.disabled_modules, names)
+    }
+}
+
+fn is_listed(list: &[String], names: &[&str]) -> bool {
+    list.iter()
+        .any(|listed| names.iter().any(|name| listed.eq_ignore_ascii_case(name)))
+}
+
+/// How headings with more than six `+` characters are handled.
+///
+/// In either case, a warning is produced noting which behavior was applied.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HeadingOverflow {
+    /// Produce an `h6` heading.
+    Clamp,
+
+    /// Leave the heading markers as plain text, as Wikidot does.
+    Text,
+}
+
+impl Default for HeadingOverflow {
+    #[inline]
+    fn default() -> Self {
+        HeadingOverflow::Text
+    }
+}
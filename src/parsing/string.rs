@@ -36,13 +36,46 @@ use std::borrow::Cow;
 /// Assumes that the string is in the proper form.
 /// If it is not, this function may panic.
 pub fn parse_string(input: &str) -> Cow<str> {
+    unescape(slice_middle(input))
+}
+
+/// Parses a double-quoted string, keeping at most the given number of characters.
+///
+/// Returns the string and whether it was cut short. Unlike cutting short the
+/// result of `parse_string()`, the rest of a long string is never copied.
+///
+/// # Panics
+/// Assumes that the string is in the proper form, as with `parse_string()`.
+pub fn parse_string_limited(input: &str, max_length: usize) -> (Cow<str>, bool) {
+    let input = slice_middle(input);
+    let mut length = 0;
+    let mut escape = false;
+
+    for (index, ch) in input.char_indices() {
+        // The second half of an escape, which was already counted
+        if escape {
+            escape = false;
+            continue;
+        }
+
+        if length == max_length {
+            return (unescape(&input[..index]), true);
+        }
+
+        escape = ch == '\\';
+        length += 1;
+    }
+
+    (unescape(input), false)
+}
+
+fn unescape(input: &str) -> Cow<str> {
     // We could do an iteration thing, but tracking
     // the index across replacements is complicated.
     //
     // So we check if there are any escapes, and if so,
     // build a new string.
 
-    let input = slice_middle(input);
     if !input.contains('\\') {
         return Cow::Borrowed(input);
     }
@@ -116,6 +149,35 @@ fn test_parse_string() {
     );
 }
 
+#[test]
+fn test_parse_string_limited() {
+    macro_rules! test {
+        ($input:expr, $max_length:expr, $expected:expr, $truncated:expr $(,)?) => {{
+            let (actual, truncated) = parse_string_limited($input, $max_length);
+
+            assert_eq!(
+                &actual, $expected,
+                "Actual string (left) doesn't match expected (right)"
+            );
+
+            assert_eq!(
+                truncated, $truncated,
+                "Truncation result doesn't match expected",
+            );
+        }};
+    }
+
+    test!(r#""apple""#, 10, "apple", false);
+    test!(r#""apple""#, 5, "apple", false);
+    test!(r#""banana""#, 3, "ban", true);
+    test!(r#""cherry""#, 0, "", true);
+    test!(r#""a\\b\"c""#, 2, "a\\", true);
+    test!(r#""a\\b\"c""#, 4, "a\\b\"", true);
+    test!(r#""a\\b\"c""#, 5, "a\\b\"c", false);
+    test!(r#""\n\t""#, 2, "\n\t", false);
+    test!(r#""dürüm""#, 2, "dü", true);
+}
+
 #[test]
 fn test_slice_middle() {
     macro_rules! test {
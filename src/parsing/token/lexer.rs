@@ -0,0 +1,370 @@
+/*
+ * parsing/token/lexer.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! A hand-written lexer, equivalent to the pest grammar in `lexer.pest`.
+//!
+//! This is used in place of pest with the `manual-lexer` feature.
+//! Each rule here corresponds to one in the grammar, and they are tried
+//! in the same order, with the first match being taken. Any change to
+//! the grammar must be mirrored here, which is checked by the
+//! `lexer_conformance` test.
+
+use super::{ExtractedToken, Token};
+use crate::span::Span;
+
+/// Lexes the text, appending the tokens to the list.
+///
+/// This does not add the final `Token::InputEnd`.
+pub fn extract<'a>(text: &'a str, tokens: &mut Vec<ExtractedToken<'a>>) {
+    let mut position = 0;
+
+    while position < text.len() {
+        let rest = &text[position..];
+
+        macro_rules! push {
+            ($token:expr, $length:expr) => {{
+                let end = position + $length;
+
+                tokens.push(ExtractedToken {
+                    token: $token,
+                    slice: &text[position..end],
+                    span: Span::new(position, end),
+                });
+
+                position = end;
+            }};
+        }
+
+        // The special cases which produce two tokens
+        if rest.starts_with("[[[[") {
+            push!(Token::LeftBracket, 1);
+            push!(Token::LeftLink, 3);
+            continue;
+        }
+
+        if rest.starts_with("]]]]") {
+            push!(Token::RightLink, 3);
+            push!(Token::RightBracket, 1);
+            continue;
+        }
+
+        let (token, length) = match_token(rest);
+        push!(token, length);
+    }
+}
+
+/// Matches a single token at the start of the text, which must not be empty.
+fn match_token(text: &str) -> (Token, usize) {
+    macro_rules! try_rule {
+        ($token:expr, $length:expr) => {
+            if let Some(length) = $length {
+                return ($token, length);
+            }
+        };
+    }
+
+    macro_rules! try_literal {
+        ($token:expr, $literal:expr) => {
+            try_rule!($token, literal(text, $literal));
+        };
+    }
+
+    // Raw
+    try_literal!(Token::Raw, "@@");
+    try_literal!(Token::LeftRaw, "@<");
+    try_literal!(Token::RightRaw, ">@");
+
+    // Comments
+    try_literal!(Token::LeftComment, "[!--");
+    try_literal!(Token::RightComment, "--]");
+
+    // Text-like
+    try_rule!(Token::Url, url(text));
+    try_rule!(Token::Identifier, identifier(text));
+    try_rule!(Token::Email, email(text));
+    try_rule!(Token::Variable, variable(text));
+    try_rule!(Token::String, string(text));
+    try_rule!(Token::Entity, entity(text));
+
+    // Alignment
+    try_literal!(Token::RightAlignOpen, "[[>]]");
+    try_literal!(Token::LeftAlignOpen, "[[<]]");
+    try_literal!(Token::CenterAlignOpen, "[[=]]");
+    try_literal!(Token::JustifyAlignOpen, "[[==]]");
+    try_literal!(Token::RightAlignClose, "[[/>]]");
+    try_literal!(Token::LeftAlignClose, "[[/<]]");
+    try_literal!(Token::CenterAlignClose, "[[/=]]");
+    try_literal!(Token::JustifyAlignClose, "[[/==]]");
+
+    // Brackets
+    try_literal!(Token::LeftLinkSpecial, "[[[*");
+    try_literal!(Token::LeftLink, "[[[");
+    try_literal!(Token::LeftMath, "[[$");
+    try_literal!(Token::LeftBlockSpecial, "[[*");
+    try_literal!(Token::LeftBlockEnd, "[[/");
+    try_literal!(Token::LeftBlock, "[[");
+    try_literal!(Token::LeftBracketAnchor, "[#");
+    try_literal!(Token::LeftBracketSpecial, "[*");
+    try_literal!(Token::LeftBracket, "[");
+    try_literal!(Token::RightLink, "]]]");
+    try_literal!(Token::RightMath, "$]]");
+    try_literal!(Token::RightBlock, "]]");
+    try_literal!(Token::RightBracket, "]");
+    try_literal!(Token::LeftParentheses, "((");
+    try_literal!(Token::RightParentheses, "))");
+
+    // Formatting
+    try_literal!(Token::Strong, "**");
+    try_literal!(Token::Emphasis, "//");
+    try_literal!(Token::Underline, "__");
+    try_literal!(Token::Superscript, "^^");
+    try_literal!(Token::Subscript, ",,");
+    try_literal!(Token::Color, "##");
+    try_literal!(Token::LeftMonospace, "{{");
+    try_literal!(Token::RightMonospace, "}}");
+
+    // Tables
+    try_literal!(Token::TableColumnTitle, "||~");
+    try_literal!(Token::TableColumn, "||");
+
+    // Singular symbols
+    try_rule!(Token::ClearFloatCenter, clear_float(text, Some('=')));
+    try_rule!(Token::ClearFloatLeft, clear_float(text, Some('<')));
+    try_rule!(Token::ClearFloatRight, clear_float(text, Some('>')));
+    try_rule!(Token::ClearFloatNeutral, clear_float(text, None));
+    try_rule!(Token::TripleDash, repeated(text, '-', 3));
+    try_literal!(Token::DoubleDash, "--");
+    try_literal!(Token::Pipe, "|");
+    try_literal!(Token::Equals, "=");
+    try_literal!(Token::Underscore, "_");
+    try_rule!(Token::Quote, quote(text));
+    try_rule!(Token::Heading, repeated(text, '+', 1));
+    try_literal!(Token::BulletItem, "*");
+    try_literal!(Token::NumberedItem, "#");
+    try_literal!(Token::Colon, ":");
+
+    // Whitespace
+    try_rule!(Token::ParagraphBreak, newlines(text, 2));
+    try_rule!(Token::LineBreak, newline(text));
+    try_rule!(
+        Token::Whitespace,
+        count_while(text, |ch| ch == ' ' || ch == '\t')
+    );
+
+    // Punctuation
+    try_rule!(Token::Punctuation, count_while(text, is_punctuation));
+
+    // Fallback, a single character
+    let length = text.chars().next().map_or(1, char::len_utf8);
+    (Token::Other, length)
+}
+
+// Rule implementations
+//
+// Each returns the length of the match at the start of the text, if any.
+
+fn literal(text: &str, literal: &str) -> Option<usize> {
+    if text.starts_with(literal) {
+        Some(literal.len())
+    } else {
+        None
+    }
+}
+
+/// Matches one or more characters satisfying the predicate.
+fn count_while<F>(text: &str, predicate: F) -> Option<usize>
+where
+    F: Fn(char) -> bool,
+{
+    let length = text
+        .char_indices()
+        .find(|&(_, ch)| !predicate(ch))
+        .map_or(text.len(), |(index, _)| index);
+
+    if length > 0 {
+        Some(length)
+    } else {
+        None
+    }
+}
+
+/// Matches at least `minimum` of the given ASCII character.
+fn repeated(text: &str, ch: char, minimum: usize) -> Option<usize> {
+    let length = count_while(text, |c| c == ch)?;
+
+    if length >= minimum {
+        Some(length)
+    } else {
+        None
+    }
+}
+
+/// Equivalent to pest's `NEWLINE`.
+fn newline(text: &str) -> Option<usize> {
+    if text.starts_with('\n') {
+        Some(1)
+    } else if text.starts_with("\r\n") {
+        Some(2)
+    } else if text.starts_with('\r') {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+fn newlines(text: &str, minimum: usize) -> Option<usize> {
+    let mut length = 0;
+    let mut count = 0;
+
+    while let Some(newline) = newline(&text[length..]) {
+        length += newline;
+        count += 1;
+    }
+
+    if count >= minimum {
+        Some(length)
+    } else {
+        None
+    }
+}
+
+/// Equivalent to the grammar's `punctuation` character class.
+fn is_punctuation(ch: char) -> bool {
+    matches!(ch, '\u{2010}'..='\u{2027}' | '\u{2030}'..='\u{205e}' | '\u{ab}' | '\u{bb}')
+}
+
+fn is_alphanumeric(ch: char) -> bool {
+    ch.is_ascii_alphanumeric()
+}
+
+fn identifier(text: &str) -> Option<usize> {
+    count_while(text, is_alphanumeric)
+}
+
+fn url(text: &str) -> Option<usize> {
+    let scheme = if text.starts_with("https") {
+        5
+    } else if text.starts_with("http") {
+        4
+    } else if text.starts_with("ftp") {
+        3
+    } else {
+        return None;
+    };
+
+    let separator = scheme + literal(&text[scheme..], "://")?;
+    let rest = count_while(&text[separator..], |ch| {
+        !matches!(ch, '\n' | '\r' | ' ' | '|' | '[' | ']')
+    })?;
+
+    Some(separator + rest)
+}
+
+fn email(text: &str) -> Option<usize> {
+    let mut length =
+        count_while(text, |ch| is_alphanumeric(ch) || ch == '-' || ch == '.')?;
+    length += literal(&text[length..], "@")?;
+    length += count_while(&text[length..], |ch| is_alphanumeric(ch) || ch == '-')?;
+    length += literal(&text[length..], ".")?;
+    length += count_while(&text[length..], |ch| is_alphanumeric(ch) || ch == '.')?;
+    Some(length)
+}
+
+fn variable(text: &str) -> Option<usize> {
+    let mut length = literal(text, "{$")?;
+    length += identifier(&text[length..])?;
+    length += literal(&text[length..], "}")?;
+    Some(length)
+}
+
+fn string(text: &str) -> Option<usize> {
+    let mut length = literal(text, "\"")?;
+
+    loop {
+        let rest = &text[length..];
+        let mut chars = rest.chars();
+
+        match chars.next() {
+            None | Some('\n') | Some('\r') => return None,
+            Some('"') => return Some(length + 1),
+            Some('\\') => match chars.next() {
+                Some('"') | Some('\\') | Some('r') | Some('n') | Some('t')
+                | Some('\'') => {
+                    length += 2;
+                }
+
+                // Invalid escape, the string is unterminated
+                _ => return None,
+            },
+            Some(ch) => length += ch.len_utf8(),
+        }
+    }
+}
+
+fn entity(text: &str) -> Option<usize> {
+    let mut length = literal(text, "&")?;
+    let rest = &text[length..];
+
+    // Like the grammar, once one of these alternatives matches,
+    // the others are not tried even if the following ";" is missing.
+    let body = if let Some(hex) = hex_entity(rest) {
+        hex
+    } else if let Some(digits) = rest.strip_prefix('#') {
+        1 + count_while(digits, |ch| ch.is_ascii_digit())?
+    } else {
+        count_while(rest, is_alphanumeric)?
+    };
+
+    length += body;
+    length += literal(&text[length..], ";")?;
+    Some(length)
+}
+
+fn hex_entity(text: &str) -> Option<usize> {
+    let mut length = literal(text, "#")?;
+
+    match text[length..].chars().next() {
+        Some('x') | Some('X') => length += 1,
+        _ => return None,
+    }
+
+    length += count_while(&text[length..], |ch| ch.is_ascii_hexdigit())?;
+    Some(length)
+}
+
+fn clear_float(text: &str, suffix: Option<char>) -> Option<usize> {
+    let length = repeated(text, '~', 4)?;
+
+    match suffix {
+        None => Some(length),
+        Some(suffix) if text[length..].starts_with(suffix) => Some(length + 1),
+        Some(_) => None,
+    }
+}
+
+fn quote(text: &str) -> Option<usize> {
+    let length = repeated(text, '>', 1)?;
+
+    if text[length..].starts_with(' ') {
+        Some(length + 1)
+    } else {
+        Some(length)
+    }
+}
@@ -21,21 +21,27 @@
 #[cfg(test)]
 mod test;
 
-use crate::span_wrap::SpanWrap;
+#[cfg(any(test, feature = "manual-lexer"))]
+mod lexer;
+
+use crate::span::Span;
+use pest::error::{Error as PestError, InputLocation};
 use pest::iterators::Pair;
 use pest::Parser;
+use std::fmt::{self, Display};
 use std::ops::Range;
 use strum_macros::IntoStaticStr;
 
 #[derive(Parser, Debug)]
 #[grammar = "parsing/lexer.pest"]
+#[cfg_attr(feature = "manual-lexer", allow(dead_code))]
 struct TokenLexer;
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
 pub struct ExtractedToken<'a> {
     pub token: Token,
     pub slice: &'a str,
-    pub span: Range<usize>,
+    pub span: Span,
 }
 
 #[derive(
@@ -54,6 +60,10 @@ pub enum Token {
     LeftBlockEnd,
     LeftBlockSpecial,
     RightBlock,
+    LeftMath,
+    RightMath,
+    LeftParentheses,
+    RightParentheses,
     DoubleDash,
     TripleDash,
     ClearFloatNeutral,
@@ -65,6 +75,9 @@ pub enum Token {
     Underscore,
     Quote,
     Heading,
+    BulletItem,
+    NumberedItem,
+    Colon,
 
     //
     // Whitespace
@@ -121,6 +134,7 @@ pub enum Token {
     Url,
     Variable,
     String,
+    Entity,
 
     //
     // Miscellaneous
@@ -129,10 +143,20 @@ pub enum Token {
     RightComment,
     InputEnd,
 
+    //
+    // Typographic punctuation
+    //
+    Punctuation,
+
     //
     // Catch-all case
     //
     Other,
+
+    //
+    // Error recovery
+    //
+    LexerError,
 }
 
 impl Token {
@@ -140,37 +164,138 @@ impl Token {
         log: &slog::Logger,
         text: &'a str,
     ) -> Vec<ExtractedToken<'a>> {
-        debug!(log, "Running lexer on input");
+        cfg_if! {
+            if #[cfg(feature = "manual-lexer")] {
+                Token::extract_all_manual(log, text)
+            } else {
+                Token::extract_all_pest(log, text)
+            }
+        }
+    }
 
-        match TokenLexer::parse(Rule::document, text) {
-            Ok(pairs) => {
-                info!(log, "Lexer produced pairs for processing");
+    /// Lexes the text using the pest grammar.
+    #[cfg_attr(feature = "manual-lexer", allow(dead_code))]
+    pub(crate) fn extract_all_pest<'a>(
+        log: &slog::Logger,
+        text: &'a str,
+    ) -> Vec<ExtractedToken<'a>> {
+        debug!(log, "Running pest lexer on input");
 
-                pairs.map(|pair| Token::convert_pair(log, pair)).collect()
-            }
-            Err(error) => {
-                // Return all of the input as one big raw text
-                // and log this as an error, since it shouldn't be happening
+        let mut tokens = Vec::new();
+        Token::extract_range(log, text, 0..text.len(), &mut tokens);
+        Token::push_input_end(text, &mut tokens);
+        tokens
+    }
+
+    /// Lexes the text using the hand-written lexer.
+    ///
+    /// This produces the same tokens as the pest grammar, which the
+    /// `lexer_conformance` test checks against every test case.
+    #[cfg(any(test, feature = "manual-lexer"))]
+    pub(crate) fn extract_all_manual<'a>(
+        log: &slog::Logger,
+        text: &'a str,
+    ) -> Vec<ExtractedToken<'a>> {
+        debug!(log, "Running manual lexer on input");
 
-                error!(log, "Error while lexing input in pest: {}", error);
+        let mut tokens = Vec::new();
+        lexer::extract(text, &mut tokens);
+        Token::push_input_end(text, &mut tokens);
+        tokens
+    }
+
+    fn push_input_end<'a>(text: &'a str, tokens: &mut Vec<ExtractedToken<'a>>) {
+        tokens.push(ExtractedToken {
+            token: Token::InputEnd,
+            slice: "",
+            span: Span::new(text.len(), text.len()),
+        });
+    }
+
+    /// Lexes the given range of the text, appending the tokens to the list.
+    ///
+    /// If pest fails, the text up to the point of failure is lexed, then the
+    /// character there is emitted as `Token::LexerError`, and lexing resumes
+    /// after it. This way one bad character does not lose the whole input.
+    fn extract_range<'a>(
+        log: &slog::Logger,
+        text: &'a str,
+        range: Range<usize>,
+        tokens: &mut Vec<ExtractedToken<'a>>,
+    ) {
+        let Range { mut start, end } = range;
+
+        while start < end {
+            let error = match TokenLexer::parse(Rule::document, &text[start..end]) {
+                Ok(pairs) => {
+                    info!(log, "Lexer produced pairs for processing");
+
+                    for pair in pairs {
+                        if pair.as_rule() != Rule::EOI {
+                            tokens.push(Token::convert_pair(log, pair, start));
+                        }
+                    }
+
+                    return;
+                }
+                Err(error) => error,
+            };
+
+            // This shouldn't be happening, so log it as an error
+            error!(log, "Error while lexing input in pest: {}", error);
+
+            let position = start + error_position(&error);
+
+            // If the failure can't be narrowed down,
+            // return the rest of the range as one token.
+            if position >= end {
+                tokens.push(ExtractedToken {
+                    token: Token::LexerError,
+                    slice: &text[start..end],
+                    span: Span::new(start, end),
+                });
+
+                return;
+            }
 
-                vec![ExtractedToken {
-                    token: Token::Other,
-                    slice: text,
-                    span: 0..text.len(),
-                }]
+            // Lex up to the failure
+            if position > start {
+                Token::extract_range(log, text, start..position, tokens);
             }
+
+            // Emit the failing character, then continue after it
+            let length = text[position..].chars().next().map_or(1, char::len_utf8);
+            let span = Span::new(position, position + length);
+
+            debug!(
+                log,
+                "Emitting lexer error token";
+                "slice" => span.slice(text),
+                "span" => span,
+            );
+
+            tokens.push(ExtractedToken {
+                token: Token::LexerError,
+                slice: span.slice(text),
+                span,
+            });
+
+            start = span.end;
         }
     }
 
     /// Converts a single `Pair` from pest into its corresponding `ExtractedToken`.
-    fn convert_pair<'a>(log: &slog::Logger, pair: Pair<'a, Rule>) -> ExtractedToken<'a> {
+    ///
+    /// The `offset` is added to the pair's span, for pairs from lexing part of the input.
+    fn convert_pair<'a>(
+        log: &slog::Logger,
+        pair: Pair<'a, Rule>,
+        offset: usize,
+    ) -> ExtractedToken<'a> {
         // Extract values from the Pair
         let rule = pair.as_rule();
         let slice = pair.as_str();
-        let start = pair.as_span().start();
-        let end = pair.as_span().end();
-        let span = start..end;
+        let span = Span::new(pair.as_span().start(), pair.as_span().end()).offset(offset);
 
         // Get matching Token.
         let token = Token::get_from_rule(rule);
@@ -180,7 +305,7 @@ impl Token {
             "Converting pair '{:?}' into token", rule;
             "token" => token.name(),
             "slice" => pair.as_str(),
-            "span" => SpanWrap::from(&span),
+            "span" => span,
         );
 
         ExtractedToken { token, slice, span }
@@ -200,6 +325,10 @@ impl Token {
             Rule::left_block_end => Token::LeftBlockEnd,
             Rule::left_block_special => Token::LeftBlockSpecial,
             Rule::right_block => Token::RightBlock,
+            Rule::left_math => Token::LeftMath,
+            Rule::right_math => Token::RightMath,
+            Rule::left_parentheses => Token::LeftParentheses,
+            Rule::right_parentheses => Token::RightParentheses,
             Rule::color => Token::Color,
             Rule::double_dash => Token::DoubleDash,
             Rule::triple_dash => Token::TripleDash,
@@ -212,6 +341,9 @@ impl Token {
             Rule::equals => Token::Equals,
             Rule::quote => Token::Quote,
             Rule::heading => Token::Heading,
+            Rule::bullet_item => Token::BulletItem,
+            Rule::numbered_item => Token::NumberedItem,
+            Rule::colon => Token::Colon,
 
             // Whitespace
             Rule::line_break => Token::LineBreak,
@@ -255,6 +387,10 @@ impl Token {
             Rule::url => Token::Url,
             Rule::variable => Token::Variable,
             Rule::string => Token::String,
+            Rule::entity => Token::Entity,
+
+            // Punctuation
+            Rule::punctuation => Token::Punctuation,
 
             // Other
             Rule::other => Token::Other,
@@ -273,6 +409,13 @@ impl Token {
     }
 }
 
+/// Writes the token's span, kind, and text, such as `0..2 Strong "**"`.
+impl Display for ExtractedToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {:?}", self.span, self.token.name(), self.slice)
+    }
+}
+
 impl slog::Value for Token {
     fn serialize(
         &self,
@@ -283,3 +426,11 @@ impl slog::Value for Token {
         serializer.emit_str(key, self.name())
     }
 }
+
+/// Gets the position in the input where lexing failed.
+fn error_position(error: &PestError<Rule>) -> usize {
+    match error.location {
+        InputLocation::Pos(position) => position,
+        InputLocation::Span((start, _)) => start,
+    }
+}
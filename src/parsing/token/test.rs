@@ -65,27 +65,78 @@ fn tokens() {
         vec![ExtractedToken {
             token: Token::Identifier,
             slice: "text",
-            span: 0..4,
+            span: Span::new(0, 4),
         }],
     );
 
+    test!(
+        "||~ a || b|c",
+        vec![
+            ExtractedToken {
+                token: Token::TableColumnTitle,
+                slice: "||~",
+                span: Span::new(0, 3),
+            },
+            ExtractedToken {
+                token: Token::Whitespace,
+                slice: " ",
+                span: Span::new(3, 4),
+            },
+            ExtractedToken {
+                token: Token::Identifier,
+                slice: "a",
+                span: Span::new(4, 5),
+            },
+            ExtractedToken {
+                token: Token::Whitespace,
+                slice: " ",
+                span: Span::new(5, 6),
+            },
+            ExtractedToken {
+                token: Token::TableColumn,
+                slice: "||",
+                span: Span::new(6, 8),
+            },
+            ExtractedToken {
+                token: Token::Whitespace,
+                slice: " ",
+                span: Span::new(8, 9),
+            },
+            ExtractedToken {
+                token: Token::Identifier,
+                slice: "b",
+                span: Span::new(9, 10),
+            },
+            ExtractedToken {
+                token: Token::Pipe,
+                slice: "|",
+                span: Span::new(10, 11),
+            },
+            ExtractedToken {
+                token: Token::Identifier,
+                slice: "c",
+                span: Span::new(11, 12),
+            },
+        ],
+    );
+
     test!(
         "-- doubleDash",
         vec![
             ExtractedToken {
                 token: Token::DoubleDash,
                 slice: "--",
-                span: 0..2,
+                span: Span::new(0, 2),
             },
             ExtractedToken {
                 token: Token::Whitespace,
                 slice: " ",
-                span: 2..3,
+                span: Span::new(2, 3),
             },
             ExtractedToken {
                 token: Token::Identifier,
                 slice: "doubleDash",
-                span: 3..13,
+                span: Span::new(3, 13),
             },
         ],
     );
@@ -96,12 +147,12 @@ fn tokens() {
             ExtractedToken {
                 token: Token::DoubleDash,
                 slice: "--",
-                span: 0..2,
+                span: Span::new(0, 2),
             },
             ExtractedToken {
                 token: Token::Identifier,
                 slice: "doubleDash",
-                span: 2..12,
+                span: Span::new(2, 12),
             },
         ],
     );
@@ -112,27 +163,27 @@ fn tokens() {
             ExtractedToken {
                 token: Token::Underline,
                 slice: "__",
-                span: 0..2,
+                span: Span::new(0, 2),
             },
             ExtractedToken {
                 token: Token::LeftBlockSpecial,
                 slice: "[[*",
-                span: 2..5,
+                span: Span::new(2, 5),
             },
             ExtractedToken {
                 token: Token::Identifier,
                 slice: "user",
-                span: 5..9,
+                span: Span::new(5, 9),
             },
             ExtractedToken {
                 token: Token::Whitespace,
                 slice: " ",
-                span: 9..10,
+                span: Span::new(9, 10),
             },
             ExtractedToken {
                 token: Token::RightMonospace,
                 slice: "}}",
-                span: 10..12,
+                span: Span::new(10, 12),
             },
         ],
     );
@@ -143,57 +194,57 @@ fn tokens() {
             ExtractedToken {
                 token: Token::LeftBlock,
                 slice: "[[",
-                span: 0..2,
+                span: Span::new(0, 2),
             },
             ExtractedToken {
                 token: Token::Quote,
                 slice: "> ",
-                span: 2..4,
+                span: Span::new(2, 4),
             },
             ExtractedToken {
                 token: Token::Identifier,
                 slice: "unsure",
-                span: 4..10,
+                span: Span::new(4, 10),
             },
             ExtractedToken {
                 token: Token::Whitespace,
                 slice: " ",
-                span: 10..11,
+                span: Span::new(10, 11),
             },
             ExtractedToken {
                 token: Token::Equals,
                 slice: "=",
-                span: 11..12,
+                span: Span::new(11, 12),
             },
             ExtractedToken {
                 token: Token::Whitespace,
                 slice: " ",
-                span: 12..13,
+                span: Span::new(12, 13),
             },
             ExtractedToken {
                 token: Token::Other,
                 slice: "\"",
-                span: 13..14,
+                span: Span::new(13, 14),
             },
             ExtractedToken {
                 token: Token::Identifier,
                 slice: "malformed",
-                span: 14..23,
+                span: Span::new(14, 23),
             },
             ExtractedToken {
                 token: Token::Whitespace,
                 slice: " ",
-                span: 23..24,
+                span: Span::new(23, 24),
             },
             ExtractedToken {
                 token: Token::Other,
                 slice: "\\",
-                span: 24..25,
+                span: Span::new(24, 25),
             },
             ExtractedToken {
                 token: Token::Identifier,
                 slice: "string",
-                span: 25..31,
+                span: Span::new(25, 31),
             },
         ],
     );
@@ -204,27 +255,109 @@ fn tokens() {
             ExtractedToken {
                 token: Token::LeftBracket,
                 slice: "[",
-                span: 0..1,
+                span: Span::new(0, 1),
             },
             ExtractedToken {
                 token: Token::LeftLink,
                 slice: "[[[",
-                span: 1..4,
+                span: Span::new(1, 4),
             },
             ExtractedToken {
                 token: Token::Identifier,
                 slice: "quadLinkTest",
-                span: 4..16,
+                span: Span::new(4, 16),
             },
             ExtractedToken {
                 token: Token::RightLink,
                 slice: "]]]",
-                span: 16..19,
+                span: Span::new(16, 19),
             },
             ExtractedToken {
                 token: Token::RightBracket,
                 slice: "]",
-                span: 19..20,
+                span: Span::new(19, 20),
+            },
+        ],
+    );
+
+    test!(
+        "(((cite)))",
+        vec![
+            ExtractedToken {
+                token: Token::LeftParentheses,
+                slice: "((",
+                span: Span::new(0, 2),
+            },
+            ExtractedToken {
+                token: Token::Other,
+                slice: "(",
+                span: Span::new(2, 3),
+            },
+            ExtractedToken {
+                token: Token::Identifier,
+                slice: "cite",
+                span: Span::new(3, 7),
+            },
+            ExtractedToken {
+                token: Token::RightParentheses,
+                slice: "))",
+                span: Span::new(7, 9),
+            },
+            ExtractedToken {
+                token: Token::Other,
+                slice: ")",
+                span: Span::new(9, 10),
+            },
+        ],
+    );
+
+    test!(
+        "“Wait…” — «no»",
+        vec![
+            ExtractedToken {
+                token: Token::Punctuation,
+                slice: "“",
+                span: Span::new(0, 3),
+            },
+            ExtractedToken {
+                token: Token::Identifier,
+                slice: "Wait",
+                span: Span::new(3, 7),
+            },
+            ExtractedToken {
+                token: Token::Punctuation,
+                slice: "…”",
+                span: Span::new(7, 13),
+            },
+            ExtractedToken {
+                token: Token::Whitespace,
+                slice: " ",
+                span: Span::new(13, 14),
+            },
+            ExtractedToken {
+                token: Token::Punctuation,
+                slice: "—",
+                span: Span::new(14, 17),
+            },
+            ExtractedToken {
+                token: Token::Whitespace,
+                slice: " ",
+                span: Span::new(17, 18),
+            },
+            ExtractedToken {
+                token: Token::Punctuation,
+                slice: "«",
+                span: Span::new(18, 20),
+            },
+            ExtractedToken {
+                token: Token::Identifier,
+                slice: "no",
+                span: Span::new(20, 22),
+            },
+            ExtractedToken {
+                token: Token::Punctuation,
+                slice: "»",
+                span: Span::new(22, 24),
             },
         ],
     );
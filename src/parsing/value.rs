@@ -0,0 +1,172 @@
+/*
+ * parsing/value.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Parsing of argument values, shared by all the block rules.
+//!
+//! Each function trims the value and matches keywords case-insensitively,
+//! following Wikidot. Failures return `None`, which callers turn into
+//! a warning carrying the offending value.
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// Matches a value against a list of keywords, case-insensitively.
+pub fn parse_choice<T: Copy>(s: &str, choices: &[(&str, T)]) -> Option<T> {
+    let s = s.trim();
+
+    for &(name, value) in choices {
+        if name.eq_ignore_ascii_case(s) {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+/// Parse a boolean string into its corresponding value.
+pub fn parse_boolean<S: AsRef<str>>(s: S) -> Option<bool> {
+    const NAMES: [(&str, bool); 10] = [
+        ("true", true),
+        ("false", false),
+        ("t", true),
+        ("f", false),
+        ("1", true),
+        ("0", false),
+        ("yes", true),
+        ("no", false),
+        ("on", true),
+        ("off", false),
+    ];
+
+    parse_choice(s.as_ref(), &NAMES)
+}
+
+/// Parse an integer, such as a count or depth.
+///
+/// Any type implementing `FromStr` may be used, such as `NonZeroU32`
+/// to reject zero. Surrounding whitespace is ignored.
+pub fn parse_integer<T: FromStr>(s: &str) -> Option<T> {
+    s.trim().parse().ok()
+}
+
+/// Parse a size with an optional unit, such as `300`, `300px`, or `50%`.
+pub fn parse_dimension(s: &str) -> Option<Dimension> {
+    const UNITS: [(&str, DimensionUnit); 4] = [
+        ("px", DimensionUnit::Pixels),
+        ("%", DimensionUnit::Percent),
+        ("em", DimensionUnit::Em),
+        ("rem", DimensionUnit::Rem),
+    ];
+
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+
+    let (number, unit) = s.split_at(split);
+    let value = number.parse().ok()?;
+    let unit = if unit.is_empty() {
+        DimensionUnit::Pixels
+    } else {
+        parse_choice(unit, &UNITS)?
+    };
+
+    Some(Dimension { value, unit })
+}
+
+/// A size specified in an argument, such as an image or iframe width.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Dimension {
+    pub value: u32,
+    pub unit: DimensionUnit,
+}
+
+impl Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.value, self.unit.suffix())
+    }
+}
+
+impl FromStr for Dimension {
+    type Err = ();
+
+    #[inline]
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_dimension(value).ok_or(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum DimensionUnit {
+    Pixels,
+    Percent,
+    Em,
+    Rem,
+}
+
+impl DimensionUnit {
+    #[inline]
+    pub fn suffix(self) -> &'static str {
+        match self {
+            DimensionUnit::Pixels => "px",
+            DimensionUnit::Percent => "%",
+            DimensionUnit::Em => "em",
+            DimensionUnit::Rem => "rem",
+        }
+    }
+}
+
+#[test]
+fn boolean() {
+    assert_eq!(parse_boolean("true"), Some(true));
+    assert_eq!(parse_boolean(" Yes "), Some(true));
+    assert_eq!(parse_boolean("ON"), Some(true));
+    assert_eq!(parse_boolean("1"), Some(true));
+    assert_eq!(parse_boolean("off"), Some(false));
+    assert_eq!(parse_boolean("No"), Some(false));
+    assert_eq!(parse_boolean("0"), Some(false));
+    assert_eq!(parse_boolean(""), None);
+    assert_eq!(parse_boolean("maybe"), None);
+}
+
+#[test]
+fn numbers() {
+    use std::num::NonZeroU32;
+
+    assert_eq!(parse_integer::<u32>(" 12 "), Some(12));
+    assert_eq!(parse_integer::<u32>("-1"), None);
+    assert_eq!(parse_integer::<NonZeroU32>("0"), None);
+
+    let dimension = |value, unit| Some(Dimension { value, unit });
+    assert_eq!(
+        parse_dimension("300"),
+        dimension(300, DimensionUnit::Pixels)
+    );
+    assert_eq!(
+        parse_dimension("300PX"),
+        dimension(300, DimensionUnit::Pixels)
+    );
+    assert_eq!(
+        parse_dimension("50%"),
+        dimension(50, DimensionUnit::Percent)
+    );
+    assert_eq!(parse_dimension("2rem"), dimension(2, DimensionUnit::Rem));
+    assert_eq!(parse_dimension("px"), None);
+    assert_eq!(parse_dimension("10 apples"), None);
+    assert_eq!(parse_dimension("50%").unwrap().to_string(), "50%");
+}
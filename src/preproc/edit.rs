@@ -0,0 +1,161 @@
+/*
+ * preproc/edit.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Tracking of the changes made to text during preprocessing.
+//!
+//! Each substitution is a pass over the text, producing a list of
+//! replacements which are then applied all at once. If requested, these are
+//! also recorded as `Edit`s, so that callers can see why their text changed
+//! and map positions between the raw and preprocessed text.
+
+use crate::Span;
+
+/// A single change made by the preprocessor.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Edit {
+    /// The name of the substitution which made this change.
+    pub name: &'static str,
+
+    /// The index of the pass over the text which made this change.
+    ///
+    /// Each pass runs on the output of the one before it, so spans
+    /// are relative to the text as it was before this pass.
+    pub pass: usize,
+
+    /// The range of text which was replaced.
+    pub span: Span,
+
+    /// The text which was replaced.
+    pub original: String,
+
+    /// The text it was replaced with.
+    pub replacement: String,
+}
+
+/// A range of the text to be replaced, and what to replace it with.
+pub type Replacement = (Span, &'static str);
+
+/// Performs a pass over the text, applying the given replacements.
+///
+/// The replacements must be in order and may not overlap.
+pub fn apply_replacements(
+    name: &'static str,
+    text: &mut String,
+    replacements: &[Replacement],
+    edits: Option<&mut Vec<Edit>>,
+) {
+    if replacements.is_empty() {
+        return;
+    }
+
+    if let Some(edits) = edits {
+        let pass = edits.last().map_or(0, |edit| edit.pass + 1);
+
+        edits.extend(replacements.iter().map(|&(span, replacement)| Edit {
+            name,
+            pass,
+            span,
+            original: str!(span.slice(text)),
+            replacement: str!(replacement),
+        }));
+    }
+
+    let mut buffer = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for &(span, replacement) in replacements {
+        buffer.push_str(&text[last..span.start]);
+        buffer.push_str(replacement);
+        last = span.end;
+    }
+
+    buffer.push_str(&text[last..]);
+    *text = buffer;
+}
+
+/// Maps a byte offset in the raw text to the corresponding one after preprocessing.
+///
+/// Offsets within replaced text map to the start of its replacement.
+pub fn map_to_preprocessed(edits: &[Edit], mut offset: usize) -> usize {
+    for pass in passes(edits) {
+        let mut delta = 0;
+
+        for edit in pass {
+            if edit.span.end <= offset {
+                delta += edit.replacement.len() as isize - edit.span.len() as isize;
+            } else {
+                if edit.span.start <= offset {
+                    offset = edit.span.start;
+                }
+
+                break;
+            }
+        }
+
+        offset = (offset as isize + delta) as usize;
+    }
+
+    offset
+}
+
+/// Maps a byte offset in the preprocessed text to the corresponding one in the raw text.
+///
+/// Offsets within a replacement map to the start of the text it replaced.
+pub fn map_to_raw(edits: &[Edit], mut offset: usize) -> usize {
+    for pass in passes(edits).into_iter().rev() {
+        let mut delta = 0;
+
+        for edit in pass {
+            let start = (edit.span.start as isize + delta) as usize;
+            let end = start + edit.replacement.len();
+
+            if end <= offset {
+                delta += edit.replacement.len() as isize - edit.span.len() as isize;
+            } else {
+                if start <= offset {
+                    offset = (start as isize - delta) as usize;
+                    delta = 0;
+                }
+
+                break;
+            }
+        }
+
+        offset = (offset as isize - delta) as usize;
+    }
+
+    offset
+}
+
+/// Splits the edits into the groups made by each pass.
+fn passes(edits: &[Edit]) -> Vec<&[Edit]> {
+    let mut passes = Vec::new();
+    let mut start = 0;
+
+    for index in 1..=edits.len() {
+        if index == edits.len() || edits[index].pass != edits[start].pass {
+            passes.push(&edits[start..index]);
+            start = index;
+        }
+    }
+
+    passes
+}
@@ -0,0 +1,268 @@
+/*
+ * preproc/exclusion.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Finding regions of text which preprocessor substitutions must leave unchanged.
+//!
+//! Authors opt out of a substitution by wrapping text in a block, such as
+//! `[[notypography]]`. Blocks whose bodies are kept verbatim, such as `[[code]]`,
+//! are excluded too, since the substitution would corrupt their contents.
+//!
+//! Some blocks are only excluded when given a particular argument, such as
+//! `[[table type="tsv"]]`, whose tabs separate the cells of its body.
+//!
+//! The region spans from the start of the opening block to the end of the
+//! first closing one. An opening block with no closing block does not exclude
+//! anything, since it will not parse as a block either.
+
+use std::ops::Range;
+
+/// Blocks within which typography substitutions are not performed.
+pub const NO_TYPOGRAPHY_BLOCKS: [ExcludedBlock; 6] = [
+    ExcludedBlock::new("notypography"),
+    ExcludedBlock::new("code"),
+    ExcludedBlock::new("math"),
+    ExcludedBlock::new("css"),
+    ExcludedBlock::new("chart"),
+    ExcludedBlock::new("mermaid"),
+];
+
+/// Blocks within which tabs are not expanded into spaces.
+pub const NO_TAB_EXPANSION_BLOCKS: [ExcludedBlock; 1] =
+    [ExcludedBlock::with_argument("table", "type", "tsv")];
+
+/// A block whose contents a substitution must leave unchanged.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExcludedBlock {
+    name: &'static str,
+
+    /// The key and value of an argument the block must have, if any.
+    argument: Option<(&'static str, &'static str)>,
+}
+
+impl ExcludedBlock {
+    #[inline]
+    pub const fn new(name: &'static str) -> Self {
+        ExcludedBlock {
+            name,
+            argument: None,
+        }
+    }
+
+    #[inline]
+    pub const fn with_argument(
+        name: &'static str,
+        key: &'static str,
+        value: &'static str,
+    ) -> Self {
+        ExcludedBlock {
+            name,
+            argument: Some((key, value)),
+        }
+    }
+}
+
+/// Finds the regions enclosed by any of the given blocks, in order.
+pub fn find_excluded(text: &str, blocks: &[ExcludedBlock]) -> Vec<Range<usize>> {
+    let mut regions = Vec::new();
+    let mut index = 0;
+
+    while let Some(offset) = text[index..].find("[[") {
+        let start = index + offset;
+
+        let region = blocks.iter().find_map(|block| {
+            let head_end = match_head(text, start + 2, block)?;
+            let tail_end = find_close(text, head_end, block.name)?;
+
+            Some(start..tail_end)
+        });
+
+        match region {
+            Some(region) => {
+                index = region.end;
+                regions.push(region);
+            }
+            None => index = start + 2,
+        }
+    }
+
+    regions
+}
+
+/// Returns the parts of the text outside the given regions, in order.
+pub fn segments_outside(length: usize, excluded: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut segments = Vec::with_capacity(excluded.len() + 1);
+    let mut last = 0;
+
+    for region in excluded {
+        segments.push(last..region.start);
+        last = region.end;
+    }
+
+    segments.push(last..length);
+    segments
+}
+
+/// Matches the remainder of a block head, such as `name arg="value"]]`, returning its end.
+fn match_head(text: &str, index: usize, block: &ExcludedBlock) -> Option<usize> {
+    let index = match_name(text, index, block.name)?;
+
+    // Any arguments must be separated from the name
+    let arguments = if text[index..].starts_with(' ') {
+        let offset = text[index..].find("]]")?;
+
+        &text[index..index + offset]
+    } else if text[index..].starts_with("]]") {
+        ""
+    } else {
+        return None;
+    };
+
+    match block.argument {
+        Some((key, value)) if !has_argument(arguments, key, value) => None,
+        _ => Some(index + arguments.len() + 2),
+    }
+}
+
+/// Checks if the arguments of a block head include `key="value"`, ignoring case.
+fn has_argument(arguments: &str, key: &str, value: &str) -> bool {
+    let mut parts = arguments.split('"');
+
+    // Each quoted value is preceded by its key and an equals sign
+    while let (Some(before), Some(quoted)) = (parts.next(), parts.next()) {
+        let name = match before.trim_end().strip_suffix('=') {
+            Some(before) => before.trim_end().rsplit(' ').next().unwrap_or(""),
+            None => continue,
+        };
+
+        if name.eq_ignore_ascii_case(key) && quoted.trim().eq_ignore_ascii_case(value) {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Matches the remainder of a block tail, such as `name ]]`, returning its end.
+fn match_block(text: &str, index: usize, name: &str) -> Option<usize> {
+    let index = match_name(text, index, name)?;
+    let index = skip_spaces(text, index);
+
+    if text[index..].starts_with("]]") {
+        Some(index + 2)
+    } else {
+        None
+    }
+}
+
+/// Finds the end of the first closing block, such as `[[/name]]`, after this index.
+fn find_close(text: &str, mut index: usize, name: &str) -> Option<usize> {
+    while let Some(offset) = text[index..].find("[[/") {
+        let start = index + offset + 3;
+
+        match match_block(text, start, name) {
+            Some(end) => return Some(end),
+            None => index = start,
+        }
+    }
+
+    None
+}
+
+/// Matches the block's name, ignoring case, returning the index after it.
+fn match_name(text: &str, index: usize, name: &str) -> Option<usize> {
+    let index = skip_spaces(text, index);
+    let end = index + name.len();
+
+    if text.get(index..end)?.eq_ignore_ascii_case(name) {
+        Some(end)
+    } else {
+        None
+    }
+}
+
+fn skip_spaces(text: &str, index: usize) -> usize {
+    let trimmed = text[index..].trim_start_matches(' ');
+
+    text.len() - trimmed.len()
+}
+
+#[test]
+fn exclusion() {
+    macro_rules! test {
+        ($input:expr, $expected:expr $(,)?) => {{
+            let actual = find_excluded($input, &NO_TYPOGRAPHY_BLOCKS);
+            let expected: Vec<Range<usize>> = $expected;
+
+            assert_eq!(
+                actual, expected,
+                "Excluded regions don't match expected for {:?}",
+                $input,
+            );
+        }};
+    }
+
+    test!("apple", vec![]);
+    test!("[[notypography]]apple", vec![]);
+    test!("[[notypography]]apple[[/notypography]]", vec![0..38]);
+    test!("a [[ NoTypography ]]b[[/ notypography ]] c", vec![2..40]);
+    test!(
+        "[[span]]a[[/span]] [[notypography]]b[[/notypography]]",
+        vec![19..53]
+    );
+    test!(
+        "[[notypography]]a[[/notypography]][[notypography]]b[[/notypography]]",
+        vec![0..34, 34..68],
+    );
+    test!("[[notypographyx]]a[[/notypography]]", vec![]);
+    test!("[[code]]a >> b[[/code]]", vec![0..23]);
+    test!("[[code type=\"css\"]]a[[/code]] b", vec![0..29]);
+    test!("[[codex]]a[[/code]]", vec![]);
+    test!("[[math eq]]a'' b[[/math]]", vec![0..25]);
+
+    let tsv = |text| find_excluded(text, &NO_TAB_EXPANSION_BLOCKS);
+
+    assert_eq!(
+        tsv("[[table type=\"tsv\"]]a\tb[[/table]]"),
+        vec![0..33],
+        "Excluded regions don't match expected for TSV table",
+    );
+    assert_eq!(
+        tsv("[[table header=\"true\" TYPE = \"TSV\"]]a[[/table]]"),
+        vec![0..47],
+        "Excluded regions don't match expected for TSV table",
+    );
+    assert!(
+        tsv("[[table type=\"csv\"]]a\tb[[/table]] [[table]][[/table]]").is_empty(),
+        "Non-TSV tables were excluded",
+    );
+    assert!(
+        tsv("[[table subtype=\"tsv\"]]a[[/table]]").is_empty(),
+        "Non-TSV table was excluded",
+    );
+
+    let text = "a [[notypography]]a[[/notypography]] a";
+    let excluded = find_excluded(text, &NO_TYPOGRAPHY_BLOCKS);
+
+    assert_eq!(
+        segments_outside(text.len(), &excluded),
+        vec![0..2, 36..38],
+        "Segments outside excluded regions don't match expected",
+    );
+}
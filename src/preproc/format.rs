@@ -0,0 +1,124 @@
+/*
+ * preproc/format.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Details of how the wikitext was stored, which preprocessing discards.
+//!
+//! A byte-order mark at the start, or a newline at the end, has no meaning
+//! in wikitext, so both are removed. Tools which write a page back out,
+//! such as to a wiki backed by version control, can record these with
+//! `SourceFormat` and restore them, so the file doesn't change needlessly.
+
+use super::edit::{apply_replacements, Edit};
+use crate::Span;
+
+/// The byte-order mark, as some editors add to the start of a file.
+const BOM: char = '\u{feff}';
+
+/// How the raw wikitext was stored, see `preprocess_with_format()`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct SourceFormat {
+    /// Whether the text began with a byte-order mark.
+    pub bom: bool,
+
+    /// The newlines the text ended with, exactly as written.
+    ///
+    /// For instance `"\n"`, or `"\r\n\r\n"` for a blank line in a file with
+    /// Windows line endings. This is empty if the text didn't end with a newline.
+    pub trailing_newlines: String,
+}
+
+impl SourceFormat {
+    /// Determines how the given wikitext is stored, before preprocessing.
+    pub fn detect(text: &str) -> Self {
+        let content = text.trim_end_matches(is_newline);
+
+        SourceFormat {
+            bom: text.starts_with(BOM),
+            trailing_newlines: str!(&text[content.len()..]),
+        }
+    }
+
+    /// Restores the byte-order mark and trailing newlines, if the original had them.
+    ///
+    /// This is for wikitext which will be stored in place of the original,
+    /// such as after preprocessing or being rewritten by a tool. Any newlines
+    /// already at the end of the text are replaced with the original ones.
+    pub fn restore(&self, text: &mut String) {
+        if self.bom && !text.starts_with(BOM) {
+            text.insert(0, BOM);
+        }
+
+        if !self.trailing_newlines.is_empty() {
+            let length = text.trim_end_matches(is_newline).len();
+            text.truncate(length);
+            text.push_str(&self.trailing_newlines);
+        }
+    }
+}
+
+#[inline]
+fn is_newline(c: char) -> bool {
+    c == '\n' || c == '\r'
+}
+
+/// Removes a byte-order mark from the start of the text.
+pub fn strip_bom(log: &slog::Logger, text: &mut String, edits: Option<&mut Vec<Edit>>) {
+    if text.starts_with(BOM) {
+        debug!(log, "Removing byte-order mark from start of text");
+
+        let span = Span::new(0, BOM.len_utf8());
+        apply_replacements("bom", text, &[(span, "")], edits);
+    }
+}
+
+#[test]
+fn source_format() {
+    let log = crate::build_logger();
+
+    macro_rules! test {
+        ($input:expr, $bom:expr, $trailing_newlines:expr $(,)?) => {{
+            let mut text = str!($input);
+            let format = super::preprocess_with_format(&log, &mut text);
+
+            assert_eq!(
+                format,
+                SourceFormat {
+                    bom: $bom,
+                    trailing_newlines: str!($trailing_newlines),
+                },
+                "Detected source format doesn't match expected",
+            );
+
+            assert!(!text.starts_with(BOM), "Byte-order mark was not removed");
+
+            format.restore(&mut text);
+            assert_eq!(text, $input, "Restored text doesn't match original");
+        }};
+    }
+
+    test!("", false, "");
+    test!("apple", false, "");
+    test!("apple\n", false, "\n");
+    test!("\u{feff}banana", true, "");
+    test!("\u{feff}cherry\r\n\r\n", true, "\r\n\r\n");
+    test!("durian\r\n", false, "\r\n");
+    test!("durian\u{feff}", false, "");
+}
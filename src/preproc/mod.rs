@@ -18,24 +18,126 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+//! Preprocessing of wikitext, which is done before tokenization.
+//!
+//! The standard set of stages is run by `preprocess()`. Each stage is also
+//! available on its own, and can be composed into a custom pipeline using
+//! `Preprocessor`, for hosts which need to skip some of them.
+//!
+//! The changes made can also be returned as a list of `Edit`s,
+//! see `preprocess_with_edits()`. Details of how the text was stored
+//! can be kept to restore later, see `preprocess_with_format()`.
+
+mod edit;
+mod exclusion;
+mod format;
 mod typography;
 mod whitespace;
 
 #[cfg(test)]
 mod test;
 
+pub use self::edit::{map_to_preprocessed, map_to_raw, Edit};
+pub use self::format::{strip_bom, SourceFormat};
+pub use self::typography::{convert_ellipses, convert_quotes};
+pub use self::whitespace::{
+    expand_tabs, join_lines, normalize_newlines, strip_whitespace_lines, trim_newlines,
+};
+
+/// A single stage of preprocessing, which modifies the wikitext in-place.
+///
+/// If a list of edits is passed, the stage appends each change it makes.
+pub type Stage = fn(&slog::Logger, &mut String, Option<&mut Vec<Edit>>);
+
+/// The stages run by `preprocess()`, in order.
+pub const STANDARD_STAGES: [Stage; 8] = [
+    strip_bom,
+    normalize_newlines,
+    strip_whitespace_lines,
+    join_lines,
+    expand_tabs,
+    trim_newlines,
+    convert_quotes,
+    convert_ellipses,
+];
+
+/// A pipeline of preprocessing stages, run in the order they were added.
+///
+/// For instance, to only normalize whitespace and leave typography as-is:
+///
+/// ```
+/// # use ftml::preproc::*;
+/// let preprocessor = Preprocessor::new()
+///     .stage(normalize_newlines)
+///     .stage(strip_whitespace_lines)
+///     .stage(trim_newlines);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Preprocessor {
+    stages: Vec<Stage>,
+}
+
+impl Preprocessor {
+    /// Creates a pipeline with no stages.
+    #[inline]
+    pub fn new() -> Self {
+        Preprocessor::default()
+    }
+
+    /// Creates a pipeline with the same stages as `preprocess()`.
+    #[inline]
+    pub fn standard() -> Self {
+        Preprocessor {
+            stages: STANDARD_STAGES.to_vec(),
+        }
+    }
+
+    /// Adds a stage to the end of the pipeline.
+    #[inline]
+    pub fn stage(mut self, stage: Stage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    #[inline]
+    pub fn stages(&self) -> &[Stage] {
+        &self.stages
+    }
+
+    /// Runs each stage on the given wikitext, which is modified in-place.
+    pub fn run(&self, log: &slog::Logger, text: &mut String) {
+        for stage in &self.stages {
+            stage(log, text, None);
+        }
+    }
+
+    /// Runs each stage on the given wikitext, returning the changes made.
+    pub fn run_with_edits(&self, log: &slog::Logger, text: &mut String) -> Vec<Edit> {
+        let mut edits = Vec::new();
+
+        for stage in &self.stages {
+            stage(log, text, Some(&mut edits));
+        }
+
+        edits
+    }
+}
+
 /// Run the preprocessor on the given wikitext, which is modified in-place.
 ///
 /// The following modifications are performed:
 /// * Expand instances of `[[include]]`
+/// * Removing a byte-order mark
 /// * Replacing DOS and legacy Mac newlines
 /// * Trimming whitespace lines
 /// * Concatenating lines that end with backslashes
 /// * Convert tabs to four spaces
-/// * Wikidot typography transformations
+/// * Wikidot typography transformations, outside of `[[notypography]]` and `[[code]]` blocks
 ///
 /// This call always succeeds. The return value designates where issues occurred
 /// to allow programmatic determination of where things were not as expected.
+///
+/// To run only some of these, see `Preprocessor`.
 pub fn preprocess(log: &slog::Logger, text: &mut String) {
     let log = &log.new(slog_o!(
         "filename" => slog_filename!(),
@@ -44,12 +146,55 @@ pub fn preprocess(log: &slog::Logger, text: &mut String) {
         "text" => str!(text),
     ));
 
-    whitespace::substitute(log, text);
-    typography::substitute(log, text);
+    Preprocessor::standard().run(log, text);
 
     info!(log, "Finished preprocessing of text"; "text" => &*text);
 }
 
+/// Run the preprocessor on the given wikitext, returning the changes it made.
+///
+/// The edits are in the order they were applied. Their spans refer to the
+/// text as it was just before, so to relate positions in the raw text to the
+/// preprocessed text, use `map_to_preprocessed()` and `map_to_raw()`.
+pub fn preprocess_with_edits(log: &slog::Logger, text: &mut String) -> Vec<Edit> {
+    let log = &log.new(slog_o!(
+        "filename" => slog_filename!(),
+        "lineno" => slog_lineno!(),
+        "function" => "preprocess_with_edits",
+        "text" => str!(text),
+    ));
+
+    let edits = Preprocessor::standard().run_with_edits(log, text);
+
+    info!(
+        log,
+        "Finished preprocessing of text";
+        "text" => &*text,
+        "edits" => edits.len(),
+    );
+
+    edits
+}
+
+/// Run the preprocessor on the given wikitext, returning how it was stored.
+///
+/// Preprocessing removes any byte-order mark and trailing newlines, so this
+/// records what the text had. Use `SourceFormat::restore()` to add
+/// them back to wikitext which will replace the original.
+pub fn preprocess_with_format(log: &slog::Logger, text: &mut String) -> SourceFormat {
+    let format = SourceFormat::detect(text);
+
+    info!(
+        log,
+        "Detected source format of text";
+        "bom" => format.bom,
+        "trailing-newlines" => format.trailing_newlines.escape_debug().to_string(),
+    );
+
+    preprocess(log, text);
+    format
+}
+
 #[test]
 fn fn_type() {
     type SubstituteFn = fn(&slog::Logger, &mut String);
@@ -18,7 +18,7 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use super::preprocess;
+use super::*;
 
 pub fn test_substitution<F>(filter_name: &str, mut substitute: F, tests: &[(&str, &str)])
 where
@@ -87,3 +87,97 @@ fn prefilter() {
         &PREFILTER_TEST_CASES,
     );
 }
+
+#[test]
+fn large_input() {
+    // Checks that a megabyte of text can be preprocessed,
+    // and that the substitutions are applied throughout.
+    const INPUT: &str = "apple\r\n\tbanana \\\n``cherry'' . . . <<durian>>\n   \n\n";
+    const OUTPUT: &str = "apple\n    banana “cherry” … «durian»";
+    const REPETITIONS: usize = 20_000;
+
+    let log = crate::build_logger();
+    let mut text = INPUT.repeat(REPETITIONS);
+    preprocess(&log, &mut text);
+
+    let expected = vec![OUTPUT; REPETITIONS].join("\n\n");
+    assert!(
+        text == expected,
+        "Output of large input preprocessing didn't match",
+    );
+}
+
+#[test]
+fn pipeline() {
+    const INPUT: &str = "\r\n``apple''\r\n\tbanana...\r\n";
+
+    let log = crate::build_logger();
+
+    // The standard pipeline is the same as preprocess()
+    let mut expected = str!(INPUT);
+    let mut text = str!(INPUT);
+    preprocess(&log, &mut expected);
+    Preprocessor::standard().run(&log, &mut text);
+    assert_eq!(
+        text, expected,
+        "Standard pipeline doesn't match preprocess()"
+    );
+
+    // Stages which aren't added are skipped
+    let mut text = str!(INPUT);
+    Preprocessor::new()
+        .stage(normalize_newlines)
+        .stage(trim_newlines)
+        .stage(convert_ellipses)
+        .run(&log, &mut text);
+    assert_eq!(
+        text, "``apple''\n\tbanana\u{2026}",
+        "Custom pipeline output doesn't match",
+    );
+}
+
+#[test]
+fn edits() {
+    use crate::Span;
+
+    let log = crate::build_logger();
+    let mut text = str!("\tapple\r\n``banana''");
+    let edits = preprocess_with_edits(&log, &mut text);
+
+    assert_eq!(text, "    apple\n“banana”", "Preprocessed text doesn't match");
+
+    let summary: Vec<_> = edits
+        .iter()
+        .map(|edit| (edit.name, edit.span, edit.original.as_str()))
+        .collect();
+
+    assert_eq!(
+        summary,
+        vec![
+            ("newlines", Span::new(6, 7), "\r"),
+            ("tabs", Span::new(0, 1), "\t"),
+            ("double-quotes", Span::new(10, 12), "``"),
+            ("double-quotes", Span::new(18, 20), "''"),
+        ],
+        "Edits don't match expected",
+    );
+
+    // "b" in "banana"
+    assert_eq!(map_to_preprocessed(&edits, 10), 13);
+    assert_eq!(map_to_raw(&edits, 13), 10);
+
+    // Within a replacement
+    assert_eq!(map_to_preprocessed(&edits, 9), 10);
+    assert_eq!(map_to_raw(&edits, 11), 8);
+    assert_eq!(map_to_raw(&edits, 2), 0);
+
+    // Every position which wasn't changed maps back to itself
+    for raw in (1..6).chain(7..8).chain(10..16) {
+        let preprocessed = map_to_preprocessed(&edits, raw);
+        assert_eq!(
+            map_to_raw(&edits, preprocessed),
+            raw,
+            "Position doesn't map back to raw",
+        );
+    }
+}
@@ -28,197 +28,276 @@
 //! * ,, .. '' to fancy lowered double quotes
 //! * << and >> to fancy French angle quotation marks
 //! * ... to an ellipsis
-
-use regex::Regex;
-
-lazy_static! {
-    // ‘ - LEFT SINGLE QUOTATION MARK
-    // ’ - RIGHT SINGLE QUOTATION MARK
-    static ref SINGLE_QUOTES: Replacer = Replacer::RegexSurround {
-        regex: Regex::new(r"`(.*?)'").unwrap(),
-        begin: "\u{2018}",
-        end: "\u{2019}",
-    };
-
-    // “ - LEFT DOUBLE QUOTATION MARK
-    // ” - RIGHT DOUBLE QUOTATION MARK
-    static ref DOUBLE_QUOTES: Replacer = Replacer::RegexSurround {
-        regex: Regex::new(r"``(.*?)''").unwrap(),
-        begin: "\u{201c}",
-        end: "\u{201d}",
-    };
-
-    // „ - DOUBLE LOW-9 QUOTATION MARK
-    static ref LOW_DOUBLE_QUOTES: Replacer = Replacer::RegexSurround {
-        regex: Regex::new(r",,(.*?)''").unwrap(),
-        begin: "\u{201e}",
-        end: "\u{201d}",
-    };
-
-    // « - LEFT-POINTING DOUBLE ANGLE QUOTATION MARK
-    static ref LEFT_DOUBLE_ANGLE: Replacer = Replacer::StrReplace {
-        pattern: "<<",
-        replacement: "\u{0ab}",
-    };
-
-    // » - RIGHT-POINTING DOUBLE ANGLE QUOTATION MARK
-    static ref RIGHT_DOUBLE_ANGLE: Replacer = Replacer::StrReplace {
-        pattern: ">>",
-        replacement: "\u{0bb}",
-    };
-
-    // … - HORIZONTAL ELLIPSIS
-    static ref ELLIPSIS: Replacer = Replacer::RegexReplace {
-        regex: Regex::new(r"(?:\.\.\.|\. \. \.)").unwrap(),
-        replacement: "\u{2026}",
-    };
-}
+//!
+//! Each transformation is a single scan over the text, finding the replacements
+//! to make, which are then applied in one go. So this is linear in the length
+//! of the input.
+//!
+//! Text within `[[notypography]]` blocks is left as-is, as is the body of
+//! blocks which are kept verbatim, such as `[[code]]`.
+
+use super::edit::{apply_replacements, Edit, Replacement};
+use super::exclusion::{find_excluded, segments_outside, NO_TYPOGRAPHY_BLOCKS};
+use crate::Span;
+use std::collections::VecDeque;
+
+// ‘ - LEFT SINGLE QUOTATION MARK
+// ’ - RIGHT SINGLE QUOTATION MARK
+const SINGLE_QUOTES: Replacer = Replacer::Surround {
+    open: "`",
+    close: "'",
+    begin: "\u{2018}",
+    end: "\u{2019}",
+};
+
+// “ - LEFT DOUBLE QUOTATION MARK
+// ” - RIGHT DOUBLE QUOTATION MARK
+const DOUBLE_QUOTES: Replacer = Replacer::Surround {
+    open: "``",
+    close: "''",
+    begin: "\u{201c}",
+    end: "\u{201d}",
+};
+
+// „ - DOUBLE LOW-9 QUOTATION MARK
+const LOW_DOUBLE_QUOTES: Replacer = Replacer::Surround {
+    open: ",,",
+    close: "''",
+    begin: "\u{201e}",
+    end: "\u{201d}",
+};
+
+// « - LEFT-POINTING DOUBLE ANGLE QUOTATION MARK
+const LEFT_DOUBLE_ANGLE: Replacer = Replacer::StrReplace {
+    patterns: &["<<"],
+    replacement: "\u{0ab}",
+};
+
+// » - RIGHT-POINTING DOUBLE ANGLE QUOTATION MARK
+const RIGHT_DOUBLE_ANGLE: Replacer = Replacer::StrReplace {
+    patterns: &[">>"],
+    replacement: "\u{0bb}",
+};
+
+// … - HORIZONTAL ELLIPSIS
+const ELLIPSIS: Replacer = Replacer::StrReplace {
+    patterns: &["...", ". . ."],
+    replacement: "\u{2026}",
+};
 
 /// Helper struct to easily perform string replacements.
 #[derive(Debug)]
 pub enum Replacer {
-    /// Replaces one static string for another static string.
+    /// Replaces any of the static strings with another static string.
+    ///
+    /// If several patterns match at the same position, the first listed is used.
     StrReplace {
-        pattern: &'static str,
+        patterns: &'static [&'static str],
         replacement: &'static str,
     },
 
-    /// Replaces any text matching the regular expression with the static string.
-    /// The entire match is used, any capture groups are ignored.
-    RegexReplace {
-        regex: Regex,
-        replacement: &'static str,
-    },
-
-    /// Takes text matching the regular expression, and replaces the exterior.
-    ///
-    /// The regular expression must return the content to be preserved in
-    /// capture group 1, and surrounds it with the `begin` and `end` strings.
+    /// Replaces a pair of delimiters on the same line, preserving the text between.
     ///
     /// For instance, say:
-    /// * `regex` matched `[% (.+) %]`
+    /// * `open` was `[%`
+    /// * `close` was `%]`
     /// * `begin` was `<(`
     /// * `end` was `)>`
     ///
     /// Then input string `[% wikidork %]` would become `<(wikidork)>`.
-    RegexSurround {
-        regex: Regex,
+    ///
+    /// Each `close` is paired with the earliest unpaired `open` before it,
+    /// so in `[% a [% b %] c %]` the first `[%` is paired with the first `%]`.
+    Surround {
+        open: &'static str,
+        close: &'static str,
         begin: &'static str,
         end: &'static str,
     },
 }
 
 impl Replacer {
-    fn replace(&self, log: &slog::Logger, text: &mut String, buffer: &mut String) {
+    /// Finds the replacements to make within `text`.
+    ///
+    /// The text is a segment of a larger string, starting at `offset`.
+    fn find(
+        &self,
+        log: &slog::Logger,
+        text: &str,
+        offset: usize,
+        replacements: &mut Vec<Replacement>,
+    ) {
         use self::Replacer::*;
 
         match *self {
             StrReplace {
-                pattern,
+                patterns,
                 replacement,
             } => {
                 debug!(
                     log,
                     "Running static string replacement";
                     "type" => "string",
-                    "pattern" => pattern,
-                    "replacement" => replacement,
-                );
-
-                while let Some(idx) = text.find(pattern) {
-                    let range = idx..idx + pattern.len();
-                    text.replace_range(range, replacement);
-                }
-            }
-            RegexReplace {
-                ref regex,
-                replacement,
-            } => {
-                debug!(
-                    log,
-                    "Running regular expression replacement";
-                    "type" => "regex",
-                    "pattern" => regex.as_str(),
+                    "patterns" => patterns.join(" "),
                     "replacement" => replacement,
                 );
 
-                while let Some(capture) = regex.captures(text) {
-                    let range = {
-                        let mtch = capture
-                            .get(0)
-                            .expect("Regular expression lacks a full match");
+                let mut index = 0;
 
-                        mtch.start()..mtch.end()
-                    };
+                'scan: while let Some(ch) = text[index..].chars().next() {
+                    for pattern in patterns {
+                        if text[index..].starts_with(pattern) {
+                            let span =
+                                Span::new(offset + index, offset + index + pattern.len());
+                            replacements.push((span, replacement));
+                            index += pattern.len();
+                            continue 'scan;
+                        }
+                    }
 
-                    text.replace_range(range, replacement);
+                    index += ch.len_utf8();
                 }
             }
-            RegexSurround {
-                ref regex,
+            Surround {
+                open,
+                close,
                 begin,
                 end,
             } => {
                 debug!(
                     log,
-                    "Running regular expression capture replacement";
+                    "Running surrounding delimiter replacement";
                     "type" => "surround",
-                    "pattern" => regex.as_str(),
+                    "open" => open,
+                    "close" => close,
                     "begin" => begin,
                     "end" => end,
                 );
 
-                while let Some(capture) = regex.captures(text) {
-                    let mtch = capture
-                        .get(1)
-                        .expect("Regular expression lacks a content group");
-
-                    let range = {
-                        let mtch = capture
-                            .get(0)
-                            .expect("Regular expression lacks a full match");
-
-                        mtch.start()..mtch.end()
-                    };
+                // Find the positions of all delimiter pairs
+                let mut openings = VecDeque::new();
+                let mut pairs = Vec::new();
+                let mut index = 0;
+
+                while index < text.len() {
+                    let remaining = &text[index..];
+
+                    if remaining.starts_with(open) {
+                        openings.push_back(index);
+                        index += open.len();
+                    } else if remaining.starts_with(close) {
+                        if let Some(opening) = openings.pop_front() {
+                            pairs.push((opening, open.len(), begin));
+                            pairs.push((index, close.len(), end));
+                        }
+
+                        index += close.len();
+                    } else {
+                        // Delimiters cannot span lines
+                        if remaining.starts_with('\n') {
+                            openings.clear();
+                        }
+
+                        index += remaining.chars().next().map_or(1, char::len_utf8);
+                    }
+                }
 
-                    buffer.clear();
-                    buffer.push_str(begin);
-                    buffer.push_str(mtch.as_str());
-                    buffer.push_str(end);
+                pairs.sort_unstable_by_key(|&(start, _, _)| start);
 
-                    text.replace_range(range, &buffer);
+                for (start, length, replacement) in pairs {
+                    let span = Span::new(offset + start, offset + start + length);
+                    replacements.push((span, replacement));
                 }
             }
         }
     }
 }
 
+/// Runs all of the substitutions in this module, in order.
+#[cfg(test)]
 pub fn substitute(log: &slog::Logger, text: &mut String) {
-    let mut buffer = String::new();
+    convert_quotes(log, text, None);
+    convert_ellipses(log, text, None);
+}
 
-    debug!(log, "Performing typography substitutions"; "text" => &*text);
+/// Converts ASCII quotation marks into their typographic forms.
+///
+/// This covers the double, lowered double, and single quotes,
+/// as well as French angle quotation marks.
+pub fn convert_quotes(
+    log: &slog::Logger,
+    text: &mut String,
+    mut edits: Option<&mut Vec<Edit>>,
+) {
+    debug!(log, "Performing quote substitutions"; "text" => &*text);
+
+    replace_outside(
+        log,
+        text,
+        "double-quotes",
+        &DOUBLE_QUOTES,
+        edits.as_deref_mut(),
+    );
+    replace_outside(
+        log,
+        text,
+        "low-double-quotes",
+        &LOW_DOUBLE_QUOTES,
+        edits.as_deref_mut(),
+    );
+    replace_outside(
+        log,
+        text,
+        "single-quotes",
+        &SINGLE_QUOTES,
+        edits.as_deref_mut(),
+    );
+    replace_outside(
+        log,
+        text,
+        "left-double-angle",
+        &LEFT_DOUBLE_ANGLE,
+        edits.as_deref_mut(),
+    );
+    replace_outside(log, text, "right-double-angle", &RIGHT_DOUBLE_ANGLE, edits);
+}
 
-    macro_rules! replace {
-        ($replacer:expr) => {
-            $replacer.replace(log, text, &mut buffer)
-        };
-    }
+/// Converts runs of three periods into an ellipsis.
+pub fn convert_ellipses(
+    log: &slog::Logger,
+    text: &mut String,
+    edits: Option<&mut Vec<Edit>>,
+) {
+    debug!(log, "Performing ellipsis substitutions"; "text" => &*text);
 
-    // Quotes
-    replace!(DOUBLE_QUOTES);
-    replace!(LOW_DOUBLE_QUOTES);
-    replace!(SINGLE_QUOTES);
+    replace_outside(log, text, "ellipsis", &ELLIPSIS, edits);
+}
 
-    // French quotes
-    replace!(LEFT_DOUBLE_ANGLE);
-    replace!(RIGHT_DOUBLE_ANGLE);
+/// Performs a pass with the given replacer, skipping excluded blocks.
+fn replace_outside(
+    log: &slog::Logger,
+    text: &mut String,
+    name: &'static str,
+    replacer: &Replacer,
+    edits: Option<&mut Vec<Edit>>,
+) {
+    let excluded = find_excluded(text, &NO_TYPOGRAPHY_BLOCKS);
+    let mut replacements = Vec::new();
+
+    for segment in segments_outside(text.len(), &excluded) {
+        replacer.find(
+            log,
+            &text[segment.clone()],
+            segment.start,
+            &mut replacements,
+        );
+    }
 
-    // Miscellaneous
-    replace!(ELLIPSIS);
+    apply_replacements(name, text, &replacements, edits);
 }
 
 #[cfg(test)]
-const TEST_CASES: [(&str, &str); 4] = [
+const TEST_CASES: [(&str, &str); 7] = [
     (
         "John laughed. ``You'll never defeat me!''\n``That's where you're wrong...''",
         "John laughed. “You'll never defeat me!”\n“That's where you're wrong…”",
@@ -235,18 +314,20 @@ const TEST_CASES: [(&str, &str); 4] = [
         "**ENTITY MAKES DRAMATIC MOTION** . . . ",
         "**ENTITY MAKES DRAMATIC MOTION** … ",
     ),
+    (
+        "``apple ``banana'' cherry''\n``durian\nfig''",
+        "“apple “banana” cherry”\n``durian\nfig''",
+    ),
+    (
+        "``a'' [[notypography]]``b'' ...[[/notypography]] ``c''...",
+        "“a” [[notypography]]``b'' ...[[/notypography]] “c”…",
+    ),
+    (
+        "<<a>> [[code type=\"rust\"]]a >> b...[[/code]] ...",
+        "«a» [[code type=\"rust\"]]a >> b...[[/code]] …",
+    ),
 ];
 
-#[test]
-fn regexes() {
-    let _ = &*SINGLE_QUOTES;
-    let _ = &*DOUBLE_QUOTES;
-    let _ = &*LOW_DOUBLE_QUOTES;
-    let _ = &*LEFT_DOUBLE_ANGLE;
-    let _ = &*RIGHT_DOUBLE_ANGLE;
-    let _ = &*ELLIPSIS;
-}
-
 #[test]
 fn test_substitute() {
     use super::test::test_substitution;
@@ -23,86 +23,195 @@
 //! * Replacing DOS and legacy Mac newlines
 //! * Trimming whitespace lines
 //! * Concatenating lines that end with backslashes
-//! * Convert tabs to four spaces
+//! * Convert tabs to four spaces, outside of TSV tables
 //! * Compress groups of 3+ newlines into 2 newlines
 //!
 //! Note on the first item:
 //! It was originally implemented in the parser, however it was moved here
 //! to prevent typography from converting the `--` in `[!--` and `--]` into
 //! em dashes.
+//!
+//! Each substitution is a single scan over the text, finding the replacements
+//! to make, which are then applied in one go. So this is linear in the length
+//! of the input.
 
-use regex::{Regex, RegexBuilder};
-
-lazy_static! {
-    static ref WHITESPACE: Regex = {
-        RegexBuilder::new(r"^\s+$")
-            .multi_line(true)
-            .build()
-            .unwrap()
-    };
-    static ref LEADING_NEWLINES: Regex = Regex::new(r"^\n+").unwrap();
-    static ref TRAILING_NEWLINES: Regex = Regex::new(r"\n+$").unwrap();
-}
+use super::edit::{apply_replacements, Edit, Replacement};
+use super::exclusion::{find_excluded, segments_outside, NO_TAB_EXPANSION_BLOCKS};
+use crate::Span;
 
+/// Runs all of the substitutions in this module, in order.
+#[cfg(test)]
 pub fn substitute(log: &slog::Logger, text: &mut String) {
-    // Replace DOS and Mac newlines
-    str_replace(log, text, "\r\n", "\n");
-    str_replace(log, text, "\r", "\n");
-
-    // Strip lines with only whitespace
-    regex_replace(log, text, &*WHITESPACE, "");
-
-    // Join concatenated lines (ending with '\')
-    str_replace(log, text, "\\\n", "");
-
-    // Tabs to spaces
-    str_replace(log, text, "\t", "    ");
+    normalize_newlines(log, text, None);
+    strip_whitespace_lines(log, text, None);
+    join_lines(log, text, None);
+    expand_tabs(log, text, None);
+    trim_newlines(log, text, None);
+}
 
-    // Remove leading and trailing newlines,
-    // save one at the end
-    regex_replace(log, text, &*LEADING_NEWLINES, "");
-    regex_replace(log, text, &*TRAILING_NEWLINES, "");
+/// Replaces DOS and legacy Mac newlines with regular ones.
+///
+/// Any number of carriage returns before a newline are removed,
+/// rather than only one.
+pub fn normalize_newlines(
+    log: &slog::Logger,
+    text: &mut String,
+    edits: Option<&mut Vec<Edit>>,
+) {
+    replace(log, "newlines", text, edits, |text, replacements| {
+        let mut carriage_returns = None;
+
+        for (index, ch) in text.char_indices() {
+            match (ch, carriage_returns) {
+                ('\r', None) => carriage_returns = Some(index),
+                ('\r', Some(_)) => (),
+                ('\n', Some(start)) => {
+                    replacements.push((Span::new(start, index), ""));
+                    carriage_returns = None;
+                }
+                (_, Some(start)) => {
+                    replace_each(start, index, "\n", replacements);
+                    carriage_returns = None;
+                }
+                (_, None) => (),
+            }
+        }
+
+        if let Some(start) = carriage_returns {
+            replace_each(start, text.len(), "\n", replacements);
+        }
+    });
 }
 
-fn str_replace(log: &slog::Logger, text: &mut String, pattern: &str, replacement: &str) {
-    debug!(
+/// Replaces each run of lines containing only whitespace with a single empty line.
+pub fn strip_whitespace_lines(
+    log: &slog::Logger,
+    text: &mut String,
+    edits: Option<&mut Vec<Edit>>,
+) {
+    replace(
         log,
-        "Replacing miscellaneous static string";
-        "type" => "string",
-        "text" => &*text,
-        "pattern" => pattern,
-        "replacement" => replacement,
+        "whitespace-lines",
+        text,
+        edits,
+        |text, replacements| {
+            let mut in_blank_run = false;
+            let mut start = 0;
+
+            for line in text.split('\n') {
+                let end = start + line.len();
+                let blank = line.chars().all(char::is_whitespace);
+
+                if blank && in_blank_run {
+                    // Remove the line entirely, along with the newline before it
+                    replacements.push((Span::new(start - 1, end), ""));
+                } else if blank && !line.is_empty() {
+                    replacements.push((Span::new(start, end), ""));
+                }
+
+                in_blank_run = blank;
+                start = end + 1;
+            }
+        },
     );
+}
 
-    while let Some(idx) = text.find(pattern) {
-        let range = idx..idx + pattern.len();
-        text.replace_range(range, replacement);
-    }
+/// Joins lines which end with a backslash to the following line.
+///
+/// A line ending in several backslashes is joined with as many following lines.
+pub fn join_lines(log: &slog::Logger, text: &mut String, edits: Option<&mut Vec<Edit>>) {
+    replace(log, "concatenate", text, edits, |text, replacements| {
+        // Backslashes at the end of the output so far
+        let mut backslashes = Vec::new();
+
+        for (index, ch) in text.char_indices() {
+            match ch {
+                '\\' => backslashes.push(index),
+                '\n' => {
+                    if let Some(backslash) = backslashes.pop() {
+                        replacements.push((Span::new(backslash, backslash + 1), ""));
+                        replacements.push((Span::new(index, index + 1), ""));
+                    }
+                }
+                _ => backslashes.clear(),
+            }
+        }
+
+        replacements.sort_unstable_by_key(|(span, _)| span.start);
+    });
+}
+
+/// Converts each tab into four spaces.
+///
+/// Tabs within `[[table type="tsv"]]` blocks are left as-is, since they separate cells.
+pub fn expand_tabs(log: &slog::Logger, text: &mut String, edits: Option<&mut Vec<Edit>>) {
+    replace(log, "tabs", text, edits, |text, replacements| {
+        let excluded = find_excluded(text, &NO_TAB_EXPANSION_BLOCKS);
+
+        for segment in segments_outside(text.len(), &excluded) {
+            for (index, _) in text[segment.clone()].match_indices('\t') {
+                let index = segment.start + index;
+                replacements.push((Span::new(index, index + 1), "    "));
+            }
+        }
+    });
 }
 
-fn regex_replace(
+/// Removes leading and trailing newlines.
+pub fn trim_newlines(
     log: &slog::Logger,
     text: &mut String,
-    regex: &Regex,
-    replacement: &str,
+    edits: Option<&mut Vec<Edit>>,
 ) {
+    replace(log, "trim-newlines", text, edits, |text, replacements| {
+        let end = text.trim_end_matches('\n').len();
+        let start = end - text[..end].trim_start_matches('\n').len();
+
+        if start > 0 {
+            replacements.push((Span::new(0, start), ""));
+        }
+
+        if end < text.len() {
+            replacements.push((Span::new(end, text.len()), ""));
+        }
+    });
+}
+
+fn replace<F>(
+    log: &slog::Logger,
+    name: &'static str,
+    text: &mut String,
+    edits: Option<&mut Vec<Edit>>,
+    find: F,
+) where
+    F: FnOnce(&str, &mut Vec<Replacement>),
+{
     debug!(
         log,
-        "Replacing miscellaneous regular expression";
-        "type" => "regex",
+        "Performing miscellaneous substitution";
+        "substitution" => name,
         "text" => &*text,
-        "pattern" => regex.as_str(),
-        "replacement" => replacement,
     );
 
-    while let Some(mtch) = regex.find(text) {
-        let range = mtch.start()..mtch.end();
-        text.replace_range(range, replacement);
+    let mut replacements = Vec::new();
+    find(text, &mut replacements);
+    apply_replacements(name, text, &replacements, edits);
+}
+
+/// Replaces each of a run of single-byte characters individually.
+fn replace_each(
+    start: usize,
+    end: usize,
+    replacement: &'static str,
+    replacements: &mut Vec<Replacement>,
+) {
+    for index in start..end {
+        replacements.push((Span::new(index, index + 1), replacement));
     }
 }
 
 #[cfg(test)]
-const TEST_CASES: [(&str, &str); 6] = [
+const TEST_CASES: [(&str, &str); 9] = [
     (
         "\tapple\n\tbanana\tcherry\n",
         "    apple\n    banana    cherry",
@@ -124,13 +233,14 @@ const TEST_CASES: [(&str, &str); 6] = [
         "concat:\napple banana CherryPineapple \\ grape\nblueberry",
     ),
     ("<\n        \n      \n  \n      \n>", "<\n\n>"),
+    ("apple\r\r\nbanana\r\rcherry", "apple\nbanana\n\ncherry"),
+    ("apple\\\\\n\nbanana\n\n\ncherry", "applebanana\n\ncherry"),
+    (
+        "\ta\n[[table type=\"tsv\"]]\nb\tc\n[[/table]]\nd\te",
+        "    a\n[[table type=\"tsv\"]]\nb\tc\n[[/table]]\nd    e",
+    ),
 ];
 
-#[test]
-fn regexes() {
-    let _ = &*WHITESPACE;
-}
-
 #[test]
 fn test_substitute() {
     use super::test::test_substitution;
@@ -0,0 +1,297 @@
+/*
+ * render/date.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Formatting of dates, for `[[date]]` blocks.
+//!
+//! Renderers never read the system clock when formatting. Instead, the
+//! current time and timezone come from `DateSettings`, so output using
+//! relative dates such as "3 days ago" is reproducible.
+//!
+//! Formats follow `strftime`, supporting `%Y`, `%y`, `%m`, `%d`, `%e`, `%H`,
+//! `%I`, `%M`, `%S`, `%p`, `%a`, `%A`, `%b`, `%B` and `%%`, along with
+//! Wikidot's `%O` for the time relative to now. Other sequences are kept as-is.
+
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The format used when a date block does not specify one.
+pub const DEFAULT_DATE_FORMAT: &str = "%e %b %Y, %H:%M";
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// The time and timezone dates are displayed relative to.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct DateSettings {
+    /// The current time, as a Unix timestamp in seconds.
+    ///
+    /// If absent, the system clock is read once when rendering begins.
+    /// Set this to make relative dates deterministic, such as in tests.
+    pub now: Option<i64>,
+
+    /// The offset of the reader's timezone from UTC, in seconds.
+    pub utc_offset: i32,
+}
+
+impl DateSettings {
+    /// Fills in the current time from the system clock, if it was not set.
+    pub fn resolve(self) -> Self {
+        let now = self.now.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs() as i64)
+                .unwrap_or(0)
+        });
+
+        DateSettings {
+            now: Some(now),
+            ..self
+        }
+    }
+}
+
+/// Formats a Unix timestamp according to the given format.
+///
+/// If no current time is set, relative dates are formatted
+/// as absolute ones, using the default format.
+pub fn format_date(
+    timestamp: i64,
+    format: Option<&str>,
+    settings: &DateSettings,
+) -> String {
+    let format = format.unwrap_or(DEFAULT_DATE_FORMAT);
+    let local = timestamp.saturating_add(i64::from(settings.utc_offset));
+    let date = CivilDate::from_timestamp(local);
+    let mut output = String::new();
+    let mut chars = format.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            output.push(ch);
+            continue;
+        }
+
+        // Writing to a String cannot fail
+        let _ = match chars.next() {
+            Some('Y') => write!(output, "{}", date.year),
+            Some('y') => write!(output, "{:02}", date.year.rem_euclid(100)),
+            Some('m') => write!(output, "{:02}", date.month),
+            Some('d') => write!(output, "{:02}", date.day),
+            Some('e') => write!(output, "{}", date.day),
+            Some('H') => write!(output, "{:02}", date.hour),
+            Some('I') => write!(output, "{:02}", (date.hour + 11) % 12 + 1),
+            Some('M') => write!(output, "{:02}", date.minute),
+            Some('S') => write!(output, "{:02}", date.second),
+            Some('p') => write!(output, "{}", if date.hour < 12 { "AM" } else { "PM" }),
+            Some('a') => write!(output, "{}", &WEEKDAYS[date.weekday][..3]),
+            Some('A') => write!(output, "{}", WEEKDAYS[date.weekday]),
+            Some('b') => write!(output, "{}", &MONTHS[date.month as usize - 1][..3]),
+            Some('B') => write!(output, "{}", MONTHS[date.month as usize - 1]),
+            Some('O') => match settings.now {
+                Some(now) => {
+                    let difference = now.saturating_sub(timestamp);
+                    write!(output, "{}", format_relative(difference))
+                }
+                None => write!(output, "{}", format_date(timestamp, None, settings)),
+            },
+            Some('%') => write!(output, "%"),
+            Some(other) => write!(output, "%{}", other),
+            None => write!(output, "%"),
+        };
+    }
+
+    output
+}
+
+/// Describes a time difference, such as "3 days ago" or "in 2 hours".
+fn format_relative(difference: i64) -> String {
+    const UNITS: [(i64, &str); 6] = [
+        (365 * 24 * 60 * 60, "year"),
+        (30 * 24 * 60 * 60, "month"),
+        (24 * 60 * 60, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+        (1, "second"),
+    ];
+
+    let seconds = difference.saturating_abs();
+    let (size, unit) = UNITS
+        .iter()
+        .find(|(size, _)| seconds >= *size)
+        .unwrap_or(&UNITS[UNITS.len() - 1]);
+
+    let amount = seconds / size;
+    let plural = if amount == 1 { "" } else { "s" };
+
+    if difference >= 0 {
+        format!("{} {}{} ago", amount, unit, plural)
+    } else {
+        format!("in {} {}{}", amount, unit, plural)
+    }
+}
+
+/// A Unix timestamp broken into its calendar fields, in the proleptic Gregorian calendar.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct CivilDate {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    weekday: usize,
+}
+
+impl CivilDate {
+    fn from_timestamp(timestamp: i64) -> Self {
+        let days = timestamp.div_euclid(86400);
+        let seconds = timestamp.rem_euclid(86400) as u32;
+
+        // See Howard Hinnant's "civil_from_days" algorithm
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        // 1970-01-01 was a Thursday
+        let weekday = (days + 3).rem_euclid(7) as usize;
+
+        CivilDate {
+            year,
+            month,
+            day,
+            hour: seconds / 3600,
+            minute: seconds / 60 % 60,
+            second: seconds % 60,
+            weekday,
+        }
+    }
+}
+
+#[test]
+fn dates() {
+    let settings = DateSettings {
+        now: Some(1_600_000_000),
+        utc_offset: 0,
+    };
+
+    macro_rules! test {
+        ($timestamp:expr, $format:expr, $expected:expr $(,)?) => {{
+            let actual = format_date($timestamp, $format, &settings);
+
+            assert_eq!(actual, $expected, "Formatted date doesn't match expected",);
+        }};
+    }
+
+    test!(0, None, "1 Jan 1970, 00:00");
+    test!(1_600_000_000, None, "13 Sep 2020, 12:26");
+    test!(
+        1_600_000_000,
+        Some("%Y-%m-%d %H:%M:%S"),
+        "2020-09-13 12:26:40"
+    );
+    test!(
+        1_600_000_000,
+        Some("%A %B %e %y, %I%p"),
+        "Sunday September 13 20, 12PM"
+    );
+    test!(951_782_400, Some("%a %d %b %Y"), "Tue 29 Feb 2000");
+    test!(-86400, Some("%Y-%m-%d"), "1969-12-31");
+    test!(1_600_000_000, Some("100%% %Q"), "100% %Q");
+    test!(1_599_740_800, Some("%O"), "3 days ago");
+    test!(1_600_000_001, Some("%O"), "in 1 second");
+    test!(1_600_000_000, Some("%O"), "0 seconds ago");
+
+    let offset = DateSettings {
+        utc_offset: -5 * 60 * 60,
+        ..settings
+    };
+
+    assert_eq!(
+        format_date(1_600_000_000, Some("%H:%M"), &offset),
+        "07:26",
+        "Formatted date with offset doesn't match expected",
+    );
+
+    assert_eq!(
+        format_date(1_600_000_000, Some("%O"), &DateSettings::default()),
+        "13 Sep 2020, 12:26",
+        "Relative date without the current time doesn't match expected",
+    );
+}
+
+#[test]
+fn extreme_dates() {
+    // Neither applying the offset nor finding the relative time may overflow
+    let settings = DateSettings {
+        now: Some(1_600_000_000),
+        utc_offset: 60 * 60,
+    };
+
+    assert_eq!(
+        format_date(i64::MAX, Some("%Y-%m-%d"), &settings),
+        "292277026596-12-04",
+    );
+    assert_eq!(
+        format_date(i64::MAX, Some("%O"), &settings),
+        "in 292471208626 years",
+    );
+
+    let settings = DateSettings {
+        utc_offset: -60 * 60,
+        ..settings
+    };
+
+    assert_eq!(
+        format_date(i64::MIN, Some("%Y-%m-%d"), &settings),
+        "-292277022657-01-27",
+    );
+    assert_eq!(
+        format_date(i64::MIN, Some("%O"), &settings),
+        "292471208677 years ago",
+    );
+}
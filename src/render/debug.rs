@@ -59,6 +59,7 @@ fn debug() {
     styles: [
         "span.hidden-text { display: none; }",
     ],
+    sections: [],
 }"#;
 
     // Syntax tree construction
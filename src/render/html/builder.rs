@@ -57,6 +57,8 @@ impl<'c, 'i, 'h> HtmlBuilder<'c, 'i, 'h> {
     tag_method!(br);
     tag_method!(code);
     tag_method!(div);
+    tag_method!(figcaption);
+    tag_method!(figure);
     tag_method!(hr);
     tag_method!(i);
     tag_method!(iframe);
@@ -64,6 +66,7 @@ impl<'c, 'i, 'h> HtmlBuilder<'c, 'i, 'h> {
     tag_method!(li);
     tag_method!(ol);
     tag_method!(p);
+    tag_method!(pre);
     tag_method!(script);
     tag_method!(span);
     tag_method!(strike);
@@ -88,6 +91,12 @@ pub struct HtmlBuilderTag<'c, 'i, 'h, 't> {
     ctx: &'c mut HtmlContext<'i, 'h>,
     tag: &'t str,
     in_tag: bool,
+
+    /// The buffer positions where each attribute begins.
+    ///
+    /// Only recorded in deterministic mode, where they are sorted
+    /// by name once the opening tag is finished.
+    attributes: Vec<usize>,
 }
 
 impl<'c, 'i, 'h, 't> HtmlBuilderTag<'c, 'i, 'h, 't> {
@@ -99,6 +108,7 @@ impl<'c, 'i, 'h, 't> HtmlBuilderTag<'c, 'i, 'h, 't> {
             ctx,
             tag,
             in_tag: true,
+            attributes: Vec::new(),
         }
     }
 
@@ -106,6 +116,11 @@ impl<'c, 'i, 'h, 't> HtmlBuilderTag<'c, 'i, 'h, 't> {
         debug_assert!(is_alphanumeric(key));
         debug_assert!(self.in_tag);
 
+        if self.ctx.deterministic() {
+            let position = self.ctx.buffer().len();
+            self.attributes.push(position);
+        }
+
         self.ctx.push_raw(' ');
         self.ctx.push_escaped(key);
         self.ctx.push_raw('=');
@@ -156,8 +171,42 @@ impl<'c, 'i, 'h, 't> HtmlBuilderTag<'c, 'i, 'h, 't> {
         self
     }
 
+    fn sort_attributes(&mut self) {
+        let start = match self.attributes.first() {
+            Some(&start) => start,
+            None => return,
+        };
+
+        let positions = &self.attributes;
+        let buffer = self.ctx.buffer();
+        let mut attributes: Vec<String> = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &begin)| {
+                let end = positions.get(i + 1).copied().unwrap_or(buffer.len());
+
+                str!(buffer[begin..end])
+            })
+            .collect();
+
+        // Each is of the form ` key="value"`
+        fn key(attribute: &str) -> &str {
+            attribute.split('=').next().unwrap_or(attribute)
+        }
+
+        attributes.sort_by(|a, b| key(a).cmp(key(b)));
+
+        buffer.truncate(start);
+        for attribute in &attributes {
+            buffer.push_str(attribute);
+        }
+
+        self.attributes.clear();
+    }
+
     fn content_start(&mut self) {
         if self.in_tag {
+            self.sort_attributes();
             self.ctx.push_raw('>');
             self.in_tag = false;
         }
@@ -184,17 +233,43 @@ impl<'c, 'i, 'h, 't> HtmlBuilderTag<'c, 'i, 'h, 't> {
 
 impl<'c, 'i, 'h, 't> Drop for HtmlBuilderTag<'c, 'i, 'h, 't> {
     fn drop(&mut self) {
-        if !self.in_tag {
-            self.ctx.push_raw_str("</");
-            self.ctx.push_raw_str(self.tag);
+        // Void elements cannot have contents, so there is no closing tag.
+        // Any other element is closed, even if it is empty.
+        if self.in_tag && is_void_tag(self.tag) {
+            self.sort_attributes();
+            self.ctx.push_raw('>');
+            return;
         }
 
+        self.content_start();
+        self.ctx.push_raw_str("</");
+        self.ctx.push_raw_str(self.tag);
         self.ctx.push_raw('>');
     }
 }
 
 // Helpers
 
+pub fn is_void_tag(tag: &str) -> bool {
+    matches!(
+        tag,
+        "area"
+            | "base"
+            | "br"
+            | "col"
+            | "embed"
+            | "hr"
+            | "img"
+            | "input"
+            | "link"
+            | "meta"
+            | "param"
+            | "source"
+            | "track"
+            | "wbr",
+    )
+}
+
 fn is_alphanumeric(value: &str) -> bool {
     value
         .chars()
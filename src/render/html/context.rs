@@ -20,29 +20,128 @@
 
 use super::builder::HtmlBuilder;
 use super::escape::escape;
+use super::handle::Handle;
+use super::math::MathRenderer;
 use super::meta::{HtmlMeta, HtmlMetaType};
 use super::output::HtmlOutput;
-use crate::data::PageInfo;
+use super::render::ElementRender;
+use super::settings::{DiagramOutput, HeadingNumbering};
+use super::HtmlRender;
+use crate::data::{PageInfo, SiteSettings};
+use crate::enums::HeadingLevel;
+use crate::parsing::ParseSettings;
+use crate::render::date::DateSettings;
+use crate::render::multi::Collector;
+use crate::render::{RenderWarning, RenderWarningKind};
+use crate::tree::{number_headings, Element, Footnotes, OutlineHeading};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fmt::{self, Write};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct HtmlContext<'i, 'h> {
     html: String,
     style: String,
     meta: Vec<HtmlMeta>,
+    warnings: Vec<RenderWarning>,
     info: PageInfo<'i>,
-    handle: &'h (),
+    site: Arc<SiteSettings>,
+    parse_settings: &'h ParseSettings,
+    handle: &'h dyn Handle,
+    math_renderer: Option<&'h dyn MathRenderer>,
+    log: Option<&'h slog::Logger>,
+    diagram_output: DiagramOutput,
+    date_settings: DateSettings,
+    deterministic: bool,
+    accessibility: bool,
+    safe_mode: bool,
+    id_counter: usize,
+    outline: Vec<PageHeading>,
+    heading_counter: usize,
+    footnotes: Vec<Vec<Element<'static>>>,
+    footnote_references: Vec<usize>,
+    footnote_counter: usize,
+    footnotes_listed: bool,
+    memo: Option<HashMap<u64, Vec<MemoEntry>>>,
+    collector: Option<Collector>,
+}
+
+/// A heading on the page, see `HtmlContext::outline()`.
+#[derive(Debug)]
+pub struct PageHeading {
+    pub level: HeadingLevel,
+
+    /// The ID the table of contents links to.
+    ///
+    /// Like Wikidot, these are numbered as `toc0`, `toc1`, and so on,
+    /// counting only listed headings. Unlisted headings have none.
+    pub id: Option<String>,
+
+    /// The section number to prefix the heading with, if numbering is enabled.
+    pub number: Option<String>,
+
+    pub elements: Vec<Element<'static>>,
+}
+
+/// The ID of a reference to a footnote, which its entry in the footnote block links back to.
+///
+/// These follow Wikidot, which uses `footnoteref-1` for footnote 1.
+/// Later references to the same footnote are suffixed, as in `footnoteref-1-2`.
+fn footnote_ref_id(number: usize, occurrence: usize) -> String {
+    match occurrence {
+        0 => format!("footnoteref-{}", number),
+        _ => format!("footnoteref-{}-{}", number, occurrence + 1),
+    }
+}
+
+/// A rendered subtree, kept so identical ones can reuse its output.
+#[derive(Debug)]
+struct MemoEntry {
+    elements: Vec<Element<'static>>,
+    html: String,
+    warnings: Vec<RenderWarning>,
+    collected: Option<Collector>,
 }
 
 impl<'i, 'h> HtmlContext<'i, 'h> {
-    #[inline]
-    pub fn new(info: PageInfo<'i>, handle: &'h ()) -> Self {
+    /// Creates a context for rendering with the given renderer's settings.
+    ///
+    /// Optional features, such as accessibility and safe mode,
+    /// are enabled separately.
+    pub fn new(render: &'h HtmlRender, handle: &'h dyn Handle) -> Self {
+        let info = render.page_info.clone();
+
         HtmlContext {
             html: String::new(),
             style: String::new(),
             meta: Self::initial_metadata(&info),
+            warnings: Vec::new(),
             info,
+            site: Arc::clone(&render.site),
+            parse_settings: &render.parse_settings,
             handle,
+            math_renderer: None,
+            log: None,
+            diagram_output: render.diagram_output,
+            date_settings: render.date_settings.resolve(),
+            deterministic: render.deterministic,
+            accessibility: false,
+            safe_mode: false,
+            id_counter: 0,
+            outline: Vec::new(),
+            heading_counter: 0,
+            footnotes: Vec::new(),
+            footnote_references: Vec::new(),
+            footnote_counter: 0,
+            footnotes_listed: false,
+            memo: if render.memoize {
+                Some(HashMap::new())
+            } else {
+                None
+            },
+            collector: None,
         }
     }
 
@@ -88,10 +187,394 @@ impl<'i, 'h> HtmlContext<'i, 'h> {
     }
 
     #[inline]
-    pub fn handle(&self) -> &'h () {
+    pub fn site(&self) -> &SiteSettings {
+        &self.site
+    }
+
+    #[inline]
+    pub fn handle(&self) -> &'h dyn Handle {
         self.handle
     }
 
+    #[inline]
+    pub fn math_renderer(&self) -> Option<&'h dyn MathRenderer> {
+        self.math_renderer
+    }
+
+    #[inline]
+    pub fn set_math_renderer(&mut self, renderer: &'h dyn MathRenderer) {
+        self.math_renderer = Some(renderer);
+    }
+
+    /// Gets the logger for parsing wikitext during rendering, such as from templates.
+    ///
+    /// If none was set, messages are discarded.
+    pub fn log(&self) -> slog::Logger {
+        match self.log {
+            Some(log) => slog::Logger::clone(log),
+            None => slog::Logger::root(slog::Discard, o!()),
+        }
+    }
+
+    #[inline]
+    pub fn set_logger(&mut self, log: &'h slog::Logger) {
+        self.log = Some(log);
+    }
+
+    #[inline]
+    pub fn diagram_output(&self) -> DiagramOutput {
+        self.diagram_output
+    }
+
+    #[inline]
+    pub fn parse_settings(&self) -> &'h ParseSettings {
+        self.parse_settings
+    }
+
+    #[inline]
+    pub fn date_settings(&self) -> &DateSettings {
+        &self.date_settings
+    }
+
+    #[inline]
+    pub fn deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Whether ARIA attributes are added to generated widgets.
+    #[inline]
+    pub fn accessibility(&self) -> bool {
+        self.accessibility
+    }
+
+    #[inline]
+    pub fn enable_accessibility(&mut self) {
+        self.accessibility = true;
+    }
+
+    /// Whether parts of the page which reach beyond its content are left out.
+    ///
+    /// See `HtmlRender::safe_mode`.
+    #[inline]
+    pub fn safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    #[inline]
+    pub fn enable_safe_mode(&mut self) {
+        self.safe_mode = true;
+    }
+
+    // ID generation
+
+    /// Produces a unique ID for an element which requires one.
+    ///
+    /// These are numbered in document order rather than randomly generated,
+    /// so rendering the same tree always produces the same IDs.
+    pub fn generate_id(&mut self, prefix: &str) -> String {
+        let id = format!("{}-{}", prefix, self.id_counter);
+        self.id_counter += 1;
+        id
+    }
+
+    // Headings
+
+    /// Sets the headings of the page, for tables of contents to list.
+    pub fn set_outline(
+        &mut self,
+        headings: &[OutlineHeading],
+        numbering: HeadingNumbering,
+    ) {
+        let numbers = match numbering {
+            HeadingNumbering::None => vec![None; headings.len()],
+            HeadingNumbering::All => number_headings(headings, true),
+            HeadingNumbering::Listed => number_headings(headings, false),
+        };
+
+        let mut listed = 0;
+
+        self.outline = headings
+            .iter()
+            .zip(numbers)
+            .map(|(heading, number)| {
+                let id = if heading.listed {
+                    listed += 1;
+                    Some(format!("toc{}", listed - 1))
+                } else {
+                    None
+                };
+
+                PageHeading {
+                    level: heading.level,
+                    id,
+                    number,
+                    elements: heading.elements.iter().map(Element::to_owned).collect(),
+                }
+            })
+            .collect();
+    }
+
+    /// The headings of the page, in document order.
+    #[inline]
+    pub fn outline(&self) -> &[PageHeading] {
+        &self.outline
+    }
+
+    /// Skips over headings which come before the part of the page being rendered.
+    ///
+    /// This is so that a partial render has the same heading IDs as a full one.
+    #[inline]
+    pub fn skip_headings(&mut self, count: usize) {
+        self.heading_counter += count;
+    }
+
+    /// Gets the ID and number for the next heading rendered.
+    pub fn next_heading(&mut self) -> (Option<String>, Option<String>) {
+        let heading = self.outline.get(self.heading_counter);
+        self.heading_counter += 1;
+
+        match heading {
+            Some(heading) => (heading.id.clone(), heading.number.clone()),
+            None => (None, None),
+        }
+    }
+
+    // Footnotes
+
+    /// Sets the footnotes of the page, for footnote blocks to list.
+    pub fn set_footnotes(&mut self, footnotes: &Footnotes) {
+        self.footnotes = footnotes
+            .contents
+            .iter()
+            .map(|elements| elements.iter().map(Element::to_owned).collect())
+            .collect();
+
+        self.footnote_references = footnotes.references.clone();
+    }
+
+    /// The contents of each footnote on the page.
+    ///
+    /// Footnote number `n` is at index `n - 1`.
+    #[inline]
+    pub fn footnotes(&self) -> &[Vec<Element<'static>>] {
+        &self.footnotes
+    }
+
+    /// Skips over footnote references which come before the part of the page being rendered.
+    ///
+    /// See `skip_headings()`.
+    #[inline]
+    pub fn skip_footnotes(&mut self, count: usize) {
+        self.footnote_counter += count;
+    }
+
+    /// Gets the number and ID for the next footnote reference rendered.
+    pub fn next_footnote(&mut self) -> Option<(usize, String)> {
+        let index = self.footnote_counter;
+        let number = *self.footnote_references.get(index)?;
+        self.footnote_counter += 1;
+
+        let occurrence = self.footnote_references[..index]
+            .iter()
+            .filter(|&&previous| previous == number)
+            .count();
+
+        Some((number, footnote_ref_id(number, occurrence)))
+    }
+
+    /// The IDs of every reference to the given footnote, in document order.
+    pub fn footnote_ref_ids(&self, number: usize) -> Vec<String> {
+        self.footnote_references
+            .iter()
+            .filter(|&&reference| reference == number)
+            .enumerate()
+            .map(|(occurrence, _)| footnote_ref_id(number, occurrence))
+            .collect()
+    }
+
+    /// Marks the footnotes as listed, returning whether they already were.
+    ///
+    /// Like Wikidot, only the first footnote block lists them.
+    #[inline]
+    pub fn list_footnotes(&mut self) -> bool {
+        let listed = self.footnotes_listed;
+        self.footnotes_listed = true;
+        listed
+    }
+
+    // Memoization
+
+    /// Renders the elements, reusing the output of an identical earlier subtree if possible.
+    ///
+    /// If memoization is disabled, this simply renders the elements.
+    pub fn render_memoized(&mut self, elements: &[Element]) {
+        let hash = match self.memo {
+            Some(_) => {
+                let mut hasher = DefaultHasher::new();
+                elements.hash(&mut hasher);
+                hasher.finish()
+            }
+            None => {
+                elements.render(self);
+                return;
+            }
+        };
+
+        // Reuse the output if this subtree was already rendered.
+        // The elements are compared in case of a hash collision.
+        let entry = self
+            .memo
+            .as_ref()
+            .and_then(|memo| memo.get(&hash))
+            .and_then(|entries| {
+                entries
+                    .iter()
+                    .find(|entry| entry.elements.as_slice() == elements)
+            });
+
+        if let Some(entry) = entry {
+            self.html.push_str(&entry.html);
+            self.warnings.extend(entry.warnings.iter().cloned());
+
+            if let (Some(collector), Some(collected)) =
+                (&mut self.collector, &entry.collected)
+            {
+                collector.extend(collected);
+            }
+
+            return;
+        }
+
+        // Otherwise, render it and save the output
+        let html_start = self.html.len();
+        let warnings_start = self.warnings.len();
+        let id_counter = self.id_counter;
+        let heading_counter = self.heading_counter;
+        let footnote_counter = self.footnote_counter;
+        let footnotes_listed = self.footnotes_listed;
+        let checkpoint = self.collector.as_ref().map(Collector::checkpoint);
+
+        elements.render(self);
+
+        // Subtrees which generated IDs can't be reused, since they must be unique
+        if self.id_counter != id_counter
+            || self.heading_counter != heading_counter
+            || self.footnote_counter != footnote_counter
+        {
+            return;
+        }
+
+        // Nor can those which listed the footnotes, which happens only once
+        if self.footnotes_listed != footnotes_listed {
+            return;
+        }
+
+        // Nor can those whose text was not collected
+        let collected = match (&self.collector, checkpoint) {
+            (Some(collector), _) if collector.suppressed() => return,
+            (Some(collector), Some(checkpoint)) => Some(collector.since(checkpoint)),
+            _ => None,
+        };
+
+        let entry = MemoEntry {
+            elements: elements.iter().map(Element::to_owned).collect(),
+            html: str!(self.html[html_start..]),
+            warnings: self.warnings[warnings_start..].to_vec(),
+            collected,
+        };
+
+        if let Some(ref mut memo) = self.memo {
+            memo.entry(hash).or_insert_with(Vec::new).push(entry);
+        }
+    }
+
+    // Collection
+
+    /// Gathers the page's text and metadata while rendering.
+    ///
+    /// See `MultiRender`.
+    #[inline]
+    pub fn enable_collection(&mut self) {
+        self.collector = Some(Collector::default());
+    }
+
+    #[inline]
+    pub(crate) fn take_collector(&mut self) -> Option<Collector> {
+        self.collector.take()
+    }
+
+    #[inline]
+    pub fn collect_enter(&mut self, element: &Element) {
+        if let Some(ref mut collector) = self.collector {
+            collector.enter_element(element);
+        }
+    }
+
+    #[inline]
+    pub fn collect_leave(&mut self, element: &Element) {
+        if let Some(ref mut collector) = self.collector {
+            collector.leave_element(element);
+        }
+    }
+
+    #[inline]
+    pub fn collect_footnote(&mut self, elements: &[Element]) {
+        if let Some(ref mut collector) = self.collector {
+            collector.add_footnote(elements);
+        }
+    }
+
+    #[inline]
+    pub fn collect_table_cell(&mut self, index: usize) {
+        if let Some(ref mut collector) = self.collector {
+            collector.enter_table_cell(index);
+        }
+    }
+
+    #[inline]
+    pub fn collect_table_row_end(&mut self) {
+        if let Some(ref mut collector) = self.collector {
+            collector.leave_table_row();
+        }
+    }
+
+    #[inline]
+    pub fn collect_list_item_end(&mut self) {
+        if let Some(ref mut collector) = self.collector {
+            collector.leave_list_item();
+        }
+    }
+
+    #[inline]
+    pub fn collect_definition_key_end(&mut self) {
+        if let Some(ref mut collector) = self.collector {
+            collector.leave_definition_key();
+        }
+    }
+
+    #[inline]
+    pub fn collect_tab_label_end(&mut self) {
+        if let Some(ref mut collector) = self.collector {
+            collector.leave_tab_label();
+        }
+    }
+
+    /// Renders something which is not part of the page's text or metadata.
+    pub fn uncollected<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        if let Some(ref mut collector) = self.collector {
+            collector.suppress();
+        }
+
+        f(self);
+
+        if let Some(ref mut collector) = self.collector {
+            collector.unsuppress();
+        }
+    }
+
     // Buffer management
     #[inline]
     pub fn buffer(&mut self) -> &mut String {
@@ -107,6 +590,11 @@ impl<'i, 'h> HtmlContext<'i, 'h> {
         self.style.push_str(style);
     }
 
+    #[inline]
+    pub fn add_warning(&mut self, kind: RenderWarningKind, value: &str) {
+        self.warnings.push(RenderWarning::new(kind, value));
+    }
+
     #[inline]
     pub fn push_raw(&mut self, ch: char) {
         self.buffer().push(ch);
@@ -131,10 +619,19 @@ impl<'i, 'h> HtmlContext<'i, 'h> {
 impl<'i, 'h> From<HtmlContext<'i, 'h>> for HtmlOutput {
     fn from(context: HtmlContext<'i, 'h>) -> HtmlOutput {
         let HtmlContext {
-            html, style, meta, ..
+            html,
+            style,
+            meta,
+            warnings,
+            ..
         } = context;
 
-        HtmlOutput { html, style, meta }
+        HtmlOutput {
+            html,
+            style,
+            meta,
+            warnings,
+        }
     }
 }
 
@@ -0,0 +1,81 @@
+/*
+ * render/html/element/bibliography.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::super::render::ElementRender;
+use super::prelude::*;
+use crate::tree::BibliographyEntry;
+
+/// The title of a bibliography which does not specify one.
+const DEFAULT_TITLE: &str = "Bibliography";
+
+pub fn render_bibliography_cite(ctx: &mut HtmlContext, number: Option<usize>) {
+    match number {
+        Some(number) => {
+            let label = format!("[{}]", number);
+            let number = number.to_string();
+
+            ctx.html()
+                .a()
+                .attr("class", &["bibcite"])
+                .attr("href", &["#bibitem-", &number])
+                .inner(&label.as_str());
+        }
+        None => {
+            ctx.html().span().attr("class", &["bibcite"]).inner(&"[??]");
+        }
+    }
+}
+
+pub fn render_bibliography(
+    ctx: &mut HtmlContext,
+    title: Option<&str>,
+    hide: bool,
+    entries: &[BibliographyEntry],
+) {
+    if hide {
+        return;
+    }
+
+    ctx.html()
+        .div()
+        .attr("class", &["bibitems"])
+        .contents(|ctx| {
+            ctx.html()
+                .div()
+                .attr("class", &["title"])
+                .inner(&title.unwrap_or(DEFAULT_TITLE));
+
+            for entry in entries {
+                let number = entry.number().to_string();
+
+                ctx.html()
+                    .div()
+                    .attr("class", &["bibitem"])
+                    .attr("id", &["bibitem-", &number])
+                    .contents(|ctx| {
+                        ctx.push_escaped(&number);
+                        ctx.push_raw_str(". ");
+                        entry.elements().render(ctx);
+                    });
+
+                ctx.collect_list_item_end();
+            }
+        });
+}
@@ -0,0 +1,73 @@
+/*
+ * render/html/element/blockquote.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::super::render::ElementRender;
+use super::prelude::*;
+
+#[derive(Debug, Copy, Clone)]
+pub struct BlockQuote<'a> {
+    pub elements: &'a [Element<'a>],
+    pub id: Option<&'a str>,
+    pub class: Option<&'a str>,
+    pub style: Option<&'a str>,
+    pub cite: Option<&'a str>,
+    pub source: Option<&'a [Element<'a>]>,
+}
+
+pub fn render_blockquote(ctx: &mut HtmlContext, blockquote: BlockQuote) {
+    let BlockQuote {
+        elements,
+        id,
+        class,
+        style,
+        cite,
+        source,
+    } = blockquote;
+
+    let mut tag = ctx.html().tag("blockquote");
+
+    if let Some(cite) = cite {
+        tag.attr("cite", &[cite]);
+    }
+
+    if let Some(id) = id {
+        tag.attr("id", &[id]);
+    }
+
+    if let Some(class) = class {
+        tag.attr("class", &[class]);
+    }
+
+    if let Some(style) = style {
+        tag.attr("style", &[style]);
+    }
+
+    tag.contents(|ctx| {
+        elements.render(ctx);
+
+        // Attribution, beneath the quotation
+        if let Some(source) = source {
+            ctx.html()
+                .tag("footer")
+                .attr("class", &["blockquote-source"])
+                .inner(&source);
+        }
+    });
+}
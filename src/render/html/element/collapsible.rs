@@ -0,0 +1,141 @@
+/*
+ * render/html/element/collapsible.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+
+const DEFAULT_SHOW_TEXT: &str = "+ show block";
+const DEFAULT_HIDE_TEXT: &str = "- hide block";
+
+#[derive(Debug, Copy, Clone)]
+pub struct Collapsible<'a> {
+    pub elements: &'a [Element<'a>],
+    pub id: Option<&'a str>,
+    pub class: Option<&'a str>,
+    pub style: Option<&'a str>,
+    pub start_open: bool,
+    pub show_text: Option<&'a [Element<'a>]>,
+    pub hide_text: Option<&'a [Element<'a>]>,
+    pub show_top: bool,
+    pub show_bottom: bool,
+}
+
+pub fn render_collapsible(ctx: &mut HtmlContext, collapsible: Collapsible) {
+    let Collapsible {
+        elements,
+        id,
+        class,
+        style,
+        start_open,
+        show_text,
+        hide_text,
+        show_top,
+        show_bottom,
+    } = collapsible;
+
+    let mut tag = ctx.html().div();
+    tag.attr("class", &["collapsible-block"]);
+
+    if let Some(id) = id {
+        tag.attr("id", &[id]);
+    }
+
+    if let Some(style) = style {
+        tag.attr("style", &[style]);
+    }
+
+    tag.contents(|ctx| {
+        // Folded view, with link to open
+        let mut folded = ctx.html().div();
+        folded.attr("class", &["collapsible-block-folded"]);
+
+        if start_open {
+            folded.attr("style", &["display: none;"]);
+        }
+
+        folded.contents(|ctx| render_link(ctx, show_text, DEFAULT_SHOW_TEXT, false));
+
+        // Drop to close the tag before starting the next one
+        drop(folded);
+
+        // Unfolded view, with the contents and links to close
+        let mut unfolded = ctx.html().div();
+        unfolded.attr("class", &["collapsible-block-unfolded"]);
+
+        if !start_open {
+            unfolded.attr("style", &["display: none;"]);
+        }
+
+        unfolded.contents(|ctx| {
+            if show_top {
+                render_unfolded_link(ctx, hide_text);
+            }
+
+            let mut content = ctx.html().div();
+
+            match class {
+                Some(class) => {
+                    content.attr("class", &["collapsible-block-content ", class])
+                }
+                None => content.attr("class", &["collapsible-block-content"]),
+            };
+
+            content.inner(&elements);
+            drop(content);
+
+            if show_bottom {
+                render_unfolded_link(ctx, hide_text);
+            }
+        });
+    });
+}
+
+fn render_unfolded_link(ctx: &mut HtmlContext, text: Option<&[Element]>) {
+    ctx.html()
+        .div()
+        .attr("class", &["collapsible-block-unfolded-link"])
+        .contents(|ctx| render_link(ctx, text, DEFAULT_HIDE_TEXT, true));
+}
+
+/// Renders the link which opens or closes the collapsible.
+///
+/// The `expanded` flag is whether the collapsible is open while this link is shown.
+fn render_link(
+    ctx: &mut HtmlContext,
+    text: Option<&[Element]>,
+    default: &str,
+    expanded: bool,
+) {
+    ctx.uncollected(|ctx| {
+        let accessibility = ctx.accessibility();
+        let mut tag = ctx.html().a();
+        tag.attr("class", &["collapsible-block-link"])
+            .attr("href", &["javascript:;"]);
+
+        if accessibility {
+            tag.attr("role", &["button"])
+                .attr("aria-expanded", &[if expanded { "true" } else { "false" }]);
+        }
+
+        match text {
+            Some(elements) => tag.inner(&elements),
+            None => tag.inner(&default),
+        };
+    });
+}
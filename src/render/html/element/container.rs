@@ -0,0 +1,100 @@
+/*
+ * render/html/element/container.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::super::render::ElementRender;
+use super::prelude::*;
+use crate::enums::{ClearFloat, FloatAlignment};
+use crate::tree::{Container, StyledContainer};
+
+pub fn render_container(ctx: &mut HtmlContext, container: &Container) {
+    // Headings have IDs so the table of contents can link to them
+    let (id, number) = match container.ctype().heading_level() {
+        Some(_) => ctx.next_heading(),
+        None => (None, None),
+    };
+
+    let mut tag = ctx.html().tag(container.ctype().html_tag());
+
+    if let Some(id) = id {
+        tag.attr("id", &[&id]);
+    }
+
+    tag.contents(|ctx| {
+        if let Some(ref number) = number {
+            render_heading_number(ctx, number);
+        }
+
+        container.elements().render(ctx);
+    });
+}
+
+pub fn render_styled_container(ctx: &mut HtmlContext, container: &StyledContainer) {
+    let mut tag = ctx.html().tag(container.ctype().html_tag());
+
+    if let Some(id) = container.id() {
+        tag.attr("id", &[id]);
+    }
+
+    if let Some(class) = container.class() {
+        tag.attr("class", &[class]);
+    }
+
+    if let Some(style) =
+        build_style(container.float(), container.clear(), container.style())
+    {
+        tag.attr("style", &[&style]);
+    }
+
+    tag.inner(&container.elements());
+}
+
+/// Combines the validated layout properties with any raw styling.
+pub fn build_style(
+    float: Option<FloatAlignment>,
+    clear: Option<ClearFloat>,
+    style: Option<&str>,
+) -> Option<String> {
+    let parts = [
+        float.map(FloatAlignment::html_style),
+        clear.map(ClearFloat::html_style),
+        style,
+    ];
+
+    let parts: Vec<&str> = parts.iter().flatten().copied().collect();
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" "))
+    }
+}
+
+/// Renders a heading's section number, which precedes its contents.
+///
+/// This is not part of the page's text, so it isn't collected.
+pub fn render_heading_number(ctx: &mut HtmlContext, number: &str) {
+    ctx.uncollected(|ctx| {
+        ctx.html()
+            .span()
+            .attr("class", &["heading-number"])
+            .inner(&number);
+
+        ctx.push_escaped(" ");
+    });
+}
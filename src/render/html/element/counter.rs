@@ -0,0 +1,56 @@
+/*
+ * render/html/element/counter.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::render::RenderWarningKind;
+use crate::tree::Counter;
+
+pub fn render_counter(ctx: &mut HtmlContext, counter: &Counter) {
+    let value = counter.value().to_string();
+    let mut tag = ctx.html().span();
+    tag.attr("class", &["counter"]);
+
+    if let Some(id) = counter.id() {
+        tag.attr("id", &["counter-", id]);
+    }
+
+    tag.inner(&value.as_str());
+}
+
+pub fn render_counter_ref(ctx: &mut HtmlContext, id: &str, value: Option<i64>) {
+    match value {
+        Some(value) => {
+            let value = value.to_string();
+
+            ctx.html()
+                .a()
+                .attr("class", &["counter-ref"])
+                .attr("href", &["#counter-", id])
+                .inner(&value.as_str());
+        }
+        None => {
+            ctx.add_warning(RenderWarningKind::UnknownCounter, id);
+            ctx.html()
+                .span()
+                .attr("class", &["counter-ref"])
+                .inner(&"??");
+        }
+    }
+}
@@ -0,0 +1,135 @@
+/*
+ * render/html/element/footnote.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::super::render::ElementRender;
+use super::prelude::*;
+
+/// The title of a footnote block which does not specify one.
+const DEFAULT_TITLE: &str = "Footnotes";
+
+pub fn render_footnote(ctx: &mut HtmlContext, elements: &[Element]) {
+    // The contents are rendered in the footnote block,
+    // but their text is part of the page here.
+    ctx.collect_footnote(elements);
+
+    let (number, id) = match ctx.next_footnote() {
+        Some(footnote) => footnote,
+        None => return,
+    };
+
+    let number = number.to_string();
+    let accessibility = ctx.accessibility();
+
+    ctx.html()
+        .sup()
+        .attr("class", &["footnoteref"])
+        .contents(|ctx| {
+            let mut tag = ctx.html().a();
+            tag.attr("id", &[&id])
+                .attr("class", &["footnoteref"])
+                .attr("href", &["#footnote-", &number]);
+
+            // The footnote's contents describe the reference
+            if accessibility {
+                tag.attr("role", &["doc-noteref"])
+                    .attr("aria-describedby", &["footnote-", &number]);
+            }
+
+            tag.inner(&number.as_str());
+        });
+}
+
+pub fn render_footnote_block(ctx: &mut HtmlContext, title: Option<&str>, hide: bool) {
+    if ctx.list_footnotes() || hide || ctx.footnotes().is_empty() {
+        return;
+    }
+
+    let footnotes: Vec<_> = ctx
+        .footnotes()
+        .iter()
+        .enumerate()
+        .map(|(index, elements)| {
+            let number = index + 1;
+
+            (
+                number.to_string(),
+                ctx.footnote_ref_ids(number),
+                elements.clone(),
+            )
+        })
+        .collect();
+
+    let accessibility = ctx.accessibility();
+    let mut tag = ctx.html().div();
+    tag.attr("class", &["footnotes-footer"]);
+
+    if accessibility {
+        tag.attr("role", &["doc-endnotes"]);
+    }
+
+    // The footnotes' text was collected where they are referenced
+    tag.contents(|ctx| {
+        ctx.uncollected(|ctx| {
+            ctx.html()
+                .div()
+                .attr("class", &["title"])
+                .inner(&title.unwrap_or(DEFAULT_TITLE));
+
+            for (number, ref_ids, elements) in &footnotes {
+                ctx.html()
+                    .div()
+                    .attr("class", &["footnote-footer"])
+                    .attr("id", &["footnote-", number])
+                    .contents(|ctx| {
+                        // Links back to the first reference, then any others after the contents
+                        let mut ref_ids = ref_ids.iter();
+
+                        if let Some(id) = ref_ids.next() {
+                            let mut tag = ctx.html().a();
+                            tag.attr("href", &["#", id]);
+
+                            if accessibility {
+                                tag.attr("role", &["doc-backlink"]);
+                            }
+
+                            tag.inner(&number.as_str());
+                        }
+
+                        ctx.push_raw_str(". ");
+                        elements.as_slice().render(ctx);
+
+                        for id in ref_ids {
+                            ctx.push_raw(' ');
+
+                            let mut tag = ctx.html().a();
+                            tag.attr("class", &["footnote-backref"])
+                                .attr("href", &["#", id]);
+
+                            if accessibility {
+                                tag.attr("role", &["doc-backlink"]);
+                            }
+
+                            tag.inner(&"↑");
+                        }
+                    });
+            }
+        });
+    });
+}
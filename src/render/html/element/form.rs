@@ -0,0 +1,200 @@
+/*
+ * render/html/element/form.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::parsing::parse_boolean;
+use crate::tree::{FormField, FormFieldType, FormRule};
+
+/// The label of a form's submit button which does not specify one.
+const DEFAULT_BUTTON_TEXT: &str = "Send";
+
+#[derive(Debug, Copy, Clone)]
+pub struct Form<'a> {
+    pub title: Option<&'a str>,
+    pub button_text: Option<&'a str>,
+    pub fields: &'a [FormField<'a>],
+
+    /// Where submissions are posted, if the host handles them.
+    pub action: Option<&'a str>,
+}
+
+pub fn render_form(ctx: &mut HtmlContext, form: Form) {
+    let Form {
+        title,
+        button_text,
+        fields,
+        action,
+    } = form;
+
+    let mut tag = ctx.html().tag("form");
+    tag.attr("class", &["mailform"]);
+
+    // Without a handler, the form is inert, and only describes its fields
+    match action {
+        Some(action) => {
+            tag.attr("method", &["post"]).attr("action", &[action]);
+        }
+        None => {
+            tag.attr("data-inert", &["true"]);
+        }
+    }
+
+    tag.contents(|ctx| {
+        if let Some(title) = title {
+            ctx.html()
+                .div()
+                .attr("class", &["mailform-title"])
+                .inner(&title);
+        }
+
+        for field in fields {
+            render_field(ctx, field);
+        }
+
+        let mut tag = ctx.html().tag("button");
+        tag.attr("type", &["submit"]);
+
+        if action.is_none() {
+            tag.attr("disabled", &[]);
+        }
+
+        tag.inner(&button_text.unwrap_or(DEFAULT_BUTTON_TEXT));
+    });
+}
+
+fn render_field(ctx: &mut HtmlContext, field: &FormField) {
+    let id = ctx.generate_id("mailform");
+    let mut tag = ctx.html().div();
+    tag.attr("class", &["mailform-field"])
+        .attr("data-name", &[&field.name])
+        .attr("data-type", &[field_type_name(field.field_type)]);
+
+    for rule in &field.rules {
+        let (key, value) = rule_attribute(rule);
+        tag.attr(key, &[&value]);
+    }
+
+    tag.contents(|ctx| {
+        ctx.html()
+            .tag("label")
+            .attr("for", &[&id])
+            .inner(&field.label());
+
+        render_input(ctx, field, &id);
+
+        if let Some(hint) = field.hint.as_deref() {
+            ctx.html()
+                .div()
+                .attr("class", &["mailform-hint"])
+                .inner(&hint);
+        }
+    });
+}
+
+fn render_input(ctx: &mut HtmlContext, field: &FormField, id: &str) {
+    let default = field.default.as_deref();
+
+    let mut tag = match field.field_type {
+        FormFieldType::Text => {
+            let is_email = field.rules.contains(&FormRule::Email);
+            let mut tag = ctx.html().tag("input");
+            tag.attr("type", &[if is_email { "email" } else { "text" }]);
+
+            if let Some(default) = default {
+                tag.attr("value", &[default]);
+            }
+
+            tag
+        }
+        FormFieldType::Checkbox => {
+            let mut tag = ctx.html().tag("input");
+            tag.attr("type", &["checkbox"]);
+
+            if default.and_then(parse_boolean) == Some(true) {
+                tag.attr("checked", &[]);
+            }
+
+            tag
+        }
+        FormFieldType::TextArea => ctx.html().tag("textarea"),
+        FormFieldType::Select => ctx.html().tag("select"),
+    };
+
+    tag.attr("id", &[id]).attr("name", &[&field.name]);
+
+    // Checks the browser can perform itself
+    for rule in &field.rules {
+        match rule {
+            FormRule::Required => {
+                tag.attr("required", &[]);
+            }
+            FormRule::MinLength(length) => {
+                tag.attr("minlength", &[&length.to_string()]);
+            }
+            FormRule::MaxLength(length) => {
+                tag.attr("maxlength", &[&length.to_string()]);
+            }
+            _ => (),
+        }
+    }
+
+    match field.field_type {
+        FormFieldType::TextArea => {
+            tag.inner(&default.unwrap_or(""));
+        }
+        FormFieldType::Select => {
+            tag.contents(|ctx| {
+                for (value, label) in &field.options {
+                    let (value, label): (&str, &str) = (value, label);
+                    let mut tag = ctx.html().tag("option");
+                    tag.attr("value", &[value]);
+
+                    if default == Some(value) {
+                        tag.attr("selected", &[]);
+                    }
+
+                    tag.inner(&label);
+                }
+            });
+        }
+        FormFieldType::Text | FormFieldType::Checkbox => (),
+    }
+}
+
+fn field_type_name(field_type: FormFieldType) -> &'static str {
+    match field_type {
+        FormFieldType::Text => "text",
+        FormFieldType::TextArea => "textarea",
+        FormFieldType::Select => "select",
+        FormFieldType::Checkbox => "checkbox",
+    }
+}
+
+/// The data attribute describing a rule, for scripts which validate the form.
+fn rule_attribute(rule: &FormRule) -> (&'static str, String) {
+    match rule {
+        FormRule::Required => ("data-rule-required", str!("true")),
+        FormRule::MinLength(length) => ("data-rule-min-length", length.to_string()),
+        FormRule::MaxLength(length) => ("data-rule-max-length", length.to_string()),
+        FormRule::Email => ("data-rule-email", str!("true")),
+        FormRule::Number => ("data-rule-number", str!("true")),
+        FormRule::Match(pattern) => ("data-rule-match", str!(pattern)),
+    }
+}
@@ -0,0 +1,151 @@
+/*
+ * render/html/element/iframe.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::render::RenderWarningKind;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Iframe<'a> {
+    pub url: &'a str,
+    pub width: Option<&'a str>,
+    pub height: Option<&'a str>,
+    pub class: Option<&'a str>,
+    pub style: Option<&'a str>,
+}
+
+pub fn render_iframe(ctx: &mut HtmlContext, iframe: Iframe) {
+    let Iframe {
+        url,
+        width,
+        height,
+        class,
+        style,
+    } = iframe;
+
+    let allowed = match url_domain(url) {
+        Some(domain) => ctx.site().is_embed_allowed(domain),
+        None => false,
+    };
+
+    // Replace embeds the site doesn't permit with a notice
+    if !allowed {
+        ctx.add_warning(RenderWarningKind::BlockedEmbed, url);
+        ctx.html()
+            .span()
+            .attr("class", &["error-inline"])
+            .contents(|ctx| {
+                ctx.push_escaped("Embedding this URL is not permitted: ");
+                ctx.push_escaped(url);
+            });
+
+        return;
+    }
+
+    let mut tag = ctx.html().iframe();
+    tag.attr("src", &[url]);
+
+    if let Some(width) = width {
+        tag.attr("width", &[width]);
+    }
+
+    if let Some(height) = height {
+        tag.attr("height", &[height]);
+    }
+
+    if let Some(class) = class {
+        tag.attr("class", &[class]);
+    }
+
+    if let Some(style) = style {
+        tag.attr("style", &[style]);
+    }
+}
+
+/// Gets the domain of an absolute HTTP or HTTPS URL.
+///
+/// Any other URL, such as a relative path or a `javascript:` link,
+/// has no domain and so can never be embedded.
+fn url_domain(url: &str) -> Option<&str> {
+    let rest = ["https://", "http://", "//"]
+        .iter()
+        .find_map(|prefix| strip_prefix_ignore_case(url, prefix))?;
+
+    // Browsers treat '\' as '/' in HTTP URLs, so it also ends the authority.
+    // Otherwise "https://evil.example\@www.youtube.com/" would appear to be
+    // on YouTube, while actually loading from evil.example.
+    let authority = rest.split(&['/', '\\', '?', '#'][..]).next()?;
+
+    // Browsers strip or reject these, so the host can't be known for certain
+    if authority
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control())
+    {
+        return None;
+    }
+
+    // Remove any userinfo and port
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+
+    if host.is_empty() {
+        None
+    } else {
+        Some(host)
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    if value.len() >= prefix.len()
+        && value.is_char_boundary(prefix.len())
+        && value[..prefix.len()].eq_ignore_ascii_case(prefix)
+    {
+        Some(&value[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+#[test]
+fn domain() {
+    macro_rules! test {
+        ($url:expr, $expected:expr $(,)?) => {{
+            let expected: Option<&str> = $expected;
+
+            assert_eq!(
+                url_domain($url),
+                expected,
+                "Extracted domain doesn't match expected",
+            );
+        }};
+    }
+
+    test!("https://www.youtube.com/embed/abc", Some("www.youtube.com"));
+    test!("HTTP://example.com:8080?a=b", Some("example.com"));
+    test!("//user@example.org#top", Some("example.org"));
+    test!("javascript:alert(1)", None);
+    test!("/local/page", None);
+    test!("https:///path", None);
+    test!(
+        "https://evil.example\\@www.youtube.com/",
+        Some("evil.example"),
+    );
+    test!("https://evil.example\t@www.youtube.com/", None);
+    test!("https://evil.example\n.www.youtube.com/", None);
+}
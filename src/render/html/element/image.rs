@@ -0,0 +1,135 @@
+/*
+ * render/html/element/image.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::container::build_style;
+use super::link::link_href;
+use super::prelude::*;
+use crate::render::RenderWarningKind;
+use crate::tree::{Image, ImageSource};
+
+pub fn render_image(ctx: &mut HtmlContext, image: &Image) {
+    let url = match get_image_url(ctx, &image.source) {
+        Some(url) => url,
+        None => return,
+    };
+
+    // Aligned images are wrapped in a container, as in Wikidot
+    match image.alignment {
+        Some(alignment) => {
+            ctx.html()
+                .div()
+                .attr("class", &["image-container ", alignment.html_class()])
+                .contents(|ctx| render_figure(ctx, &url, image));
+        }
+        None => render_figure(ctx, &url, image),
+    }
+}
+
+/// Gets where the image is, from the host if it knows.
+///
+/// Returns `None` if the image cannot be found, in which case it isn't rendered.
+fn get_image_url(ctx: &mut HtmlContext, source: &ImageSource) -> Option<String> {
+    match ctx.handle().get_image_url(source, ctx.info()) {
+        Ok(Some(url)) => Some(url),
+        Ok(None) => match source.default_url(&ctx.info().slug) {
+            Some(url) => Some(url.into_owned()),
+            None => {
+                ctx.add_warning(RenderWarningKind::MissingImage, &source.to_string());
+                None
+            }
+        },
+        Err(error) => {
+            let value = format!("{}: {}", source, error);
+            ctx.add_warning(RenderWarningKind::MissingImage, &value);
+            None
+        }
+    }
+}
+
+fn render_figure(ctx: &mut HtmlContext, url: &str, image: &Image) {
+    let style = build_style(image.float, image.clear, image.style.as_deref());
+
+    match image.caption.as_deref() {
+        Some(caption) => {
+            let mut figure = ctx.html().figure();
+
+            if let Some(style) = &style {
+                figure.attr("style", &[style]);
+            }
+
+            figure.contents(|ctx| {
+                render_image_link(ctx, url, image, None);
+                ctx.html().figcaption().inner(&caption);
+            });
+        }
+        None => render_image_link(ctx, url, image, style.as_deref()),
+    }
+}
+
+fn render_image_link(
+    ctx: &mut HtmlContext,
+    url: &str,
+    image: &Image,
+    style: Option<&str>,
+) {
+    match image.link.as_deref().and_then(|link| link_href(ctx, link)) {
+        Some(link) => {
+            ctx.html()
+                .a()
+                .attr("href", &[&*link])
+                .contents(|ctx| render_image_tag(ctx, url, image, style));
+        }
+        None => render_image_tag(ctx, url, image, style),
+    }
+}
+
+fn render_image_tag(
+    ctx: &mut HtmlContext,
+    url: &str,
+    image: &Image,
+    style: Option<&str>,
+) {
+    // Like Wikidot, use the file name as the alt text by default
+    let alt = image
+        .alt
+        .as_deref()
+        .or_else(|| image.source.file_name())
+        .unwrap_or_default();
+
+    let mut tag = ctx.html().img();
+    tag.attr("src", &[url]).attr("alt", &[alt]);
+
+    match image.class.as_deref() {
+        Some(class) => tag.attr("class", &["image ", class]),
+        None => tag.attr("class", &["image"]),
+    };
+
+    if let Some(width) = &image.width {
+        tag.attr("width", &[width]);
+    }
+
+    if let Some(height) = &image.height {
+        tag.attr("height", &[height]);
+    }
+
+    if let Some(style) = style {
+        tag.attr("style", &[style]);
+    }
+}
@@ -0,0 +1,223 @@
+/*
+ * render/html/element/link.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::enums::{AnchorTarget, LinkLabel};
+use crate::includes::PageRef;
+use crate::render::html::PageMeta;
+use crate::render::RenderWarningKind;
+use std::borrow::Cow;
+
+/// URL schemes which links on untrusted pages may use.
+const SAFE_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+pub fn render_email(ctx: &mut HtmlContext, email: &str) {
+    ctx.html()
+        .a()
+        .attr("href", &["mailto:", email])
+        .inner(&email);
+}
+
+pub fn render_link(
+    ctx: &mut HtmlContext,
+    url: &str,
+    label: &LinkLabel,
+    anchor: AnchorTarget,
+) {
+    let href = link_href(ctx, url);
+    let meta = get_page_meta(ctx, url);
+
+    let mut tag = ctx.html().a();
+
+    if let Some(href) = href.as_deref() {
+        tag.attr("href", &[href]);
+    }
+
+    if let Some(target) = anchor.html_attr_needed() {
+        tag.attr("target", &[target]);
+    }
+
+    if let Some(rel) = anchor.default_rel() {
+        tag.attr("rel", &[rel]);
+    }
+
+    // Links to pages which don't exist are styled differently
+    if let Some(PageMeta { exists: false, .. }) = meta {
+        tag.attr("class", &["newpage"]);
+    }
+
+    // If the page title isn't available, fall back to the URL
+    let title = meta.as_ref().and_then(|meta| meta.title.as_ref());
+
+    match (label, title) {
+        (LinkLabel::Text(text), _) => tag.inner(&text.as_ref()),
+        (LinkLabel::Page, Some(title)) => tag.inner(&title.as_str()),
+        (LinkLabel::Url, _) | (LinkLabel::Page, None) => tag.inner(&url),
+        (LinkLabel::Elements(elements), _) => tag.inner(&elements.as_slice()),
+    };
+}
+
+/// Gets the URL a link should point to.
+///
+/// On untrusted pages, only links to web pages, email addresses, pages on the
+/// site, and anchors are kept, so a link can't run script through a URL such as
+/// `javascript:` or `data:`. Other links are left without a target, and a
+/// warning is recorded.
+pub fn link_href<'a>(ctx: &mut HtmlContext, url: &'a str) -> Option<Cow<'a, str>> {
+    if !ctx.safe_mode() && !ctx.parse_settings().sanitize_arguments {
+        return Some(Cow::Borrowed(url));
+    }
+
+    match url_scheme(url) {
+        None => Some(Cow::Borrowed(url)),
+        Some(scheme)
+            if SAFE_SCHEMES
+                .iter()
+                .any(|safe| safe.eq_ignore_ascii_case(&scheme)) =>
+        {
+            Some(Cow::Borrowed(url))
+        }
+
+        // Pages in a category, such as "fruit:apple", look like they have
+        // a scheme, so the link is made relative to the site instead
+        Some(_) if is_page_name(url) => Some(Cow::Owned(format!("/{}", url))),
+        Some(_) => {
+            ctx.add_warning(RenderWarningKind::BlockedLink, url);
+            None
+        }
+    }
+}
+
+/// Gets the scheme of the URL, such as `https`, if it has one.
+///
+/// Like browsers, this skips over whitespace and control characters,
+/// so the scheme of `java\tscript:` is `javascript`.
+fn url_scheme(url: &str) -> Option<String> {
+    let mut scheme = String::new();
+
+    for ch in url.chars() {
+        match ch {
+            ':' if scheme.is_empty() => return None,
+            ':' => return Some(scheme),
+            '/' | '?' | '#' => return None,
+            _ if ch.is_whitespace() || ch.is_control() => (),
+            _ => scheme.push(ch),
+        }
+    }
+
+    None
+}
+
+/// Whether this is a plain page name, such as `fruit:apple`, which is safe to link to.
+pub fn is_page_name(fullname: &str) -> bool {
+    !fullname.is_empty()
+        && fullname
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | ':'))
+}
+
+/// Looks up information on the page a link points to, if it points to one.
+fn get_page_meta(ctx: &mut HtmlContext, url: &str) -> Option<PageMeta> {
+    let page = page_ref_from_url(url)?;
+
+    match ctx.handle().get_page_meta(&page) {
+        Ok(meta) => meta,
+        Err(error) => {
+            let value = format!("{}: {}", page, error);
+            ctx.add_warning(RenderWarningKind::PageMetaFailed, &value);
+            None
+        }
+    }
+}
+
+fn page_ref_from_url(url: &str) -> Option<PageRef<'_>> {
+    const SCHEMES: [&str; 2] = ["javascript:", "mailto:"];
+
+    if url.starts_with('#')
+        || url.contains("://")
+        || SCHEMES.iter().any(|scheme| url.starts_with(scheme))
+    {
+        return None;
+    }
+
+    let url = url.trim_start_matches('/');
+    let page = match url.find('#') {
+        Some(index) => &url[..index],
+        None => url,
+    };
+
+    PageRef::parse(page)
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Anchor<'a> {
+    pub elements: &'a [Element<'a>],
+    pub url: &'a str,
+    pub anchor: AnchorTarget,
+    pub rel: Option<&'a str>,
+    pub id: Option<&'a str>,
+    pub class: Option<&'a str>,
+    pub style: Option<&'a str>,
+}
+
+pub fn render_anchor(ctx: &mut HtmlContext, anchor: Anchor) {
+    let Anchor {
+        elements,
+        url,
+        anchor,
+        rel,
+        id,
+        class,
+        style,
+    } = anchor;
+
+    let href = link_href(ctx, url);
+    let mut tag = ctx.html().a();
+
+    if let Some(href) = href.as_deref() {
+        tag.attr("href", &[href]);
+    }
+
+    if let Some(target) = anchor.html_attr_needed() {
+        tag.attr("target", &[target]);
+    }
+
+    // An empty "rel" removes the default
+    match rel.or_else(|| anchor.default_rel()) {
+        Some(rel) if !rel.is_empty() => {
+            tag.attr("rel", &[rel]);
+        }
+        _ => (),
+    }
+
+    if let Some(id) = id {
+        tag.attr("id", &[id]);
+    }
+
+    if let Some(class) = class {
+        tag.attr("class", &[class]);
+    }
+
+    if let Some(style) = style {
+        tag.attr("style", &[style]);
+    }
+
+    tag.inner(&elements);
+}
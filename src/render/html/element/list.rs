@@ -1,5 +1,5 @@
 /*
- * span_wrap.rs
+ * render/html/element/list.rs
  *
  * ftml - Library to parse Wikidot text
  * Copyright (C) 2019-2021 Wikijump Team
@@ -18,36 +18,25 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-//! Wrapper struct to allow formatting of `Range
+use super::prelude::*;
+use crate::tree::{DefinitionListItem, List};
 
-use std::ops::Range;
-
-#[derive(Debug, Clone)]
-pub struct SpanWrap(Range<usize>);
-
-impl From<Range<usize>> for SpanWrap {
-    #[inline]
-    fn from(range: Range<usize>) -> Self {
-        SpanWrap(range)
-    }
+pub fn render_list(ctx: &mut HtmlContext, list: &List) {
+    ctx.html().tag(list.style().html_tag()).contents(|ctx| {
+        for item in list.items() {
+            ctx.html().li().inner(&item.elements());
+            ctx.collect_list_item_end();
+        }
+    });
 }
 
-impl From<&'_ Range<usize>> for SpanWrap {
-    #[inline]
-    fn from(range: &'_ Range<usize>) -> Self {
-        SpanWrap(Range::clone(range))
-    }
-}
-
-impl slog::Value for SpanWrap {
-    fn serialize(
-        &self,
-        _: &slog::Record,
-        key: slog::Key,
-        serializer: &mut dyn slog::Serializer,
-    ) -> slog::Result {
-        let value = format!("{}..{}", self.0.start, self.0.end);
-
-        serializer.emit_str(key, &value)
-    }
+pub fn render_definition_list(ctx: &mut HtmlContext, items: &[DefinitionListItem]) {
+    ctx.html().tag("dl").contents(|ctx| {
+        for item in items {
+            ctx.html().tag("dt").inner(&item.key());
+            ctx.collect_definition_key_end();
+            ctx.html().tag("dd").inner(&item.value());
+            ctx.collect_list_item_end();
+        }
+    });
 }
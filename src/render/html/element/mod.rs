@@ -0,0 +1,231 @@
+/*
+ * render/html/element/mod.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Module that implements HTML rendering for `Element` and its children.
+
+mod bibliography;
+mod blockquote;
+mod collapsible;
+mod container;
+mod counter;
+mod footnote;
+mod form;
+mod iframe;
+mod image;
+mod link;
+mod list;
+mod module;
+mod tab;
+mod table;
+mod text;
+mod toc;
+
+mod prelude {
+    pub use super::super::context::HtmlContext;
+    pub use crate::tree::Element;
+}
+
+use self::bibliography::{render_bibliography, render_bibliography_cite};
+use self::blockquote::{render_blockquote, BlockQuote};
+use self::collapsible::{render_collapsible, Collapsible};
+use self::container::{render_container, render_styled_container};
+use self::counter::{render_counter, render_counter_ref};
+use self::footnote::{render_footnote, render_footnote_block};
+use self::iframe::{render_iframe, Iframe};
+use self::image::render_image;
+use self::link::{render_anchor, render_email, render_link, Anchor};
+use self::list::{render_definition_list, render_list};
+use self::module::render_module;
+use self::tab::render_tabview;
+use self::table::render_table;
+use self::text::{
+    render_code, render_color, render_date, render_diagram, render_math, render_raw,
+};
+use self::toc::render_table_of_contents;
+use super::context::HtmlContext;
+use super::render::ElementRender;
+use crate::tree::Element;
+
+impl ElementRender for &'_ [Element<'_>] {
+    fn render(&self, ctx: &mut HtmlContext) {
+        for element in self.iter() {
+            element.render(ctx);
+        }
+    }
+}
+
+impl ElementRender for Element<'_> {
+    fn render(&self, ctx: &mut HtmlContext) {
+        ctx.collect_enter(self);
+
+        match self {
+            Element::Container(container) => render_container(ctx, container),
+            Element::StyledContainer(container) => {
+                render_styled_container(ctx, container)
+            }
+            Element::Module(module) => render_module(ctx, module),
+            Element::Include { elements, .. } => ctx.render_memoized(elements),
+            Element::Text(text) => ctx.push_escaped(text),
+            Element::Raw(text) => render_raw(ctx, text),
+            Element::Email(email) => render_email(ctx, email),
+            Element::Link { url, label, anchor } => render_link(ctx, url, label, *anchor),
+            Element::Anchor {
+                elements,
+                url,
+                anchor,
+                rel,
+                id,
+                class,
+                style,
+            } => render_anchor(
+                ctx,
+                Anchor {
+                    elements,
+                    url,
+                    anchor: *anchor,
+                    rel: rel.as_deref(),
+                    id: id.as_deref(),
+                    class: class.as_deref(),
+                    style: style.as_deref(),
+                },
+            ),
+            Element::Image(image) => render_image(ctx, image),
+            Element::Iframe {
+                url,
+                width,
+                height,
+                class,
+                style,
+            } => render_iframe(
+                ctx,
+                Iframe {
+                    url,
+                    width: width.as_deref(),
+                    height: height.as_deref(),
+                    class: class.as_deref(),
+                    style: style.as_deref(),
+                },
+            ),
+            Element::Collapsible {
+                elements,
+                id,
+                class,
+                style,
+                start_open,
+                show_text,
+                hide_text,
+                show_top,
+                show_bottom,
+            } => render_collapsible(
+                ctx,
+                Collapsible {
+                    elements,
+                    id: id.as_deref(),
+                    class: class.as_deref(),
+                    style: style.as_deref(),
+                    start_open: *start_open,
+                    show_text: show_text.as_deref(),
+                    hide_text: hide_text.as_deref(),
+                    show_top: *show_top,
+                    show_bottom: *show_bottom,
+                },
+            ),
+            Element::BlockQuote {
+                elements,
+                id,
+                class,
+                style,
+                cite,
+                source,
+            } => render_blockquote(
+                ctx,
+                BlockQuote {
+                    elements,
+                    id: id.as_deref(),
+                    class: class.as_deref(),
+                    style: style.as_deref(),
+                    cite: cite.as_deref(),
+                    source: source.as_deref(),
+                },
+            ),
+            Element::Poem { elements } => {
+                ctx.html()
+                    .p()
+                    .attr("class", &["poem"])
+                    .attr("style", &["white-space: pre-wrap;"])
+                    .inner(&elements.as_slice());
+            }
+            Element::TabView(tabs) => render_tabview(ctx, tabs),
+            Element::Table(table) => render_table(ctx, table),
+            Element::List(list) => render_list(ctx, list),
+            Element::DefinitionList(items) => render_definition_list(ctx, items),
+            Element::TableOfContents { depth, float } => {
+                render_table_of_contents(ctx, *depth, *float)
+            }
+            Element::Abbreviation { title, elements } => {
+                ctx.html()
+                    .tag("abbr")
+                    .attr("title", &[title])
+                    .inner(&elements.as_slice());
+            }
+            Element::Date { timestamp, format } => {
+                render_date(ctx, *timestamp, format.as_deref())
+            }
+            Element::Counter(counter) => render_counter(ctx, counter),
+            Element::CounterRef { id, value } => render_counter_ref(ctx, id, *value),
+            Element::Footnote { elements } => render_footnote(ctx, elements),
+            Element::FootnoteBlock { title, hide } => {
+                render_footnote_block(ctx, title.as_deref(), *hide)
+            }
+            Element::BibliographyCite { number, .. } => {
+                render_bibliography_cite(ctx, *number)
+            }
+            Element::Bibliography {
+                title,
+                hide,
+                entries,
+            } => render_bibliography(ctx, title.as_deref(), *hide, entries),
+            Element::NoTypography { elements } => elements.as_slice().render(ctx),
+            Element::Color { color, elements } => render_color(ctx, color, elements),
+            Element::Code { contents, language } => {
+                render_code(ctx, contents, language.as_deref())
+            }
+            Element::Diagram { kind, contents } => render_diagram(ctx, *kind, contents),
+            Element::Math { latex, inline } => render_math(ctx, latex, *inline),
+            Element::LineBreak => {
+                ctx.html().br();
+            }
+            Element::LineBreaks(amount) => {
+                for _ in 0..amount.get() {
+                    ctx.html().br();
+                }
+            }
+            Element::HorizontalRule => {
+                ctx.html().hr();
+            }
+            Element::Partial(_) => {
+                panic!("Encountered partial element during rendering")
+            }
+            Element::Null => (),
+        }
+
+        ctx.collect_leave(self);
+    }
+}
@@ -0,0 +1,368 @@
+/*
+ * render/html/element/module.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::form::{render_form, Form};
+use super::link::is_page_name;
+use super::prelude::*;
+use crate::parsing::ParseOptions;
+use crate::render::date::format_date;
+use crate::render::html::{FeedItem, ListedPage};
+use crate::render::RenderWarningKind;
+use crate::tree::Module;
+
+pub fn render_module(ctx: &mut HtmlContext, module: &Module) {
+    // Modules are left out of pages by untrusted authors
+    if ctx.safe_mode() {
+        return;
+    }
+
+    // Feeds are rendered here if the host provides their data
+    if let Module::Feed {
+        src,
+        limit,
+        template,
+    } = module
+    {
+        match ctx.handle().get_feed(src) {
+            Ok(Some(mut items)) => {
+                if let Some(limit) = limit {
+                    items.truncate(limit.get() as usize);
+                }
+
+                render_feed(ctx, &items, template.as_deref());
+                return;
+            }
+            Ok(None) => (),
+            Err(error) => {
+                let value = format!("{}: {}", module.name(), error);
+                ctx.add_warning(RenderWarningKind::ModuleFailed, &value);
+                return;
+            }
+        }
+    }
+
+    // Likewise for page listings
+    if let Module::ListPages { query, template } = module {
+        match ctx.handle().get_list_pages(query, ctx.info()) {
+            Ok(Some(mut pages)) => {
+                pages.truncate(query.page_size() as usize);
+                render_list_pages(ctx, &pages, template.as_deref());
+                return;
+            }
+            Ok(None) => (),
+            Err(error) => {
+                let value = format!("{}: {}", module.name(), error);
+                ctx.add_warning(RenderWarningKind::ModuleFailed, &value);
+                return;
+            }
+        }
+    }
+
+    // Forms are always rendered, but only submittable if the host handles them
+    if let Module::MailForm {
+        title,
+        button_text,
+        fields,
+        ..
+    } = module
+    {
+        let action = match ctx.handle().get_form_action(module, ctx.info()) {
+            Ok(action) => action,
+            Err(error) => {
+                let value = format!("{}: {}", module.name(), error);
+                ctx.add_warning(RenderWarningKind::ModuleFailed, &value);
+                None
+            }
+        };
+
+        render_form(
+            ctx,
+            Form {
+                title: title.as_deref(),
+                button_text: button_text.as_deref(),
+                fields,
+                action: action.as_deref(),
+            },
+        );
+        return;
+    }
+
+    // Modules require data from the host, which may not be available
+    match ctx.handle().render_module(module, ctx.info()) {
+        Ok(html) => ctx.push_raw_str(&html),
+        Err(error) => {
+            let value = format!("{}: {}", module.name(), error);
+            ctx.add_warning(RenderWarningKind::ModuleFailed, &value);
+        }
+    }
+}
+
+fn render_feed(ctx: &mut HtmlContext, items: &[FeedItem], template: Option<&str>) {
+    match template {
+        // Without a template, each item is a link to the entry
+        None => {
+            ctx.html().ul().attr("class", &["feed"]).contents(|ctx| {
+                for item in items {
+                    ctx.html()
+                        .li()
+                        .attr("class", &["feed-item"])
+                        .contents(|ctx| match web_url(&item.link) {
+                            Some(link) => {
+                                ctx.html()
+                                    .a()
+                                    .attr("href", &[link])
+                                    .inner(&item.title.as_str());
+                            }
+                            None => ctx.push_escaped(&item.title),
+                        });
+                }
+            });
+        }
+
+        // Otherwise, the template is parsed and rendered for each item
+        Some(template) => {
+            ctx.html().div().attr("class", &["feed"]).contents(|ctx| {
+                for item in items {
+                    let date = item
+                        .published
+                        .map(|timestamp| {
+                            format_date(timestamp, None, ctx.date_settings())
+                        })
+                        .unwrap_or_default();
+
+                    let text = fill_template(template, item, &date);
+                    render_template(ctx, text, "feed-item");
+                }
+            });
+        }
+    }
+}
+
+fn render_list_pages(
+    ctx: &mut HtmlContext,
+    pages: &[ListedPage],
+    template: Option<&str>,
+) {
+    match template {
+        // Without a template, each page is a link to it
+        None => {
+            ctx.html()
+                .ul()
+                .attr("class", &["list-pages"])
+                .contents(|ctx| {
+                    for page in pages {
+                        ctx.html()
+                            .li()
+                            .attr("class", &["list-pages-item"])
+                            .contents(|ctx| {
+                                if is_page_name(&page.fullname) {
+                                    ctx.html()
+                                        .a()
+                                        .attr("href", &["/", &page.fullname])
+                                        .inner(&page.title.as_str());
+                                } else {
+                                    ctx.push_escaped(&page.title);
+                                }
+                            });
+                    }
+                });
+        }
+
+        // Otherwise, the template is parsed and rendered for each page
+        Some(template) => {
+            ctx.html()
+                .div()
+                .attr("class", &["list-pages"])
+                .contents(|ctx| {
+                    for page in pages {
+                        let date = page
+                            .created_at
+                            .map(|timestamp| {
+                                format_date(timestamp, None, ctx.date_settings())
+                            })
+                            .unwrap_or_default();
+
+                        let text = fill_page_template(template, page, &date);
+                        render_template(ctx, text, "list-pages-item");
+                    }
+                });
+        }
+    }
+}
+
+/// Parses and renders the wikitext filled in from a template, for one item.
+///
+/// This uses the page's own parse settings, see `HtmlRender::parse_settings`.
+fn render_template(ctx: &mut HtmlContext, mut text: String, class: &str) {
+    let log = ctx.log();
+
+    crate::preprocess(&log, &mut text);
+    let tokens = crate::tokenize(&log, &text);
+    let options = ParseOptions::new(ctx.parse_settings().clone());
+    let output = crate::parse_with_options(&log, &tokens, &options);
+    let (tree, _) = output.outcome.into();
+
+    ctx.html()
+        .div()
+        .attr("class", &[class])
+        .inner(&tree.elements.as_slice());
+}
+
+/// Substitutes an item's fields into the placeholders of a feed template.
+///
+/// Values are inserted as raw text, so they cannot add formatting of their own.
+/// The link is inserted as-is, but only if it is a web URL.
+fn fill_template(template: &str, item: &FeedItem, date: &str) -> String {
+    fill_placeholders(template, |name| feed_field(name, item, date))
+}
+
+/// Substitutes a page's fields into the placeholders of a `ListPages` template.
+///
+/// As with feeds, values are inserted as raw text.
+fn fill_page_template(template: &str, page: &ListedPage, date: &str) -> String {
+    fill_placeholders(template, |name| page_field(name, page, date))
+}
+
+/// Replaces each placeholder, such as `%%title%%`, with the value for its name.
+///
+/// Placeholders are replaced in a single pass, so a value which contains one,
+/// such as a title, is left as-is. Unknown placeholders are also left as-is.
+fn fill_placeholders<F>(template: &str, field: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("%%") {
+        let after = &rest[start + 2..];
+        let end = match after.find("%%") {
+            Some(end) => end,
+            None => break,
+        };
+
+        match field(&after[..end]) {
+            Some(value) => {
+                output.push_str(&rest[..start]);
+                output.push_str(&value);
+                rest = &after[end + 2..];
+            }
+            None => {
+                output.push_str(&rest[..start + 2]);
+                rest = after;
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Gets the value of a feed placeholder, such as `title` for `%%title%%`.
+fn feed_field(name: &str, item: &FeedItem, date: &str) -> Option<String> {
+    let value = match name {
+        "title" => raw(&item.title),
+        "description" => raw(&item.description),
+        "author" => raw(item.author.as_deref().unwrap_or("")),
+        "date" => raw(date),
+        "link" => web_url(&item.link).map(str::to_string).unwrap_or_default(),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Gets the value of a `ListPages` placeholder, such as `title` for `%%title%%`.
+fn page_field(name: &str, page: &ListedPage, date: &str) -> Option<String> {
+    let value = match name {
+        "title" => raw(&page.title),
+        "name" => raw(page.name()),
+        "fullname" => raw(&page.fullname),
+        "category" => raw(page.category()),
+        "tags" => raw(&page.tags.join(" ")),
+        "created_by" => raw(page.created_by.as_deref().unwrap_or("")),
+        "created_at" => raw(date),
+        "rating" => raw(&page
+            .rating
+            .map(|rating| rating.to_string())
+            .unwrap_or_default()),
+        "link" if is_page_name(&page.fullname) => format!("/{}", page.fullname),
+        "link" => String::new(),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Wraps text in a raw block, so it is not interpreted as wikitext.
+fn raw(value: &str) -> String {
+    let value = value.replace("@@", "").replace('\n', " ");
+
+    if value.is_empty() {
+        value
+    } else {
+        format!("@@{}@@", value)
+    }
+}
+
+/// Returns the URL if it is an absolute HTTP or HTTPS link.
+///
+/// Anything else, such as a `javascript:` URL, is not linked to.
+fn web_url(url: &str) -> Option<&str> {
+    let lowercase = url.to_ascii_lowercase();
+    let is_web = lowercase.starts_with("https://") || lowercase.starts_with("http://");
+    let is_plain = !url
+        .chars()
+        .any(|ch| ch.is_whitespace() || matches!(ch, '[' | ']' | '|'));
+
+    if is_web && is_plain {
+        Some(url)
+    } else {
+        None
+    }
+}
+
+#[test]
+fn templates() {
+    let item = FeedItem {
+        title: str!("Apple %%description%%"),
+        description: str!("Banana"),
+        link: str!("https://example.com/apple"),
+        ..FeedItem::default()
+    };
+
+    assert_eq!(
+        fill_template("%%title%% - %%description%% %%link%% %%other%%", &item, ""),
+        "@@Apple %%description%%@@ - @@Banana@@ https://example.com/apple %%other%%",
+        "Filled feed template doesn't match expected",
+    );
+
+    let page = ListedPage {
+        fullname: str!("fruit:apple"),
+        title: str!("Apple %%link%%"),
+        ..ListedPage::default()
+    };
+
+    assert_eq!(
+        fill_page_template("%%title%% %%link%%", &page, ""),
+        "@@Apple %%link%%@@ /fruit:apple",
+        "Filled ListPages template doesn't match expected",
+    );
+}
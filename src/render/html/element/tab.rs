@@ -0,0 +1,115 @@
+/*
+ * render/html/element/tab.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::Tab;
+
+pub fn render_tabview(ctx: &mut HtmlContext, tabs: &[Tab]) {
+    let id = ctx.generate_id("wiki-tabview");
+    let accessibility = ctx.accessibility();
+
+    // With ARIA, each tab and its panel refer to each other by ID
+    let tab_id = |i| format!("{}-tab-{}", id, i);
+    let panel_id = |i, tab: &Tab| match tab.id() {
+        Some(id) => str!(id),
+        None => format!("{}-panel-{}", id, i),
+    };
+
+    ctx.html()
+        .div()
+        .attr("id", &[&id])
+        .attr("class", &["yui-navset"])
+        .contents(|ctx| {
+            // Tab selector list
+            let mut ul = ctx.html().ul();
+            ul.attr("class", &["yui-nav"]);
+
+            if accessibility {
+                ul.attr("role", &["tablist"]);
+            }
+
+            ul.contents(|ctx| {
+                for (i, tab) in tabs.iter().enumerate() {
+                    let mut li = ctx.html().li();
+
+                    if i == 0 {
+                        li.attr("class", &["selected"]);
+                    }
+
+                    if accessibility {
+                        li.attr("role", &["presentation"]);
+                    }
+
+                    li.contents(|ctx| {
+                        let mut a = ctx.html().a();
+                        a.attr("href", &["javascript:;"]);
+
+                        if accessibility {
+                            a.attr("id", &[&tab_id(i)])
+                                .attr("role", &["tab"])
+                                .attr(
+                                    "aria-selected",
+                                    &[if i == 0 { "true" } else { "false" }],
+                                )
+                                .attr("aria-controls", &[&panel_id(i, tab)]);
+                        }
+
+                        a.contents(|ctx| {
+                            ctx.html().tag("em").inner(&tab.label());
+                        });
+
+                        drop(a);
+                        ctx.collect_tab_label_end();
+                    });
+                }
+            });
+
+            // Drop to close the tag before starting the next one
+            drop(ul);
+
+            // Tab contents, with only the first visible
+            ctx.html()
+                .div()
+                .attr("class", &["yui-content"])
+                .contents(|ctx| {
+                    for (i, tab) in tabs.iter().enumerate() {
+                        let mut div = ctx.html().div();
+
+                        if accessibility {
+                            div.attr("id", &[&panel_id(i, tab)])
+                                .attr("role", &["tabpanel"])
+                                .attr("aria-labelledby", &[&tab_id(i)]);
+                        } else if let Some(id) = tab.id() {
+                            div.attr("id", &[id]);
+                        }
+
+                        if let Some(class) = tab.class() {
+                            div.attr("class", &[class]);
+                        }
+
+                        if i > 0 {
+                            div.attr("style", &["display: none;"]);
+                        }
+
+                        div.inner(&tab.elements());
+                    }
+                });
+        });
+}
@@ -0,0 +1,95 @@
+/*
+ * render/html/element/table.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::Table;
+
+pub fn render_table(ctx: &mut HtmlContext, table: &Table) {
+    let mut tag = ctx.html().table();
+
+    if let Some(id) = table.id() {
+        tag.attr("id", &[id]);
+    }
+
+    tag.attr("class", &[table.class().unwrap_or("wiki-content-table")]);
+
+    if let Some(style) = table.style() {
+        tag.attr("style", &[style]);
+    }
+
+    tag.contents(|ctx| {
+        for row in table.rows() {
+            let mut tag = ctx.html().tr();
+
+            if let Some(id) = row.id() {
+                tag.attr("id", &[id]);
+            }
+
+            if let Some(class) = row.class() {
+                tag.attr("class", &[class]);
+            }
+
+            if let Some(style) = row.style() {
+                tag.attr("style", &[style]);
+            }
+
+            tag.contents(|ctx| {
+                for (i, cell) in row.cells().iter().enumerate() {
+                    ctx.collect_table_cell(i);
+
+                    let tag = if cell.header() { "th" } else { "td" };
+                    let mut tag = ctx.html().tag(tag);
+
+                    if let Some(id) = cell.id() {
+                        tag.attr("id", &[id]);
+                    }
+
+                    if let Some(class) = cell.class() {
+                        tag.attr("class", &[class]);
+                    }
+
+                    if cell.column_span().get() > 1 {
+                        tag.attr_fmt("colspan", |ctx| {
+                            str_write!(ctx, "{}", cell.column_span());
+                        });
+                    }
+
+                    // Alignment from "||" syntax comes first, so the block's style may override it
+                    match (cell.align(), cell.style()) {
+                        (Some(align), Some(style)) => {
+                            tag.attr("style", &[align.html_style(), " ", style]);
+                        }
+                        (Some(align), None) => {
+                            tag.attr("style", &[align.html_style()]);
+                        }
+                        (None, Some(style)) => {
+                            tag.attr("style", &[style]);
+                        }
+                        (None, None) => (),
+                    }
+
+                    tag.inner(&cell.elements());
+                }
+
+                ctx.collect_table_row_end();
+            });
+        }
+    });
+}
@@ -0,0 +1,124 @@
+/*
+ * render/html/element/text.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::super::settings::DiagramOutput;
+use super::prelude::*;
+use crate::enums::DiagramKind;
+use crate::render::date::format_date;
+use crate::render::RenderWarningKind;
+
+pub fn render_raw(ctx: &mut HtmlContext, text: &str) {
+    // Raw text must be displayed exactly as written,
+    // so spaces are made non-breaking.
+    for (i, part) in text.split(' ').enumerate() {
+        if i > 0 {
+            ctx.push_raw_str("&nbsp;");
+        }
+
+        ctx.push_escaped(part);
+    }
+}
+
+pub fn render_color(ctx: &mut HtmlContext, color: &str, elements: &[Element]) {
+    // Since the color is placed within a style attribute,
+    // only allow values which cannot add other properties.
+    if !is_valid_color(color) {
+        ctx.add_warning(RenderWarningKind::InvalidColor, color);
+        ctx.html().span().inner(&elements);
+        return;
+    }
+
+    ctx.html()
+        .span()
+        .attr("style", &["color: ", color, ";"])
+        .inner(&elements);
+}
+
+pub fn render_date(ctx: &mut HtmlContext, timestamp: i64, format: Option<&str>) {
+    let date = format_date(timestamp, format, ctx.date_settings());
+    let timestamp = timestamp.to_string();
+
+    // The timestamp is kept so scripts can update relative dates
+    ctx.html()
+        .span()
+        .attr("class", &["odate"])
+        .attr("data-timestamp", &[&timestamp])
+        .inner(&date.as_str());
+}
+
+/// Determines if this is a CSS color name, or a hex color code.
+fn is_valid_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => {
+            matches!(hex.len(), 3 | 4 | 6 | 8)
+                && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic()),
+    }
+}
+
+pub fn render_code(ctx: &mut HtmlContext, contents: &str, language: Option<&str>) {
+    ctx.html().div().attr("class", &["code"]).contents(|ctx| {
+        ctx.html().pre().contents(|ctx| {
+            let mut code = ctx.html().code();
+
+            if let Some(language) = language {
+                code.attr("class", &["language-", language]);
+            }
+
+            code.inner(&contents);
+        });
+    });
+}
+
+pub fn render_diagram(ctx: &mut HtmlContext, kind: DiagramKind, contents: &str) {
+    match ctx.diagram_output() {
+        DiagramOutput::Preformatted => {
+            ctx.html()
+                .pre()
+                .attr("class", &[kind.html_class()])
+                .inner(&contents);
+        }
+        DiagramOutput::DataAttribute => {
+            ctx.html()
+                .div()
+                .attr("class", &["diagram"])
+                .attr("data-kind", &[kind.html_class()])
+                .attr("data-source", &[contents])
+                .contents(|_| ());
+        }
+    }
+}
+
+pub fn render_math(ctx: &mut HtmlContext, latex: &str, inline: bool) {
+    let mathml = ctx
+        .math_renderer()
+        .and_then(|renderer| renderer.render_mathml(latex, inline));
+
+    match mathml {
+        Some(mathml) => ctx.push_raw_str(&mathml),
+        None if inline => {
+            ctx.html().span().attr("class", &["math"]).inner(&latex);
+        }
+        None => {
+            ctx.html().div().attr("class", &["math"]).inner(&latex);
+        }
+    }
+}
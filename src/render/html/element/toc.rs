@@ -0,0 +1,93 @@
+/*
+ * render/html/element/toc.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::super::render::ElementRender;
+use super::container::render_heading_number;
+use super::prelude::*;
+use crate::enums::{FloatAlignment, HeadingLevel};
+
+pub fn render_table_of_contents(
+    ctx: &mut HtmlContext,
+    depth: Option<HeadingLevel>,
+    float: Option<FloatAlignment>,
+) {
+    let max_level = depth.map_or(u8::MAX, HeadingLevel::value);
+    let headings: Vec<_> = ctx
+        .outline()
+        .iter()
+        .filter(|heading| heading.level.value() <= max_level)
+        .filter_map(|heading| {
+            let id = heading.id.clone()?;
+
+            Some((
+                id,
+                heading.level,
+                heading.number.clone(),
+                heading.elements.clone(),
+            ))
+        })
+        .collect();
+
+    let accessibility = ctx.accessibility();
+    let mut tag = ctx.html().div();
+    tag.attr("class", &["toc"]);
+
+    if accessibility {
+        tag.attr("role", &["navigation"])
+            .attr("aria-label", &["Table of Contents"]);
+    }
+
+    if let Some(float) = float {
+        tag.attr("style", &[float.html_style()]);
+    }
+
+    // The headings' text is already part of the page
+    tag.contents(|ctx| {
+        ctx.uncollected(|ctx| {
+            ctx.html()
+                .div()
+                .attr("class", &["toc-title"])
+                .inner(&"Table of Contents");
+
+            ctx.html()
+                .div()
+                .attr("class", &["toc-list"])
+                .contents(|ctx| {
+                    for (id, level, number, elements) in &headings {
+                        let indent = format!("margin-left: {}em;", level.value());
+
+                        ctx.html()
+                            .div()
+                            .attr("class", &["toc-item"])
+                            .attr("style", &[&indent])
+                            .contents(|ctx| {
+                                ctx.html().a().attr("href", &["#", id]).contents(|ctx| {
+                                    if let Some(number) = number {
+                                        render_heading_number(ctx, number);
+                                    }
+
+                                    elements.as_slice().render(ctx);
+                                });
+                            });
+                    }
+                });
+        });
+    });
+}
@@ -0,0 +1,225 @@
+/*
+ * render/html/handle.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Access to data held by the host, such as the contents of modules.
+//!
+//! Rendering the syntax tree itself cannot fail, so `ElementRender` is
+//! infallible. Only lookups through the `Handle` may fail, since they can
+//! require remote requests. On failure the element is rendered without
+//! that data, and a warning is added to the output.
+
+use crate::data::PageInfo;
+use crate::includes::PageRef;
+use crate::tree::{ImageSource, ListPagesQuery, Module};
+use std::fmt::{self, Debug, Display};
+
+/// An error from a lookup performed by the host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandleError {
+    message: String,
+}
+
+impl HandleError {
+    #[inline]
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        HandleError {
+            message: message.into(),
+        }
+    }
+
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for HandleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for HandleError {}
+
+/// Information about another page, as known by the host.
+///
+/// This is fetched as one bundle so that hosts need only a single
+/// lookup (and cache entry) per referenced page.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct PageMeta {
+    /// Whether the page exists.
+    pub exists: bool,
+
+    /// The title of the page, if it has one.
+    pub title: Option<String>,
+
+    /// The current set of tags the page has.
+    pub tags: Vec<String>,
+
+    /// The current rating the page has, if any.
+    pub rating: Option<f32>,
+}
+
+/// An entry from an RSS or Atom feed, as fetched by the host.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct FeedItem {
+    /// The title of the entry.
+    pub title: String,
+
+    /// The URL the entry links to.
+    pub link: String,
+
+    /// A summary of the entry, as plain text.
+    pub description: String,
+
+    /// The name of the entry's author, if given.
+    pub author: Option<String>,
+
+    /// When the entry was published, as a UNIX timestamp.
+    pub published: Option<i64>,
+}
+
+/// A page matching the query of a `ListPages` module, as found by the host.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListedPage {
+    /// The name of the page, including its category, such as `fruit:apple`.
+    pub fullname: String,
+
+    /// The title of the page.
+    pub title: String,
+
+    /// The current set of tags the page has.
+    pub tags: Vec<String>,
+
+    /// The name of the user who created the page, if known.
+    pub created_by: Option<String>,
+
+    /// When the page was created, as a UNIX timestamp.
+    pub created_at: Option<i64>,
+
+    /// The current rating the page has, if any.
+    pub rating: Option<f32>,
+}
+
+impl ListedPage {
+    /// The category of the page, which is `_default` if it has none.
+    pub fn category(&self) -> &str {
+        match self.fullname.find(':') {
+            Some(index) => &self.fullname[..index],
+            None => "_default",
+        }
+    }
+
+    /// The name of the page, without its category.
+    pub fn name(&self) -> &str {
+        match self.fullname.find(':') {
+            Some(index) => &self.fullname[index + 1..],
+            None => &self.fullname,
+        }
+    }
+}
+
+/// Lookups the renderer needs from the host.
+///
+/// Each lookup defaults to the host not having the data, so hosts
+/// need only implement those they support.
+pub trait Handle: Debug {
+    /// Fetches information about the given page.
+    ///
+    /// Returns `None` if the host has no information about pages,
+    /// in which case the renderer behaves as if it had not asked.
+    fn get_page_meta(&self, _: &PageRef) -> Result<Option<PageMeta>, HandleError> {
+        Ok(None)
+    }
+
+    /// Fetches the entries of the feed at the given URL.
+    ///
+    /// Returns `None` if the host doesn't provide feed data, in which
+    /// case the feed module is rendered through `render_module()` instead.
+    fn get_feed(&self, _: &str) -> Result<Option<Vec<FeedItem>>, HandleError> {
+        Ok(None)
+    }
+
+    /// Finds the pages matching the query of a `ListPages` module on the given page.
+    ///
+    /// The pages are returned in the order they are listed, with the query's
+    /// offset already applied. Pagination is up to the host, which returns
+    /// the pages for the page of results being viewed.
+    ///
+    /// Returns `None` if the host doesn't provide page listings, in which
+    /// case the module is rendered through `render_module()` instead.
+    fn get_list_pages(
+        &self,
+        _: &ListPagesQuery,
+        _: &PageInfo,
+    ) -> Result<Option<Vec<ListedPage>>, HandleError> {
+        Ok(None)
+    }
+
+    /// Gets the URL of the image from the given source, on the given page.
+    ///
+    /// Returns `None` to use the usual location, see `ImageSource::default_url()`.
+    /// Since `:first` has no usual location, it is only rendered if the host finds it.
+    fn get_image_url(
+        &self,
+        _: &ImageSource,
+        _: &PageInfo,
+    ) -> Result<Option<String>, HandleError> {
+        Ok(None)
+    }
+
+    /// Gets the URL that submissions of the given form are posted to.
+    ///
+    /// Returns `None` if the host doesn't handle form submissions,
+    /// in which case the form is rendered, but cannot be submitted.
+    fn get_form_action(
+        &self,
+        _: &Module,
+        _: &PageInfo,
+    ) -> Result<Option<String>, HandleError> {
+        Ok(None)
+    }
+
+    /// Produces the HTML for a module on the given page.
+    ///
+    /// The returned HTML is trusted, and is inserted into the output as-is.
+    fn render_module(
+        &self,
+        module: &Module,
+        info: &PageInfo,
+    ) -> Result<String, HandleError>;
+}
+
+/// A handle for use when no host is available.
+///
+/// Modules are rendered as nothing, no page, feed, or listing information is
+/// available, images are at their usual locations, and forms cannot be submitted.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct NullHandle;
+
+impl Handle for NullHandle {
+    #[inline]
+    fn render_module(&self, _: &Module, _: &PageInfo) -> Result<String, HandleError> {
+        Ok(String::new())
+    }
+}
@@ -0,0 +1,37 @@
+/*
+ * render/html/math.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Pluggable conversion of LaTeX math into MathML.
+//!
+//! ftml does not typeset LaTeX itself. If a `MathRenderer` is set in
+//! `HtmlRender::math_renderer`, it is asked to convert each math element.
+//! Otherwise, or if it declines, the source is emitted in a wrapper with
+//! the `math` class, to be typeset by a client-side library such as KaTeX.
+
+use std::fmt::Debug;
+
+/// Converts LaTeX math expressions into MathML.
+pub trait MathRenderer: Debug {
+    /// Converts the expression, returning a `<math>` element.
+    ///
+    /// The output is inserted into the page as-is, so must be well-formed.
+    /// Returning `None` falls back to emitting the LaTeX source.
+    fn render_mathml(&self, latex: &str, inline: bool) -> Option<String>;
+}
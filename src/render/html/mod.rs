@@ -18,9 +18,6 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-// TODO! remove when this is implemented
-#![allow(dead_code)]
-
 #[cfg(test)]
 mod test;
 
@@ -29,27 +26,258 @@ mod macros;
 
 mod builder;
 mod context;
+mod element;
 mod escape;
+mod handle;
+mod math;
 mod meta;
 mod output;
 mod render;
+mod settings;
+mod tag_balance;
 
+pub use self::handle::{FeedItem, Handle, HandleError, ListedPage, NullHandle, PageMeta};
+pub use self::math::MathRenderer;
 pub use self::meta::{HtmlMeta, HtmlMetaType};
 pub use self::output::HtmlOutput;
+pub use self::settings::{DiagramOutput, HeadingNumbering};
+pub use self::tag_balance::check_tag_balance;
 
 #[cfg(test)]
 use super::prelude;
 
+use self::context::HtmlContext;
+use self::render::ElementRender;
+use crate::data::{AuthorTrust, PageInfo, SiteSettings};
+use crate::parsing::ParseSettings;
+use crate::render::date::DateSettings;
+use crate::render::multi::MultiOutput;
 use crate::render::Render;
-use crate::tree::SyntaxTree;
+use crate::tree::{footnotes_before, headings_before, Element, ElementPath, SyntaxTree};
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+pub struct HtmlRender {
+    /// How chart and diagram elements are emitted.
+    pub diagram_output: DiagramOutput,
+
+    /// The current time and timezone, for displaying dates.
+    ///
+    /// Set `DateSettings::now` for output with reproducible relative dates.
+    pub date_settings: DateSettings,
+
+    /// Whether to prefix headings with section numbers, such as `1.2`.
+    ///
+    /// The numbers are computed from the page's outline,
+    /// and also appear in its table of contents.
+    pub heading_numbering: HeadingNumbering,
+
+    /// Information about the page being rendered, used for its metadata.
+    pub page_info: PageInfo<'static>,
+
+    /// Conventions for the site the page belongs to.
+    ///
+    /// This should be the same instance passed in `ParseSettings::site`.
+    pub site: Arc<SiteSettings>,
+
+    /// The settings the page was parsed with.
+    ///
+    /// Wikitext from module templates, such as those of `ListPages`, is parsed
+    /// with these as well, so for instance its headings are shifted like the
+    /// rest of the page's.
+    pub parse_settings: ParseSettings,
+
+    /// Where to log the parsing of wikitext from module templates.
+    ///
+    /// If absent, these messages are discarded.
+    pub log: Option<slog::Logger>,
+
+    /// Performs lookups requiring data from the host, such as rendering modules.
+    ///
+    /// If absent, `NullHandle` is used.
+    pub handle: Option<Arc<dyn Handle + Send + Sync>>,
+
+    /// Converts math elements into MathML.
+    ///
+    /// If absent, the LaTeX source is emitted for client-side rendering.
+    pub math_renderer: Option<Arc<dyn MathRenderer + Send + Sync>>,
+
+    /// Whether to produce output which is byte-for-byte reproducible.
+    ///
+    /// In this mode the attributes of each tag are sorted by name, rather
+    /// than being emitted in the order the renderer adds them. This means
+    /// the output does not change if element rendering is reordered
+    /// internally, and can be hashed and compared across builds.
+    ///
+    /// Generated IDs are always stable, since they are numbered
+    /// by their order of appearance in the document.
+    pub deterministic: bool,
+
+    /// Whether to add ARIA roles and states to generated widgets.
+    ///
+    /// This marks up collapsibles, tab views, and the table of contents,
+    /// so they can be understood and navigated with assistive technology.
+    pub accessibility: bool,
+
+    /// Whether to reuse the rendered HTML of repeated included pages.
+    ///
+    /// Pages often include the same component many times, such as
+    /// several identical ACS bars. With this enabled, each distinct
+    /// include is rendered once, keyed by a hash of its subtree, and
+    /// identical ones copy that output.
+    ///
+    /// Each included subtree is hashed, and compared against earlier
+    /// subtrees with the same hash.
+    pub memoize: bool,
+
+    /// Whether to leave out parts of the page which reach beyond its own content,
+    /// for pages by untrusted authors.
+    ///
+    /// Page styles, such as from `[[module CSS]]`, are not applied,
+    /// and modules are not rendered, so the host is never asked for them.
+    /// Links may only point to web pages, email addresses, pages on the site,
+    /// and anchors, so they can't run script.
+    pub safe_mode: bool,
+}
+
+impl HtmlRender {
+    /// Creates a renderer for a page by an author with the given trust.
+    ///
+    /// See `ParseSettings::for_author()` for the corresponding parse settings.
+    pub fn for_author(trust: AuthorTrust, site: &Arc<SiteSettings>) -> Self {
+        HtmlRender {
+            safe_mode: !trust.is_trusted(),
+            site: trust.site_settings(site),
+            ..HtmlRender::default()
+        }
+    }
+
+    /// Renders the tree, also gathering its text and metadata.
+    ///
+    /// See `MultiRender`.
+    pub(crate) fn render_collecting(&self, tree: &SyntaxTree) -> MultiOutput {
+        let handle = self.handle();
+        let mut ctx = self.context(tree, handle);
+        ctx.enable_collection();
+        tree.elements.as_slice().render(&mut ctx);
+        render_footnotes(&mut ctx);
+
+        let collector = ctx.take_collector().unwrap_or_default();
+        let (text, metadata) = collector.finish();
+
+        MultiOutput {
+            html: ctx.into(),
+            text,
+            metadata,
+        }
+    }
+
+    /// Renders only some of the tree's elements, such as a single section.
+    ///
+    /// Page-wide state, such as styles and the table of contents, still comes
+    /// from the whole tree. So if the elements are from within the tree,
+    /// the output is the same as the corresponding part of a full render.
+    pub fn render_partial(&self, tree: &SyntaxTree, elements: &[Element]) -> HtmlOutput {
+        let handle = self.handle();
+        let mut ctx = self.context(tree, handle);
 
-#[derive(Debug)]
-pub struct HtmlRender;
+        if let Some(first) = elements.first() {
+            let skipped = headings_before(&tree.elements, first).unwrap_or(0);
+            ctx.skip_headings(skipped);
+
+            let skipped = footnotes_before(&tree.elements, first).unwrap_or(0);
+            ctx.skip_footnotes(skipped);
+        }
+
+        elements.render(&mut ctx);
+        ctx.into()
+    }
+
+    /// Renders only the element at the given path, and its descendants.
+    ///
+    /// Returns `None` if the path does not refer to an element in the tree.
+    pub fn render_path(
+        &self,
+        tree: &SyntaxTree,
+        path: &ElementPath,
+    ) -> Option<HtmlOutput> {
+        self.render_range(tree, path, 1)
+    }
+
+    /// Renders the element at the given path, and up to `count - 1` of its following siblings.
+    ///
+    /// Returns `None` if the path does not refer to an element in the tree.
+    pub fn render_range(
+        &self,
+        tree: &SyntaxTree,
+        start: &ElementPath,
+        count: usize,
+    ) -> Option<HtmlOutput> {
+        let (siblings, index) = start.resolve_siblings(&tree.elements)?;
+        let end = siblings.len().min(index.saturating_add(count));
+
+        Some(self.render_partial(tree, &siblings[index..end]))
+    }
+
+    fn handle(&self) -> &dyn Handle {
+        match self.handle {
+            Some(ref handle) => handle.as_ref(),
+            None => &NullHandle,
+        }
+    }
+
+    fn context<'h>(
+        &'h self,
+        tree: &SyntaxTree,
+        handle: &'h dyn Handle,
+    ) -> HtmlContext<'static, 'h> {
+        let mut ctx = HtmlContext::new(self, handle);
+
+        if self.safe_mode {
+            ctx.enable_safe_mode();
+        } else {
+            for style in &tree.styles {
+                ctx.add_style(style);
+            }
+        }
+
+        ctx.set_outline(&tree.headings(), self.heading_numbering);
+        ctx.set_footnotes(&tree.footnotes());
+
+        if self.accessibility {
+            ctx.enable_accessibility();
+        }
+
+        if let Some(ref renderer) = self.math_renderer {
+            ctx.set_math_renderer(renderer.as_ref());
+        }
+
+        if let Some(ref log) = self.log {
+            ctx.set_logger(log);
+        }
+
+        ctx
+    }
+}
 
 impl Render for HtmlRender {
     type Output = HtmlOutput;
 
-    fn render(&self, _tree: &SyntaxTree) -> HtmlOutput {
-        todo!()
+    fn render(&self, tree: &SyntaxTree) -> HtmlOutput {
+        let handle = self.handle();
+        let mut ctx = self.context(tree, handle);
+        tree.elements.as_slice().render(&mut ctx);
+        render_footnotes(&mut ctx);
+        ctx.into()
     }
 }
+
+/// Lists the footnotes at the end of the page, if there was no footnote block.
+fn render_footnotes(ctx: &mut HtmlContext) {
+    let element = Element::FootnoteBlock {
+        title: None,
+        hide: false,
+    };
+
+    element.render(ctx);
+}
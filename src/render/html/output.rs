@@ -19,10 +19,14 @@
  */
 
 use super::meta::HtmlMeta;
+use crate::render::RenderWarning;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct HtmlOutput {
     pub html: String,
     pub style: String,
     pub meta: Vec<HtmlMeta>,
+
+    /// Any issues encountered while rendering, such as disallowed embeds.
+    pub warnings: Vec<RenderWarning>,
 }
@@ -0,0 +1,64 @@
+/*
+ * render/html/settings.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/// How chart and diagram elements are emitted.
+///
+/// Neither form draws the diagram itself, this is left to
+/// whichever client-side library the page is using.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DiagramOutput {
+    /// Emit the source in a `<pre>` tag, with the diagram kind as its class.
+    ///
+    /// For instance, `<pre class="mermaid">`.
+    Preformatted,
+
+    /// Emit an empty `<div>`, with the source in a `data-source` attribute.
+    ///
+    /// For instance, `<div class="diagram" data-kind="mermaid" data-source="...">`.
+    DataAttribute,
+}
+
+impl Default for DiagramOutput {
+    #[inline]
+    fn default() -> Self {
+        DiagramOutput::Preformatted
+    }
+}
+
+/// Which headings are prefixed with hierarchical section numbers, such as `1.2`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HeadingNumbering {
+    /// Headings are rendered as written.
+    None,
+
+    /// Every heading is numbered.
+    All,
+
+    /// Only headings in the table of contents are numbered,
+    /// leaving out those written as `+* Title`.
+    Listed,
+}
+
+impl Default for HeadingNumbering {
+    #[inline]
+    fn default() -> Self {
+        HeadingNumbering::None
+    }
+}
@@ -0,0 +1,231 @@
+/*
+ * render/html/tag_balance.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Tag balance check for generated HTML, used to check the test corpus.
+//!
+//! This is not an HTML validator. It only checks the lexical form that the
+//! renderer is meant to produce: every non-void tag is closed in order,
+//! attribute values are quoted, and all special characters are escaped.
+//! Content model errors, such as a `<ul>` within a `<p>`, are not detected.
+//!
+//! It also rejects output which could run scripts, since no wikitext input
+//! should be able to produce it.
+
+use super::builder::is_void_tag;
+
+/// Tags which rendered output must never contain.
+const DISALLOWED_TAGS: [&str; 10] = [
+    "applet", "base", "embed", "frame", "frameset", "link", "meta", "object", "script",
+    "style",
+];
+
+/// The placeholder link target used for interactive elements like collapsibles.
+const NOOP_SCRIPT_URL: &str = "javascript:;";
+
+/// Checks that the tags in the given HTML are balanced, and that it's safe.
+///
+/// Returns a description of the first problem found, if any.
+pub fn check_tag_balance(html: &str) -> Result<(), String> {
+    let mut stack = Vec::new();
+    let mut chars = html.char_indices().peekable();
+
+    while let Some((index, ch)) = chars.next() {
+        match ch {
+            '<' => {
+                let remainder = &html[index..];
+                let end = match remainder.find('>') {
+                    Some(end) => end,
+                    None => return Err(format!("Unterminated tag at {}", index)),
+                };
+
+                let tag = &remainder[1..end];
+                match tag.strip_prefix('/') {
+                    Some(name) => match stack.pop() {
+                        Some(open) if open == name => (),
+                        Some(open) => {
+                            return Err(format!(
+                                "Closing tag '{}' at {} does not match open tag '{}'",
+                                name, index, open,
+                            ))
+                        }
+                        None => {
+                            return Err(format!(
+                                "Closing tag '{}' at {} has no open tag",
+                                name, index,
+                            ))
+                        }
+                    },
+                    None => {
+                        let name = validate_open_tag(tag)
+                            .map_err(|error| format!("{} (at {})", error, index))?;
+
+                        if !is_void_tag(name) {
+                            stack.push(name);
+                        }
+                    }
+                }
+
+                // Skip to after the tag
+                while let Some((next, _)) = chars.peek() {
+                    if *next > index + end {
+                        break;
+                    }
+
+                    chars.next();
+                }
+            }
+            '>' | '"' => return Err(format!("Unescaped '{}' at {}", ch, index)),
+            '&' if !is_entity(&html[index..]) => {
+                return Err(format!("Unescaped '&' at {}", index));
+            }
+            _ => (),
+        }
+    }
+
+    match stack.pop() {
+        Some(open) => Err(format!("Tag '{}' was never closed", open)),
+        None => Ok(()),
+    }
+}
+
+/// Validates the interior of an opening tag, returning its name.
+fn validate_open_tag(tag: &str) -> Result<&str, String> {
+    let (name, mut attributes) = match tag.find(' ') {
+        Some(index) => (&tag[..index], &tag[index..]),
+        None => (tag, ""),
+    };
+
+    if !is_name(name) {
+        return Err(format!("Invalid tag name '{}'", name));
+    }
+
+    if DISALLOWED_TAGS.contains(&name) {
+        return Err(format!("Disallowed tag '{}'", name));
+    }
+
+    while !attributes.is_empty() {
+        // Each attribute is ' key="value"'
+        let remainder = attributes
+            .strip_prefix(' ')
+            .ok_or_else(|| format!("Malformed attributes in '{}'", name))?;
+
+        let (key, remainder) = match remainder.find("=\"") {
+            Some(index) => (&remainder[..index], &remainder[index + 2..]),
+            None => return Err(format!("Unquoted attribute in '{}'", name)),
+        };
+
+        let (value, remainder) = match remainder.find('"') {
+            Some(index) => (&remainder[..index], &remainder[index + 1..]),
+            None => return Err(format!("Unterminated attribute in '{}'", name)),
+        };
+
+        validate_attribute(name, key, value)?;
+        attributes = remainder;
+    }
+
+    Ok(name)
+}
+
+fn validate_attribute(tag: &str, key: &str, value: &str) -> Result<(), String> {
+    if !is_name(key) {
+        return Err(format!("Invalid attribute name '{}' in '{}'", key, tag));
+    }
+
+    if key.starts_with("on") {
+        return Err(format!("Event handler attribute '{}' in '{}'", key, tag));
+    }
+
+    if value.contains(&['<', '>'][..]) {
+        return Err(format!("Unescaped value for '{}' in '{}'", key, tag));
+    }
+
+    if (key == "href" || key == "src")
+        && value
+            .trim_start()
+            .to_ascii_lowercase()
+            .starts_with("javascript:")
+        && value != NOOP_SCRIPT_URL
+    {
+        return Err(format!("Script URL for '{}' in '{}'", key, tag));
+    }
+
+    Ok(())
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn is_entity(s: &str) -> bool {
+    let end = match s.find(';') {
+        Some(end) => end,
+        None => return false,
+    };
+
+    let body = &s[1..end];
+    match body.strip_prefix('#') {
+        Some(number) => match number.strip_prefix('x') {
+            Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+            None => !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()),
+        },
+        None => !body.is_empty() && body.chars().all(|c| c.is_ascii_alphanumeric()),
+    }
+}
+
+#[test]
+fn tag_balance() {
+    macro_rules! valid {
+        ($html:expr) => {
+            assert_eq!(
+                check_tag_balance($html),
+                Ok(()),
+                "HTML was not considered balanced",
+            );
+        };
+    }
+
+    macro_rules! invalid {
+        ($html:expr) => {
+            assert!(
+                check_tag_balance($html).is_err(),
+                "HTML was not considered unbalanced",
+            );
+        };
+    }
+
+    valid!("");
+    valid!("Apple &amp; banana &#39;cherry&#39;");
+    valid!(r#"<p>Apple<br><strong class="fruit">banana</strong></p>"#);
+    valid!(r#"<img src="apple.png" alt="apple.png"><hr>"#);
+    valid!(r#"<a href="javascript:;">Toggle</a>"#);
+
+    invalid!("<p>Apple");
+    invalid!("<p><em>Apple</p></em>");
+    invalid!("Apple</p>");
+    invalid!("Apple & banana");
+    invalid!("x > 3");
+    invalid!("<p class=fruit>Apple</p>");
+    invalid!("<script>alert(1);</script>");
+    invalid!(r#"<img src="apple.png" onerror="alert(1);">"#);
+    invalid!(r#"<a href="javascript:alert(1);">Apple</a>"#);
+}
@@ -19,13 +19,1615 @@
  */
 
 use super::prelude::*;
-use super::HtmlRender;
+use super::{
+    DiagramOutput, FeedItem, Handle, HandleError, HtmlRender, ListedPage, PageMeta,
+};
+use crate::data::{Alignment, PageInfo};
+use crate::enums::{ClearFloat, DiagramKind, FloatAlignment, ListStyle};
+use crate::includes::PageRef;
+use crate::tree::{
+    DefinitionListItem, List, ListItem, ListPagesQuery, Module, Table, TableCell,
+    TableRow,
+};
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+
+macro_rules! test {
+    ($elements:expr, $expected:expr $(,)?) => {{
+        let result = SyntaxTree::from_element_result($elements, vec![], vec![]);
+        let (tree, _) = result.into();
+        let output = HtmlRender::default().render(&tree);
+
+        assert_eq!(
+            output.html, $expected,
+            "Rendered HTML doesn't match expected"
+        );
+    }};
+}
+
+/// A handle for tests, which answers lookups with the data it was given.
+#[derive(Debug, Default)]
+struct TestHandle {
+    /// Information about pages, lookups of any others fail.
+    ///
+    /// If `None`, the host has no information about pages.
+    pages: Option<HashMap<&'static str, PageMeta>>,
+
+    /// The entries of every feed.
+    feed: Option<Vec<FeedItem>>,
+
+    /// The pages found by every `ListPages` query.
+    listed_pages: Option<Vec<ListedPage>>,
+
+    /// The HTML of modules by name, rendering any others fails.
+    modules: HashMap<&'static str, &'static str>,
+}
+
+impl Handle for TestHandle {
+    fn get_page_meta(&self, page: &PageRef) -> Result<Option<PageMeta>, HandleError> {
+        match self.pages {
+            Some(ref pages) => match pages.get(page.page()) {
+                Some(meta) => Ok(Some(meta.clone())),
+                None => Err(HandleError::new("lookup failed")),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn get_feed(&self, _: &str) -> Result<Option<Vec<FeedItem>>, HandleError> {
+        Ok(self.feed.clone())
+    }
+
+    fn get_list_pages(
+        &self,
+        _: &ListPagesQuery,
+        _: &PageInfo,
+    ) -> Result<Option<Vec<ListedPage>>, HandleError> {
+        Ok(self.listed_pages.clone())
+    }
+
+    fn render_module(
+        &self,
+        module: &Module,
+        _: &PageInfo,
+    ) -> Result<String, HandleError> {
+        match self.modules.get(module.name()) {
+            Some(html) => Ok(str!(html)),
+            None => Err(HandleError::new("lookup failed")),
+        }
+    }
+}
 
 #[test]
 fn html() {
-    let result = SyntaxTree::from_element_result(vec![], vec![], vec![]);
+    test!(vec![], "");
+
+    test!(
+        vec![Element::Container(Container::new(
+            ContainerType::Paragraph,
+            vec![
+                text!("Apple "),
+                Element::Container(Container::new(
+                    ContainerType::Strong,
+                    vec![text!("banana")],
+                )),
+                text!(" <cherry>"),
+            ],
+        ))],
+        "<p>Apple <strong>banana</strong> &lt;cherry&gt;</p>",
+    );
+}
+
+#[test]
+fn styled_container() {
+    use crate::tree::{StyledContainer, StyledContainerType};
+
+    test!(
+        vec![
+            Element::StyledContainer(StyledContainer::new(
+                StyledContainerType::Mark,
+                vec![text!("Apple")],
+                None,
+                Some(cow!("fruit")),
+                None,
+                None,
+                None,
+            )),
+            Element::StyledContainer(StyledContainer::new(
+                StyledContainerType::Keyboard,
+                vec![text!("Banana")],
+                None,
+                None,
+                None,
+                None,
+                None,
+            )),
+        ],
+        r#"<mark class="fruit">Apple</mark><kbd>Banana</kbd>"#,
+    );
+}
+
+#[test]
+fn abbreviation() {
+    test!(
+        vec![Element::Abbreviation {
+            title: cow!("HyperText Markup Language"),
+            elements: vec![text!("HTML")],
+        }],
+        r#"<abbr title="HyperText Markup Language">HTML</abbr>"#,
+    );
+}
+
+#[test]
+fn anchor() {
+    use crate::enums::{AnchorTarget, LinkLabel};
+
+    test!(
+        vec![Element::Link {
+            url: cow!("https://example.com/"),
+            label: LinkLabel::Text(cow!("Apple")),
+            anchor: AnchorTarget::NewTab,
+        }],
+        r#"<a href="https://example.com/" target="_blank" rel="noopener noreferrer">Apple</a>"#,
+    );
+
+    test!(
+        vec![Element::Anchor {
+            elements: vec![text!("Banana")],
+            url: cow!("/scp-001"),
+            anchor: AnchorTarget::Top,
+            rel: None,
+            id: None,
+            class: Some(cow!("fruit")),
+            style: None,
+        }],
+        r#"<a href="/scp-001" target="_top" class="fruit">Banana</a>"#,
+    );
+
+    test!(
+        vec![Element::Anchor {
+            elements: vec![text!("Cherry")],
+            url: cow!("https://example.com/"),
+            anchor: AnchorTarget::NewTab,
+            rel: Some(cow!("nofollow")),
+            id: None,
+            class: None,
+            style: None,
+        }],
+        r#"<a href="https://example.com/" target="_blank" rel="nofollow">Cherry</a>"#,
+    );
+
+    test!(
+        vec![Element::Anchor {
+            elements: vec![text!("Durian")],
+            url: cow!("https://example.com/"),
+            anchor: AnchorTarget::NewTab,
+            rel: Some(cow!("")),
+            id: None,
+            class: None,
+            style: None,
+        }],
+        r#"<a href="https://example.com/" target="_blank">Durian</a>"#,
+    );
+
+    test!(
+        vec![Element::Link {
+            url: cow!("/scp-001"),
+            label: LinkLabel::Elements(vec![
+                text!("Eggplant "),
+                Element::Container(Container::new(
+                    ContainerType::Strong,
+                    vec![text!("Fig")],
+                )),
+            ]),
+            anchor: AnchorTarget::Same,
+        }],
+        r#"<a href="/scp-001">Eggplant <strong>Fig</strong></a>"#,
+    );
+}
+
+#[test]
+fn blockquote() {
+    test!(
+        vec![Element::BlockQuote {
+            elements: vec![text!("Apple")],
+            id: None,
+            class: None,
+            style: None,
+            cite: Some(cow!("https://example.com/")),
+            source: Some(vec![text!("Banana")]),
+        }],
+        r#"<blockquote cite="https://example.com/">Apple<footer class="blockquote-source">Banana</footer></blockquote>"#,
+    );
+}
+
+#[test]
+fn image() {
+    use crate::data::{ImageAlignment, PageInfo};
+    use crate::render::{RenderWarning, RenderWarningKind};
+    use crate::tree::{Image, ImageSource};
+
+    let renderer = HtmlRender {
+        page_info: PageInfo {
+            slug: cow!("fruit"),
+            ..PageInfo::default()
+        },
+        ..HtmlRender::default()
+    };
+
+    macro_rules! test {
+        ($element:expr, $expected:expr $(,)?) => {
+            test!($element, $expected, Vec::<RenderWarning>::new())
+        };
+        ($element:expr, $expected:expr, $warnings:expr $(,)?) => {{
+            let result = SyntaxTree::from_element_result(vec![$element], vec![], vec![]);
+            let (tree, _) = result.into();
+            let output = renderer.render(&tree);
+
+            assert_eq!(
+                output.html, $expected,
+                "Rendered HTML doesn't match expected"
+            );
+            assert_eq!(
+                output.warnings, $warnings,
+                "Render warnings don't match expected",
+            );
+        }};
+    }
+
+    test!(
+        Element::Image(Box::new(Image::new(ImageSource::Url(cow!(
+            "files/apple.png"
+        ))))),
+        r#"<img src="files/apple.png" alt="apple.png" class="image">"#,
+    );
+
+    test!(
+        Element::Image(Box::new(Image {
+            caption: Some(vec![
+                text!("Banana "),
+                Element::Container(Container::new(
+                    ContainerType::Emphasis,
+                    vec![text!("cherry")],
+                )),
+            ]),
+            ..Image::new(ImageSource::File1 {
+                file: cow!("apple.png"),
+            })
+        })),
+        r#"<figure><img src="/local--files/fruit/apple.png" alt="apple.png" class="image"><figcaption>Banana <em>cherry</em></figcaption></figure>"#,
+    );
+
+    test!(
+        Element::Image(Box::new(Image {
+            float: Some(FloatAlignment::Right),
+            clear: Some(ClearFloat::Both),
+            ..Image::new(ImageSource::File2 {
+                page: cow!("tree"),
+                file: cow!("apple.png"),
+            })
+        })),
+        r#"<img src="/local--files/tree/apple.png" alt="apple.png" class="image" style="float: right; clear: both;">"#,
+    );
+
+    test!(
+        Element::Image(Box::new(Image {
+            link: Some(cow!("https://example.com/")),
+            alignment: ImageAlignment::parse("f<"),
+            alt: Some(cow!("An apple")),
+            width: Some(cow!("100")),
+            height: Some(cow!("50")),
+            class: Some(cow!("fruit")),
+            style: Some(cow!("border: none;")),
+            ..Image::new(ImageSource::Url(cow!(
+                "https://example.com/apple.png?size=1"
+            )))
+        })),
+        r#"<div class="image-container floatleft"><a href="https://example.com/"><img src="https://example.com/apple.png?size=1" alt="An apple" class="image fruit" width="100" height="50" style="border: none;"></a></div>"#,
+    );
+
+    // Only the host knows which file is attached first
+    test!(
+        Element::Image(Box::new(Image::new(ImageSource::FirstFile))),
+        "",
+        vec![RenderWarning::new(
+            RenderWarningKind::MissingImage,
+            ":first"
+        )],
+    );
+}
+
+#[test]
+fn table() {
+    let one = NonZeroU32::new(1).unwrap();
+    let two = NonZeroU32::new(2).unwrap();
+
+    test!(
+        vec![Element::Table(Table::new(vec![
+            TableRow::new(vec![TableCell::new(true, two, None, vec![text!("Apple")])]),
+            TableRow::new(vec![
+                TableCell::new(
+                    false,
+                    one,
+                    Some(Alignment::Center),
+                    vec![text!("Banana")]
+                ),
+                TableCell::new(false, one, None, vec![text!("Cherry")]),
+            ]),
+        ]))],
+        r#"<table class="wiki-content-table"><tr><th colspan="2">Apple</th></tr><tr><td style="text-align: center;">Banana</td><td>Cherry</td></tr></table>"#,
+    );
+}
+
+#[test]
+fn table_attributes() {
+    let one = NonZeroU32::new(1).unwrap();
+
+    test!(
+        vec![Element::Table(
+            Table::new(vec![TableRow::new(vec![
+                TableCell::new(false, one, None, vec![text!("Apple")]).with_attributes(
+                    Some(cow!("apple")),
+                    None,
+                    None
+                ),
+                TableCell::new(false, one, Some(Alignment::Right), vec![text!("Banana")])
+                    .with_attributes(
+                        None,
+                        Some(cow!("fruit")),
+                        Some(cow!("color: red;"))
+                    ),
+            ])
+            .with_attributes(None, Some(cow!("first")), None)])
+            .with_attributes(
+                Some(cow!("fruits")),
+                Some(cow!("produce")),
+                Some(cow!("width: 100%;")),
+            )
+        )],
+        r#"<table id="fruits" class="produce" style="width: 100%;"><tr class="first"><td id="apple">Apple</td><td class="fruit" style="text-align: right; color: red;">Banana</td></tr></table>"#,
+    );
+}
+
+#[test]
+fn list() {
+    test!(
+        vec![Element::List(List::new(
+            ListStyle::Bullet,
+            vec![
+                ListItem::new(vec![
+                    text!("Apple"),
+                    Element::List(List::new(
+                        ListStyle::Numbered,
+                        vec![ListItem::new(vec![text!("Banana")])],
+                    )),
+                ]),
+                ListItem::new(vec![Element::Container(Container::new(
+                    ContainerType::Paragraph,
+                    vec![text!("Cherry")],
+                ))]),
+            ],
+        ))],
+        "<ul><li>Apple<ol><li>Banana</li></ol></li><li><p>Cherry</p></li></ul>",
+    );
+}
+
+#[test]
+fn definition_list() {
+    test!(
+        vec![Element::DefinitionList(vec![
+            DefinitionListItem::new(vec![text!("Apple")], vec![text!("A red fruit")]),
+            DefinitionListItem::new(
+                vec![Element::Container(Container::new(
+                    ContainerType::Strong,
+                    vec![text!("Banana")],
+                ))],
+                vec![text!("Yellow")],
+            ),
+        ])],
+        "<dl><dt>Apple</dt><dd>A red fruit</dd><dt><strong>Banana</strong></dt><dd>Yellow</dd></dl>",
+    );
+}
+
+#[test]
+fn date() {
+    use crate::render::date::DateSettings;
+
+    let result = SyntaxTree::from_element_result(
+        vec![
+            Element::Date {
+                timestamp: 1_600_000_000,
+                format: None,
+            },
+            Element::Date {
+                timestamp: 1_599_740_800,
+                format: Some(cow!("%O")),
+            },
+        ],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    let output = HtmlRender {
+        date_settings: DateSettings {
+            now: Some(1_600_000_000),
+            utc_offset: 60 * 60,
+        },
+        ..HtmlRender::default()
+    }
+    .render(&tree);
+
+    assert_eq!(
+        output.html,
+        concat!(
+            r#"<span class="odate" data-timestamp="1600000000">13 Sep 2020, 13:26</span>"#,
+            r#"<span class="odate" data-timestamp="1599740800">3 days ago</span>"#,
+        ),
+        "Rendered HTML doesn't match expected",
+    );
+}
+
+#[test]
+fn poem() {
+    let result = SyntaxTree::from_element_result(
+        vec![Element::Poem {
+            elements: vec![text!("  Apple"), Element::LineBreak, text!("Banana")],
+        }],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+    let output = HtmlRender::default().render(&tree);
+
+    assert_eq!(
+        output.html,
+        r#"<p class="poem" style="white-space: pre-wrap;">  Apple<br>Banana</p>"#,
+        "Rendered HTML doesn't match expected",
+    );
+}
+
+#[test]
+fn table_of_contents() {
+    use crate::enums::HeadingLevel;
+
+    let heading = |level, text| {
+        Element::Container(Container::new(
+            ContainerType::Header(level),
+            vec![Element::Text(cow!(text))],
+        ))
+    };
+
+    test!(
+        vec![
+            Element::TableOfContents {
+                depth: Some(HeadingLevel::Two),
+                float: Some(FloatAlignment::Right),
+            },
+            heading(HeadingLevel::One, "Apple"),
+            heading(HeadingLevel::Three, "Banana"),
+            heading(HeadingLevel::Two, "Cherry"),
+        ],
+        concat!(
+            r#"<div class="toc" style="float: right;">"#,
+            r#"<div class="toc-title">Table of Contents</div>"#,
+            r#"<div class="toc-list">"#,
+            r##"<div class="toc-item" style="margin-left: 1em;"><a href="#toc0">Apple</a></div>"##,
+            r##"<div class="toc-item" style="margin-left: 2em;"><a href="#toc2">Cherry</a></div>"##,
+            r#"</div></div>"#,
+            r#"<h1 id="toc0">Apple</h1><h3 id="toc1">Banana</h3><h2 id="toc2">Cherry</h2>"#,
+        ),
+    );
+}
+
+#[test]
+fn heading_numbering() {
+    use super::HeadingNumbering;
+    use crate::enums::HeadingLevel;
+
+    let heading = |ctype, text| {
+        Element::Container(Container::new(ctype, vec![Element::Text(cow!(text))]))
+    };
+
+    let result = SyntaxTree::from_element_result(
+        vec![
+            Element::TableOfContents {
+                depth: None,
+                float: None,
+            },
+            heading(ContainerType::Header(HeadingLevel::One), "Apple"),
+            heading(ContainerType::UnlistedHeader(HeadingLevel::Two), "Banana"),
+            heading(ContainerType::Header(HeadingLevel::Two), "Cherry"),
+        ],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    macro_rules! check {
+        ($numbering:expr, $expected:expr $(,)?) => {{
+            let renderer = HtmlRender {
+                heading_numbering: $numbering,
+                ..HtmlRender::default()
+            };
+            let output = renderer.render(&tree);
+
+            assert_eq!(
+                output.html, $expected,
+                "Rendered HTML doesn't match expected",
+            );
+        }};
+    }
+
+    check!(
+        HeadingNumbering::Listed,
+        concat!(
+            r#"<div class="toc"><div class="toc-title">Table of Contents</div><div class="toc-list">"#,
+            r##"<div class="toc-item" style="margin-left: 1em;"><a href="#toc0"><span class="heading-number">1.</span> Apple</a></div>"##,
+            r##"<div class="toc-item" style="margin-left: 2em;"><a href="#toc1"><span class="heading-number">1.1</span> Cherry</a></div>"##,
+            r#"</div></div>"#,
+            r#"<h1 id="toc0"><span class="heading-number">1.</span> Apple</h1>"#,
+            r#"<h2>Banana</h2>"#,
+            r#"<h2 id="toc1"><span class="heading-number">1.1</span> Cherry</h2>"#,
+        ),
+    );
+
+    check!(
+        HeadingNumbering::All,
+        concat!(
+            r#"<div class="toc"><div class="toc-title">Table of Contents</div><div class="toc-list">"#,
+            r##"<div class="toc-item" style="margin-left: 1em;"><a href="#toc0"><span class="heading-number">1.</span> Apple</a></div>"##,
+            r##"<div class="toc-item" style="margin-left: 2em;"><a href="#toc1"><span class="heading-number">1.2</span> Cherry</a></div>"##,
+            r#"</div></div>"#,
+            r#"<h1 id="toc0"><span class="heading-number">1.</span> Apple</h1>"#,
+            r#"<h2><span class="heading-number">1.1</span> Banana</h2>"#,
+            r#"<h2 id="toc1"><span class="heading-number">1.2</span> Cherry</h2>"#,
+        ),
+    );
+}
+
+#[test]
+fn partial() {
+    use crate::enums::HeadingLevel;
+    use crate::tree::{ElementPath, PathStep};
+
+    let heading = |level, text| {
+        Element::Container(Container::new(
+            ContainerType::Header(level),
+            vec![Element::Text(cow!(text))],
+        ))
+    };
+
+    let paragraph =
+        |elements| Element::Container(Container::new(ContainerType::Paragraph, elements));
+
+    let result = SyntaxTree::from_element_result(
+        vec![
+            paragraph(vec![heading(HeadingLevel::One, "Apple"), text!("Banana")]),
+            paragraph(vec![
+                heading(HeadingLevel::Two, "Cherry"),
+                text!("Durian"),
+                Element::HorizontalRule,
+            ]),
+        ],
+        vec![],
+        vec![cow!("p { color: red; }")],
+    );
+    let (tree, _) = result.into();
+    let renderer = HtmlRender::default();
+
+    let path = |steps: &[(usize, usize)]| {
+        let steps: Vec<_> = steps
+            .iter()
+            .map(|&(list, index)| PathStep { list, index })
+            .collect();
+
+        ElementPath::from(steps)
+    };
+
+    let output = renderer
+        .render_path(&tree, &path(&[(0, 1), (0, 0)]))
+        .expect("Path doesn't resolve");
+
+    assert_eq!(output.html, r#"<h2 id="toc1">Cherry</h2>"#);
+    assert_eq!(output.style, "p { color: red; }");
+
+    let output = renderer
+        .render_range(&tree, &path(&[(0, 1), (0, 1)]), 5)
+        .expect("Path doesn't resolve");
+
+    assert_eq!(output.html, "Durian<hr>");
+    assert!(renderer.render_path(&tree, &path(&[(0, 2)])).is_none());
+}
+
+#[test]
+fn footnotes() {
+    let footnote = |text| Element::Footnote {
+        elements: vec![text!(text)],
+    };
+
+    test!(
+        vec![
+            Element::Container(Container::new(
+                ContainerType::Paragraph,
+                vec![
+                    text!("Apple"),
+                    footnote("Banana"),
+                    text!(" cherry"),
+                    footnote("Banana"),
+                    footnote("Durian"),
+                ],
+            )),
+            Element::FootnoteBlock {
+                title: Some(cow!("Notes")),
+                hide: false,
+            },
+        ],
+        concat!(
+            "<p>Apple",
+            r#"<sup class="footnoteref">"#,
+            r##"<a id="footnoteref-1" class="footnoteref" href="#footnote-1">1</a></sup>"##,
+            " cherry",
+            r#"<sup class="footnoteref">"#,
+            r##"<a id="footnoteref-1-2" class="footnoteref" href="#footnote-1">1</a></sup>"##,
+            r#"<sup class="footnoteref">"#,
+            r##"<a id="footnoteref-2" class="footnoteref" href="#footnote-2">2</a></sup>"##,
+            "</p>",
+            r#"<div class="footnotes-footer"><div class="title">Notes</div>"#,
+            r#"<div class="footnote-footer" id="footnote-1">"#,
+            r##"<a href="#footnoteref-1">1</a>. Banana "##,
+            r##"<a class="footnote-backref" href="#footnoteref-1-2">↑</a></div>"##,
+            r#"<div class="footnote-footer" id="footnote-2">"#,
+            r##"<a href="#footnoteref-2">2</a>. Durian</div>"##,
+            "</div>",
+        ),
+    );
+
+    // Without a footnote block, they are listed at the end
+    test!(
+        vec![footnote("Apple")],
+        concat!(
+            r#"<sup class="footnoteref">"#,
+            r##"<a id="footnoteref-1" class="footnoteref" href="#footnote-1">1</a></sup>"##,
+            r#"<div class="footnotes-footer"><div class="title">Footnotes</div>"#,
+            r#"<div class="footnote-footer" id="footnote-1">"#,
+            r##"<a href="#footnoteref-1">1</a>. Apple</div>"##,
+            "</div>",
+        ),
+    );
+
+    test!(
+        vec![
+            footnote("Apple"),
+            Element::FootnoteBlock {
+                title: None,
+                hide: true,
+            },
+        ],
+        concat!(
+            r#"<sup class="footnoteref">"#,
+            r##"<a id="footnoteref-1" class="footnoteref" href="#footnote-1">1</a></sup>"##,
+        ),
+    );
+}
+
+#[test]
+fn bibliography() {
+    use crate::tree::{resolve_bibliography, BibliographyEntry};
+
+    let cite = |label| Element::BibliographyCite {
+        label: cow!(label),
+        number: None,
+    };
+
+    let mut elements = vec![
+        Element::Container(Container::new(
+            ContainerType::Paragraph,
+            vec![text!("Apple "), cite("banana"), text!(" "), cite("cherry")],
+        )),
+        Element::Bibliography {
+            title: Some(cow!("Sources")),
+            hide: false,
+            entries: vec![BibliographyEntry::new(
+                cow!("banana"),
+                vec![text!("Banana, 1998")],
+            )],
+        },
+    ];
+
+    resolve_bibliography(&mut elements);
+
+    test!(
+        elements,
+        concat!(
+            r##"<p>Apple <a class="bibcite" href="#bibitem-1">[1]</a> "##,
+            r#"<span class="bibcite">[??]</span></p>"#,
+            r#"<div class="bibitems"><div class="title">Sources</div>"#,
+            r#"<div class="bibitem" id="bibitem-1">1. Banana, 1998</div>"#,
+            "</div>",
+        ),
+    );
+}
+
+#[test]
+fn counter() {
+    use crate::render::{RenderWarning, RenderWarningKind};
+    use crate::tree::{resolve_counters, Counter, CounterAction};
+
+    let mut elements = vec![
+        Element::Counter(Counter::new(
+            cow!("figure"),
+            Some(cow!("tree")),
+            CounterAction::Increment(1),
+        )),
+        Element::CounterRef {
+            id: cow!("tree"),
+            value: None,
+        },
+        Element::CounterRef {
+            id: cow!("missing"),
+            value: None,
+        },
+    ];
+
+    resolve_counters(&mut elements);
+
+    let result = SyntaxTree::from_element_result(elements, vec![], vec![]);
+    let (tree, _) = result.into();
+
+    let output = HtmlRender::default().render(&tree);
+    assert_eq!(
+        output.html,
+        concat!(
+            r#"<span class="counter" id="counter-tree">1</span>"#,
+            r##"<a class="counter-ref" href="#counter-tree">1</a>"##,
+            r#"<span class="counter-ref">??</span>"#,
+        ),
+        "Rendered HTML doesn't match expected",
+    );
+    assert_eq!(
+        output.warnings,
+        vec![RenderWarning::new(
+            RenderWarningKind::UnknownCounter,
+            "missing"
+        )],
+        "Render warnings don't match expected",
+    );
+}
+
+#[test]
+fn diagram() {
+    test!(
+        vec![Element::Diagram {
+            kind: DiagramKind::Mermaid,
+            contents: cow!("graph TD; A-->B;"),
+        }],
+        r#"<pre class="mermaid">graph TD; A--&gt;B;</pre>"#,
+    );
+
+    let result = SyntaxTree::from_element_result(
+        vec![Element::Diagram {
+            kind: DiagramKind::Chart,
+            contents: cow!("{}"),
+        }],
+        vec![],
+        vec![],
+    );
     let (tree, _) = result.into();
-    if false {
-        let _output = HtmlRender.render(&tree);
+    let renderer = HtmlRender {
+        diagram_output: DiagramOutput::DataAttribute,
+        ..HtmlRender::default()
+    };
+
+    assert_eq!(
+        renderer.render(&tree).html,
+        r#"<div class="diagram" data-kind="chart" data-source="{}"></div>"#,
+        "Rendered HTML doesn't match expected",
+    );
+}
+
+#[test]
+fn math() {
+    use super::MathRenderer;
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct TestMath;
+
+    impl MathRenderer for TestMath {
+        fn render_mathml(&self, latex: &str, inline: bool) -> Option<String> {
+            if latex == "x" {
+                let display = if inline { "inline" } else { "block" };
+                Some(format!(r#"<math display="{}"><mi>x</mi></math>"#, display))
+            } else {
+                None
+            }
+        }
     }
+
+    let elements = || {
+        vec![
+            Element::Math {
+                latex: cow!("a < b"),
+                inline: true,
+            },
+            Element::Math {
+                latex: cow!("x"),
+                inline: false,
+            },
+        ]
+    };
+
+    test!(
+        elements(),
+        r#"<span class="math">a &lt; b</span><div class="math">x</div>"#,
+    );
+
+    let result = SyntaxTree::from_element_result(elements(), vec![], vec![]);
+    let (tree, _) = result.into();
+    let renderer = HtmlRender {
+        math_renderer: Some(Arc::new(TestMath)),
+        ..HtmlRender::default()
+    };
+
+    assert_eq!(
+        renderer.render(&tree).html,
+        r#"<span class="math">a &lt; b</span><math display="block"><mi>x</mi></math>"#,
+        "Rendered HTML doesn't match expected",
+    );
+}
+
+#[test]
+fn deterministic() {
+    use crate::tree::{StyledContainer, StyledContainerType, Tab};
+
+    let result = SyntaxTree::from_element_result(
+        vec![
+            Element::StyledContainer(StyledContainer::new(
+                StyledContainerType::Span,
+                vec![text!("Apple")],
+                Some(cow!("fruit")),
+                Some(cow!("red")),
+                Some(cow!("color: red;")),
+                None,
+                None,
+            )),
+            Element::TabView(vec![Tab::new(
+                vec![text!("Banana")],
+                vec![text!("Cherry")],
+                None,
+                None,
+            )]),
+        ],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    let renderer = HtmlRender {
+        deterministic: true,
+        ..HtmlRender::default()
+    };
+
+    let html = renderer.render(&tree).html;
+    assert!(
+        html.starts_with(r#"<span class="red" id="fruit" style="color: red;">Apple</span><div class="yui-navset" id="wiki-tabview-0">"#),
+        "Rendered HTML doesn't match expected: {}",
+        html,
+    );
+
+    assert_eq!(
+        html,
+        renderer.render(&tree).html,
+        "Rendering the same tree twice produced different HTML",
+    );
+}
+
+#[test]
+fn accessibility() {
+    use crate::tree::Tab;
+
+    let result = SyntaxTree::from_element_result(
+        vec![
+            Element::Collapsible {
+                elements: vec![text!("Apple")],
+                id: None,
+                class: None,
+                style: None,
+                start_open: false,
+                show_text: None,
+                hide_text: None,
+                show_top: true,
+                show_bottom: false,
+            },
+            Element::TabView(vec![
+                Tab::new(vec![text!("Banana")], vec![text!("Cherry")], None, None),
+                Tab::new(
+                    vec![text!("Durian")],
+                    vec![text!("Fig")],
+                    Some(cow!("fig")),
+                    None,
+                ),
+            ]),
+        ],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    let renderer = HtmlRender {
+        accessibility: true,
+        ..HtmlRender::default()
+    };
+
+    assert_eq!(
+        renderer.render(&tree).html,
+        concat!(
+            r#"<div class="collapsible-block">"#,
+            r#"<div class="collapsible-block-folded">"#,
+            r#"<a class="collapsible-block-link" href="javascript:;" role="button" aria-expanded="false">+ show block</a>"#,
+            r#"</div>"#,
+            r#"<div class="collapsible-block-unfolded" style="display: none;">"#,
+            r#"<div class="collapsible-block-unfolded-link">"#,
+            r#"<a class="collapsible-block-link" href="javascript:;" role="button" aria-expanded="true">- hide block</a>"#,
+            r#"</div>"#,
+            r#"<div class="collapsible-block-content">Apple</div>"#,
+            r#"</div></div>"#,
+            r#"<div id="wiki-tabview-0" class="yui-navset">"#,
+            r#"<ul class="yui-nav" role="tablist">"#,
+            r#"<li class="selected" role="presentation">"#,
+            r#"<a href="javascript:;" id="wiki-tabview-0-tab-0" role="tab" aria-selected="true" aria-controls="wiki-tabview-0-panel-0"><em>Banana</em></a>"#,
+            r#"</li>"#,
+            r#"<li role="presentation">"#,
+            r#"<a href="javascript:;" id="wiki-tabview-0-tab-1" role="tab" aria-selected="false" aria-controls="fig"><em>Durian</em></a>"#,
+            r#"</li></ul>"#,
+            r#"<div class="yui-content">"#,
+            r#"<div id="wiki-tabview-0-panel-0" role="tabpanel" aria-labelledby="wiki-tabview-0-tab-0">Cherry</div>"#,
+            r#"<div id="fig" role="tabpanel" aria-labelledby="wiki-tabview-0-tab-1" style="display: none;">Fig</div>"#,
+            r#"</div></div>"#,
+        ),
+        "Rendered HTML doesn't match expected",
+    );
+
+    let result = SyntaxTree::from_element_result(
+        vec![Element::Footnote {
+            elements: vec![text!("Apple")],
+        }],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    assert_eq!(
+        renderer.render(&tree).html,
+        concat!(
+            r#"<sup class="footnoteref">"#,
+            r##"<a id="footnoteref-1" class="footnoteref" href="#footnote-1" role="doc-noteref" aria-describedby="footnote-1">1</a></sup>"##,
+            r#"<div class="footnotes-footer" role="doc-endnotes"><div class="title">Footnotes</div>"#,
+            r#"<div class="footnote-footer" id="footnote-1">"#,
+            r##"<a href="#footnoteref-1" role="doc-backlink">1</a>. Apple</div>"##,
+            "</div>",
+        ),
+        "Rendered footnote HTML doesn't match expected",
+    );
+}
+
+#[test]
+fn iframe() {
+    use crate::data::SiteSettings;
+    use crate::render::{RenderWarning, RenderWarningKind};
+    use std::sync::Arc;
+
+    let result = SyntaxTree::from_element_result(
+        vec![
+            Element::Iframe {
+                url: cow!("https://www.example.com/embed"),
+                width: Some(cow!("100%")),
+                height: None,
+                class: None,
+                style: None,
+            },
+            Element::Iframe {
+                url: cow!("https://example.org/embed"),
+                width: None,
+                height: None,
+                class: None,
+                style: None,
+            },
+        ],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    let mut site = SiteSettings::default();
+    site.embed_domains.push(str!("example.com"));
+
+    let renderer = HtmlRender {
+        site: Arc::new(site),
+        ..HtmlRender::default()
+    };
+
+    let output = renderer.render(&tree);
+    assert_eq!(
+        output.html,
+        concat!(
+            r#"<iframe src="https://www.example.com/embed" width="100%"></iframe>"#,
+            r#"<span class="error-inline">Embedding this URL is not permitted: https://example.org/embed</span>"#,
+        ),
+        "Rendered HTML doesn't match expected",
+    );
+    assert_eq!(
+        output.warnings,
+        vec![RenderWarning::new(
+            RenderWarningKind::BlockedEmbed,
+            "https://example.org/embed",
+        )],
+        "Render warnings don't match expected",
+    );
+}
+
+#[test]
+fn author_trust() {
+    use crate::data::{AuthorTrust, SiteSettings};
+    use crate::render::{RenderWarning, RenderWarningKind};
+    use crate::tree::Module;
+    use std::sync::Arc;
+
+    let result = SyntaxTree::from_element_result(
+        vec![
+            Element::Iframe {
+                url: cow!("https://example.com/embed"),
+                width: None,
+                height: None,
+                class: None,
+                style: None,
+            },
+            Element::Module(Module::MailForm {
+                to: cow!("staff@example.com"),
+                title: None,
+                button_text: None,
+                success_page: None,
+                fields: vec![],
+            }),
+        ],
+        vec![],
+        vec![cow!("body { display: none; }")],
+    );
+    let (tree, _) = result.into();
+
+    let mut site = SiteSettings::default();
+    site.embed_domains.push(str!("example.com"));
+    let site = Arc::new(site);
+
+    let output = HtmlRender::for_author(AuthorTrust::Trusted, &site).render(&tree);
+    assert_eq!(
+        output.html,
+        concat!(
+            r#"<iframe src="https://example.com/embed"></iframe>"#,
+            r#"<form class="mailform" data-inert="true"><button type="submit" disabled="">Send</button></form>"#,
+        ),
+        "Rendered HTML doesn't match expected",
+    );
+    assert_eq!(output.style, "body { display: none; }");
+    assert!(output.warnings.is_empty(), "Unexpected render warnings");
+
+    // Untrusted pages may not embed, style the site, or use modules
+    let output = HtmlRender::for_author(AuthorTrust::Untrusted, &site).render(&tree);
+    assert_eq!(
+        output.html,
+        r#"<span class="error-inline">Embedding this URL is not permitted: https://example.com/embed</span>"#,
+        "Rendered HTML doesn't match expected",
+    );
+    assert_eq!(output.style, "");
+    assert_eq!(
+        output.warnings,
+        vec![RenderWarning::new(
+            RenderWarningKind::BlockedEmbed,
+            "https://example.com/embed",
+        )],
+        "Render warnings don't match expected",
+    );
+}
+
+#[test]
+fn color() {
+    use crate::render::{RenderWarning, RenderWarningKind};
+
+    let result = SyntaxTree::from_element_result(
+        vec![
+            Element::Color {
+                color: cow!("#ccc"),
+                elements: vec![text!("Apple")],
+            },
+            Element::Color {
+                color: cow!("red; background: blue"),
+                elements: vec![text!("Banana")],
+            },
+        ],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+    let output = HtmlRender::default().render(&tree);
+
+    assert_eq!(
+        output.html, r#"<span style="color: #ccc;">Apple</span><span>Banana</span>"#,
+        "Rendered HTML doesn't match expected",
+    );
+    assert_eq!(
+        output.warnings,
+        vec![RenderWarning::new(
+            RenderWarningKind::InvalidColor,
+            "red; background: blue",
+        )],
+        "Render warnings don't match expected",
+    );
+}
+
+#[test]
+fn module() {
+    use crate::render::{RenderWarning, RenderWarningKind};
+    use std::sync::Arc;
+
+    let handle = TestHandle {
+        modules: hashmap! { "Rate" => "<div class=\"rate\"></div>" },
+        ..TestHandle::default()
+    };
+
+    let result = SyntaxTree::from_element_result(
+        vec![Element::Module(Module::Rate), Element::Module(Module::Null)],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    let renderer = HtmlRender {
+        handle: Some(Arc::new(handle)),
+        ..HtmlRender::default()
+    };
+
+    let output = renderer.render(&tree);
+    assert_eq!(
+        output.html, r#"<div class="rate"></div>"#,
+        "Rendered HTML doesn't match expected",
+    );
+    assert_eq!(
+        output.warnings,
+        vec![RenderWarning::new(
+            RenderWarningKind::ModuleFailed,
+            "Null: lookup failed",
+        )],
+        "Render warnings don't match expected",
+    );
+}
+
+#[test]
+fn page_meta() {
+    use super::PageMeta;
+    use crate::enums::{AnchorTarget, LinkLabel};
+    use crate::render::{RenderWarning, RenderWarningKind};
+    use std::sync::Arc;
+
+    let scp_001 = PageMeta {
+        exists: true,
+        title: Some(str!("SCP-001")),
+        tags: vec![str!("scp")],
+        rating: Some(100.0),
+    };
+
+    let handle = TestHandle {
+        pages: Some(hashmap! {
+            "scp-001" => scp_001,
+            "missing" => PageMeta::default(),
+        }),
+        ..TestHandle::default()
+    };
+
+    let link = |url, label| Element::Link {
+        url: cow!(url),
+        label,
+        anchor: AnchorTarget::Same,
+    };
+
+    let result = SyntaxTree::from_element_result(
+        vec![
+            link("scp-001", LinkLabel::Page),
+            link("/missing", LinkLabel::Text(cow!("Apple"))),
+            link("https://example.com/", LinkLabel::Url),
+            link("other", LinkLabel::Page),
+        ],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    let renderer = HtmlRender {
+        handle: Some(Arc::new(handle)),
+        ..HtmlRender::default()
+    };
+
+    let output = renderer.render(&tree);
+    assert_eq!(
+        output.html,
+        concat!(
+            r#"<a href="scp-001">SCP-001</a>"#,
+            r#"<a href="/missing" class="newpage">Apple</a>"#,
+            r#"<a href="https://example.com/">https://example.com/</a>"#,
+            r#"<a href="other">other</a>"#,
+        ),
+        "Rendered HTML doesn't match expected",
+    );
+    assert_eq!(
+        output.warnings,
+        vec![RenderWarning::new(
+            RenderWarningKind::PageMetaFailed,
+            "other: lookup failed",
+        )],
+        "Render warnings don't match expected",
+    );
+}
+
+#[test]
+fn memoize() {
+    use crate::includes::PageRef;
+    use crate::tree::Tab;
+    use std::collections::HashMap;
+
+    let include = |elements| Element::Include {
+        page: PageRef::page_only(cow!("component:acs")),
+        variables: HashMap::new(),
+        elements,
+    };
+
+    let acs = || {
+        vec![Element::Color {
+            color: cow!("red; x: y"),
+            elements: vec![text!("Keter")],
+        }]
+    };
+
+    let tabs = || {
+        vec![Element::TabView(vec![Tab::new(
+            vec![text!("Apple")],
+            vec![text!("Banana")],
+            None,
+            None,
+        )])]
+    };
+
+    let result = SyntaxTree::from_element_result(
+        vec![
+            include(acs()),
+            include(acs()),
+            include(tabs()),
+            include(tabs()),
+        ],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    let regular = HtmlRender::default().render(&tree);
+    let memoized = HtmlRender {
+        memoize: true,
+        ..HtmlRender::default()
+    }
+    .render(&tree);
+
+    assert_eq!(
+        regular.html, memoized.html,
+        "Memoized HTML doesn't match regular rendering",
+    );
+    assert_eq!(
+        regular.warnings, memoized.warnings,
+        "Memoized warnings don't match regular rendering",
+    );
+    assert_eq!(memoized.warnings.len(), 2, "Warnings not replayed on reuse");
+    assert!(
+        memoized.html.contains("wiki-tabview-1"),
+        "Subtree with generated IDs was reused",
+    );
+}
+
+#[test]
+fn feed() {
+    use super::FeedItem;
+    use std::sync::Arc;
+
+    let item = |title: &str, link: &str| FeedItem {
+        title: str!(title),
+        link: str!(link),
+        ..FeedItem::default()
+    };
+
+    let handle = TestHandle {
+        feed: Some(vec![
+            item("Apple & Banana", "https://example.com/apple"),
+            item("Cherry", "javascript:alert(1)"),
+            item("Durian", "https://example.com/durian"),
+        ]),
+        ..TestHandle::default()
+    };
+
+    let result = SyntaxTree::from_element_result(
+        vec![Element::Module(Module::Feed {
+            src: cow!("https://example.com/feed.xml"),
+            limit: NonZeroU32::new(2),
+            template: None,
+        })],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    let output = HtmlRender {
+        handle: Some(Arc::new(handle)),
+        ..HtmlRender::default()
+    }
+    .render(&tree);
+
+    assert_eq!(
+        output.html,
+        concat!(
+            r#"<ul class="feed">"#,
+            r#"<li class="feed-item"><a href="https://example.com/apple">Apple &amp; Banana</a></li>"#,
+            r#"<li class="feed-item">Cherry</li>"#,
+            "</ul>",
+        ),
+        "Rendered HTML doesn't match expected",
+    );
+}
+
+#[test]
+fn list_pages() {
+    use super::ListedPage;
+    use crate::parsing::ParseSettings;
+    use crate::tree::ListPagesOrder;
+    use std::sync::Arc;
+
+    let page = |fullname: &str, title: &str| ListedPage {
+        fullname: str!(fullname),
+        title: str!(title),
+        ..ListedPage::default()
+    };
+
+    let handle = Arc::new(TestHandle {
+        listed_pages: Some(vec![
+            page("fruit:apple", "Apple & Banana"),
+            page("cherry", "Cherry"),
+            page("javascript:alert(1)", "Durian"),
+            page("fig", "Fig"),
+        ]),
+        ..TestHandle::default()
+    });
+
+    let query = ListPagesQuery {
+        categories: vec![],
+        excluded_categories: vec![],
+        required_tags: vec![cow!("fruit")],
+        any_tags: vec![],
+        excluded_tags: vec![],
+        order: ListPagesOrder::default(),
+        limit: NonZeroU32::new(3),
+        per_page: None,
+        offset: 0,
+    };
+
+    let result = SyntaxTree::from_element_result(
+        vec![Element::Module(Module::ListPages {
+            query: query.clone(),
+            template: None,
+        })],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    let output = HtmlRender {
+        handle: Some(handle.clone()),
+        ..HtmlRender::default()
+    }
+    .render(&tree);
+
+    assert_eq!(
+        output.html,
+        concat!(
+            r#"<ul class="list-pages">"#,
+            r#"<li class="list-pages-item"><a href="/fruit:apple">Apple &amp; Banana</a></li>"#,
+            r#"<li class="list-pages-item"><a href="/cherry">Cherry</a></li>"#,
+            r#"<li class="list-pages-item">Durian</li>"#,
+            "</ul>",
+        ),
+        "Rendered HTML doesn't match expected",
+    );
+
+    // Templates are parsed with the page's settings
+    let tree = SyntaxTree {
+        elements: vec![Element::Module(Module::ListPages {
+            query: query.clone(),
+            template: Some(cow!("+ %%title%%")),
+        })],
+        styles: vec![],
+        sections: vec![],
+    };
+
+    let output = HtmlRender {
+        handle: Some(handle.clone()),
+        parse_settings: ParseSettings {
+            heading_shift: 1,
+            ..ParseSettings::default()
+        },
+        ..HtmlRender::default()
+    }
+    .render(&tree);
+
+    assert!(
+        output.html.contains("<h2") && !output.html.contains("<h1"),
+        "Template headings were not shifted: {}",
+        output.html,
+    );
+
+    // Including which blocks are disabled
+    let tree = SyntaxTree {
+        elements: vec![Element::Module(Module::ListPages {
+            query,
+            template: Some(cow!("[[span]]%%title%%[[/span]]")),
+        })],
+        styles: vec![],
+        sections: vec![],
+    };
+
+    let output = HtmlRender {
+        handle: Some(handle),
+        parse_settings: ParseSettings {
+            disabled_blocks: vec![str!("span")],
+            ..ParseSettings::default()
+        },
+        log: Some(crate::build_logger()),
+        ..HtmlRender::default()
+    }
+    .render(&tree);
+
+    assert!(
+        output.html.contains("[[span]]") && !output.html.contains("<span"),
+        "Template used a disabled block: {}",
+        output.html,
+    );
+}
+
+#[test]
+fn mail_form() {
+    use crate::tree::{FormField, FormFieldType, FormRule, Module};
+
+    let mut name = FormField::new(cow!("name"));
+    name.title = Some(cow!("Your name"));
+    name.rules = vec![FormRule::Required, FormRule::MaxLength(40)];
+
+    let mut topic = FormField::new(cow!("topic"));
+    topic.field_type = FormFieldType::Select;
+    topic.default = Some(cow!("b"));
+    topic.options = vec![(cow!("a"), cow!("Apple")), (cow!("b"), cow!("Banana"))];
+
+    let result = SyntaxTree::from_element_result(
+        vec![Element::Module(Module::MailForm {
+            to: cow!("editor@example.com"),
+            title: Some(cow!("Contact")),
+            button_text: None,
+            success_page: None,
+            fields: vec![name, topic],
+        })],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    // No handler, so the form cannot be submitted
+    let output = HtmlRender::default().render(&tree);
+
+    assert_eq!(
+        output.html,
+        concat!(
+            r#"<form class="mailform" data-inert="true">"#,
+            r#"<div class="mailform-title">Contact</div>"#,
+            r#"<div class="mailform-field" data-name="name" data-type="text" data-rule-required="true" data-rule-max-length="40">"#,
+            r#"<label for="mailform-0">Your name</label>"#,
+            r#"<input type="text" id="mailform-0" name="name" required="" maxlength="40">"#,
+            "</div>",
+            r#"<div class="mailform-field" data-name="topic" data-type="select">"#,
+            r#"<label for="mailform-1">topic</label>"#,
+            r#"<select id="mailform-1" name="topic">"#,
+            r#"<option value="a">Apple</option>"#,
+            r#"<option value="b" selected="">Banana</option>"#,
+            "</select>",
+            "</div>",
+            r#"<button type="submit" disabled="">Send</button>"#,
+            "</form>",
+        ),
+        "Rendered HTML doesn't match expected",
+    );
+}
+
+#[test]
+fn untrusted_links() {
+    use crate::enums::{AnchorTarget, LinkLabel};
+    use crate::render::{RenderWarning, RenderWarningKind};
+    use crate::tree::{Image, ImageSource};
+
+    let link = |url, label| Element::Link {
+        url: cow!(url),
+        label,
+        anchor: AnchorTarget::Same,
+    };
+
+    let result = SyntaxTree::from_element_result(
+        vec![
+            link("javascript:alert(1)", LinkLabel::Text(cow!("Apple"))),
+            Element::Anchor {
+                elements: vec![text!("Banana")],
+                url: cow!("data:text/html,<script>alert(1)</script>"),
+                anchor: AnchorTarget::Same,
+                rel: None,
+                id: None,
+                class: None,
+                style: None,
+            },
+            link("java\tscript:alert(1)", LinkLabel::Text(cow!("Cherry"))),
+            Element::Image(Box::new(Image {
+                link: Some(cow!("JavaScript:alert(1)")),
+                ..Image::new(ImageSource::Url(cow!("https://example.com/durian.png")))
+            })),
+            link("fruit:fig", LinkLabel::Url),
+            link("https://example.com/", LinkLabel::Url),
+            link("mailto:admin@example.com", LinkLabel::Text(cow!("Email"))),
+            link("#grape", LinkLabel::Text(cow!("Grape"))),
+        ],
+        vec![],
+        vec![],
+    );
+    let (tree, _) = result.into();
+
+    // Trusted pages may link anywhere
+    let output = HtmlRender::default().render(&tree);
+    assert!(
+        output
+            .html
+            .starts_with(r#"<a href="javascript:alert(1)">Apple</a>"#),
+        "Trusted link was changed: {}",
+        output.html,
+    );
+
+    // Untrusted pages may not link to script
+    let output = HtmlRender {
+        safe_mode: true,
+        ..HtmlRender::default()
+    }
+    .render(&tree);
+
+    assert_eq!(
+        output.html,
+        concat!(
+            "<a>Apple</a>",
+            "<a>Banana</a>",
+            "<a>Cherry</a>",
+            r#"<img src="https://example.com/durian.png" alt="durian.png" class="image">"#,
+            r#"<a href="/fruit:fig">fruit:fig</a>"#,
+            r#"<a href="https://example.com/">https://example.com/</a>"#,
+            r#"<a href="mailto:admin@example.com">Email</a>"#,
+            r##"<a href="#grape">Grape</a>"##,
+        ),
+        "Rendered HTML doesn't match expected",
+    );
+
+    let blocked = |url| RenderWarning::new(RenderWarningKind::BlockedLink, url);
+    assert_eq!(
+        output.warnings,
+        vec![
+            blocked("javascript:alert(1)"),
+            blocked("data:text/html,<script>alert(1)</script>"),
+            blocked("java\tscript:alert(1)"),
+            blocked("JavaScript:alert(1)"),
+        ],
+        "Render warnings don't match expected",
+    );
 }
@@ -24,22 +24,34 @@
 //! output as is used in the AST tests at `src/test.rs`.
 
 use super::prelude::*;
+use crate::tree::MAX_SERIALIZED_DEPTH;
 
 #[derive(Debug)]
 pub struct JsonRender {
     /// Whether to use the human-readable JSON formatter or the minified formatter.
     pub pretty: bool,
+
+    /// How deeply elements may be nested in the output.
+    ///
+    /// Deeper elements are flattened into text, see `tree::limit_depth()`.
+    pub max_depth: usize,
 }
 
 impl JsonRender {
     #[inline]
     pub fn pretty() -> Self {
-        JsonRender { pretty: true }
+        JsonRender {
+            pretty: true,
+            max_depth: MAX_SERIALIZED_DEPTH,
+        }
     }
 
     #[inline]
     pub fn compact() -> Self {
-        JsonRender { pretty: false }
+        JsonRender {
+            pretty: false,
+            max_depth: MAX_SERIALIZED_DEPTH,
+        }
     }
 }
 
@@ -53,6 +65,12 @@ impl Render for JsonRender {
             serde_json::to_string
         };
 
+        if tree.depth() > self.max_depth {
+            let mut tree = tree.clone();
+            tree.limit_depth(self.max_depth);
+            return writer(&tree).expect("Unable to serialize JSON");
+        }
+
         writer(tree).expect("Unable to serialize JSON")
     }
 }
@@ -0,0 +1,97 @@
+/*
+ * render/markdown/escape.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Escaping for text placed in Markdown or in its embedded HTML.
+
+/// Escapes characters which would otherwise be interpreted as Markdown syntax.
+///
+/// This covers inline syntax anywhere in the text. Syntax which only applies
+/// at the start of a line is handled separately by `escape_line_start`.
+pub fn escape(text: &str) -> String {
+    let mut buffer = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' | '&' => {
+                buffer.push('\\');
+                buffer.push(ch);
+            }
+            _ => buffer.push(ch),
+        }
+    }
+
+    buffer
+}
+
+/// Escapes a line which would otherwise begin a list item or setext heading.
+///
+/// Headings, quotes, and code spans are already escaped by `escape`.
+pub fn escape_line_start(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+
+    // Bullets and setext underlines
+    if let Some(ch) = trimmed.chars().next() {
+        if matches!(ch, '-' | '+' | '=') {
+            return format!("{}\\{}", indent, trimmed);
+        }
+    }
+
+    // Numbered list markers, such as "1." or "1)"
+    let digits = trimmed.bytes().take_while(u8::is_ascii_digit).count();
+    if digits > 0 {
+        if let Some(b'.') | Some(b')') = trimmed.as_bytes().get(digits) {
+            let (number, rest) = trimmed.split_at(digits);
+            return format!("{}{}\\{}", indent, number, rest);
+        }
+    }
+
+    str!(line)
+}
+
+/// Escapes text for use in an HTML attribute or element.
+pub fn escape_html(text: &str) -> String {
+    let mut buffer = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '&' => buffer.push_str("&amp;"),
+            '<' => buffer.push_str("&lt;"),
+            '>' => buffer.push_str("&gt;"),
+            '"' => buffer.push_str("&quot;"),
+            _ => buffer.push(ch),
+        }
+    }
+
+    buffer
+}
+
+#[test]
+fn escaping() {
+    assert_eq!(escape("a *b* [c]"), "a \\*b\\* \\[c\\]");
+    assert_eq!(escape("plain text"), "plain text");
+    assert_eq!(escape_line_start("- item"), "\\- item");
+    assert_eq!(escape_line_start("  10. item"), "  10\\. item");
+    assert_eq!(escape_line_start("2021 was"), "2021 was");
+    assert_eq!(
+        escape_html("<a href=\"x\">"),
+        "&lt;a href=&quot;x&quot;&gt;"
+    );
+}
@@ -0,0 +1,743 @@
+/*
+ * render/markdown/mod.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! A renderer which converts the syntax tree into CommonMark.
+//!
+//! This is intended for moving content to platforms which use Markdown.
+//! Formatting with a Markdown equivalent is converted to it, while constructs
+//! specific to Wikidot fall back to HTML fragments, which Markdown passes
+//! through. For instance, collapsibles and tabs become `<details>` elements.
+//! Elements with no static equivalent, such as modules, are left out.
+
+#[cfg(test)]
+mod test;
+
+mod escape;
+
+#[cfg(test)]
+use super::prelude;
+
+use self::escape::{escape, escape_html, escape_line_start};
+use super::date::{format_date, DateSettings};
+use super::prelude::*;
+use crate::data::Alignment;
+use crate::enums::{LinkLabel, ListStyle};
+use crate::tree::{
+    walk_elements_mut, BibliographyEntry, DefinitionListItem, Footnotes, ImageSource,
+    List, ListItem, StyledContainer, StyledContainerType, Tab, Table,
+};
+use std::borrow::Cow;
+
+/// The summary of a collapsible with no show text.
+const DEFAULT_SUMMARY: &str = "+ show block";
+
+#[derive(Debug)]
+pub struct MarkdownRender;
+
+impl Render for MarkdownRender {
+    type Output = String;
+
+    fn render(&self, tree: &SyntaxTree) -> String {
+        let footnotes = tree.footnotes();
+        let mut output = if footnotes.is_empty() {
+            render_blocks(&tree.elements)
+        } else {
+            render_blocks(&resolve_footnotes(&tree.elements, &footnotes))
+        };
+
+        if !output.is_empty() {
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+/// Collects the Markdown blocks produced by a list of elements.
+///
+/// Consecutive inline elements are gathered into a paragraph,
+/// which is ended whenever a block element is reached.
+#[derive(Debug, Default)]
+struct Blocks {
+    blocks: Vec<String>,
+    inline: String,
+    line_break: bool,
+}
+
+impl Blocks {
+    fn push_inline(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        // Deferred so that breaks at the end of a paragraph are dropped
+        if self.line_break {
+            self.inline.push_str("\\\n");
+            self.line_break = false;
+        }
+
+        self.inline.push_str(text);
+    }
+
+    fn push_line_break(&mut self) {
+        if !self.inline.is_empty() {
+            self.line_break = true;
+        }
+    }
+
+    fn push_block(&mut self, block: String) {
+        self.flush();
+
+        if !block.is_empty() {
+            self.blocks.push(block);
+        }
+    }
+
+    fn flush(&mut self) {
+        let paragraph = self.inline.trim();
+
+        if !paragraph.is_empty() {
+            let lines: Vec<_> = paragraph.lines().map(escape_line_start).collect();
+            self.blocks.push(lines.join("\n"));
+        }
+
+        self.inline.clear();
+        self.line_break = false;
+    }
+
+    fn finish(mut self, separator: &str) -> String {
+        self.flush();
+        self.blocks.join(separator)
+    }
+}
+
+/// Replaces footnotes with their numbers, and lists them at the footnote block.
+///
+/// CommonMark has no footnotes, so they become superscript references
+/// to a numbered list. If there is no footnote block, the list is added
+/// at the end of the page.
+fn resolve_footnotes<'t>(
+    elements: &[Element<'t>],
+    footnotes: &Footnotes<'_, 't>,
+) -> Vec<Element<'t>> {
+    let mut elements = elements.to_vec();
+    let mut references = footnotes.references.iter();
+
+    walk_elements_mut(&mut elements, &mut |element| {
+        if let Element::Footnote { .. } = element {
+            let number = references.next().map_or(0, |number| *number);
+
+            *element = Element::Container(Container::new(
+                ContainerType::Superscript,
+                vec![Element::Text(Cow::Owned(number.to_string()))],
+            ));
+        }
+    });
+
+    let items = footnotes
+        .contents
+        .iter()
+        .map(|contents| ListItem::new(contents.to_vec()))
+        .collect();
+
+    // Only the first footnote block lists the footnotes
+    let mut list = Some(Element::List(List::new(ListStyle::Numbered, items)));
+    let mut has_block = false;
+
+    walk_elements_mut(&mut elements, &mut |element| {
+        if let Element::FootnoteBlock { hide, .. } = *element {
+            *element = match list.take() {
+                Some(list) if !hide => list,
+                _ => Element::Null,
+            };
+
+            has_block = true;
+        }
+    });
+
+    if !has_block {
+        elements.extend(list);
+    }
+
+    elements
+}
+
+fn render_blocks(elements: &[Element]) -> String {
+    let mut blocks = Blocks::default();
+    render_flow(&mut blocks, elements);
+    blocks.finish("\n\n")
+}
+
+fn render_flow(blocks: &mut Blocks, elements: &[Element]) {
+    for element in elements {
+        match element {
+            Element::Container(container)
+                if container.ctype() == ContainerType::Paragraph =>
+            {
+                blocks.flush();
+                render_flow(blocks, container.elements());
+                blocks.flush();
+            }
+            Element::Include { elements, .. } | Element::NoTypography { elements } => {
+                render_flow(blocks, elements);
+            }
+            Element::LineBreak => blocks.push_line_break(),
+            Element::LineBreaks(amount) if amount.get() > 1 => blocks.flush(),
+            Element::LineBreaks(_) => blocks.push_line_break(),
+            _ if element.is_block() => blocks.push_block(render_block(element)),
+            _ => blocks.push_inline(&render_inline(element)),
+        }
+    }
+}
+
+fn render_block(element: &Element) -> String {
+    match element {
+        Element::Container(container) => match container.ctype().heading_level() {
+            Some(level) => {
+                let contents = render_inline_list(container.elements());
+                let marker = "#".repeat(level.value().into());
+                format!("{} {}", marker, contents.trim())
+            }
+            None => render_blocks(container.elements()),
+        },
+        Element::StyledContainer(container) => {
+            let open = open_tag(container);
+            let tag = container.ctype().html_tag();
+            let contents = render_blocks(container.elements());
+            format!("{}\n\n{}\n\n</{}>", open, contents, tag)
+        }
+        Element::Image(image) => {
+            let caption = match &image.caption {
+                Some(caption) => render_inline_list(caption),
+                None => String::new(),
+            };
+            let image =
+                render_image(&image.source, image.alt.as_deref(), image.link.as_deref());
+
+            if caption.trim().is_empty() {
+                image
+            } else {
+                format!("{}\\\n*{}*", image, caption.trim())
+            }
+        }
+        Element::Collapsible {
+            elements,
+            start_open,
+            show_text,
+            ..
+        } => {
+            let summary = match show_text {
+                Some(elements) => render_inline_list(elements),
+                None => str!(DEFAULT_SUMMARY),
+            };
+
+            render_details(&summary, &render_blocks(elements), *start_open)
+        }
+        Element::BlockQuote {
+            elements, source, ..
+        } => {
+            let mut contents = render_blocks(elements);
+
+            if let Some(source) = source {
+                contents.push_str("\n\n— ");
+                contents.push_str(render_inline_list(source).trim());
+            }
+
+            prefix_lines(&contents, "> ")
+        }
+        Element::Poem { elements } => render_blocks(elements),
+        Element::TabView(tabs) => render_tabs(tabs),
+        Element::Table(table) => render_table(table),
+        Element::List(list) => render_list(list),
+        Element::DefinitionList(items) => render_definition_list(items),
+        Element::Code { contents, language } => {
+            render_fence(contents, language.as_deref().unwrap_or(""))
+        }
+        Element::Diagram { kind, contents } => render_fence(contents, kind.html_class()),
+        Element::Math {
+            latex,
+            inline: false,
+        } => format!("$$\n{}\n$$", latex.trim()),
+        Element::HorizontalRule => str!("---"),
+        Element::TableOfContents { .. } => str!("<!-- toc -->"),
+
+        // Generated when the page is viewed, there is nothing to write
+        Element::Module(_) => String::new(),
+
+        // Replaced by the list of footnotes, see resolve_footnotes()
+        Element::FootnoteBlock { .. } => String::new(),
+        Element::Bibliography { hide: true, .. } => String::new(),
+        Element::Bibliography { entries, .. } => render_bibliography(entries),
+        _ => render_inline(element),
+    }
+}
+
+fn render_inline_list(elements: &[Element]) -> String {
+    elements.iter().map(render_inline).collect()
+}
+
+fn render_inline(element: &Element) -> String {
+    match element {
+        Element::Text(text) | Element::Raw(text) => escape(text),
+        Element::Email(email) => format!("<{}>", email),
+        Element::Container(container) => {
+            let contents = render_inline_list(container.elements());
+
+            match container.ctype() {
+                ContainerType::Strong => wrap(&contents, "**", "**"),
+                ContainerType::Emphasis => wrap(&contents, "*", "*"),
+                ContainerType::Strikethrough => wrap(&contents, "~~", "~~"),
+                ContainerType::Monospace => render_code_span(container.elements())
+                    .unwrap_or_else(|| wrap(&contents, "<code>", "</code>")),
+                ContainerType::Underline
+                | ContainerType::Superscript
+                | ContainerType::Subscript => {
+                    let tag = container.ctype().html_tag();
+                    wrap(&contents, &format!("<{}>", tag), &format!("</{}>", tag))
+                }
+                ContainerType::Paragraph
+                | ContainerType::Header(_)
+                | ContainerType::UnlistedHeader(_) => render_block(element),
+            }
+        }
+        Element::StyledContainer(container) => {
+            let contents = render_inline_list(container.elements());
+            let plain = container.id().is_none()
+                && container.class().is_none()
+                && container.style().is_none();
+
+            match container.ctype() {
+                StyledContainerType::Span if plain => contents,
+                StyledContainerType::Div => render_block(element),
+                ctype => {
+                    let close = format!("</{}>", ctype.html_tag());
+                    wrap(&contents, &open_tag(container), &close)
+                }
+            }
+        }
+        Element::Link { url, label, .. } => {
+            let label = match label {
+                LinkLabel::Text(text) => escape(text),
+                LinkLabel::Url | LinkLabel::Page => escape(url),
+                LinkLabel::Elements(elements) => render_inline_list(elements),
+            };
+
+            render_link(&label, url)
+        }
+        Element::Anchor { elements, url, .. } => {
+            render_link(&render_inline_list(elements), url)
+        }
+        Element::Image(image) => {
+            render_image(&image.source, image.alt.as_deref(), image.link.as_deref())
+        }
+        Element::Iframe {
+            url, width, height, ..
+        } => {
+            let mut output = format!("<iframe src=\"{}\"", escape_html(url));
+
+            if let Some(width) = width {
+                output.push_str(&format!(" width=\"{}\"", escape_html(width)));
+            }
+
+            if let Some(height) = height {
+                output.push_str(&format!(" height=\"{}\"", escape_html(height)));
+            }
+
+            output.push_str("></iframe>");
+            output
+        }
+        Element::Abbreviation { title, elements } => format!(
+            "<abbr title=\"{}\">{}</abbr>",
+            escape_html(title),
+            render_inline_list(elements),
+        ),
+        Element::Color { color, elements } => format!(
+            "<span style=\"color: {};\">{}</span>",
+            escape_html(color),
+            render_inline_list(elements),
+        ),
+        Element::Date { timestamp, format } => {
+            // Without a current time, relative dates are written out in full
+            let settings = DateSettings::default();
+            escape(&format_date(*timestamp, format.as_deref(), &settings))
+        }
+        Element::Counter(counter) => counter.value().to_string(),
+        Element::CounterRef { value, .. } => match value {
+            Some(value) => value.to_string(),
+            None => str!("??"),
+        },
+        Element::Math {
+            latex,
+            inline: true,
+        } => format!("${}$", latex.trim()),
+        Element::BibliographyCite { number, .. } => match number {
+            Some(number) => escape(&format!("[{}]", number)),
+            None => escape("[??]"),
+        },
+        Element::Include { elements, .. } | Element::NoTypography { elements } => {
+            render_inline_list(elements)
+        }
+        // Only footnotes within other footnotes are left by resolve_footnotes(),
+        // and these have nowhere to be listed, so are written in place.
+        Element::Footnote { elements } => {
+            wrap(&render_inline_list(elements), "<sup>(", ")</sup>")
+        }
+
+        // Within inline formatting a Markdown hard break could end up
+        // directly before a closing delimiter, so use HTML instead.
+        Element::LineBreak => str!("<br>"),
+        Element::LineBreaks(amount) => "<br>".repeat(amount.get() as usize),
+        Element::Module(_)
+        | Element::Collapsible { .. }
+        | Element::BlockQuote { .. }
+        | Element::Poem { .. }
+        | Element::TabView(_)
+        | Element::Table(_)
+        | Element::List(_)
+        | Element::DefinitionList(_)
+        | Element::TableOfContents { .. }
+        | Element::FootnoteBlock { .. }
+        | Element::Bibliography { .. }
+        | Element::Code { .. }
+        | Element::Diagram { .. }
+        | Element::Math { inline: false, .. }
+        | Element::HorizontalRule => render_block(element),
+        Element::Partial(_) => panic!("Encountered partial element during rendering"),
+        Element::Null => String::new(),
+    }
+}
+
+/// Surrounds inline contents with delimiters, unless there are no contents.
+///
+/// Whitespace is moved outside the delimiters, since Markdown
+/// does not treat emphasis with inner padding as formatting.
+fn wrap(contents: &str, open: &str, close: &str) -> String {
+    let trimmed = contents.trim();
+
+    if trimmed.is_empty() {
+        return str!(contents);
+    }
+
+    let start = contents.len() - contents.trim_start().len();
+    let end = contents.trim_end().len();
+
+    format!(
+        "{}{}{}{}{}",
+        &contents[..start],
+        open,
+        trimmed,
+        close,
+        &contents[end..],
+    )
+}
+
+fn open_tag(container: &StyledContainer) -> String {
+    let mut output = format!("<{}", container.ctype().html_tag());
+
+    let attributes = [
+        ("id", container.id()),
+        ("class", container.class()),
+        ("style", container.style()),
+    ];
+
+    for (key, value) in attributes.iter() {
+        if let Some(value) = value {
+            output.push_str(&format!(" {}=\"{}\"", key, escape_html(value)));
+        }
+    }
+
+    output.push('>');
+    output
+}
+
+fn render_link(label: &str, url: &str) -> String {
+    format!("[{}]({})", label, render_url(url))
+}
+
+fn render_image(source: &ImageSource, alt: Option<&str>, link: Option<&str>) -> String {
+    // The first attached file is only known to the host
+    if *source == ImageSource::FirstFile {
+        return String::new();
+    }
+
+    // Use the file name as the alt text by default
+    let alt = alt.or_else(|| source.file_name()).unwrap_or_default();
+    let image = format!("![{}]({})", escape(alt), render_url(&source.to_string()));
+
+    match link {
+        Some(link) => render_link(&image, link),
+        None => image,
+    }
+}
+
+/// Formats a link destination, using angle brackets if it can't be written bare.
+fn render_url(url: &str) -> String {
+    if url.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+        format!("<{}>", url.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        str!(url)
+    }
+}
+
+/// Renders monospace text as a code span, if it contains only text.
+fn render_code_span(elements: &[Element]) -> Option<String> {
+    let mut contents = String::new();
+
+    for element in elements {
+        match element {
+            Element::Text(text) | Element::Raw(text) => contents.push_str(text),
+            _ => return None,
+        }
+    }
+
+    if contents.trim().is_empty() || contents.contains('\n') {
+        return None;
+    }
+
+    let fence = "`".repeat(longest_run(&contents, '`') + 1);
+    let padding = if contents.starts_with('`') || contents.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+
+    Some(format!(
+        "{}{}{}{}{}",
+        fence, padding, contents, padding, fence,
+    ))
+}
+
+/// Renders a fenced code block, longer than any run of backticks within it.
+fn render_fence(contents: &str, language: &str) -> String {
+    let fence = "`".repeat(longest_run(contents, '`').max(2) + 1);
+    let contents = contents.trim_end_matches('\n');
+
+    format!("{}{}\n{}\n{}", fence, language, contents, fence)
+}
+
+fn longest_run(text: &str, target: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+
+    for ch in text.chars() {
+        if ch == target {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+
+    longest
+}
+
+fn prefix_lines(text: &str, prefix: &str) -> String {
+    let lines: Vec<_> = text
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                str!(prefix.trim_end())
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect();
+
+    lines.join("\n")
+}
+
+fn render_details(summary: &str, contents: &str, open: bool) -> String {
+    let tag = if open { "<details open>" } else { "<details>" };
+
+    if contents.is_empty() {
+        format!("{}\n<summary>{}</summary>\n</details>", tag, summary)
+    } else {
+        format!(
+            "{}\n<summary>{}</summary>\n\n{}\n\n</details>",
+            tag, summary, contents,
+        )
+    }
+}
+
+fn render_tabs(tabs: &[Tab]) -> String {
+    // Each tab becomes its own disclosure, with the first shown initially
+    let blocks: Vec<_> = tabs
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| {
+            let label = render_inline_list(tab.label());
+            render_details(&label, &render_blocks(tab.elements()), i == 0)
+        })
+        .collect();
+
+    blocks.join("\n\n")
+}
+
+/// Lists the entries of a bibliography, keeping the numbers citations refer to.
+fn render_bibliography(entries: &[BibliographyEntry]) -> String {
+    let items: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            let contents = render_inline_list(entry.elements());
+            format!("{}. {}", entry.number(), contents.trim())
+        })
+        .collect();
+
+    items.join("\n")
+}
+
+fn render_list(list: &List) -> String {
+    // Items with paragraphs need blank lines between blocks
+    let loose = list.items().iter().any(|item| {
+        item.elements().iter().any(|element| {
+            matches!(element, Element::Container(container) if container.ctype() == ContainerType::Paragraph)
+        })
+    });
+
+    let separator = if loose { "\n\n" } else { "\n" };
+    let items: Vec<_> = list
+        .items()
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let marker = match list.style() {
+                ListStyle::Bullet => str!("-"),
+                ListStyle::Numbered => format!("{}.", i + 1),
+            };
+
+            let mut blocks = Blocks::default();
+            render_flow(&mut blocks, item.elements());
+            let contents = blocks.finish(separator);
+            let indent = " ".repeat(marker.len() + 1);
+
+            let mut output = marker;
+            for (j, line) in contents.lines().enumerate() {
+                if j == 0 {
+                    output.push(' ');
+                } else {
+                    output.push('\n');
+
+                    if !line.is_empty() {
+                        output.push_str(&indent);
+                    }
+                }
+
+                output.push_str(line);
+            }
+
+            output
+        })
+        .collect();
+
+    items.join(separator)
+}
+
+/// Writes each term on its own line, followed by its definition after a `:`.
+///
+/// Markdown has no definition lists, but this is the syntax of extensions
+/// which do, and otherwise still reads naturally.
+fn render_definition_list(items: &[DefinitionListItem]) -> String {
+    let items: Vec<_> = items
+        .iter()
+        .map(|item| {
+            format!(
+                "{}\n: {}",
+                render_inline_list(item.key()).trim(),
+                render_inline_list(item.value()).trim(),
+            )
+        })
+        .collect();
+
+    items.join("\n\n")
+}
+
+fn render_table(table: &Table) -> String {
+    let mut rows = Vec::new();
+    let mut alignments = Vec::new();
+
+    for (i, row) in table.rows().iter().enumerate() {
+        let mut cells = Vec::new();
+
+        for cell in row.cells() {
+            // Pad out spanned columns, since Markdown has no equivalent
+            for j in 0..cell.column_span().get() {
+                if j == 0 {
+                    cells.push(render_cell(cell.elements()));
+                } else {
+                    cells.push(String::new());
+                }
+
+                if i == 0 {
+                    alignments.push(cell.align());
+                }
+            }
+        }
+
+        rows.push(cells);
+    }
+
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return String::new();
+    }
+
+    let delimiters: Vec<_> = (0..columns)
+        .map(|i| {
+            let delimiter = match alignments.get(i).copied().flatten() {
+                Some(Alignment::Left) => ":---",
+                Some(Alignment::Right) => "---:",
+                Some(Alignment::Center) => ":---:",
+                Some(Alignment::Justify) | None => "---",
+            };
+
+            str!(delimiter)
+        })
+        .collect();
+
+    let mut lines = vec![
+        render_row(&rows[0], columns),
+        render_row(&delimiters, columns),
+    ];
+    for row in &rows[1..] {
+        lines.push(render_row(row, columns));
+    }
+
+    lines.join("\n")
+}
+
+fn render_row(cells: &[String], columns: usize) -> String {
+    let mut line = str!("|");
+
+    for i in 0..columns {
+        line.push(' ');
+        line.push_str(cells.get(i).map(String::as_str).unwrap_or(""));
+        line.push_str(" |");
+    }
+
+    line
+}
+
+/// Renders a table cell, which must fit on one line.
+fn render_cell(elements: &[Element]) -> String {
+    render_blocks(elements)
+        .replace("\\\n", "<br>")
+        .replace("\n\n", "<br><br>")
+        .replace('\n', "<br>")
+}
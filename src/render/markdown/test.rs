@@ -0,0 +1,292 @@
+/*
+ * render/markdown/test.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use super::MarkdownRender;
+use crate::data::Alignment;
+use crate::enums::{AnchorTarget, HeadingLevel, LinkLabel, ListStyle};
+use crate::tree::{
+    DefinitionListItem, Image, ImageSource, List, ListItem, Tab, Table, TableCell,
+    TableRow,
+};
+use std::num::NonZeroU32;
+
+macro_rules! test {
+    ($elements:expr, $expected:expr $(,)?) => {{
+        let result = SyntaxTree::from_element_result($elements, vec![], vec![]);
+        let (tree, _) = result.into();
+        let output = MarkdownRender.render(&tree);
+
+        assert_eq!(
+            output, $expected,
+            "Rendered Markdown doesn't match expected"
+        );
+    }};
+}
+
+macro_rules! container {
+    ($ctype:expr, $elements:expr $(,)?) => {
+        Element::Container(Container::new($ctype, $elements))
+    };
+}
+
+#[test]
+fn markdown() {
+    test!(vec![], "");
+
+    test!(
+        vec![
+            container!(
+                ContainerType::Header(HeadingLevel::Two),
+                vec![text!("Fruit")],
+            ),
+            container!(
+                ContainerType::Paragraph,
+                vec![
+                    text!("Apple "),
+                    container!(ContainerType::Strong, vec![text!("banana ")]),
+                    container!(ContainerType::Emphasis, vec![text!("*cherry*")]),
+                    Element::LineBreak,
+                    container!(ContainerType::Monospace, vec![text!("a`b")]),
+                    Element::LineBreak,
+                ],
+            ),
+            container!(ContainerType::Paragraph, vec![text!("- durian")]),
+            Element::HorizontalRule,
+        ],
+        "## Fruit\n\nApple **banana** *\\*cherry\\**\\\n``a`b``\n\n\\- durian\n\n---\n",
+    );
+}
+
+#[test]
+fn links() {
+    test!(
+        vec![container!(
+            ContainerType::Paragraph,
+            vec![
+                Element::Link {
+                    url: cow!("https://example.com"),
+                    label: LinkLabel::Text(cow!("Example")),
+                    anchor: AnchorTarget::Same,
+                },
+                text!(" "),
+                Element::Link {
+                    url: cow!("/some page"),
+                    label: LinkLabel::Url,
+                    anchor: AnchorTarget::Same,
+                },
+                text!(" "),
+                Element::Image(Box::new(Image::new(ImageSource::Url(cow!(
+                    "/local--files/page/apple.png"
+                ))))),
+                text!(" "),
+                Element::Email(cow!("user@example.com")),
+            ],
+        )],
+        "[Example](https://example.com) [/some page](</some page>) \
+         ![apple.png](/local--files/page/apple.png) <user@example.com>\n",
+    );
+}
+
+#[test]
+fn code() {
+    test!(
+        vec![Element::Code {
+            contents: cow!("let x = 1;\n```\n"),
+            language: Some(cow!("rust")),
+        }],
+        "````rust\nlet x = 1;\n```\n````\n",
+    );
+}
+
+#[test]
+fn math() {
+    test!(
+        vec![
+            container!(
+                ContainerType::Paragraph,
+                vec![
+                    text!("Area "),
+                    Element::Math {
+                        latex: cow!("\\pi r^2"),
+                        inline: true,
+                    },
+                ],
+            ),
+            Element::Math {
+                latex: cow!("e^{i \\pi} + 1 = 0"),
+                inline: false,
+            },
+        ],
+        "Area $\\pi r^2$\n\n$$\ne^{i \\pi} + 1 = 0\n$$\n",
+    );
+}
+
+#[test]
+fn list() {
+    let item = |text| ListItem::new(vec![text!(text)]);
+
+    test!(
+        vec![Element::List(List::new(
+            ListStyle::Numbered,
+            vec![
+                item("Apple"),
+                ListItem::new(vec![
+                    text!("Banana"),
+                    Element::List(List::new(
+                        ListStyle::Bullet,
+                        vec![item("Cherry"), item("Durian")],
+                    )),
+                ]),
+            ],
+        ))],
+        "1. Apple\n2. Banana\n   - Cherry\n   - Durian\n",
+    );
+}
+
+#[test]
+fn definition_list() {
+    test!(
+        vec![Element::DefinitionList(vec![
+            DefinitionListItem::new(vec![text!("Apple")], vec![text!("A red fruit")]),
+            DefinitionListItem::new(
+                vec![Element::Container(Container::new(
+                    ContainerType::Strong,
+                    vec![text!("Banana")],
+                ))],
+                vec![text!("Yellow")],
+            ),
+        ])],
+        "Apple\n: A red fruit\n\n**Banana**\n: Yellow\n",
+    );
+}
+
+#[test]
+fn footnotes() {
+    let footnote = |text| Element::Footnote {
+        elements: vec![text!(text)],
+    };
+
+    test!(
+        vec![container!(
+            ContainerType::Paragraph,
+            vec![
+                text!("Apple"),
+                footnote("Banana"),
+                text!(" cherry"),
+                footnote("Banana"),
+                footnote("Durian"),
+            ],
+        )],
+        "Apple<sup>1</sup> cherry<sup>1</sup><sup>2</sup>\n\n1. Banana\n2. Durian\n",
+    );
+}
+
+#[test]
+fn bibliography() {
+    use crate::tree::{resolve_bibliography, BibliographyEntry};
+
+    let mut elements = vec![
+        container!(
+            ContainerType::Paragraph,
+            vec![
+                text!("Apple "),
+                Element::BibliographyCite {
+                    label: cow!("banana"),
+                    number: None,
+                },
+            ],
+        ),
+        Element::Bibliography {
+            title: None,
+            hide: false,
+            entries: vec![BibliographyEntry::new(
+                cow!("banana"),
+                vec![text!("Banana")],
+            )],
+        },
+    ];
+
+    resolve_bibliography(&mut elements);
+    test!(elements, "Apple \\[1\\]\n\n1. Banana\n");
+}
+
+#[test]
+fn table() {
+    let cell = |text, align| {
+        TableCell::new(false, NonZeroU32::new(1).unwrap(), align, vec![text!(text)])
+    };
+
+    test!(
+        vec![Element::Table(Table::new(vec![
+            TableRow::new(vec![
+                cell("Fruit", None),
+                cell("Count", Some(Alignment::Right)),
+            ]),
+            TableRow::new(vec![cell("Apple", None), cell("3 | 4", None)]),
+        ]))],
+        "| Fruit | Count |\n| --- | ---: |\n| Apple | 3 \\| 4 |\n",
+    );
+}
+
+#[test]
+fn fallback() {
+    test!(
+        vec![Element::Collapsible {
+            elements: vec![container!(
+                ContainerType::Paragraph,
+                vec![container!(ContainerType::Strong, vec![text!("Hidden")])],
+            )],
+            id: None,
+            class: None,
+            style: None,
+            start_open: false,
+            show_text: None,
+            hide_text: None,
+            show_top: true,
+            show_bottom: false,
+        }],
+        "<details>\n<summary>+ show block</summary>\n\n**Hidden**\n\n</details>\n",
+    );
+
+    test!(
+        vec![Element::TabView(vec![
+            Tab::new(vec![text!("First")], vec![text!("Apple")], None, None),
+            Tab::new(vec![text!("Second")], vec![text!("Banana")], None, None),
+        ])],
+        "<details open>\n<summary>First</summary>\n\nApple\n\n</details>\n\n\
+         <details>\n<summary>Second</summary>\n\nBanana\n\n</details>\n",
+    );
+
+    test!(
+        vec![Element::BlockQuote {
+            elements: vec![
+                container!(ContainerType::Paragraph, vec![text!("Apple")]),
+                container!(ContainerType::Paragraph, vec![text!("Banana")]),
+            ],
+            id: None,
+            class: None,
+            style: None,
+            cite: None,
+            source: Some(vec![text!("Cherry")]),
+        }],
+        "> Apple\n>\n> Banana\n>\n> — Cherry\n",
+    );
+}
@@ -23,10 +23,26 @@ mod prelude {
     pub use crate::tree::{Container, ContainerType, Element, SyntaxTree};
 }
 
+#[cfg(feature = "text")]
+pub mod date;
+#[cfg(feature = "debug")]
 pub mod debug;
+#[cfg(feature = "html")]
 pub mod html;
+#[cfg(feature = "json")]
 pub mod json;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+#[cfg(feature = "html")]
+pub mod multi;
 pub mod null;
+#[cfg(feature = "text")]
+pub mod text;
+pub mod tokens;
+
+mod warning;
+
+pub use self::warning::{RenderWarning, RenderWarningKind};
 
 use crate::tree::SyntaxTree;
 
@@ -0,0 +1,339 @@
+/*
+ * render/multi.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! A renderer which produces several outputs in a single traversal.
+//!
+//! Rendering a page to HTML, then again to text for search indexing,
+//! walks the syntax tree twice. Instead, this renderer drives the HTML
+//! renderer and collects the text and page metadata as it goes.
+
+use super::html::{HtmlOutput, HtmlRender};
+use super::prelude::*;
+use super::text;
+use crate::tree::walk_elements;
+
+#[derive(Debug, Default)]
+pub struct MultiRender {
+    /// The HTML renderer to use, with whatever settings it requires.
+    pub html: HtmlRender,
+}
+
+impl Render for MultiRender {
+    type Output = MultiOutput;
+
+    #[inline]
+    fn render(&self, tree: &SyntaxTree) -> MultiOutput {
+        self.html.render_collecting(tree)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MultiOutput {
+    /// The same output as `HtmlRender`.
+    pub html: HtmlOutput,
+
+    /// The same output as `TextRender`.
+    pub text: String,
+
+    /// Information about the page gathered from its elements.
+    pub metadata: RenderMetadata,
+}
+
+/// What a page refers to, in order of appearance.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct RenderMetadata {
+    /// The URLs of all links.
+    pub links: Vec<String>,
+
+    /// The sources of all images.
+    pub images: Vec<String>,
+
+    /// The pages which were included.
+    pub includes: Vec<String>,
+
+    /// The names of all modules.
+    pub modules: Vec<String>,
+}
+
+impl RenderMetadata {
+    fn add(&mut self, element: &Element) {
+        match element {
+            Element::Link { url, .. } | Element::Anchor { url, .. } => {
+                self.links.push(str!(url))
+            }
+            Element::Image(image) => self.images.push(image.source.to_string()),
+            Element::Include { page, .. } => self.includes.push(page.to_string()),
+            Element::Module(module) => self.modules.push(str!(module.name())),
+            _ => (),
+        }
+    }
+}
+
+/// Accumulates text and metadata as the HTML renderer walks the tree.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Collector {
+    text: String,
+    metadata: RenderMetadata,
+    suppressed: usize,
+}
+
+/// The position of a `Collector`, to retrieve what was added since.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Checkpoint {
+    text: usize,
+    links: usize,
+    images: usize,
+    includes: usize,
+    modules: usize,
+}
+
+impl Collector {
+    #[inline]
+    pub fn enter_element(&mut self, element: &Element) {
+        if !self.suppressed() {
+            text::enter_element(&mut self.text, element);
+            self.metadata.add(element);
+        }
+    }
+
+    #[inline]
+    pub fn leave_element(&mut self, element: &Element) {
+        if !self.suppressed() {
+            text::leave_element(&mut self.text, element);
+        }
+    }
+
+    /// Collects the contents of a footnote where it is referenced.
+    ///
+    /// The HTML renderer lists them in the footnote block instead,
+    /// but this keeps the text in the same order as `TextRender`.
+    pub fn add_footnote(&mut self, elements: &[Element]) {
+        if !self.suppressed() {
+            let metadata = &mut self.metadata;
+
+            text::render_elements(&mut self.text, elements);
+            walk_elements(elements, &mut |element| metadata.add(element));
+        }
+    }
+
+    #[inline]
+    pub fn enter_table_cell(&mut self, index: usize) {
+        if !self.suppressed() {
+            text::enter_table_cell(&mut self.text, index);
+        }
+    }
+
+    #[inline]
+    pub fn leave_table_row(&mut self) {
+        if !self.suppressed() {
+            text::leave_table_row(&mut self.text);
+        }
+    }
+
+    #[inline]
+    pub fn leave_list_item(&mut self) {
+        if !self.suppressed() {
+            text::leave_list_item(&mut self.text);
+        }
+    }
+
+    #[inline]
+    pub fn leave_definition_key(&mut self) {
+        if !self.suppressed() {
+            text::leave_definition_key(&mut self.text);
+        }
+    }
+
+    #[inline]
+    pub fn leave_tab_label(&mut self) {
+        if !self.suppressed() {
+            text::leave_tab_label(&mut self.text);
+        }
+    }
+
+    // Suppression
+
+    /// Stops collecting, for elements rendered as part of the interface.
+    ///
+    /// For instance, the show and hide links of a collapsible
+    /// are not part of the page's text.
+    #[inline]
+    pub fn suppress(&mut self) {
+        self.suppressed += 1;
+    }
+
+    #[inline]
+    pub fn unsuppress(&mut self) {
+        self.suppressed -= 1;
+    }
+
+    #[inline]
+    pub fn suppressed(&self) -> bool {
+        self.suppressed > 0
+    }
+
+    // Memoization
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            text: self.text.len(),
+            links: self.metadata.links.len(),
+            images: self.metadata.images.len(),
+            includes: self.metadata.includes.len(),
+            modules: self.metadata.modules.len(),
+        }
+    }
+
+    /// Copies everything collected after the given checkpoint.
+    pub fn since(&self, checkpoint: Checkpoint) -> Collector {
+        let metadata = &self.metadata;
+
+        Collector {
+            text: str!(self.text[checkpoint.text..]),
+            metadata: RenderMetadata {
+                links: metadata.links[checkpoint.links..].to_vec(),
+                images: metadata.images[checkpoint.images..].to_vec(),
+                includes: metadata.includes[checkpoint.includes..].to_vec(),
+                modules: metadata.modules[checkpoint.modules..].to_vec(),
+            },
+            suppressed: 0,
+        }
+    }
+
+    /// Appends everything collected by another instance.
+    pub fn extend(&mut self, other: &Collector) {
+        if self.suppressed() {
+            return;
+        }
+
+        let metadata = &mut self.metadata;
+        self.text.push_str(&other.text);
+        metadata.links.extend(other.metadata.links.iter().cloned());
+        metadata
+            .images
+            .extend(other.metadata.images.iter().cloned());
+        metadata
+            .includes
+            .extend(other.metadata.includes.iter().cloned());
+        metadata
+            .modules
+            .extend(other.metadata.modules.iter().cloned());
+    }
+
+    pub fn finish(self) -> (String, RenderMetadata) {
+        let Collector {
+            mut text, metadata, ..
+        } = self;
+
+        text::finish(&mut text);
+        (text, metadata)
+    }
+}
+
+#[test]
+fn multi() {
+    use super::text::TextRender;
+    use crate::enums::{AnchorTarget, LinkLabel};
+    use crate::tree::{Image, ImageSource, Tab, Table, TableCell, TableRow};
+    use std::num::NonZeroU32;
+
+    let one = NonZeroU32::new(1).unwrap();
+    let paragraph = |text| {
+        Element::Container(Container::new(ContainerType::Paragraph, vec![text!(text)]))
+    };
+
+    let elements = vec![
+        Element::Container(Container::new(
+            ContainerType::Paragraph,
+            vec![
+                text!("Apple "),
+                Element::Link {
+                    url: cow!("https://example.com/"),
+                    label: LinkLabel::Text(cow!("banana")),
+                    anchor: AnchorTarget::Same,
+                },
+            ],
+        )),
+        Element::Image(Box::new(Image {
+            caption: Some(vec![text!("Durian")]),
+            ..Image::new(ImageSource::File1 {
+                file: cow!("cherry.png"),
+            })
+        })),
+        Element::Collapsible {
+            elements: vec![paragraph("Fig")],
+            id: None,
+            class: None,
+            style: None,
+            start_open: false,
+            show_text: None,
+            hide_text: Some(vec![text!("Hidden")]),
+            show_top: true,
+            show_bottom: false,
+        },
+        Element::TabView(vec![
+            Tab::new(
+                vec![text!("Grape")],
+                vec![paragraph("Honeydew")],
+                None,
+                None,
+            ),
+            Tab::new(vec![text!("Kiwi")], vec![paragraph("Lemon")], None, None),
+        ]),
+        Element::Table(Table::new(vec![TableRow::new(vec![
+            TableCell::new(false, one, None, vec![text!("Mango")]),
+            TableCell::new(false, one, None, vec![text!("Nectarine")]),
+        ])])),
+    ];
+
+    let result = SyntaxTree::from_element_result(elements, vec![], vec![]);
+    let (tree, _) = result.into();
+    let renderer = MultiRender::default();
+    let output = renderer.render(&tree);
+
+    assert_eq!(
+        output.html.html,
+        renderer.html.render(&tree).html,
+        "HTML output doesn't match HtmlRender",
+    );
+
+    assert_eq!(
+        output.text,
+        TextRender.render(&tree),
+        "Text output doesn't match TextRender",
+    );
+
+    assert_eq!(
+        output.text,
+        "Apple banana\n\nDurian\nFig\n\nGrape\nKiwi\nHoneydew\n\nLemon\n\nMango\tNectarine",
+        "Text output doesn't match expected",
+    );
+
+    assert_eq!(
+        output.metadata,
+        RenderMetadata {
+            links: vec![str!("https://example.com/")],
+            images: vec![str!("cherry.png")],
+            includes: vec![],
+            modules: vec![],
+        },
+        "Collected metadata doesn't match expected",
+    );
+}
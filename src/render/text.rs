@@ -0,0 +1,276 @@
+/*
+ * render/text.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! A renderer which outputs only the textual content of the syntax tree.
+//!
+//! All formatting is discarded. Block elements and list items are separated
+//! by newlines, and table cells by tabs. This is suitable for search indexing or
+//! producing plain-text previews.
+
+use super::date::{format_date, DateSettings};
+use super::prelude::*;
+use crate::enums::LinkLabel;
+
+#[derive(Debug)]
+pub struct TextRender;
+
+impl Render for TextRender {
+    type Output = String;
+
+    fn render(&self, tree: &SyntaxTree) -> String {
+        let mut buffer = String::new();
+        render_elements(&mut buffer, &tree.elements);
+        finish(&mut buffer);
+        buffer
+    }
+}
+
+pub(crate) fn render_elements(buffer: &mut String, elements: &[Element]) {
+    for element in elements {
+        render_element(buffer, element);
+    }
+}
+
+fn render_element(buffer: &mut String, element: &Element) {
+    enter_element(buffer, element);
+
+    match element {
+        Element::Container(container) => render_elements(buffer, container.elements()),
+        Element::StyledContainer(container) => {
+            render_elements(buffer, container.elements())
+        }
+        Element::Image(image) => {
+            if let Some(caption) = &image.caption {
+                render_elements(buffer, caption);
+            }
+        }
+        Element::Link {
+            label: LinkLabel::Elements(elements),
+            ..
+        } => render_elements(buffer, elements),
+        Element::Include { elements, .. }
+        | Element::Anchor { elements, .. }
+        | Element::Collapsible { elements, .. }
+        | Element::Abbreviation { elements, .. }
+        | Element::Footnote { elements }
+        | Element::NoTypography { elements }
+        | Element::Poem { elements }
+        | Element::Color { elements, .. } => render_elements(buffer, elements),
+        Element::BlockQuote {
+            elements, source, ..
+        } => {
+            render_elements(buffer, elements);
+
+            if let Some(source) = source {
+                render_elements(buffer, source);
+            }
+        }
+        Element::TabView(tabs) => {
+            // Same order as the HTML, all the labels and then all the contents
+            for tab in tabs {
+                render_elements(buffer, tab.label());
+                leave_tab_label(buffer);
+            }
+
+            for tab in tabs {
+                render_elements(buffer, tab.elements());
+            }
+        }
+        Element::Table(table) => {
+            for row in table.rows() {
+                for (i, cell) in row.cells().iter().enumerate() {
+                    enter_table_cell(buffer, i);
+                    render_elements(buffer, cell.elements());
+                }
+
+                leave_table_row(buffer);
+            }
+        }
+        Element::List(list) => {
+            for item in list.items() {
+                render_elements(buffer, item.elements());
+                leave_list_item(buffer);
+            }
+        }
+        Element::DefinitionList(items) => {
+            for item in items {
+                render_elements(buffer, item.key());
+                leave_definition_key(buffer);
+                render_elements(buffer, item.value());
+                leave_list_item(buffer);
+            }
+        }
+        Element::Bibliography { hide, entries, .. } if !hide => {
+            for entry in entries {
+                render_elements(buffer, entry.elements());
+                leave_list_item(buffer);
+            }
+        }
+        _ => (),
+    }
+
+    leave_element(buffer, element);
+}
+
+// Text output for each step of a traversal.
+//
+// These are separate from the traversal itself so that other renderers
+// walking the tree can produce the same text alongside their own output.
+
+/// Appends the text which precedes an element's children.
+pub(crate) fn enter_element(buffer: &mut String, element: &Element) {
+    match element {
+        Element::Text(text) | Element::Raw(text) | Element::Email(text) => {
+            buffer.push_str(text);
+        }
+        Element::Link { url, label, .. } => match label {
+            LinkLabel::Text(text) => buffer.push_str(text),
+            LinkLabel::Url | LinkLabel::Page => buffer.push_str(url),
+            LinkLabel::Elements(_) => (),
+        },
+        Element::Date { timestamp, format } => {
+            // Without a current time, relative dates are written out in full
+            let settings = DateSettings::default();
+            buffer.push_str(&format_date(*timestamp, format.as_deref(), &settings));
+        }
+        Element::Counter(counter) => buffer.push_str(&counter.value().to_string()),
+        Element::CounterRef { value, .. } => match value {
+            Some(value) => buffer.push_str(&value.to_string()),
+            None => buffer.push_str("??"),
+        },
+        Element::BibliographyCite { number, .. } => match number {
+            Some(number) => buffer.push_str(&format!("[{}]", number)),
+            None => buffer.push_str("[??]"),
+        },
+        Element::Code { contents, .. } => {
+            buffer.push_str(contents);
+            buffer.push('\n');
+        }
+        Element::Math { latex, inline } => {
+            buffer.push_str(latex);
+
+            if !inline {
+                buffer.push('\n');
+            }
+        }
+        Element::List(_) | Element::DefinitionList(_) => end_line(buffer),
+        Element::LineBreak | Element::HorizontalRule => buffer.push('\n'),
+        Element::LineBreaks(amount) => {
+            for _ in 0..amount.get() {
+                buffer.push('\n');
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Appends the text which follows an element's children.
+pub(crate) fn leave_element(buffer: &mut String, element: &Element) {
+    match element {
+        Element::Container(container) if !container.ctype().is_inline() => {
+            buffer.push_str("\n\n");
+        }
+        Element::Poem { .. } => {
+            buffer.push_str("\n\n");
+        }
+        Element::StyledContainer(container) if !container.ctype().is_inline() => {
+            buffer.push('\n');
+        }
+        Element::Image(image) if image.caption.is_some() => buffer.push('\n'),
+        Element::BlockQuote {
+            source: Some(_), ..
+        } => buffer.push('\n'),
+        _ => (),
+    }
+}
+
+#[inline]
+pub(crate) fn enter_table_cell(buffer: &mut String, index: usize) {
+    if index > 0 {
+        buffer.push('\t');
+    }
+}
+
+#[inline]
+pub(crate) fn leave_table_row(buffer: &mut String) {
+    buffer.push('\n');
+}
+
+#[inline]
+pub(crate) fn leave_list_item(buffer: &mut String) {
+    end_line(buffer);
+}
+
+#[inline]
+pub(crate) fn leave_definition_key(buffer: &mut String) {
+    buffer.push_str(": ");
+}
+
+#[inline]
+pub(crate) fn leave_tab_label(buffer: &mut String) {
+    buffer.push('\n');
+}
+
+/// Starts a new line, unless the text is empty or already on one.
+///
+/// This puts a nested list on the line after its parent item,
+/// without adding blank lines between items.
+fn end_line(buffer: &mut String) {
+    if !buffer.is_empty() && !buffer.ends_with('\n') {
+        buffer.push('\n');
+    }
+}
+
+/// Removes trailing whitespace left by the final elements.
+pub(crate) fn finish(buffer: &mut String) {
+    let length = buffer.trim_end().len();
+    buffer.truncate(length);
+}
+
+#[test]
+fn text() {
+    let elements = vec![
+        Element::Container(Container::new(
+            ContainerType::Paragraph,
+            vec![
+                text!("apple "),
+                Element::Container(Container::new(
+                    ContainerType::Strong,
+                    vec![text!("banana")],
+                )),
+                Element::LineBreak,
+                text!("cherry"),
+            ],
+        )),
+        Element::Container(Container::new(
+            ContainerType::Paragraph,
+            vec![text!("durian")],
+        )),
+    ];
+
+    let result = SyntaxTree::from_element_result(elements, vec![], vec![]);
+    let (tree, _) = result.into();
+    let output = TextRender.render(&tree);
+
+    assert_eq!(
+        output, "apple banana\ncherry\n\ndurian",
+        "Text output doesn't match expected",
+    );
+}
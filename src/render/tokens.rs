@@ -0,0 +1,139 @@
+/*
+ * render/tokens.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! A debugging utility to pretty-print the token stream.
+//!
+//! Each line of the source text is printed, followed by the tokens
+//! which begin on that line, along with their spans and slices.
+//!
+//! Because this operates on a `Tokenization` rather than a `SyntaxTree`,
+//! it is a standalone function instead of an implementation of `Render`.
+
+use crate::tokenizer::Tokenization;
+use std::fmt::Write;
+
+/// Produce a listing of all the tokens, aligned to their source lines.
+pub fn render_tokens(tokenization: &Tokenization) -> String {
+    let text = tokenization.full_text().inner();
+    let tokens = tokenization.tokens();
+
+    // Get the starting index of each line
+    let mut line_starts = vec![0];
+    line_starts.extend(
+        text.char_indices()
+            .filter(|&(_, ch)| ch == '\n')
+            .map(|(idx, _)| idx + 1),
+    );
+
+    let get_line = |line: usize| {
+        let start = line_starts[line];
+        let end = line_starts.get(line + 1).copied().unwrap_or(text.len());
+
+        text[start..end].trim_end_matches(&['\r', '\n'][..])
+    };
+
+    // Determine column widths
+    let spans: Vec<String> = tokens
+        .iter()
+        .map(|token| format!("{}..{}", token.span.start, token.span.end))
+        .collect();
+
+    let number_width = line_starts.len().to_string().len();
+    let span_width = spans.iter().map(|span| span.len()).max().unwrap_or(0);
+    let name_width = tokens
+        .iter()
+        .map(|token| token.token.name().len())
+        .max()
+        .unwrap_or(0);
+
+    // Build output
+    let mut output = String::new();
+    let mut next_line = 0;
+
+    macro_rules! print_lines_until {
+        ($end:expr) => {
+            while next_line < $end {
+                writeln!(
+                    output,
+                    "{:>width$} | {}",
+                    next_line + 1,
+                    get_line(next_line),
+                    width = number_width,
+                )
+                .expect("Writing to string failed");
+
+                next_line += 1;
+            }
+        };
+    }
+
+    for (token, span) in tokens.iter().zip(spans.iter()) {
+        let line = match line_starts.binary_search(&token.span.start) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+
+        print_lines_until!(line + 1);
+
+        writeln!(
+            output,
+            "{:number_width$} |   {:span_width$} {:name_width$} {:?}",
+            "",
+            span,
+            token.token.name(),
+            token.slice,
+            number_width = number_width,
+            span_width = span_width,
+            name_width = name_width,
+        )
+        .expect("Writing to string failed");
+    }
+
+    print_lines_until!(line_starts.len());
+
+    output
+}
+
+#[test]
+fn tokens() {
+    const OUTPUT: &str = r#"1 | **a**
+  |   0..2 Strong     "**"
+  |   2..3 Identifier "a"
+  |   3..5 Strong     "**"
+  |   5..6 LineBreak  "\n"
+2 | b
+  |   6..7 Identifier "b"
+  |   7..7 InputEnd   ""
+"#;
+
+    let log = crate::build_logger();
+    let tokenization = crate::tokenize(&log, "**a**\nb");
+    let output = render_tokens(&tokenization);
+
+    assert_eq!(output, OUTPUT, "Token listing output doesn't match");
+    assert_eq!(
+        tokenization.to_string(),
+        OUTPUT,
+        "Token display doesn't match"
+    );
+
+    let token = &tokenization.tokens()[0];
+    assert_eq!(token.to_string(), r#"0..2 Strong "**""#);
+}
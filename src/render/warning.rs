@@ -0,0 +1,88 @@
+/*
+ * render/warning.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Non-fatal issues encountered while rendering.
+//!
+//! Like parsing, rendering never fails outright. Instead, parts of the
+//! tree which cannot be rendered as requested are substituted, and a
+//! warning is recorded so hosts can surface the issue to editors.
+
+use strum_macros::IntoStaticStr;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RenderWarning {
+    kind: RenderWarningKind,
+    value: String,
+}
+
+impl RenderWarning {
+    #[inline]
+    pub fn new(kind: RenderWarningKind, value: &str) -> Self {
+        RenderWarning {
+            kind,
+            value: str!(value),
+        }
+    }
+
+    #[inline]
+    pub fn kind(&self) -> RenderWarningKind {
+        self.kind
+    }
+
+    /// The part of the element which could not be rendered, such as a URL.
+    #[inline]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+#[derive(Serialize, Deserialize, IntoStaticStr, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RenderWarningKind {
+    /// An embed's URL is not on a domain the site permits, so a notice was rendered instead.
+    BlockedEmbed,
+
+    /// A link's URL is not permitted on an untrusted page, such as a `javascript:` URL,
+    /// so it was rendered without its target.
+    BlockedLink,
+
+    /// A color is not a valid CSS color name or hex code, so it was not applied.
+    InvalidColor,
+
+    /// An image's location could not be found, so it was omitted.
+    MissingImage,
+
+    /// The host failed to produce the contents of a module, so it was omitted.
+    ModuleFailed,
+
+    /// The host failed to look up information on a linked page, so it was rendered without it.
+    PageMetaFailed,
+
+    /// A counter reference's label does not belong to any counter, so a placeholder was rendered.
+    UnknownCounter,
+}
+
+impl RenderWarningKind {
+    #[inline]
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
+}
@@ -0,0 +1,153 @@
+/*
+ * span.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! A byte range within the source text.
+
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+/// A half-open byte range, `start..end`, within some source text.
+///
+/// Unlike `Range<usize>` this is `Copy`, and it serializes the same way,
+/// as an object with `start` and `end` fields.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, Hash, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    #[inline]
+    pub fn new(start: usize, end: usize) -> Self {
+        debug_assert!(start <= end, "Span start is after its end");
+
+        Span { start, end }
+    }
+
+    #[inline]
+    pub fn len(self) -> usize {
+        self.end - self.start
+    }
+
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.start == self.end
+    }
+
+    /// Determines if the given byte index falls within this span.
+    #[inline]
+    pub fn contains(self, index: usize) -> bool {
+        self.start <= index && index < self.end
+    }
+
+    /// Returns the smallest span covering both this span and `other`.
+    #[inline]
+    pub fn merge(self, other: Span) -> Self {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// Moves this span forward by `offset` bytes.
+    #[inline]
+    pub fn offset(self, offset: usize) -> Self {
+        Span {
+            start: self.start + offset,
+            end: self.end + offset,
+        }
+    }
+
+    /// Gets the portion of `text` this span refers to.
+    ///
+    /// # Panics
+    /// Like string slicing, this panics if the span is out of bounds
+    /// or does not fall on character boundaries.
+    #[inline]
+    pub fn slice(self, text: &str) -> &str {
+        &text[self.range()]
+    }
+
+    /// Like `slice()`, but returns `None` instead of panicking.
+    #[inline]
+    pub fn get(self, text: &str) -> Option<&str> {
+        text.get(self.range())
+    }
+
+    #[inline]
+    pub fn range(self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+impl From<Range<usize>> for Span {
+    #[inline]
+    fn from(range: Range<usize>) -> Self {
+        Span::new(range.start, range.end)
+    }
+}
+
+impl From<&'_ Range<usize>> for Span {
+    #[inline]
+    fn from(range: &'_ Range<usize>) -> Self {
+        Span::new(range.start, range.end)
+    }
+}
+
+impl From<Span> for Range<usize> {
+    #[inline]
+    fn from(span: Span) -> Self {
+        span.range()
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl slog::Value for Span {
+    fn serialize(
+        &self,
+        _: &slog::Record,
+        key: slog::Key,
+        serializer: &mut dyn slog::Serializer,
+    ) -> slog::Result {
+        serializer.emit_arguments(key, &format_args!("{}", self))
+    }
+}
+
+#[test]
+fn span() {
+    let span = Span::new(2, 5);
+    assert_eq!(span.len(), 3);
+    assert!(!span.is_empty());
+    assert!(span.contains(2));
+    assert!(!span.contains(5));
+    assert_eq!(span.slice("abcdefg"), "cde");
+    assert_eq!(span.get("abc"), None);
+    assert_eq!(span.offset(10), Span::new(12, 15));
+    assert_eq!(span.merge(Span::new(7, 9)), Span::new(2, 9));
+    assert_eq!(Span::from(2..5), span);
+    assert_eq!(Range::from(span), 2..5);
+    assert_eq!(span.to_string(), "2..5");
+    assert!(Span::new(4, 4).is_empty());
+}
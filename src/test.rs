@@ -23,13 +23,13 @@
 //! Additionally performs some other tests from the parser which are better
 //! in a dedicated test file.
 
-use crate::includes::DebugIncluder;
-use crate::parsing::{ParseWarning, ParseWarningKind, Token};
+use crate::parsing::{ParseWarningKind, Token};
+use crate::span::Span;
+use crate::testing::TestCase;
 use crate::tree::{Element, SyntaxTree};
 use std::borrow::Cow;
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
-use void::ResultVoidExt;
 
 const SKIP_TESTS: &[&str] = &[];
 
@@ -47,16 +47,13 @@ macro_rules! file_name {
     };
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Test<'a> {
-    #[serde(skip)]
+#[derive(Debug)]
+struct Test {
     name: String,
-    input: String,
-    tree: SyntaxTree<'a>,
-    warnings: Vec<ParseWarning>,
+    case: TestCase<'static>,
 }
 
-impl Test<'_> {
+impl Test {
     pub fn load(path: &Path, name: &str) -> Self {
         assert!(path.is_absolute());
 
@@ -65,15 +62,17 @@ impl Test<'_> {
             Err(error) => panic!("Unable to open file '{}': {}", path.display(), error),
         };
 
-        let mut test: Self = match serde_json::from_reader(&mut file) {
-            Ok(test) => test,
+        let case = match serde_json::from_reader(&mut file) {
+            Ok(case) => case,
             Err(error) => {
                 panic!("Unable to parse JSON file '{}': {}", path.display(), error)
             }
         };
 
-        test.name = str!(name);
-        test
+        Test {
+            name: str!(name),
+            case,
+        }
     }
 
     pub fn run(&self, log: &slog::Logger) {
@@ -81,7 +80,7 @@ impl Test<'_> {
             &log,
             "Running syntax tree test case";
             "name" => &self.name,
-            "input" => &self.input,
+            "input" => &self.case.input,
         );
 
         if SKIP_TESTS.contains(&&*self.name) {
@@ -91,13 +90,8 @@ impl Test<'_> {
 
         println!("+ {}", self.name);
 
-        let (mut text, _pages) =
-            crate::include(log, &self.input, DebugIncluder, || unreachable!())
-                .void_unwrap();
-        crate::preprocess(log, &mut text);
-        let tokens = crate::tokenize(log, &text);
-        let result = crate::parse(log, &tokens);
-        let (tree, warnings) = result.into();
+        let expected = &self.case;
+        let actual = expected.run(log);
 
         fn json<T>(object: &T) -> String
         where
@@ -110,50 +104,39 @@ impl Test<'_> {
             output
         }
 
-        if tree != self.tree {
+        if actual.tree != expected.tree {
             panic!(
                 "Running test '{}' failed! AST did not match:\nExpected: {:#?}\nActual: {:#?}\n{}\nWarnings: {:#?}",
                 self.name,
-                self.tree,
-                tree,
-                json(&tree),
-                &warnings,
+                expected.tree,
+                actual.tree,
+                json(&actual.tree),
+                &actual.warnings,
             );
         }
 
-        if warnings != self.warnings {
+        if actual.warnings != expected.warnings {
             panic!(
                 "Running test '{}' failed! Warnings did not match:\nExpected: {:#?}\nActual: {:#?}\n{}\nTree (correct): {:#?}",
                 self.name,
-                self.warnings,
-                warnings,
-                json(&warnings),
-                &tree,
+                expected.warnings,
+                actual.warnings,
+                json(&actual.warnings),
+                &actual.tree,
             );
         }
-    }
-}
 
-#[test]
-fn ast() {
-    let log = crate::build_logger();
-
-    // Warn if any test are being skipped
-    if !SKIP_TESTS.is_empty() {
-        println!("=========");
-        println!(" WARNING ");
-        println!("=========");
-        println!();
-        println!("The following tests are being SKIPPED:");
-
-        for test in SKIP_TESTS {
-            println!("- {}", test);
+        #[cfg(feature = "html")]
+        {
+            if let Err(error) = actual.check_html() {
+                panic!("Running test '{}' failed! {}", self.name, error);
+            }
         }
-
-        println!();
     }
+}
 
-    // Load tests from JSON files
+/// Loads all the JSON test cases, sorted by name.
+fn load_tests() -> Vec<Test> {
     let entries = fs::read_dir(&*TEST_DIRECTORY) //
         .expect("Unable to read directory");
 
@@ -183,6 +166,30 @@ fn ast() {
     // Sort tests by name
     let mut tests: Vec<Test> = tests_iter.collect();
     tests.sort_by(|a, b| (a.name).cmp(&b.name));
+    tests
+}
+
+#[test]
+fn ast() {
+    let log = crate::build_logger();
+
+    // Warn if any test are being skipped
+    if !SKIP_TESTS.is_empty() {
+        println!("=========");
+        println!(" WARNING ");
+        println!("=========");
+        println!();
+        println!("The following tests are being SKIPPED:");
+
+        for test in SKIP_TESTS {
+            println!("- {}", test);
+        }
+
+        println!();
+    }
+
+    // Load tests from JSON files
+    let tests = load_tests();
 
     // Run tests
     println!("Running tests:");
@@ -198,6 +205,8 @@ fn ast() {
 /// also goes past serde_json's recursion limit, lol.
 #[test]
 fn recursion_depth() {
+    use crate::tree::{Container, ContainerType};
+
     let log = crate::build_logger();
 
     // Build wikitext input
@@ -220,19 +229,66 @@ fn recursion_depth() {
     let warning = warnings.get(0).expect("No warnings produced");
     assert_eq!(warning.token(), Token::LeftBlock);
     assert_eq!(warning.rule(), "block-div");
-    assert_eq!(warning.span(), 800..802);
+    assert_eq!(warning.span(), Span::new(800, 802));
     assert_eq!(warning.kind(), ParseWarningKind::RecursionDepthExceeded);
 
     // Check syntax tree
     //
-    // It outputs the entire input string as text
+    // It outputs the entire input string as text, in a paragraph
 
     let SyntaxTree { elements, .. } = tree;
     assert_eq!(elements.len(), 1);
 
     let element = elements.get(0).expect("No elements produced");
     let input_cow = Cow::Borrowed(input.as_ref());
-    assert_eq!(element, &Element::Text(input_cow));
+    assert_eq!(
+        element,
+        &Element::Container(Container::new(
+            ContainerType::Paragraph,
+            vec![Element::Text(input_cow)],
+        )),
+    );
+}
+
+/// Test that nesting up to the recursion limit fits on a thread's stack.
+///
+/// Spawned threads have a 2 MiB stack by default, so elements
+/// must stay small enough for the parser to reach the limit on one.
+#[test]
+fn recursion_depth_stack() {
+    use std::thread;
+
+    const DEPTH: usize = 99;
+
+    let handle = thread::Builder::new()
+        .stack_size(2 * 1024 * 1024)
+        .spawn(|| {
+            let log = crate::build_logger();
+
+            // Build wikitext input
+            let mut input = String::new();
+
+            for _ in 0..DEPTH {
+                input.push_str("[[div]]\n");
+            }
+
+            input.push_str("apple\n");
+
+            for _ in 0..DEPTH {
+                input.push_str("[[/div]]\n");
+            }
+
+            // Run parser steps
+            crate::preprocess(&log, &mut input);
+            let tokens = crate::tokenize(&log, &input);
+            let (tree, warnings) = crate::parse(&log, &tokens).into();
+
+            assert!(warnings.is_empty(), "Warnings produced: {:?}", warnings);
+            assert_eq!(tree.elements.len(), 1);
+        })
+        .expect("Unable to spawn parser thread");
+
+    handle.join().expect("Parser overflowed its stack");
 }
 
 /// Test the parser's ability to process large bodies
@@ -271,3 +327,44 @@ In hac habitasse platea dictumst. Vestibulum fermentum libero nec erat porttitor
     // Check output
     assert_eq!(warnings.len(), ITERATIONS * 3);
 }
+
+/// Test that the hand-written lexer produces the same tokens as the pest grammar.
+#[test]
+fn lexer_conformance() {
+    const EXTRA_INPUTS: [&str; 16] = [
+        "[[[[a]]]]",
+        "[[[ [[[a]]] ]]]",
+        "~~~~= apple",
+        "&#x; &#x41; &amp &amp; &;",
+        "\"a\\qb\" \"unclosed",
+        "https//x https://example.com/ mailto:a@b.com",
+        "a\r\n\r\nb\n\n\nc",
+        ">> x\n> y\n>",
+        "--- ---- -- ---",
+        "||~ a || b|c ||",
+        "{{a}} @@b@@ @<c>@ {$var} {$",
+        "**//__--,,^^##",
+        "[[/div]] [[*user]] [[# anchor]]",
+        "\u{2014} ünïcödé ✓ 日本語",
+        ": a : b\n:: c",
+        "[[$ a $]] $]] [[[$ $$]]]",
+    ];
+
+    let log = crate::build_logger();
+    let tests = load_tests();
+    let inputs = tests
+        .iter()
+        .map(|test| test.case.input.as_str())
+        .chain(EXTRA_INPUTS.iter().copied());
+
+    for input in inputs {
+        let expected = Token::extract_all_pest(&log, input);
+        let actual = Token::extract_all_manual(&log, input);
+
+        assert_eq!(
+            actual, expected,
+            "Manual lexer output differs from pest for input {:?}",
+            input,
+        );
+    }
+}
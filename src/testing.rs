@@ -0,0 +1,110 @@
+/*
+ * testing.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! The format of the JSON test corpus, and how its test cases are run.
+//!
+//! Each file in the root `/test` directory contains a wikitext input, the
+//! settings to parse it with if not the defaults, and the syntax tree and
+//! warnings it is expected to produce.
+//!
+//! This is shared by the library's own tests and the CLI's `test` subcommand,
+//! so that both check test cases in the same way.
+
+use crate::includes::{DebugIncluder, IncludeSettings};
+use crate::parsing::{ParseOptions, ParseSettings, ParseWarning};
+use crate::tree::SyntaxTree;
+use void::ResultVoidExt;
+
+/// A single test case from the corpus.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TestCase<'t> {
+    pub input: String,
+
+    /// Parser settings for this test case, if it doesn't use the defaults.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub settings: ParseSettings,
+
+    pub tree: SyntaxTree<'t>,
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl TestCase<'_> {
+    /// Runs the input through the include, preprocess, tokenize, and parse stages.
+    ///
+    /// This produces a test case with the same input and settings, but with the
+    /// tree and warnings which were actually produced. So it can be compared to
+    /// this one, or written out in its place.
+    pub fn run(&self, log: &slog::Logger) -> TestCase<'static> {
+        let (mut text, _pages) = crate::include(
+            log,
+            &self.input,
+            &IncludeSettings::default(),
+            DebugIncluder,
+            || unreachable!(),
+        )
+        .void_unwrap();
+
+        crate::preprocess(log, &mut text);
+        let tokens = crate::tokenize(log, &text);
+        let options = ParseOptions::new(self.settings.clone());
+        let output = crate::parse_with_options(log, &tokens, &options);
+        let (tree, warnings) = output.outcome.into();
+
+        TestCase {
+            input: self.input.clone(),
+            settings: self.settings.clone(),
+            tree: tree.to_owned(),
+            warnings,
+        }
+    }
+
+    /// Checks the HTML rendered from this test case's tree, in both the default
+    /// and safe modes.
+    ///
+    /// See `check_tag_balance()` for what this does and doesn't detect.
+    #[cfg(feature = "html")]
+    pub fn check_html(&self) -> Result<(), String> {
+        use crate::render::html::{check_tag_balance, HtmlRender};
+        use crate::render::Render;
+
+        let safe = HtmlRender {
+            safe_mode: true,
+            ..HtmlRender::default()
+        };
+
+        for (mode, renderer) in &[("default", HtmlRender::default()), ("safe", safe)] {
+            let output = renderer.render(&self.tree);
+
+            check_tag_balance(&output.html).map_err(|error| {
+                format!(
+                    "Rendered HTML ({} mode) is unbalanced: {}\nHTML: {}",
+                    mode, error, output.html,
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+fn is_default(settings: &ParseSettings) -> bool {
+    settings == &ParseSettings::default()
+}
@@ -93,6 +93,20 @@ impl<'t> FullText<'t> {
         self.slice_impl(log, "partial", start, end)
     }
 
+    /// Finds the position of the given string within the full text.
+    ///
+    /// Returns `None` if it is not a slice of the full text,
+    /// for instance if it was unescaped into a new string.
+    pub fn position(&self, slice: &str) -> Option<usize> {
+        let start = (slice.as_ptr() as usize).checked_sub(self.text.as_ptr() as usize)?;
+
+        if start + slice.len() <= self.text.len() {
+            Some(start)
+        } else {
+            None
+        }
+    }
+
     fn slice_impl(
         &self,
         log: &slog::Logger,
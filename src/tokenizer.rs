@@ -19,7 +19,9 @@
  */
 
 use crate::parsing::{ExtractedToken, Token};
+use crate::render::tokens::render_tokens;
 use crate::text::FullText;
+use std::fmt::{self, Display};
 
 #[derive(Debug, Clone)]
 pub struct Tokenization<'t> {
@@ -39,6 +41,14 @@ impl<'t> Tokenization<'t> {
     }
 }
 
+/// Lists the tokens beneath the lines they begin on, see `render_tokens()`.
+impl Display for Tokenization<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&render_tokens(self))
+    }
+}
+
 impl<'t> From<Tokenization<'t>> for Vec<ExtractedToken<'t>> {
     #[inline]
     fn from(tokenization: Tokenization<'t>) -> Vec<ExtractedToken<'t>> {
@@ -0,0 +1,191 @@
+/*
+ * tree/bibliography.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Bibliographies, from `[[bibliography]]` blocks, and citations of their entries.
+//!
+//! Entries are numbered after parsing, once the whole page is known,
+//! so that `((bibcite label))` citations may come before the bibliography.
+
+use super::clone::{elements_to_owned, string_to_owned};
+use super::visit::{walk_elements, walk_elements_mut};
+use super::{Element, SyntaxTree};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// An entry in a bibliography, which citations refer to by its label.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct BibliographyEntry<'t> {
+    label: Cow<'t, str>,
+    number: usize,
+    elements: Vec<Element<'t>>,
+}
+
+impl<'t> BibliographyEntry<'t> {
+    /// Creates an entry, whose number is assigned by `resolve_bibliography()`.
+    #[inline]
+    pub fn new(label: Cow<'t, str>, elements: Vec<Element<'t>>) -> Self {
+        BibliographyEntry {
+            label,
+            number: 0,
+            elements,
+        }
+    }
+
+    #[inline]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The position of this entry among all bibliography entries on the page.
+    #[inline]
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    #[inline]
+    pub fn elements(&self) -> &[Element<'t>] {
+        &self.elements
+    }
+
+    #[inline]
+    pub fn elements_mut(&mut self) -> &mut Vec<Element<'t>> {
+        &mut self.elements
+    }
+
+    pub fn to_owned(&self) -> BibliographyEntry<'static> {
+        BibliographyEntry {
+            label: string_to_owned(&self.label),
+            number: self.number,
+            elements: elements_to_owned(&self.elements),
+        }
+    }
+}
+
+/// Gets every bibliography entry within the elements, in document order.
+pub fn collect_bibliography<'a, 't>(
+    elements: &'a [Element<'t>],
+) -> Vec<&'a BibliographyEntry<'t>> {
+    let mut entries = Vec::new();
+
+    walk_elements(elements, &mut |element| {
+        if let Element::Bibliography { entries: block, .. } = element {
+            entries.extend(block);
+        }
+    });
+
+    entries
+}
+
+/// Numbers each bibliography entry, and resolves each citation of one.
+///
+/// Entries are numbered from one, in document order, across every bibliography.
+/// If a label is used by several entries, citations refer to the first.
+/// Citations of a label which no entry has are left unresolved.
+pub fn resolve_bibliography(elements: &mut [Element]) {
+    let mut numbers = HashMap::new();
+    let mut count = 0;
+
+    walk_elements_mut(elements, &mut |element| {
+        if let Element::Bibliography { entries, .. } = element {
+            for entry in entries {
+                count += 1;
+                entry.number = count;
+                numbers.entry(entry.label.to_string()).or_insert(count);
+            }
+        }
+    });
+
+    walk_elements_mut(elements, &mut |element| {
+        if let Element::BibliographyCite { label, number } = element {
+            *number = numbers.get(label.as_ref()).copied();
+        }
+    });
+}
+
+impl<'t> SyntaxTree<'t> {
+    /// Gets every bibliography entry in the tree, in document order.
+    #[inline]
+    pub fn bibliography(&self) -> Vec<&BibliographyEntry<'t>> {
+        collect_bibliography(&self.elements)
+    }
+}
+
+#[test]
+fn bibliography() {
+    use super::{Container, ContainerType};
+
+    let cite = |label| Element::BibliographyCite {
+        label: cow!(label),
+        number: None,
+    };
+
+    let entry =
+        |label| BibliographyEntry::new(cow!(label), vec![Element::Text(cow!(label))]);
+
+    let mut elements = vec![
+        Element::Container(Container::new(
+            ContainerType::Paragraph,
+            vec![cite("banana"), cite("missing"), cite("apple")],
+        )),
+        Element::Bibliography {
+            title: None,
+            hide: false,
+            entries: vec![entry("apple"), entry("banana")],
+        },
+        Element::Bibliography {
+            title: None,
+            hide: false,
+            entries: vec![entry("apple"), entry("cherry")],
+        },
+        cite("cherry"),
+    ];
+
+    resolve_bibliography(&mut elements);
+
+    let numbers: Vec<_> = collect_bibliography(&elements)
+        .iter()
+        .map(|entry| (entry.label(), entry.number()))
+        .collect();
+
+    assert_eq!(
+        numbers,
+        vec![("apple", 1), ("banana", 2), ("apple", 3), ("cherry", 4)],
+        "Bibliography entry numbers don't match expected",
+    );
+
+    let citations = {
+        let mut citations = Vec::new();
+
+        walk_elements(&elements, &mut |element| {
+            if let Element::BibliographyCite { number, .. } = element {
+                citations.push(*number);
+            }
+        });
+
+        citations
+    };
+
+    assert_eq!(
+        citations,
+        vec![Some(2), None, Some(1), Some(4)],
+        "Resolved citation numbers don't match expected",
+    );
+}
@@ -0,0 +1,48 @@
+/*
+ * tree/clone.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Helper functions for converting borrowed syntax tree data into owned data.
+//!
+//! These are used by the various `to_owned()` methods, which produce
+//! `'static` versions of their structures.
+
+use super::Element;
+use ref_map::*;
+use std::borrow::Cow;
+
+#[inline]
+pub fn string_to_owned(value: &str) -> Cow<'static, str> {
+    Cow::Owned(value.to_owned())
+}
+
+#[inline]
+pub fn option_string_to_owned(value: &Option<Cow<str>>) -> Option<Cow<'static, str>> {
+    value.ref_map(|value| string_to_owned(value))
+}
+
+#[inline]
+pub fn strings_to_owned(values: &[Cow<str>]) -> Vec<Cow<'static, str>> {
+    values.iter().map(|value| string_to_owned(value)).collect()
+}
+
+#[inline]
+pub fn elements_to_owned(elements: &[Element]) -> Vec<Element<'static>> {
+    elements.iter().map(|element| element.to_owned()).collect()
+}
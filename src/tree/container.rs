@@ -20,13 +20,14 @@
 
 //! Representation of generic syntax elements which wrap other elements.
 
-use crate::enums::HeadingLevel;
+use super::clone::{elements_to_owned, option_string_to_owned};
+use crate::enums::{ClearFloat, FloatAlignment, HeadingLevel};
 use crate::tree::Element;
 use ref_map::*;
 use std::borrow::Cow;
 use strum_macros::IntoStaticStr;
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub struct Container<'t> {
     #[serde(rename = "type")]
@@ -49,6 +50,18 @@ impl<'t> Container<'t> {
     pub fn elements(&self) -> &[Element<'t>] {
         &self.elements
     }
+
+    #[inline]
+    pub(crate) fn elements_mut(&mut self) -> &mut Vec<Element<'t>> {
+        &mut self.elements
+    }
+
+    pub fn to_owned(&self) -> Container<'static> {
+        Container {
+            ctype: self.ctype,
+            elements: elements_to_owned(&self.elements),
+        }
+    }
 }
 
 impl<'t> From<Container<'t>> for Vec<Element<'t>> {
@@ -60,7 +73,7 @@ impl<'t> From<Container<'t>> for Vec<Element<'t>> {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub struct StyledContainer<'t> {
     #[serde(rename = "type")]
@@ -69,6 +82,12 @@ pub struct StyledContainer<'t> {
     id: Option<Cow<'t, str>>,
     class: Option<Cow<'t, str>>,
     style: Option<Cow<'t, str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    float: Option<FloatAlignment>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    clear: Option<ClearFloat>,
 }
 
 impl<'t> StyledContainer<'t> {
@@ -79,6 +98,8 @@ impl<'t> StyledContainer<'t> {
         id: Option<Cow<'t, str>>,
         class: Option<Cow<'t, str>>,
         style: Option<Cow<'t, str>>,
+        float: Option<FloatAlignment>,
+        clear: Option<ClearFloat>,
     ) -> Self {
         StyledContainer {
             ctype,
@@ -86,6 +107,8 @@ impl<'t> StyledContainer<'t> {
             id,
             class,
             style,
+            float,
+            clear,
         }
     }
 
@@ -99,6 +122,11 @@ impl<'t> StyledContainer<'t> {
         &self.elements
     }
 
+    #[inline]
+    pub(crate) fn elements_mut(&mut self) -> &mut Vec<Element<'t>> {
+        &mut self.elements
+    }
+
     #[inline]
     pub fn id(&self) -> Option<&str> {
         self.id.ref_map(|s| s.as_ref())
@@ -113,6 +141,43 @@ impl<'t> StyledContainer<'t> {
     pub fn style(&self) -> Option<&str> {
         self.style.ref_map(|s| s.as_ref())
     }
+
+    #[inline]
+    pub fn float(&self) -> Option<FloatAlignment> {
+        self.float
+    }
+
+    #[inline]
+    pub fn clear(&self) -> Option<ClearFloat> {
+        self.clear
+    }
+
+    /// Creates a container of the same type and styling, but with different contents.
+    ///
+    /// The ID is not copied, since it must remain unique.
+    pub(crate) fn clone_with_elements(&self, elements: Vec<Element<'t>>) -> Self {
+        StyledContainer {
+            ctype: self.ctype,
+            elements,
+            id: None,
+            class: self.class.clone(),
+            style: self.style.clone(),
+            float: self.float,
+            clear: self.clear,
+        }
+    }
+
+    pub fn to_owned(&self) -> StyledContainer<'static> {
+        StyledContainer {
+            ctype: self.ctype,
+            elements: elements_to_owned(&self.elements),
+            id: option_string_to_owned(&self.id),
+            class: option_string_to_owned(&self.class),
+            style: option_string_to_owned(&self.style),
+            float: self.float,
+            clear: self.clear,
+        }
+    }
 }
 
 impl<'t> From<StyledContainer<'t>> for Vec<Element<'t>> {
@@ -138,6 +203,9 @@ pub enum ContainerType {
     Strikethrough,
     Monospace,
     Header(HeadingLevel),
+
+    /// A heading which is left out of the table of contents, written `+* Title`.
+    UnlistedHeader(HeadingLevel),
 }
 
 impl ContainerType {
@@ -146,18 +214,51 @@ impl ContainerType {
         self.into()
     }
 
+    /// Whether this container is inline, such as formatting, rather than a block.
+    #[inline]
+    pub fn is_inline(self) -> bool {
+        !matches!(
+            self,
+            ContainerType::Paragraph
+                | ContainerType::Header(_)
+                | ContainerType::UnlistedHeader(_),
+        )
+    }
+
+    /// Whether this container may directly contain the given element.
+    ///
+    /// None of these may contain blocks, so paragraph gathering
+    /// ends the current paragraph at each block element.
+    #[inline]
+    pub fn accepts(self, child: &Element) -> bool {
+        !child.is_block()
+    }
+
+    /// The level of this heading, if it is one.
+    #[inline]
+    pub fn heading_level(self) -> Option<HeadingLevel> {
+        match self {
+            ContainerType::Header(level) | ContainerType::UnlistedHeader(level) => {
+                Some(level)
+            }
+            _ => None,
+        }
+    }
+
     #[inline]
     pub fn html_tag(self) -> &'static str {
         match self {
             ContainerType::Paragraph => "p",
             ContainerType::Strong => "strong",
-            ContainerType::Emphasis => "italics",
+            ContainerType::Emphasis => "em",
             ContainerType::Underline => "u",
             ContainerType::Superscript => "sup",
             ContainerType::Subscript => "sub",
             ContainerType::Strikethrough => "s",
             ContainerType::Monospace => "tt",
-            ContainerType::Header(level) => level.html_tag(),
+            ContainerType::Header(level) | ContainerType::UnlistedHeader(level) => {
+                level.html_tag()
+            }
         }
     }
 }
@@ -183,6 +284,9 @@ pub enum StyledContainerType {
     Mark,
     Insertion,
     Deletion,
+    Keyboard,
+    Variable,
+    Sample,
 }
 
 impl StyledContainerType {
@@ -191,6 +295,20 @@ impl StyledContainerType {
         self.into()
     }
 
+    /// Whether this container is inline, such as a span, rather than a block.
+    #[inline]
+    pub fn is_inline(self) -> bool {
+        !matches!(self, StyledContainerType::Div)
+    }
+
+    /// Whether this container may directly contain the given element.
+    pub fn accepts(self, child: &Element) -> bool {
+        match self {
+            StyledContainerType::Div => true,
+            _ => !child.is_block(),
+        }
+    }
+
     #[inline]
     pub fn html_tag(self) -> &'static str {
         match self {
@@ -199,6 +317,9 @@ impl StyledContainerType {
             StyledContainerType::Mark => "mark",
             StyledContainerType::Insertion => "ins",
             StyledContainerType::Deletion => "del",
+            StyledContainerType::Keyboard => "kbd",
+            StyledContainerType::Variable => "var",
+            StyledContainerType::Sample => "samp",
         }
     }
 }
@@ -0,0 +1,179 @@
+/*
+ * tree/counter.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Named counters, such as for numbering figures, from `[[counter]]` blocks.
+//!
+//! Counter values are assigned after parsing, once the whole page is known,
+//! so that `[[counter-ref]]` blocks may refer to counters later on the page.
+
+use super::clone::{option_string_to_owned, string_to_owned};
+use super::visit::walk_elements_mut;
+use super::Element;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// A use of a named counter, which is displayed as its resulting value.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Counter<'t> {
+    name: Cow<'t, str>,
+    id: Option<Cow<'t, str>>,
+    action: CounterAction,
+    value: i64,
+}
+
+impl<'t> Counter<'t> {
+    /// Creates a counter use, whose value is assigned by `resolve_counters()`.
+    #[inline]
+    pub fn new(
+        name: Cow<'t, str>,
+        id: Option<Cow<'t, str>>,
+        action: CounterAction,
+    ) -> Self {
+        Counter {
+            name,
+            id,
+            action,
+            value: 0,
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The label by which `[[counter-ref]]` blocks refer to this use.
+    #[inline]
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    #[inline]
+    pub fn action(&self) -> CounterAction {
+        self.action
+    }
+
+    #[inline]
+    pub fn value(&self) -> i64 {
+        self.value
+    }
+
+    pub fn to_owned(&self) -> Counter<'static> {
+        Counter {
+            name: string_to_owned(&self.name),
+            id: option_string_to_owned(&self.id),
+            action: self.action,
+            value: self.value,
+        }
+    }
+}
+
+/// How a counter's value changes at a particular use.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CounterAction {
+    /// Add this amount to the counter.
+    Increment(i64),
+
+    /// Set the counter to this value.
+    Reset(i64),
+}
+
+/// Assigns the value of each counter, and of each reference to one.
+///
+/// Counters start at zero, and are updated in document order.
+/// References to a label which no counter has are left unresolved.
+pub fn resolve_counters(elements: &mut [Element]) {
+    let mut counters: HashMap<String, i64> = HashMap::new();
+    let mut labels = HashMap::new();
+
+    walk_elements_mut(elements, &mut |element| {
+        if let Element::Counter(counter) = element {
+            let value = counters.entry(counter.name.to_string()).or_insert(0);
+
+            *value = match counter.action {
+                CounterAction::Increment(amount) => value.saturating_add(amount),
+                CounterAction::Reset(start) => start,
+            };
+
+            counter.value = *value;
+
+            if let Some(ref id) = counter.id {
+                labels.entry(id.to_string()).or_insert(*value);
+            }
+        }
+    });
+
+    walk_elements_mut(elements, &mut |element| {
+        if let Element::CounterRef { id, value } = element {
+            *value = labels.get(id.as_ref()).copied();
+        }
+    });
+}
+
+#[test]
+fn counters() {
+    use super::{Container, ContainerType};
+
+    let counter = |name, id: Option<&'static str>, action| {
+        Element::Counter(Counter::new(cow!(name), id.map(Cow::Borrowed), action))
+    };
+
+    let reference = |id| Element::CounterRef {
+        id: cow!(id),
+        value: None,
+    };
+
+    let mut elements = vec![
+        reference("fig-tree"),
+        counter("figure", None, CounterAction::Increment(1)),
+        Element::Container(Container::new(
+            ContainerType::Paragraph,
+            vec![
+                counter("figure", Some("fig-tree"), CounterAction::Increment(1)),
+                counter("table", None, CounterAction::Increment(1)),
+            ],
+        )),
+        counter("figure", None, CounterAction::Reset(10)),
+        counter("figure", None, CounterAction::Increment(5)),
+        reference("fig-missing"),
+    ];
+
+    resolve_counters(&mut elements);
+
+    let values: Vec<_> = {
+        let mut values = Vec::new();
+
+        walk_elements_mut(&mut elements, &mut |element| match element {
+            Element::Counter(counter) => values.push(Some(counter.value())),
+            Element::CounterRef { value, .. } => values.push(*value),
+            _ => (),
+        });
+
+        values
+    };
+
+    assert_eq!(
+        values,
+        vec![Some(2), Some(1), Some(2), Some(1), Some(10), Some(15), None],
+        "Resolved counter values don't match expected",
+    );
+}
@@ -0,0 +1,300 @@
+/*
+ * tree/depth.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Measuring and limiting how deeply elements in a syntax tree are nested.
+//!
+//! Serialization formats handle deep nesting poorly: serializers recurse
+//! once per level, and readers such as `serde_json` refuse input past a
+//! fixed nesting limit. Trees from hostile input, for instance built up
+//! through nested includes, can be cut down to a safe depth before output.
+//!
+//! Measuring a tree and gathering text work iteratively, so they are safe on
+//! trees of any depth. Limiting recurses, but only down to the permitted depth.
+
+use super::visit::{for_each_child_list, for_each_child_list_mut};
+use super::{Element, SyntaxTree};
+use crate::enums::LinkLabel;
+use std::borrow::Cow;
+
+/// The deepest JSON nesting `serde_json` reads, it rejects the 128th level.
+const JSON_NESTING_LIMIT: usize = 127;
+
+/// JSON nesting around the elements: the tree object and its element list.
+const JSON_TREE_LEVELS: usize = 2;
+
+/// JSON nesting between an element and its children, at most.
+///
+/// This is for tables: the element, its data, the rows, a row, its cells,
+/// a cell, and the cell's element list.
+const JSON_LEVELS_PER_ELEMENT: usize = 7;
+
+/// JSON nesting of an element without children, at most.
+///
+/// This is for mail forms: the element, its data, the module's data, the
+/// fields, a field, its options, and an option's value and label.
+const JSON_LEAF_LEVELS: usize = 7;
+
+/// The deepest nesting of elements permitted in serialized output.
+///
+/// This keeps JSON output within the nesting limit of `serde_json`, even if
+/// every level is a table and the deepest element is a mail form.
+pub const MAX_SERIALIZED_DEPTH: usize =
+    (JSON_NESTING_LIMIT - JSON_TREE_LEVELS - JSON_LEAF_LEVELS) / JSON_LEVELS_PER_ELEMENT
+        + 1;
+
+/// Finds how deeply the given elements are nested, where top-level elements are 1.
+pub fn element_depth(elements: &[Element]) -> usize {
+    let mut stack = vec![(elements, 1)];
+    let mut max_depth = 0;
+
+    while let Some((elements, depth)) = stack.pop() {
+        for element in elements {
+            max_depth = max_depth.max(depth);
+
+            for_each_child_list(element, &mut |children| {
+                stack.push((children, depth + 1));
+            });
+        }
+    }
+
+    max_depth
+}
+
+/// Limits how deeply the given elements are nested.
+///
+/// Any element at the deepest permitted level which has children is replaced
+/// with the text it contains, including link labels, code, and math.
+/// Elements without text of their own, such as images and modules, and text
+/// produced only when rendering, such as dates and counters, are dropped.
+///
+/// Returns whether any elements were replaced.
+pub fn limit_depth(elements: &mut [Element], max_depth: usize) -> bool {
+    if max_depth == 0 {
+        return false;
+    }
+
+    let mut changed = false;
+
+    for element in elements {
+        if max_depth == 1 {
+            if has_child_lists(element) {
+                let text = collect_text(element);
+                *element = Element::Text(Cow::Owned(text));
+                changed = true;
+            }
+        } else {
+            for_each_child_list_mut(element, &mut |children| {
+                changed |= limit_depth(children, max_depth - 1);
+            });
+        }
+    }
+
+    changed
+}
+
+fn has_child_lists(element: &Element) -> bool {
+    let mut found = false;
+    for_each_child_list(element, &mut |children| found |= !children.is_empty());
+    found
+}
+
+/// Gathers all the text within an element, in document order.
+fn collect_text(element: &Element) -> String {
+    let mut text = String::new();
+    let mut stack = vec![element];
+
+    while let Some(element) = stack.pop() {
+        match element {
+            Element::Text(value) | Element::Raw(value) | Element::Email(value) => {
+                text.push_str(value);
+            }
+            Element::Link { url, label, .. } => match label {
+                LinkLabel::Text(label) => text.push_str(label),
+                LinkLabel::Url | LinkLabel::Page => text.push_str(url),
+                LinkLabel::Elements(elements) => {
+                    stack.extend(elements.iter().rev());
+                }
+            },
+            Element::Code { contents, .. } => {
+                text.push_str(contents);
+                text.push('\n');
+            }
+            Element::Math { latex, inline } => {
+                text.push_str(latex);
+
+                if !inline {
+                    text.push('\n');
+                }
+            }
+            Element::LineBreak | Element::LineBreaks(_) => text.push('\n'),
+            _ => {
+                // Push in reverse, so children are popped in order
+                let mut lists = Vec::new();
+                for_each_child_list(element, &mut |children| lists.push(children));
+
+                for children in lists.into_iter().rev() {
+                    stack.extend(children.iter().rev());
+                }
+            }
+        }
+    }
+
+    text
+}
+
+impl SyntaxTree<'_> {
+    /// Finds how deeply the elements in this tree are nested.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        element_depth(&self.elements)
+    }
+
+    /// Limits how deeply the elements in this tree are nested.
+    ///
+    /// See `limit_depth()`.
+    #[inline]
+    pub fn limit_depth(&mut self, max_depth: usize) -> bool {
+        limit_depth(&mut self.elements, max_depth)
+    }
+}
+
+#[test]
+fn depth() {
+    fn nest(levels: usize) -> Element<'static> {
+        let mut element = text!("apple");
+
+        for _ in 1..levels {
+            element = Element::Color {
+                color: cow!("blue"),
+                elements: vec![element, Element::LineBreak],
+            };
+        }
+
+        element
+    }
+
+    let mut tree = SyntaxTree {
+        elements: vec![text!("banana"), nest(60)],
+        styles: vec![],
+        sections: vec![],
+    };
+
+    assert_eq!(tree.depth(), 60, "Tree depth doesn't match expected");
+    assert!(
+        !tree.clone().limit_depth(60),
+        "Tree within the limit was changed",
+    );
+
+    assert!(
+        tree.limit_depth(MAX_SERIALIZED_DEPTH),
+        "Tree was not limited"
+    );
+    assert_eq!(
+        tree.depth(),
+        MAX_SERIALIZED_DEPTH,
+        "Limited depth doesn't match"
+    );
+
+    // The innermost element is now the flattened text
+    let mut element = &tree.elements[1];
+    for _ in 1..MAX_SERIALIZED_DEPTH {
+        element = &element.children()[0];
+    }
+
+    let expected = format!("apple{}", "\n".repeat(60 - MAX_SERIALIZED_DEPTH));
+    assert_eq!(element, &Element::Text(Cow::Owned(expected)));
+
+    // And the output can be read back in
+    let json = serde_json::to_string(&tree).expect("Unable to serialize JSON");
+    let _: SyntaxTree =
+        serde_json::from_str(&json).expect("Unable to deserialize limited tree");
+}
+
+#[test]
+fn serialized_depth() {
+    use super::{FormField, Module, Table, TableCell, TableRow};
+    use std::num::NonZeroU32;
+
+    // Tables and mail forms nest the most JSON for their level
+    let mut field = FormField::new(cow!("fruit"));
+    field.options.push((cow!("apple"), cow!("Apple")));
+
+    let mut element = Element::Module(Module::MailForm {
+        to: cow!("admin@example.com"),
+        title: None,
+        button_text: None,
+        success_page: None,
+        fields: vec![field],
+    });
+
+    for _ in 1..MAX_SERIALIZED_DEPTH {
+        let column_span = NonZeroU32::new(1).unwrap();
+        let cell = TableCell::new(false, column_span, None, vec![element]);
+        element = Element::Table(Table::new(vec![TableRow::new(vec![cell])]));
+    }
+
+    let tree = SyntaxTree {
+        elements: vec![element],
+        styles: vec![],
+        sections: vec![],
+    };
+
+    assert_eq!(tree.depth(), MAX_SERIALIZED_DEPTH);
+
+    let json = serde_json::to_string(&tree).expect("Unable to serialize JSON");
+    let _: SyntaxTree =
+        serde_json::from_str(&json).expect("Unable to deserialize deepest tree");
+}
+
+#[test]
+fn flattened_text() {
+    use crate::enums::AnchorTarget;
+
+    let mut elements = vec![Element::Color {
+        color: cow!("blue"),
+        elements: vec![
+            Element::Link {
+                url: cow!("https://example.com"),
+                label: LinkLabel::Url,
+                anchor: AnchorTarget::Same,
+            },
+            text!(" "),
+            Element::Link {
+                url: cow!("apple"),
+                label: LinkLabel::Text(cow!("Apple")),
+                anchor: AnchorTarget::Same,
+            },
+            Element::Code {
+                contents: cow!("banana"),
+                language: None,
+            },
+            Element::Math {
+                latex: cow!("x^2"),
+                inline: true,
+            },
+        ],
+    }];
+
+    assert!(limit_depth(&mut elements, 1), "Elements were not limited");
+    assert_eq!(
+        elements,
+        vec![text!("https://example.com Applebanana\nx^2")],
+    );
+}
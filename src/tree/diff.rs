@@ -0,0 +1,313 @@
+/*
+ * tree/diff.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Comparison of two revisions of a page.
+//!
+//! This produces a single tree with the changes marked up, which can be
+//! rendered as usual to show a visual diff. Removed content is wrapped in
+//! `Deletion` containers, and added content in `Insertion` containers.
+//!
+//! Text is compared word by word. Elements which differ only in their
+//! contents, such as a paragraph whose text was edited, are compared
+//! recursively. Any other changed element is replaced as a whole.
+
+use super::visit::{for_each_child_list, for_each_child_list_mut};
+use super::{Element, StyledContainer, StyledContainerType, SyntaxTree};
+use std::borrow::Cow;
+use std::slice;
+
+/// The most pairs of words or elements which are compared at once.
+///
+/// Past this the lists are instead compared element by element,
+/// to bound the time and memory taken by very large changes.
+const MAX_COMPARISONS: usize = 1 << 22;
+
+/// Compares two revisions, producing the new one with its changes marked up.
+///
+/// The styles are those of the new revision. The result has no sections,
+/// since it doesn't correspond to any source text.
+pub fn diff_trees<'t>(old: &SyntaxTree<'t>, new: &SyntaxTree<'t>) -> SyntaxTree<'t> {
+    SyntaxTree {
+        elements: diff_elements(&old.elements, &new.elements),
+        styles: new.styles.clone(),
+        sections: Vec::new(),
+    }
+}
+
+/// Compares two lists of elements, see `diff_trees()`.
+pub fn diff_elements<'t>(old: &[Element<'t>], new: &[Element<'t>]) -> Vec<Element<'t>> {
+    let old = split_words(old);
+    let new = split_words(new);
+    let mut output = Output::default();
+
+    // Usually only a small part of a page changes, so skip what's the same
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    output.extend(&new[..prefix]);
+    diff_middle(
+        &mut output,
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+    output.extend(&new[new.len() - suffix..]);
+    output.elements
+}
+
+/// Finds the longest common subsequence of the lists, and marks up the rest.
+fn diff_middle<'t>(output: &mut Output<'t>, old: &[Element<'t>], new: &[Element<'t>]) {
+    if old.len().saturating_mul(new.len()) > MAX_COMPARISONS {
+        let old: Vec<_> = old.iter().collect();
+        let new: Vec<_> = new.iter().collect();
+        output.change(&old, &new);
+        return;
+    }
+
+    // lengths[i][j] is the length of the subsequence for old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut deleted = Vec::new();
+    let mut inserted = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            output.change(&deleted, &inserted);
+            deleted.clear();
+            inserted.clear();
+
+            output.extend(slice::from_ref(&new[j]));
+            i += 1;
+            j += 1;
+        } else if j < new.len()
+            && (i == old.len()
+                || lengths[i * width + j + 1] >= lengths[(i + 1) * width + j])
+        {
+            inserted.push(&new[j]);
+            j += 1;
+        } else {
+            deleted.push(&old[i]);
+            i += 1;
+        }
+    }
+
+    output.change(&deleted, &inserted);
+}
+
+/// The marked up elements, merging adjacent text as it is added.
+#[derive(Debug, Default)]
+struct Output<'t> {
+    elements: Vec<Element<'t>>,
+}
+
+impl<'t> Output<'t> {
+    fn push(&mut self, element: Element<'t>) {
+        match (self.elements.last_mut(), element) {
+            (Some(Element::Text(last)), Element::Text(text)) => {
+                last.to_mut().push_str(&text);
+            }
+            (_, element) => self.elements.push(element),
+        }
+    }
+
+    fn extend(&mut self, elements: &[Element<'t>]) {
+        for element in elements {
+            self.push(element.clone());
+        }
+    }
+
+    /// Adds a change, where the old elements were replaced by the new ones.
+    ///
+    /// Pairs of elements at the start which differ only in their contents
+    /// are compared recursively, the rest are deleted and inserted.
+    fn change(&mut self, deleted: &[&Element<'t>], inserted: &[&Element<'t>]) {
+        let paired = deleted
+            .iter()
+            .zip(inserted)
+            .take_while(|(old, new)| same_shape(old, new))
+            .count();
+
+        for (old, new) in deleted.iter().zip(inserted).take(paired) {
+            self.push(diff_children(old, new));
+        }
+
+        self.wrap(StyledContainerType::Deletion, &deleted[paired..]);
+        self.wrap(StyledContainerType::Insertion, &inserted[paired..]);
+    }
+
+    fn wrap(&mut self, ctype: StyledContainerType, elements: &[&Element<'t>]) {
+        if elements.is_empty() {
+            return;
+        }
+
+        let mut contents = Output::default();
+        for element in elements {
+            contents.push(Element::clone(element));
+        }
+
+        let container =
+            StyledContainer::new(ctype, contents.elements, None, None, None, None, None);
+
+        self.push(Element::StyledContainer(container));
+    }
+}
+
+/// Whether both elements have contents, and are the same apart from them.
+fn same_shape(old: &Element, new: &Element) -> bool {
+    let mut lists = 0;
+    for_each_child_list(old, &mut |_| lists += 1);
+
+    lists > 0 && without_children(old) == without_children(new)
+}
+
+fn without_children<'t>(element: &Element<'t>) -> Element<'t> {
+    let mut element = element.clone();
+    for_each_child_list_mut(&mut element, &mut |children| children.clear());
+    element
+}
+
+/// Produces the new element, with each list of contents compared to the old one's.
+fn diff_children<'t>(old: &Element<'t>, new: &Element<'t>) -> Element<'t> {
+    let mut old_lists = Vec::new();
+    for_each_child_list(old, &mut |children| old_lists.push(children));
+
+    let mut element = new.clone();
+    let mut index = 0;
+
+    for_each_child_list_mut(&mut element, &mut |children| {
+        *children = diff_elements(old_lists[index], children);
+        index += 1;
+    });
+
+    element
+}
+
+/// Splits text into words and runs of whitespace, so that text is compared by word.
+fn split_words<'t>(elements: &[Element<'t>]) -> Vec<Element<'t>> {
+    let mut output = Vec::new();
+    let mut text = String::new();
+
+    for element in elements {
+        match element {
+            Element::Text(value) => text.push_str(value),
+            Element::Null => (),
+            _ => {
+                push_words(&mut output, &text);
+                text.clear();
+                output.push(element.clone());
+            }
+        }
+    }
+
+    push_words(&mut output, &text);
+    output
+}
+
+fn push_words(output: &mut Vec<Element>, text: &str) {
+    let mut start = 0;
+    let mut last_whitespace = None;
+
+    for (index, ch) in text.char_indices() {
+        let whitespace = ch.is_whitespace();
+
+        if last_whitespace == Some(!whitespace) {
+            output.push(Element::Text(Cow::Owned(str!(text[start..index]))));
+            start = index;
+        }
+
+        last_whitespace = Some(whitespace);
+    }
+
+    if start < text.len() {
+        output.push(Element::Text(Cow::Owned(str!(text[start..]))));
+    }
+}
+
+#[test]
+fn diff() {
+    use super::{Container, ContainerType};
+
+    let paragraph =
+        |elements| Element::Container(Container::new(ContainerType::Paragraph, elements));
+
+    let change = |ctype, elements| {
+        Element::StyledContainer(StyledContainer::new(
+            ctype, elements, None, None, None, None, None,
+        ))
+    };
+
+    // Text is compared by word, within the paragraph
+    let old = vec![paragraph(vec![text!("The quick"), text!(" fox")])];
+    let new = vec![paragraph(vec![text!("The slow fox")])];
+
+    assert_eq!(
+        diff_elements(&old, &new),
+        vec![paragraph(vec![
+            text!("The "),
+            change(StyledContainerType::Deletion, vec![text!("quick")]),
+            change(StyledContainerType::Insertion, vec![text!("slow")]),
+            text!(" fox"),
+        ])],
+    );
+
+    // Other elements are replaced as a whole
+    let old = vec![paragraph(vec![text!("Apple")]), Element::HorizontalRule];
+    let new = vec![
+        paragraph(vec![text!("Apple")]),
+        Element::Code {
+            contents: cow!("banana"),
+            language: None,
+        },
+    ];
+
+    assert_eq!(
+        diff_elements(&old, &new),
+        vec![
+            paragraph(vec![text!("Apple")]),
+            change(StyledContainerType::Deletion, vec![Element::HorizontalRule]),
+            change(
+                StyledContainerType::Insertion,
+                vec![Element::Code {
+                    contents: cow!("banana"),
+                    language: None,
+                }],
+            ),
+        ],
+    );
+
+    // Identical revisions are unchanged
+    assert_eq!(diff_elements(&new, &new), new);
+}
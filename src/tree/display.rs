@@ -0,0 +1,156 @@
+/*
+ * tree/display.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! A human-readable outline of a syntax tree, for debugging.
+//!
+//! Each element is written on its own line, with its children indented
+//! beneath it. Text and other long values are shortened, so the outline
+//! shows the structure of the tree rather than its full contents.
+//! Use `DebugRender` or `JsonRender` to see everything.
+
+use super::visit::for_each_child_list;
+use super::{Element, SyntaxTree};
+use crate::enums::LinkLabel;
+use std::fmt::{self, Display};
+
+/// The most characters of a value which are written, before it is shortened.
+const MAX_VALUE_LENGTH: usize = 40;
+
+const INDENT: &str = "  ";
+
+impl Display for SyntaxTree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for element in &self.elements {
+            write_element(f, element, 0)?;
+        }
+
+        for style in &self.styles {
+            writeln!(f, "Style {}", Shortened(style))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Element<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_element(f, self, 0)
+    }
+}
+
+fn write_element(f: &mut fmt::Formatter, element: &Element, depth: usize) -> fmt::Result {
+    for _ in 0..depth {
+        f.write_str(INDENT)?;
+    }
+
+    f.write_str(element.name())?;
+
+    match element {
+        Element::Text(value) | Element::Raw(value) | Element::Email(value) => {
+            write!(f, " {}", Shortened(value))?;
+        }
+        Element::Link { url, label, .. } => {
+            write!(f, " {}", Shortened(url))?;
+
+            if let LinkLabel::Text(text) = label {
+                write!(f, " {}", Shortened(text))?;
+            }
+        }
+        Element::Anchor { url, .. } | Element::Iframe { url, .. } => {
+            write!(f, " {}", Shortened(url))?;
+        }
+        Element::Include { page, .. } => write!(f, " {}", page)?,
+        Element::Image(image) => {
+            write!(f, " {}", Shortened(&image.source.to_string()))?;
+        }
+        Element::Color { color, .. } => write!(f, " {}", Shortened(color))?,
+        Element::Code { contents, .. }
+        | Element::Diagram { contents, .. }
+        | Element::Math {
+            latex: contents, ..
+        } => {
+            write!(f, " {}", Shortened(contents))?;
+        }
+        _ => (),
+    }
+
+    writeln!(f)?;
+
+    let mut result = Ok(());
+    for_each_child_list(element, &mut |children| {
+        for child in children {
+            if result.is_ok() {
+                result = write_element(f, child, depth + 1);
+            }
+        }
+    });
+
+    result
+}
+
+/// Writes a quoted value, cut short if it is too long.
+struct Shortened<'a>(&'a str);
+
+impl Display for Shortened<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = self.0;
+
+        match value.char_indices().nth(MAX_VALUE_LENGTH) {
+            Some((index, _)) => write!(f, "{:?}...", &value[..index]),
+            None => write!(f, "{:?}", value),
+        }
+    }
+}
+
+#[test]
+fn display() {
+    use super::{Container, ContainerType};
+
+    const OUTPUT: &str = r#"Paragraph
+  Text "apple"
+  Strong
+    Text "banana"
+  Text "The quick brown fox jumps over the lazy "...
+Style "span.hidden-text { display: none; }"
+"#;
+
+    let tree = SyntaxTree {
+        elements: vec![Element::Container(Container::new(
+            ContainerType::Paragraph,
+            vec![
+                text!("apple"),
+                Element::Container(Container::new(
+                    ContainerType::Strong,
+                    vec![text!("banana")],
+                )),
+                text!("The quick brown fox jumps over the lazy dog"),
+            ],
+        ))],
+        styles: vec![cow!("span.hidden-text { display: none; }")],
+        sections: Vec::new(),
+    };
+
+    assert_eq!(
+        tree.to_string(),
+        OUTPUT,
+        "Syntax tree outline doesn't match expected",
+    );
+}
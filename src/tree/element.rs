@@ -18,9 +18,17 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use super::{Container, Module, StyledContainer};
-use crate::enums::{AnchorTarget, LinkLabel};
+use super::clone::{elements_to_owned, option_string_to_owned, string_to_owned};
+use super::{
+    BibliographyEntry, Container, Counter, DefinitionListItem, Image, List, Module,
+    PartialElement, StyledContainer, Tab, Table,
+};
+use crate::enums::{AnchorTarget, DiagramKind, FloatAlignment, HeadingLevel, LinkLabel};
+use crate::includes::{variables_to_owned, IncludeVariables, PageRef};
+use ref_map::*;
 use std::borrow::Cow;
+use std::hash::{Hash, Hasher};
+use std::mem;
 use std::num::NonZeroU32;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -43,6 +51,16 @@ pub enum Element<'t> {
     /// directly rendered.
     Module(Module<'t>),
 
+    /// An included page, parsed separately from the including page.
+    ///
+    /// This is produced by parse-time inclusion (see `include_elements()`),
+    /// which keeps the boundaries of included content within the tree.
+    Include {
+        page: PageRef<'t>,
+        variables: IncludeVariables<'t>,
+        elements: Vec<Element<'t>>,
+    },
+
     /// An element only containing text.
     ///
     /// Should be formatted like typical body text.
@@ -73,11 +91,47 @@ pub enum Element<'t> {
         anchor: AnchorTarget,
     },
 
+    /// A link wrapping other elements, as produced by `[[a]]`.
+    ///
+    /// Unlike `Link`, it may be styled and may override the "rel" attribute.
+    /// If "rel" is `None`, the anchor target's default is used.
+    Anchor {
+        elements: Vec<Element<'t>>,
+        url: Cow<'t, str>,
+        anchor: AnchorTarget,
+        rel: Option<Cow<'t, str>>,
+        id: Option<Cow<'t, str>>,
+        class: Option<Cow<'t, str>>,
+        style: Option<Cow<'t, str>>,
+    },
+
+    /// An image, optionally with a caption.
+    ///
+    /// The image's data is boxed, since it is much larger than most elements,
+    /// and every element is as large as its largest variant.
+    Image(Box<Image<'t>>),
+
+    /// An embedded page from another site.
+    ///
+    /// Only URLs on domains the site permits may be embedded,
+    /// see `SiteSettings::embed_domains`. This is checked at render time,
+    /// so the same tree can be rendered for sites with different policies.
+    Iframe {
+        url: Cow<'t, str>,
+        width: Option<Cow<'t, str>>,
+        height: Option<Cow<'t, str>>,
+        class: Option<Cow<'t, str>>,
+        style: Option<Cow<'t, str>>,
+    },
+
     /// A collapsible, containing content hidden to be opened on click.
     ///
     /// This is an interactable element provided by Wikidot which allows hiding
     /// all of the internal elements until it is opened by clicking, which can
     /// then be re-hidden by clicking again.
+    ///
+    /// The "show-text" and "hide-text" fields are the contents of the links
+    /// to open and close the collapsible. If `None`, use the default text.
     #[serde(rename_all = "kebab-case")]
     Collapsible {
         elements: Vec<Element<'t>>,
@@ -85,12 +139,116 @@ pub enum Element<'t> {
         class: Option<Cow<'t, str>>,
         style: Option<Cow<'t, str>>,
         start_open: bool,
-        show_text: Option<Cow<'t, str>>,
-        hide_text: Option<Cow<'t, str>>,
+        show_text: Option<Vec<Element<'t>>>,
+        hide_text: Option<Vec<Element<'t>>>,
         show_top: bool,
         show_bottom: bool,
     },
 
+    /// A block quotation, with an optional citation and attribution.
+    ///
+    /// The "cite" field is a URL to the quotation's origin, while the
+    /// "source" field is the attribution displayed beneath the quote.
+    #[serde(rename_all = "kebab-case")]
+    BlockQuote {
+        elements: Vec<Element<'t>>,
+        id: Option<Cow<'t, str>>,
+        class: Option<Cow<'t, str>>,
+        style: Option<Cow<'t, str>>,
+        cite: Option<Cow<'t, str>>,
+        source: Option<Vec<Element<'t>>>,
+    },
+
+    /// A block of verse, preserving its line breaks and leading spaces.
+    ///
+    /// Unlike code, the contents are still formatted wikitext.
+    Poem { elements: Vec<Element<'t>> },
+
+    /// A tab view, containing tabs which can be switched between.
+    ///
+    /// Only one tab's contents are visible at a time, with the first tab
+    /// being shown initially.
+    TabView(Vec<Tab<'t>>),
+
+    /// A table, composed of rows of cells.
+    Table(Table<'t>),
+
+    /// A bulleted or numbered list, composed of items.
+    List(List<'t>),
+
+    /// A definition list, composed of terms and their definitions.
+    DefinitionList(Vec<DefinitionListItem<'t>>),
+
+    /// A table of contents, listing the headings on the page.
+    ///
+    /// If a depth is given, only headings at that level or above are listed.
+    TableOfContents {
+        depth: Option<HeadingLevel>,
+        float: Option<FloatAlignment>,
+    },
+
+    /// An abbreviation, with its expansion shown on hover.
+    Abbreviation {
+        title: Cow<'t, str>,
+        elements: Vec<Element<'t>>,
+    },
+
+    /// A point in time, displayed in the reader's timezone.
+    ///
+    /// The timestamp is in seconds since the Unix epoch, and the format
+    /// follows `strftime`. If no format is given, the renderer's default is used.
+    Date {
+        timestamp: i64,
+        format: Option<Cow<'t, str>>,
+    },
+
+    /// A use of a named counter, displayed as its value.
+    Counter(Counter<'t>),
+
+    /// A reference to the value of a labelled counter elsewhere on the page.
+    ///
+    /// The value is `None` if no counter has this label.
+    CounterRef {
+        id: Cow<'t, str>,
+        value: Option<i64>,
+    },
+
+    /// A footnote, displayed as a numbered reference to its contents.
+    ///
+    /// The contents are listed at the page's footnote block, see `FootnoteBlock`.
+    Footnote { elements: Vec<Element<'t>> },
+
+    /// The location where the page's footnotes are listed.
+    ///
+    /// If the page has none, they are listed at its end instead.
+    /// If `hide` is set, the footnotes are not listed at all.
+    FootnoteBlock {
+        title: Option<Cow<'t, str>>,
+        hide: bool,
+    },
+
+    /// A citation of a bibliography entry, displayed as the entry's number.
+    ///
+    /// The number is `None` if no bibliography entry has this label.
+    BibliographyCite {
+        label: Cow<'t, str>,
+        number: Option<usize>,
+    },
+
+    /// A list of entries which may be cited elsewhere on the page.
+    ///
+    /// If `hide` is set, the entries are not listed, but can still be cited.
+    Bibliography {
+        title: Option<Cow<'t, str>>,
+        hide: bool,
+        entries: Vec<BibliographyEntry<'t>>,
+    },
+
+    /// A region which was left unchanged by typography substitutions.
+    ///
+    /// This has no effect on rendering, its elements are output as-is.
+    NoTypography { elements: Vec<Element<'t>> },
+
     /// Element containing colored text.
     ///
     /// The CSS designation of the color is specified, followed by the elements contained within.
@@ -105,6 +263,21 @@ pub enum Element<'t> {
         language: Option<Cow<'t, str>>,
     },
 
+    /// Element containing the source for a chart or diagram.
+    ///
+    /// The contents are kept as-is, to be drawn client-side
+    /// by a library appropriate for the "kind" field.
+    Diagram {
+        kind: DiagramKind,
+        contents: Cow<'t, str>,
+    },
+
+    /// Element containing a LaTeX math expression.
+    ///
+    /// Inline math is from `[[$ ... $]]`, and appears within text,
+    /// while block math is from `[[math]]`, and is displayed on its own.
+    Math { latex: Cow<'t, str>, inline: bool },
+
     /// A newline or line break.
     ///
     /// This calls for a newline in the final output, such as `<br>` in HTML.
@@ -116,6 +289,12 @@ pub enum Element<'t> {
     /// A horizontal rule.
     HorizontalRule,
 
+    /// An element which is only valid within a particular parent.
+    ///
+    /// This is an internal element used during parsing, it should
+    /// not be present in a completed syntax tree.
+    Partial(PartialElement<'t>),
+
     /// A null element.
     ///
     /// The element equivalent of a no-op instruction. No action should be taken,
@@ -129,19 +308,457 @@ impl Element<'_> {
             Element::Container(container) => container.ctype().name(),
             Element::StyledContainer(container) => container.ctype().name(),
             Element::Module(module) => module.name(),
+            Element::Include { .. } => "Include",
             Element::Text(_) => "Text",
             Element::Raw(_) => "Raw",
             Element::Email(_) => "Email",
             Element::Link { .. } => "Link",
+            Element::Anchor { .. } => "Anchor",
+            Element::Image(_) => "Image",
+            Element::Iframe { .. } => "Iframe",
             Element::Collapsible { .. } => "Collapsible",
+            Element::BlockQuote { .. } => "BlockQuote",
+            Element::Poem { .. } => "Poem",
+            Element::TabView(_) => "TabView",
+            Element::Table(_) => "Table",
+            Element::List(_) => "List",
+            Element::DefinitionList(_) => "DefinitionList",
+            Element::TableOfContents { .. } => "TableOfContents",
+            Element::Abbreviation { .. } => "Abbreviation",
+            Element::Date { .. } => "Date",
+            Element::Counter(_) => "Counter",
+            Element::CounterRef { .. } => "CounterRef",
+            Element::Footnote { .. } => "Footnote",
+            Element::FootnoteBlock { .. } => "FootnoteBlock",
+            Element::BibliographyCite { .. } => "BibliographyCite",
+            Element::Bibliography { .. } => "Bibliography",
+            Element::NoTypography { .. } => "NoTypography",
             Element::Color { .. } => "Color",
             Element::Code { .. } => "Code",
+            Element::Diagram { .. } => "Diagram",
+            Element::Math { .. } => "Math",
             Element::LineBreak => "LineBreak",
             Element::LineBreaks { .. } => "LineBreaks",
             Element::HorizontalRule => "HorizontalRule",
+            Element::Partial(partial) => partial.name(),
             Element::Null => "Null",
         }
     }
+
+    /// Whether this element is block-level, rather than inline.
+    ///
+    /// In HTML, block elements may not be placed within inline ones,
+    /// such as a `<div>` inside of a `<strong>`.
+    pub fn is_block(&self) -> bool {
+        match self {
+            Element::Container(container) => !container.ctype().is_inline(),
+            Element::StyledContainer(container) => !container.ctype().is_inline(),
+            Element::Image(image) => image.caption.is_some(),
+            Element::Math { inline, .. } => !inline,
+            Element::Include { elements, .. } => elements.iter().any(Element::is_block),
+            Element::Module(_)
+            | Element::Collapsible { .. }
+            | Element::BlockQuote { .. }
+            | Element::Poem { .. }
+            | Element::TabView(_)
+            | Element::Table(_)
+            | Element::List(_)
+            | Element::DefinitionList(_)
+            | Element::TableOfContents { .. }
+            | Element::FootnoteBlock { .. }
+            | Element::Bibliography { .. }
+            | Element::Code { .. }
+            | Element::Diagram { .. }
+            | Element::HorizontalRule => true,
+            Element::Text(_)
+            | Element::Raw(_)
+            | Element::Email(_)
+            | Element::Link { .. }
+            | Element::Anchor { .. }
+            | Element::Iframe { .. }
+            | Element::Abbreviation { .. }
+            | Element::Date { .. }
+            | Element::Counter(_)
+            | Element::CounterRef { .. }
+            | Element::Footnote { .. }
+            | Element::BibliographyCite { .. }
+            | Element::NoTypography { .. }
+            | Element::Color { .. }
+            | Element::LineBreak
+            | Element::LineBreaks(_)
+            | Element::Partial(_)
+            | Element::Null => false,
+        }
+    }
+
+    /// Returns the direct children of this element which its content model applies to.
+    ///
+    /// Elements which cannot contain other elements return an empty slice.
+    pub fn children(&self) -> &[Element<'_>] {
+        match self {
+            Element::Container(container) => container.elements(),
+            Element::StyledContainer(container) => container.elements(),
+            Element::Link {
+                label: LinkLabel::Elements(elements),
+                ..
+            }
+            | Element::Anchor { elements, .. }
+            | Element::Abbreviation { elements, .. }
+            | Element::Footnote { elements }
+            | Element::NoTypography { elements }
+            | Element::Poem { elements }
+            | Element::Color { elements, .. } => elements,
+            _ => &[],
+        }
+    }
+
+    /// Determines whether this element may directly contain the given one.
+    ///
+    /// This mirrors HTML content models, where inline elements
+    /// may only contain other inline elements.
+    pub fn accepts(&self, child: &Element) -> bool {
+        match self {
+            Element::Container(container) => container.ctype().accepts(child),
+            Element::StyledContainer(container) => container.ctype().accepts(child),
+            Element::Abbreviation { .. }
+            | Element::Footnote { .. }
+            | Element::Poem { .. }
+            | Element::Color { .. } => !child.is_block(),
+            _ => true,
+        }
+    }
+
+    /// Deep-clones the element, making it owned and using `'static`.
+    pub fn to_owned(&self) -> Element<'static> {
+        match self {
+            Element::Container(container) => Element::Container(container.to_owned()),
+            Element::StyledContainer(container) => {
+                Element::StyledContainer(container.to_owned())
+            }
+            Element::Module(module) => Element::Module(module.to_owned()),
+            Element::Include {
+                page,
+                variables,
+                elements,
+            } => Element::Include {
+                page: page.to_owned(),
+                variables: variables_to_owned(variables),
+                elements: elements_to_owned(elements),
+            },
+            Element::Text(text) => Element::Text(string_to_owned(text)),
+            Element::Raw(text) => Element::Raw(string_to_owned(text)),
+            Element::Email(email) => Element::Email(string_to_owned(email)),
+            Element::Link { url, label, anchor } => Element::Link {
+                url: string_to_owned(url),
+                label: label.to_owned(),
+                anchor: *anchor,
+            },
+            Element::Anchor {
+                elements,
+                url,
+                anchor,
+                rel,
+                id,
+                class,
+                style,
+            } => Element::Anchor {
+                elements: elements_to_owned(elements),
+                url: string_to_owned(url),
+                anchor: *anchor,
+                rel: option_string_to_owned(rel),
+                id: option_string_to_owned(id),
+                class: option_string_to_owned(class),
+                style: option_string_to_owned(style),
+            },
+            Element::Image(image) => Element::Image(Box::new(Image::to_owned(image))),
+            Element::Iframe {
+                url,
+                width,
+                height,
+                class,
+                style,
+            } => Element::Iframe {
+                url: string_to_owned(url),
+                width: option_string_to_owned(width),
+                height: option_string_to_owned(height),
+                class: option_string_to_owned(class),
+                style: option_string_to_owned(style),
+            },
+            Element::Collapsible {
+                elements,
+                id,
+                class,
+                style,
+                start_open,
+                show_text,
+                hide_text,
+                show_top,
+                show_bottom,
+            } => Element::Collapsible {
+                elements: elements_to_owned(elements),
+                id: option_string_to_owned(id),
+                class: option_string_to_owned(class),
+                style: option_string_to_owned(style),
+                start_open: *start_open,
+                show_text: show_text.ref_map(|elements| elements_to_owned(elements)),
+                hide_text: hide_text.ref_map(|elements| elements_to_owned(elements)),
+                show_top: *show_top,
+                show_bottom: *show_bottom,
+            },
+            Element::BlockQuote {
+                elements,
+                id,
+                class,
+                style,
+                cite,
+                source,
+            } => Element::BlockQuote {
+                elements: elements_to_owned(elements),
+                id: option_string_to_owned(id),
+                class: option_string_to_owned(class),
+                style: option_string_to_owned(style),
+                cite: option_string_to_owned(cite),
+                source: source.ref_map(|elements| elements_to_owned(elements)),
+            },
+            Element::Poem { elements } => Element::Poem {
+                elements: elements_to_owned(elements),
+            },
+            Element::TabView(tabs) => {
+                Element::TabView(tabs.iter().map(|tab| tab.to_owned()).collect())
+            }
+            Element::Table(table) => Element::Table(table.to_owned()),
+            Element::List(list) => Element::List(list.to_owned()),
+            Element::DefinitionList(items) => Element::DefinitionList(
+                items.iter().map(|item| item.to_owned()).collect(),
+            ),
+            Element::TableOfContents { depth, float } => Element::TableOfContents {
+                depth: *depth,
+                float: *float,
+            },
+            Element::Abbreviation { title, elements } => Element::Abbreviation {
+                title: string_to_owned(title),
+                elements: elements_to_owned(elements),
+            },
+            Element::Date { timestamp, format } => Element::Date {
+                timestamp: *timestamp,
+                format: option_string_to_owned(format),
+            },
+            Element::Counter(counter) => Element::Counter(counter.to_owned()),
+            Element::CounterRef { id, value } => Element::CounterRef {
+                id: string_to_owned(id),
+                value: *value,
+            },
+            Element::Footnote { elements } => Element::Footnote {
+                elements: elements_to_owned(elements),
+            },
+            Element::FootnoteBlock { title, hide } => Element::FootnoteBlock {
+                title: option_string_to_owned(title),
+                hide: *hide,
+            },
+            Element::BibliographyCite { label, number } => Element::BibliographyCite {
+                label: string_to_owned(label),
+                number: *number,
+            },
+            Element::Bibliography {
+                title,
+                hide,
+                entries,
+            } => Element::Bibliography {
+                title: option_string_to_owned(title),
+                hide: *hide,
+                entries: entries.iter().map(BibliographyEntry::to_owned).collect(),
+            },
+            Element::NoTypography { elements } => Element::NoTypography {
+                elements: elements_to_owned(elements),
+            },
+            Element::Color { color, elements } => Element::Color {
+                color: string_to_owned(color),
+                elements: elements_to_owned(elements),
+            },
+            Element::Code { contents, language } => Element::Code {
+                contents: string_to_owned(contents),
+                language: option_string_to_owned(language),
+            },
+            Element::Diagram { kind, contents } => Element::Diagram {
+                kind: *kind,
+                contents: string_to_owned(contents),
+            },
+            Element::Math { latex, inline } => Element::Math {
+                latex: string_to_owned(latex),
+                inline: *inline,
+            },
+            Element::LineBreak => Element::LineBreak,
+            Element::LineBreaks(amount) => Element::LineBreaks(*amount),
+            Element::HorizontalRule => Element::HorizontalRule,
+            Element::Partial(partial) => Element::Partial(partial.to_owned()),
+            Element::Null => Element::Null,
+        }
+    }
+}
+
+// Implemented manually since include variables are a HashMap,
+// which are hashed here in key order to be consistent with equality.
+impl Hash for Element<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            Element::Container(container) => container.hash(state),
+            Element::StyledContainer(container) => container.hash(state),
+            Element::Module(module) => module.hash(state),
+            Element::Include {
+                page,
+                variables,
+                elements,
+            } => {
+                let mut variables: Vec<_> = variables.iter().collect();
+                variables.sort_unstable();
+
+                page.hash(state);
+                variables.hash(state);
+                elements.hash(state);
+            }
+            Element::Text(text) | Element::Raw(text) | Element::Email(text) => {
+                text.hash(state)
+            }
+            Element::Link { url, label, anchor } => {
+                url.hash(state);
+                label.hash(state);
+                anchor.hash(state);
+            }
+            Element::Anchor {
+                elements,
+                url,
+                anchor,
+                rel,
+                id,
+                class,
+                style,
+            } => {
+                elements.hash(state);
+                url.hash(state);
+                anchor.hash(state);
+                rel.hash(state);
+                id.hash(state);
+                class.hash(state);
+                style.hash(state);
+            }
+            Element::Image(image) => image.hash(state),
+            Element::Iframe {
+                url,
+                width,
+                height,
+                class,
+                style,
+            } => {
+                url.hash(state);
+                width.hash(state);
+                height.hash(state);
+                class.hash(state);
+                style.hash(state);
+            }
+            Element::Collapsible {
+                elements,
+                id,
+                class,
+                style,
+                start_open,
+                show_text,
+                hide_text,
+                show_top,
+                show_bottom,
+            } => {
+                elements.hash(state);
+                id.hash(state);
+                class.hash(state);
+                style.hash(state);
+                start_open.hash(state);
+                show_text.hash(state);
+                hide_text.hash(state);
+                show_top.hash(state);
+                show_bottom.hash(state);
+            }
+            Element::BlockQuote {
+                elements,
+                id,
+                class,
+                style,
+                cite,
+                source,
+            } => {
+                elements.hash(state);
+                id.hash(state);
+                class.hash(state);
+                style.hash(state);
+                cite.hash(state);
+                source.hash(state);
+            }
+            Element::Poem { elements } => elements.hash(state),
+            Element::TabView(tabs) => tabs.hash(state),
+            Element::Table(table) => table.hash(state),
+            Element::List(list) => list.hash(state),
+            Element::DefinitionList(items) => items.hash(state),
+            Element::TableOfContents { depth, float } => {
+                depth.hash(state);
+                float.hash(state);
+            }
+            Element::Abbreviation {
+                title: value,
+                elements,
+            }
+            | Element::Color {
+                color: value,
+                elements,
+            } => {
+                value.hash(state);
+                elements.hash(state);
+            }
+            Element::Date { timestamp, format } => {
+                timestamp.hash(state);
+                format.hash(state);
+            }
+            Element::Counter(counter) => counter.hash(state),
+            Element::CounterRef { id, value } => {
+                id.hash(state);
+                value.hash(state);
+            }
+            Element::Footnote { elements } | Element::NoTypography { elements } => {
+                elements.hash(state)
+            }
+            Element::FootnoteBlock { title, hide } => {
+                title.hash(state);
+                hide.hash(state);
+            }
+            Element::BibliographyCite { label, number } => {
+                label.hash(state);
+                number.hash(state);
+            }
+            Element::Bibliography {
+                title,
+                hide,
+                entries,
+            } => {
+                title.hash(state);
+                hide.hash(state);
+                entries.hash(state);
+            }
+            Element::Code { contents, language } => {
+                contents.hash(state);
+                language.hash(state);
+            }
+            Element::Diagram { kind, contents } => {
+                kind.hash(state);
+                contents.hash(state);
+            }
+            Element::Math { latex, inline } => {
+                latex.hash(state);
+                inline.hash(state);
+            }
+            Element::LineBreaks(amount) => amount.hash(state),
+            Element::Partial(partial) => partial.hash(state),
+            Element::LineBreak | Element::HorizontalRule | Element::Null => (),
+        }
+    }
 }
 
 impl slog::Value for Element<'_> {
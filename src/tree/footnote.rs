@@ -0,0 +1,204 @@
+/*
+ * tree/footnote.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! The footnotes of a document, numbered in order of first appearance.
+//!
+//! Footnotes with identical contents are the same footnote,
+//! and so share a number and an entry in the footnote block.
+
+use super::visit::{for_each_child_list, walk_elements};
+use super::{Element, SyntaxTree};
+
+#[cfg(feature = "html")]
+use std::ptr;
+
+/// The footnotes within the document.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Footnotes<'a, 't> {
+    /// The contents of each distinct footnote.
+    ///
+    /// Footnote number `n` is at index `n - 1`.
+    pub contents: Vec<&'a [Element<'t>]>,
+
+    /// The number of the footnote each reference refers to, in document order.
+    pub references: Vec<usize>,
+}
+
+impl Footnotes<'_, '_> {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_empty()
+    }
+}
+
+/// Gets every footnote within the elements, in document order.
+///
+/// Footnotes are not searched for within other footnotes,
+/// since their contents are only rendered in the footnote block.
+pub fn collect_footnotes<'a, 't>(elements: &'a [Element<'t>]) -> Footnotes<'a, 't> {
+    let mut footnotes = Footnotes::default();
+    collect_footnotes_internal(elements, &mut footnotes);
+    footnotes
+}
+
+fn collect_footnotes_internal<'a, 't>(
+    elements: &'a [Element<'t>],
+    footnotes: &mut Footnotes<'a, 't>,
+) {
+    for element in elements {
+        if let Element::Footnote { elements: contents } = element {
+            let index = footnotes
+                .contents
+                .iter()
+                .position(|&existing| existing == contents.as_slice());
+
+            let number = match index {
+                Some(index) => index + 1,
+                None => {
+                    footnotes.contents.push(contents);
+                    footnotes.contents.len()
+                }
+            };
+
+            footnotes.references.push(number);
+            continue;
+        }
+
+        for_each_child_list(element, &mut |children| {
+            collect_footnotes_internal(children, footnotes)
+        });
+    }
+}
+
+/// Whether there is a footnote block within the elements.
+pub fn has_footnote_block(elements: &[Element]) -> bool {
+    let mut found = false;
+
+    walk_elements(elements, &mut |element| {
+        if let Element::FootnoteBlock { .. } = element {
+            found = true;
+        }
+    });
+
+    found
+}
+
+/// Counts the footnote references which come before the given element, in document order.
+///
+/// Returns `None` if the element is not within the elements.
+#[cfg(feature = "html")]
+pub(crate) fn footnotes_before(elements: &[Element], target: &Element) -> Option<usize> {
+    let mut count = 0;
+
+    if footnotes_before_internal(elements, target as *const _ as *const (), &mut count) {
+        Some(count)
+    } else {
+        None
+    }
+}
+
+#[cfg(feature = "html")]
+fn footnotes_before_internal(
+    elements: &[Element],
+    target: *const (),
+    count: &mut usize,
+) -> bool {
+    for element in elements {
+        if ptr::eq(element as *const _ as *const (), target) {
+            return true;
+        }
+
+        if let Element::Footnote { .. } = element {
+            *count += 1;
+            continue;
+        }
+
+        let mut found = false;
+        for_each_child_list(element, &mut |children| {
+            if !found {
+                found = footnotes_before_internal(children, target, count);
+            }
+        });
+
+        if found {
+            return true;
+        }
+    }
+
+    false
+}
+
+impl<'t> SyntaxTree<'t> {
+    /// Gets every footnote in the tree, in document order.
+    #[inline]
+    pub fn footnotes(&self) -> Footnotes<'_, 't> {
+        collect_footnotes(&self.elements)
+    }
+}
+
+#[test]
+fn footnotes() {
+    use super::{Container, ContainerType};
+
+    let footnote = |text| Element::Footnote {
+        elements: vec![Element::Text(cow!(text))],
+    };
+
+    let elements = vec![
+        Element::Container(Container::new(
+            ContainerType::Paragraph,
+            vec![footnote("Apple"), footnote("Banana")],
+        )),
+        footnote("Apple"),
+        Element::Container(Container::new(
+            ContainerType::Strong,
+            vec![footnote("Cherry")],
+        )),
+        Element::FootnoteBlock {
+            title: None,
+            hide: false,
+        },
+    ];
+
+    let footnotes = collect_footnotes(&elements);
+    let contents: Vec<&str> = footnotes
+        .contents
+        .iter()
+        .map(|elements| match elements {
+            [Element::Text(text)] => text.as_ref(),
+            _ => panic!("Unexpected footnote contents: {:?}", elements),
+        })
+        .collect();
+
+    assert_eq!(
+        contents,
+        vec!["Apple", "Banana", "Cherry"],
+        "Footnote contents don't match expected",
+    );
+
+    assert_eq!(
+        footnotes.references,
+        vec![1, 2, 1, 3],
+        "Footnote references don't match expected",
+    );
+
+    assert!(has_footnote_block(&elements), "Footnote block not found");
+    assert!(!has_footnote_block(&elements[..3]), "Footnote block found");
+}
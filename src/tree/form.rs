@@ -0,0 +1,176 @@
+/*
+ * tree/form.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Representation of the fields of a form, such as in the `MailForm` module.
+//!
+//! The fields describe what the form asks for and how entries are validated.
+//! Handling submissions is up to the host.
+
+use super::clone::{option_string_to_owned, string_to_owned};
+use std::borrow::Cow;
+use strum_macros::IntoStaticStr;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct FormField<'t> {
+    /// The name the field's value is submitted under.
+    pub name: Cow<'t, str>,
+
+    /// The label shown for the field, if different from its name.
+    pub title: Option<Cow<'t, str>>,
+
+    /// A description of what to enter, shown alongside the field.
+    pub hint: Option<Cow<'t, str>>,
+
+    /// The value the field starts with.
+    pub default: Option<Cow<'t, str>>,
+
+    #[serde(rename = "type")]
+    pub field_type: FormFieldType,
+
+    /// The choices of a select field, as pairs of value and label.
+    pub options: Vec<(Cow<'t, str>, Cow<'t, str>)>,
+
+    /// The checks an entry must pass before the form is submitted.
+    pub rules: Vec<FormRule<'t>>,
+}
+
+impl<'t> FormField<'t> {
+    #[inline]
+    pub fn new(name: Cow<'t, str>) -> Self {
+        FormField {
+            name,
+            title: None,
+            hint: None,
+            default: None,
+            field_type: FormFieldType::Text,
+            options: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// The label to show for the field.
+    #[inline]
+    pub fn label(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Whether an entry must be given for this field.
+    pub fn required(&self) -> bool {
+        self.rules.contains(&FormRule::Required)
+    }
+
+    pub fn to_owned(&self) -> FormField<'static> {
+        FormField {
+            name: string_to_owned(&self.name),
+            title: option_string_to_owned(&self.title),
+            hint: option_string_to_owned(&self.hint),
+            default: option_string_to_owned(&self.default),
+            field_type: self.field_type,
+            options: self
+                .options
+                .iter()
+                .map(|(value, label)| (string_to_owned(value), string_to_owned(label)))
+                .collect(),
+            rules: self.rules.iter().map(FormRule::to_owned).collect(),
+        }
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, IntoStaticStr, Debug, Copy, Clone, Hash, PartialEq, Eq,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum FormFieldType {
+    /// A single line of text.
+    Text,
+
+    /// Multiple lines of text.
+    TextArea,
+
+    /// A choice from a list of options.
+    Select,
+
+    /// A box which is either checked or not.
+    Checkbox,
+}
+
+impl FormFieldType {
+    #[inline]
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
+
+    /// Gets the field type with the name used in Wikidot's form syntax.
+    pub fn from_name(name: &str) -> Option<Self> {
+        const TYPES: [(&str, FormFieldType); 4] = [
+            ("text", FormFieldType::Text),
+            ("textarea", FormFieldType::TextArea),
+            ("select", FormFieldType::Select),
+            ("checkbox", FormFieldType::Checkbox),
+        ];
+
+        TYPES
+            .iter()
+            .find(|(type_name, _)| type_name.eq_ignore_ascii_case(name))
+            .map(|(_, field_type)| *field_type)
+    }
+}
+
+/// A check on the value entered into a form field.
+#[derive(Serialize, Deserialize, IntoStaticStr, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "rule", content = "value")]
+pub enum FormRule<'t> {
+    /// The field must not be left empty.
+    Required,
+
+    /// The entry must be at least this many characters long.
+    MinLength(u32),
+
+    /// The entry must be at most this many characters long.
+    MaxLength(u32),
+
+    /// The entry must be an email address.
+    Email,
+
+    /// The entry must be a number.
+    Number,
+
+    /// The entry must match this regular expression.
+    Match(Cow<'t, str>),
+}
+
+impl FormRule<'_> {
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.into()
+    }
+
+    pub fn to_owned(&self) -> FormRule<'static> {
+        match self {
+            FormRule::Required => FormRule::Required,
+            FormRule::MinLength(length) => FormRule::MinLength(*length),
+            FormRule::MaxLength(length) => FormRule::MaxLength(*length),
+            FormRule::Email => FormRule::Email,
+            FormRule::Number => FormRule::Number,
+            FormRule::Match(pattern) => FormRule::Match(string_to_owned(pattern)),
+        }
+    }
+}
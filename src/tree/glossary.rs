@@ -0,0 +1,328 @@
+/*
+ * tree/glossary.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Optional pass to link terms to their definitions, for wikis with glossaries.
+//!
+//! Given a map of terms, the first occurrence of each in the page's text is
+//! wrapped in a link to the page describing it, or an abbreviation showing
+//! its definition on hover. Text within code, links and abbreviations is
+//! left alone.
+
+use super::visit::for_each_child_list_mut;
+use super::{ContainerType, Element};
+use crate::enums::{AnchorTarget, LinkLabel};
+use std::borrow::Cow;
+use std::mem;
+
+/// A set of terms, and what each should be linked to.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Glossary {
+    terms: Vec<(String, GlossaryTarget)>,
+}
+
+impl Glossary {
+    #[inline]
+    pub fn new() -> Self {
+        Glossary::default()
+    }
+
+    /// Adds a term, replacing any existing one which is the same ignoring case.
+    pub fn insert<S: Into<String>>(&mut self, term: S, target: GlossaryTarget) {
+        let term = term.into();
+
+        if term.is_empty() {
+            return;
+        }
+
+        self.terms
+            .retain(|(other, _)| !other.eq_ignore_ascii_case(&term));
+
+        // Longer terms are matched first, so "red dwarf" wins over "red"
+        let index = self
+            .terms
+            .iter()
+            .position(|(other, _)| other.len() < term.len())
+            .unwrap_or(self.terms.len());
+
+        self.terms.insert(index, (term, target));
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+/// What a glossary term is linked to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GlossaryTarget {
+    /// Link to the page with this URL.
+    Page(String),
+
+    /// Show this definition when hovering over the term.
+    Definition(String),
+}
+
+/// Wraps the first occurrence of each glossary term in the given elements.
+///
+/// Terms are matched as whole words, ignoring ASCII case.
+pub fn apply_glossary(elements: &mut Vec<Element>, glossary: &Glossary) {
+    let mut used = vec![false; glossary.terms.len()];
+
+    apply_elements(elements, glossary, &mut used);
+}
+
+fn apply_elements<'t>(
+    elements: &mut Vec<Element<'t>>,
+    glossary: &Glossary,
+    used: &mut [bool],
+) {
+    let old_elements = mem::take(elements);
+    let mut run = Vec::new();
+
+    for mut element in old_elements {
+        if let Element::Text(_) = element {
+            run.push(element);
+            continue;
+        }
+
+        apply_run(elements, mem::take(&mut run), glossary, used);
+
+        if !is_excluded(&element) {
+            for_each_child_list_mut(&mut element, &mut |children| {
+                apply_elements(children, glossary, used)
+            });
+        }
+
+        elements.push(element);
+    }
+
+    apply_run(elements, run, glossary, used);
+}
+
+/// Whether terms within this element should not be linked.
+fn is_excluded(element: &Element) -> bool {
+    match element {
+        Element::Container(container) => container.ctype() == ContainerType::Monospace,
+        Element::Link { .. }
+        | Element::Anchor { .. }
+        | Element::Abbreviation { .. }
+        | Element::Code { .. } => true,
+        _ => false,
+    }
+}
+
+/// Links terms in a run of adjacent text elements, appending the result to `output`.
+fn apply_run<'t>(
+    output: &mut Vec<Element<'t>>,
+    run: Vec<Element<'t>>,
+    glossary: &Glossary,
+    used: &mut [bool],
+) {
+    let text: String = run
+        .iter()
+        .filter_map(|element| match element {
+            Element::Text(text) => Some(text.as_ref()),
+            _ => None,
+        })
+        .collect();
+
+    let matches = find_terms(&text, glossary, used);
+
+    if matches.is_empty() {
+        output.extend(run);
+        return;
+    }
+
+    let mut last = 0;
+
+    for (start, end, index) in matches {
+        if last < start {
+            output.push(Element::Text(Cow::Owned(text[last..start].to_string())));
+        }
+
+        let term = text[start..end].to_string();
+        let element = match &glossary.terms[index].1 {
+            GlossaryTarget::Page(url) => Element::Link {
+                url: Cow::Owned(url.clone()),
+                label: LinkLabel::Text(Cow::Owned(term)),
+                anchor: AnchorTarget::Same,
+            },
+            GlossaryTarget::Definition(definition) => Element::Abbreviation {
+                title: Cow::Owned(definition.clone()),
+                elements: vec![Element::Text(Cow::Owned(term))],
+            },
+        };
+
+        output.push(element);
+        last = end;
+    }
+
+    if last < text.len() {
+        output.push(Element::Text(Cow::Owned(text[last..].to_string())));
+    }
+}
+
+/// Finds the first occurrence of each unused term, as (start, end, term index).
+fn find_terms(
+    text: &str,
+    glossary: &Glossary,
+    used: &mut [bool],
+) -> Vec<(usize, usize, usize)> {
+    let is_word_char = |ch: Option<char>| matches!(ch, Some(ch) if ch.is_alphanumeric());
+    let mut matches = Vec::new();
+    let mut start = 0;
+
+    while start < text.len() {
+        let at_word_start = !is_word_char(text[..start].chars().next_back());
+
+        let found = if at_word_start {
+            glossary
+                .terms
+                .iter()
+                .enumerate()
+                .find_map(|(index, (term, _))| {
+                    let end = start + term.len();
+                    let candidate = text.get(start..end)?;
+                    let at_word_end = !is_word_char(text[end..].chars().next());
+
+                    if !used[index] && at_word_end && candidate.eq_ignore_ascii_case(term)
+                    {
+                        Some((end, index))
+                    } else {
+                        None
+                    }
+                })
+        } else {
+            None
+        };
+
+        match found {
+            Some((end, index)) => {
+                used[index] = true;
+                matches.push((start, end, index));
+                start = end;
+            }
+            None => {
+                let ch = text[start..].chars().next().expect("No character at index");
+                start += ch.len_utf8();
+            }
+        }
+    }
+
+    matches
+}
+
+#[test]
+fn glossary() {
+    use super::Container;
+
+    let mut glossary = Glossary::new();
+    glossary.insert(
+        "SCP",
+        GlossaryTarget::Page(str!("/about-the-scp-foundation")),
+    );
+    glossary.insert(
+        "keter",
+        GlossaryTarget::Definition(str!("Difficult to contain")),
+    );
+    glossary.insert("red", GlossaryTarget::Definition(str!("A color")));
+    glossary.insert("red dwarf", GlossaryTarget::Page(str!("/red-dwarf")));
+
+    let mut elements = vec![
+        Element::Container(Container::new(
+            ContainerType::Paragraph,
+            vec![
+                text!("The"),
+                text!(" "),
+                text!("scp"),
+                text!(" "),
+                text!("is"),
+                text!(" "),
+                text!("Keter"),
+                text!(","),
+                text!(" "),
+                text!("SCPs"),
+                text!(" "),
+                text!("and"),
+                text!(" "),
+                text!("SCP"),
+            ],
+        )),
+        Element::Container(Container::new(ContainerType::Monospace, vec![text!("red")])),
+        text!("A"),
+        text!(" "),
+        text!("red"),
+        text!(" "),
+        text!("dwarf"),
+        text!(" "),
+        text!("is"),
+        text!(" "),
+        text!("red"),
+    ];
+
+    apply_glossary(&mut elements, &glossary);
+
+    assert_eq!(
+        elements,
+        vec![
+            Element::Container(Container::new(
+                ContainerType::Paragraph,
+                vec![
+                    text!("The "),
+                    Element::Link {
+                        url: cow!("/about-the-scp-foundation"),
+                        label: LinkLabel::Text(cow!("scp")),
+                        anchor: AnchorTarget::Same,
+                    },
+                    text!(" is "),
+                    Element::Abbreviation {
+                        title: cow!("Difficult to contain"),
+                        elements: vec![text!("Keter")],
+                    },
+                    text!(", SCPs and SCP"),
+                ],
+            )),
+            Element::Container(Container::new(
+                ContainerType::Monospace,
+                vec![text!("red")],
+            )),
+            text!("A "),
+            Element::Link {
+                url: cow!("/red-dwarf"),
+                label: LinkLabel::Text(cow!("red dwarf")),
+                anchor: AnchorTarget::Same,
+            },
+            text!(" is "),
+            Element::Abbreviation {
+                title: cow!("A color"),
+                elements: vec![text!("red")],
+            },
+        ],
+        "Elements with glossary applied don't match expected",
+    );
+}
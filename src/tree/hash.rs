@@ -0,0 +1,230 @@
+/*
+ * tree/hash.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Stable content hashes of elements, for caching and change detection.
+//!
+//! Unlike the `Hash` implementations, these are the same on every platform and
+//! across releases, so they may be stored, such as to find which parts of a page
+//! changed between revisions. A hash is computed as follows:
+//!
+//! 1. Within the element, adjacent text elements are merged and null elements
+//!    are removed, so how the parser happened to split up the text doesn't matter.
+//! 2. The element is serialized to JSON, in the same form as the JSON renderer,
+//!    but without any whitespace and with the keys of every object sorted.
+//! 3. The hash is the 128-bit FNV-1a hash of the UTF-8 bytes of that JSON.
+//!
+//! For a syntax tree, the JSON is an object of its elements and styles.
+//! Source spans, such as the tree's sections, are not part of the hash.
+//!
+//! Since this only depends on the JSON form of the tree, other consumers of it
+//! can compute the same hashes. Any change to the hash of an element is a
+//! breaking change, which the tests here check for.
+
+use super::visit::for_each_child_list_mut;
+use super::{Element, SyntaxTree};
+use serde_json::{json, Value};
+use std::fmt::{self, Display};
+use std::mem;
+
+const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+/// A stable hash of the contents of an element, list of elements, or tree.
+///
+/// Displayed as 32 lowercase hexadecimal digits.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ContentHash(u128);
+
+impl ContentHash {
+    /// Hashes a list of elements, such as the contents of a container.
+    pub fn of_elements(elements: &[Element]) -> Self {
+        let mut elements = elements.to_vec();
+        canonicalize(&mut elements);
+        ContentHash::of_value(&json!(elements))
+    }
+
+    fn of_value(value: &Value) -> Self {
+        let mut buffer = String::new();
+        write_canonical(&mut buffer, value);
+        ContentHash(fnv1a(buffer.as_bytes()))
+    }
+
+    #[inline]
+    pub fn value(self) -> u128 {
+        self.0
+    }
+}
+
+impl Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+impl Element<'_> {
+    /// Gets the stable hash of this element and its contents.
+    pub fn content_hash(&self) -> ContentHash {
+        let mut element = self.clone();
+        for_each_child_list_mut(&mut element, &mut |children| canonicalize(children));
+        ContentHash::of_value(&json!(element))
+    }
+}
+
+impl SyntaxTree<'_> {
+    /// Gets the stable hash of this tree's elements and styles.
+    pub fn content_hash(&self) -> ContentHash {
+        let mut elements = self.elements.clone();
+        canonicalize(&mut elements);
+
+        ContentHash::of_value(&json!({
+            "elements": elements,
+            "styles": self.styles,
+        }))
+    }
+}
+
+/// Merges adjacent text and removes null elements, throughout the elements.
+fn canonicalize(elements: &mut Vec<Element>) {
+    let old_elements = mem::take(elements);
+
+    for mut element in old_elements {
+        for_each_child_list_mut(&mut element, &mut |children| canonicalize(children));
+
+        match element {
+            Element::Null => (),
+            Element::Text(text) => match elements.last_mut() {
+                Some(Element::Text(last)) => last.to_mut().push_str(&text),
+                _ => elements.push(Element::Text(text)),
+            },
+            _ => elements.push(element),
+        }
+    }
+}
+
+/// Writes the value as JSON, without whitespace and with sorted object keys.
+///
+/// The order of keys is not left to `serde_json`, since it depends
+/// on which of its features are enabled.
+fn write_canonical(buffer: &mut String, value: &Value) {
+    match value {
+        Value::Array(values) => {
+            buffer.push('[');
+
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    buffer.push(',');
+                }
+
+                write_canonical(buffer, value);
+            }
+
+            buffer.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+            buffer.push('{');
+
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    buffer.push(',');
+                }
+
+                write_canonical(buffer, &Value::String(key.clone()));
+                buffer.push(':');
+                write_canonical(buffer, value);
+            }
+
+            buffer.push('}');
+        }
+        _ => buffer.push_str(&value.to_string()),
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u128 {
+    let mut hash = FNV_OFFSET_BASIS;
+
+    for &byte in bytes {
+        hash ^= u128::from(byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+
+    hash
+}
+
+#[test]
+fn content_hash() {
+    use super::{Container, ContainerType, Image, ImageSource};
+
+    let strong =
+        |elements| Element::Container(Container::new(ContainerType::Strong, elements));
+
+    // Known values, which must not change between releases
+    assert_eq!(
+        text!("apple").content_hash().to_string(),
+        "c8f1c60f0fa74a6fddbb4f14e5cf9c1e",
+    );
+    assert_eq!(
+        strong(vec![text!("apple banana")])
+            .content_hash()
+            .to_string(),
+        "258b20d6400c825eed1288dde0307e50",
+    );
+    assert_eq!(
+        Element::Image(Box::new(Image {
+            alt: Some(cow!("Apple")),
+            width: Some(cow!("100px")),
+            caption: Some(vec![text!("apple"), text!(" banana")]),
+            ..Image::new(ImageSource::File1 {
+                file: cow!("apple.png"),
+            })
+        }))
+        .content_hash()
+        .to_string(),
+        "2cc28b2d31e5ee69ea32b8f644843c49",
+    );
+
+    // How the text is split up doesn't matter
+    assert_eq!(
+        strong(vec![
+            text!("apple"),
+            text!(" "),
+            Element::Null,
+            text!("banana")
+        ])
+        .content_hash(),
+        strong(vec![text!("apple banana")]).content_hash(),
+    );
+
+    // But the contents do
+    assert_ne!(
+        strong(vec![text!("apple")]).content_hash(),
+        strong(vec![text!("banana")]).content_hash(),
+    );
+    assert_ne!(
+        text!("apple").content_hash(),
+        Element::Raw(cow!("apple")).content_hash(),
+    );
+
+    assert_eq!(
+        ContentHash::of_elements(&[text!("apple"), text!(" banana")]),
+        ContentHash::of_elements(&[text!("apple banana")]),
+    );
+}
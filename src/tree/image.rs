@@ -0,0 +1,277 @@
+/*
+ * tree/image.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Representation of images, such as those produced by `[[image]]` blocks.
+//!
+//! As in Wikidot, the source is either a full URL, or names
+//! a file attached to this page or to another one.
+
+use super::clone::{elements_to_owned, option_string_to_owned, string_to_owned};
+use super::Element;
+use crate::data::ImageAlignment;
+use crate::enums::{ClearFloat, FloatAlignment};
+use ref_map::*;
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Image<'t> {
+    /// Where the image is, either a file attached to a page or a full URL.
+    pub source: ImageSource<'t>,
+
+    /// The URL or page the image links to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<Cow<'t, str>>,
+
+    /// The alignment from the block's name, such as `[[f<image]]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alignment: Option<ImageAlignment>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt: Option<Cow<'t, str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<Cow<'t, str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<Cow<'t, str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<Cow<'t, str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<Cow<'t, str>>,
+
+    /// The elements shown as the image's caption, if it has one.
+    pub caption: Option<Vec<Element<'t>>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub float: Option<FloatAlignment>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clear: Option<ClearFloat>,
+}
+
+impl<'t> Image<'t> {
+    #[inline]
+    pub fn new(source: ImageSource<'t>) -> Self {
+        Image {
+            source,
+            link: None,
+            alignment: None,
+            alt: None,
+            width: None,
+            height: None,
+            class: None,
+            style: None,
+            caption: None,
+            float: None,
+            clear: None,
+        }
+    }
+
+    pub fn to_owned(&self) -> Image<'static> {
+        Image {
+            source: self.source.to_owned(),
+            link: option_string_to_owned(&self.link),
+            alignment: self.alignment,
+            alt: option_string_to_owned(&self.alt),
+            width: option_string_to_owned(&self.width),
+            height: option_string_to_owned(&self.height),
+            class: option_string_to_owned(&self.class),
+            style: option_string_to_owned(&self.style),
+            caption: self.caption.ref_map(|elements| elements_to_owned(elements)),
+            float: self.float,
+            clear: self.clear,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "type", content = "data")]
+pub enum ImageSource<'t> {
+    /// An image at a URL, such as `https://example.com/apple.png`.
+    Url(Cow<'t, str>),
+
+    /// A file attached to this page, such as `apple.png`.
+    File1 { file: Cow<'t, str> },
+
+    /// A file attached to another page, such as `/fruit/apple.png`.
+    File2 {
+        page: Cow<'t, str>,
+        file: Cow<'t, str>,
+    },
+
+    /// The first image attached to this page, from `:first`.
+    FirstFile,
+}
+
+impl<'t> ImageSource<'t> {
+    /// Determines what kind of source the argument of an `[[image]]` block is.
+    ///
+    /// Returns `None` if the source is empty.
+    pub fn parse(source: &'t str) -> Option<Self> {
+        if source.is_empty() {
+            return None;
+        }
+
+        if source.eq_ignore_ascii_case(":first") {
+            return Some(ImageSource::FirstFile);
+        }
+
+        if source.contains("://") || source.starts_with("//") {
+            return Some(ImageSource::Url(cow!(source)));
+        }
+
+        // A file on another page is written "/page/file"
+        if let Some(path) = source.strip_prefix('/') {
+            let (page, file) = match path.find('/') {
+                Some(index) => (&path[..index], &path[index + 1..]),
+                None => ("", path),
+            };
+
+            return if page.is_empty() || file.is_empty() || file.contains('/') {
+                Some(ImageSource::Url(cow!(source)))
+            } else {
+                Some(ImageSource::File2 {
+                    page: cow!(page),
+                    file: cow!(file),
+                })
+            };
+        }
+
+        // Any other path is relative to the page, rather than a file name
+        if source.contains('/') {
+            Some(ImageSource::Url(cow!(source)))
+        } else {
+            Some(ImageSource::File1 { file: cow!(source) })
+        }
+    }
+
+    /// The name of the image file, used as its alt text if none is given.
+    ///
+    /// Returns `None` for `:first`, since the file isn't known until rendering.
+    pub fn file_name(&self) -> Option<&str> {
+        match self {
+            ImageSource::Url(url) => {
+                let path = url.split(&['?', '#'][..]).next().unwrap_or("");
+                path.rsplit('/').next()
+            }
+            ImageSource::File1 { file } | ImageSource::File2 { file, .. } => {
+                Some(file.as_ref())
+            }
+            ImageSource::FirstFile => None,
+        }
+    }
+
+    /// The usual location of the image, relative to the page.
+    ///
+    /// Attached files are under `/local--files/`, as in Wikidot.
+    /// Returns `None` for `:first`, whose location only the host knows.
+    pub fn default_url(&self, page_slug: &str) -> Option<Cow<str>> {
+        match self {
+            ImageSource::Url(url) => Some(Cow::Borrowed(url.as_ref())),
+            ImageSource::File1 { file } => {
+                Some(Cow::Owned(format!("/local--files/{}/{}", page_slug, file)))
+            }
+            ImageSource::File2 { page, file } => {
+                Some(Cow::Owned(format!("/local--files/{}/{}", page, file)))
+            }
+            ImageSource::FirstFile => None,
+        }
+    }
+
+    pub fn to_owned(&self) -> ImageSource<'static> {
+        match self {
+            ImageSource::Url(url) => ImageSource::Url(string_to_owned(url)),
+            ImageSource::File1 { file } => ImageSource::File1 {
+                file: string_to_owned(file),
+            },
+            ImageSource::File2 { page, file } => ImageSource::File2 {
+                page: string_to_owned(page),
+                file: string_to_owned(file),
+            },
+            ImageSource::FirstFile => ImageSource::FirstFile,
+        }
+    }
+}
+
+/// Writes the source as it appears in an `[[image]]` block.
+impl Display for ImageSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageSource::Url(url) => write!(f, "{}", url),
+            ImageSource::File1 { file } => write!(f, "{}", file),
+            ImageSource::File2 { page, file } => write!(f, "/{}/{}", page, file),
+            ImageSource::FirstFile => write!(f, ":first"),
+        }
+    }
+}
+
+#[test]
+fn image_source() {
+    macro_rules! test {
+        ($input:expr, $expected:expr $(,)?) => {{
+            let source = ImageSource::parse($input);
+
+            assert_eq!(
+                source, $expected,
+                "Parsed image source doesn't match expected"
+            );
+
+            if let Some(source) = source {
+                assert_eq!(
+                    source.to_string(),
+                    $input,
+                    "Image source doesn't round-trip"
+                );
+            }
+        }};
+    }
+
+    test!("", None);
+    test!(
+        "apple.png",
+        Some(ImageSource::File1 {
+            file: cow!("apple.png"),
+        }),
+    );
+    test!(
+        "/fruit/banana.png",
+        Some(ImageSource::File2 {
+            page: cow!("fruit"),
+            file: cow!("banana.png"),
+        }),
+    );
+    test!(
+        "https://example.com/cherry.png",
+        Some(ImageSource::Url(cow!("https://example.com/cherry.png"))),
+    );
+    test!(
+        "/local--files/fruit/durian.png",
+        Some(ImageSource::Url(cow!("/local--files/fruit/durian.png"))),
+    );
+    test!(
+        "files/fig.png",
+        Some(ImageSource::Url(cow!("files/fig.png")))
+    );
+    test!(":first", Some(ImageSource::FirstFile));
+}
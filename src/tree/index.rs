@@ -0,0 +1,249 @@
+/*
+ * tree/index.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! An index of the elements in a syntax tree, grouped by kind.
+//!
+//! Hosts which run several analyses on a page, such as finding all of its
+//! images and then all of its links, can build this once rather than walking
+//! the whole tree for each query.
+
+use super::visit::for_each_child_list;
+use super::{Element, SyntaxTree};
+use std::collections::HashMap;
+
+/// One step in the path to an element.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct PathStep {
+    /// Which list of children of the parent this element is in.
+    ///
+    /// This follows the order the lists are in within the parent, for instance
+    /// a blockquote's contents are list 0 and its source is list 1.
+    /// For top-level elements, this is always 0.
+    pub list: usize,
+
+    /// The index of the element within that list.
+    pub index: usize,
+}
+
+/// The location of an element within a syntax tree.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct ElementPath(Vec<PathStep>);
+
+impl ElementPath {
+    #[inline]
+    pub fn steps(&self) -> &[PathStep] {
+        &self.0
+    }
+
+    /// How many elements deep this path goes, where top-level elements are 1.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Gets the element this path refers to, within the given top-level elements.
+    pub fn resolve<'a, 't>(
+        &self,
+        elements: &'a [Element<'t>],
+    ) -> Option<&'a Element<'t>> {
+        let (siblings, index) = self.resolve_siblings(elements)?;
+
+        siblings.get(index)
+    }
+
+    /// Gets the list of elements containing the one this path refers to, and its index there.
+    ///
+    /// This allows a range of adjacent elements to be taken, starting from this one.
+    pub fn resolve_siblings<'a, 't>(
+        &self,
+        elements: &'a [Element<'t>],
+    ) -> Option<(&'a [Element<'t>], usize)> {
+        let (first, rest) = self.0.split_first()?;
+        if first.list != 0 {
+            return None;
+        }
+
+        let mut siblings = elements;
+        let mut index = first.index;
+
+        for step in rest {
+            let element = siblings.get(index)?;
+            let mut children = None;
+            let mut list = 0;
+
+            for_each_child_list(element, &mut |elements| {
+                if list == step.list {
+                    children = Some(elements);
+                }
+
+                list += 1;
+            });
+
+            siblings = children?;
+            index = step.index;
+        }
+
+        if index < siblings.len() {
+            Some((siblings, index))
+        } else {
+            None
+        }
+    }
+}
+
+impl From<Vec<PathStep>> for ElementPath {
+    #[inline]
+    fn from(steps: Vec<PathStep>) -> Self {
+        ElementPath(steps)
+    }
+}
+
+/// Paths to the elements of a syntax tree, grouped by kind.
+///
+/// Kinds are the names given by `Element::name()`, such as `"Image"` or `"Link"`.
+/// Containers are grouped by their type, such as `"Strong"`.
+/// Each kind's paths are in document order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ElementIndex {
+    kinds: HashMap<&'static str, Vec<ElementPath>>,
+}
+
+impl ElementIndex {
+    /// Walks the given elements and their descendants, recording where each one is.
+    pub fn build(elements: &[Element]) -> Self {
+        let mut index = ElementIndex::default();
+        let mut path = Vec::new();
+
+        index.add_list(elements, 0, &mut path);
+        index
+    }
+
+    fn add_list(&mut self, elements: &[Element], list: usize, path: &mut Vec<PathStep>) {
+        for (index, element) in elements.iter().enumerate() {
+            path.push(PathStep { list, index });
+
+            self.kinds
+                .entry(element.name())
+                .or_default()
+                .push(ElementPath(path.clone()));
+
+            let mut child_list = 0;
+            for_each_child_list(element, &mut |children| {
+                self.add_list(children, child_list, path);
+                child_list += 1;
+            });
+
+            path.pop();
+        }
+    }
+
+    /// Gets the paths of all elements of the given kind.
+    pub fn get(&self, kind: &str) -> &[ElementPath] {
+        self.kinds.get(kind).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Counts the elements of the given kind.
+    #[inline]
+    pub fn count(&self, kind: &str) -> usize {
+        self.get(kind).len()
+    }
+
+    /// Returns each kind of element present in the tree.
+    pub fn kinds(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.kinds.keys().copied()
+    }
+}
+
+impl SyntaxTree<'_> {
+    /// Builds an index of the elements in this tree, grouped by kind.
+    #[inline]
+    pub fn index(&self) -> ElementIndex {
+        ElementIndex::build(&self.elements)
+    }
+}
+
+#[test]
+fn index() {
+    let color = |elements| Element::Color {
+        color: cow!("blue"),
+        elements,
+    };
+
+    let tree = SyntaxTree {
+        elements: vec![
+            color(vec![text!("apple"), Element::LineBreak]),
+            Element::BlockQuote {
+                elements: vec![text!("banana")],
+                id: None,
+                class: None,
+                style: None,
+                cite: None,
+                source: Some(vec![color(vec![text!("cherry")])]),
+            },
+        ],
+        styles: vec![],
+        sections: vec![],
+    };
+
+    let index = tree.index();
+    let path = |steps: &[(usize, usize)]| {
+        ElementPath(
+            steps
+                .iter()
+                .map(|&(list, index)| PathStep { list, index })
+                .collect(),
+        )
+    };
+
+    assert_eq!(
+        index.get("Text"),
+        &[
+            path(&[(0, 0), (0, 0)]),
+            path(&[(0, 1), (0, 0)]),
+            path(&[(0, 1), (1, 0), (0, 0)]),
+        ],
+        "Text element paths don't match expected",
+    );
+    assert_eq!(index.count("Color"), 2);
+    assert_eq!(index.count("BlockQuote"), 1);
+    assert_eq!(index.count("Image"), 0);
+
+    for kind in index.kinds() {
+        for path in index.get(kind) {
+            let element = path.resolve(&tree.elements).expect("Path doesn't resolve");
+            assert_eq!(element.name(), kind, "Resolved element has the wrong kind");
+        }
+    }
+
+    assert_eq!(
+        path(&[(0, 1), (1, 0), (0, 0)]).resolve(&tree.elements),
+        Some(&text!("cherry")),
+    );
+    assert_eq!(path(&[(0, 1), (2, 0)]).resolve(&tree.elements), None);
+
+    assert_eq!(
+        path(&[(0, 0), (0, 1)]).resolve_siblings(&tree.elements),
+        Some((&[text!("apple"), Element::LineBreak][..], 1)),
+    );
+    assert_eq!(
+        path(&[(0, 0), (0, 2)]).resolve_siblings(&tree.elements),
+        None
+    );
+}
@@ -0,0 +1,141 @@
+/*
+ * tree/list.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Representation of lists, such as those produced by `*`, `#`, and `:` syntax.
+
+use super::clone::elements_to_owned;
+use super::Element;
+use crate::enums::ListStyle;
+
+/// A bulleted or numbered list.
+///
+/// Nested lists are represented as an `Element::List` within an item's elements.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct List<'t> {
+    style: ListStyle,
+    items: Vec<ListItem<'t>>,
+}
+
+impl<'t> List<'t> {
+    #[inline]
+    pub fn new(style: ListStyle, items: Vec<ListItem<'t>>) -> Self {
+        List { style, items }
+    }
+
+    #[inline]
+    pub fn style(&self) -> ListStyle {
+        self.style
+    }
+
+    #[inline]
+    pub fn items(&self) -> &[ListItem<'t>] {
+        &self.items
+    }
+
+    #[inline]
+    pub(crate) fn items_mut(&mut self) -> &mut [ListItem<'t>] {
+        &mut self.items
+    }
+
+    pub fn to_owned(&self) -> List<'static> {
+        List {
+            style: self.style,
+            items: self.items.iter().map(ListItem::to_owned).collect(),
+        }
+    }
+}
+
+/// A single item within a list.
+///
+/// Items of a "tight" list contain their inline elements directly,
+/// while items of a "loose" list (one with blank lines between items)
+/// have their contents wrapped in paragraphs.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListItem<'t> {
+    elements: Vec<Element<'t>>,
+}
+
+impl<'t> ListItem<'t> {
+    #[inline]
+    pub fn new(elements: Vec<Element<'t>>) -> Self {
+        ListItem { elements }
+    }
+
+    #[inline]
+    pub fn elements(&self) -> &[Element<'t>] {
+        &self.elements
+    }
+
+    #[inline]
+    pub(crate) fn elements_mut(&mut self) -> &mut Vec<Element<'t>> {
+        &mut self.elements
+    }
+
+    pub fn to_owned(&self) -> ListItem<'static> {
+        ListItem {
+            elements: elements_to_owned(&self.elements),
+        }
+    }
+}
+
+/// A single term and its definition, within a definition list.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct DefinitionListItem<'t> {
+    key: Vec<Element<'t>>,
+    value: Vec<Element<'t>>,
+}
+
+impl<'t> DefinitionListItem<'t> {
+    #[inline]
+    pub fn new(key: Vec<Element<'t>>, value: Vec<Element<'t>>) -> Self {
+        DefinitionListItem { key, value }
+    }
+
+    /// The term being defined.
+    #[inline]
+    pub fn key(&self) -> &[Element<'t>] {
+        &self.key
+    }
+
+    #[inline]
+    pub fn value(&self) -> &[Element<'t>] {
+        &self.value
+    }
+
+    #[inline]
+    pub(crate) fn key_mut(&mut self) -> &mut Vec<Element<'t>> {
+        &mut self.key
+    }
+
+    #[inline]
+    pub(crate) fn value_mut(&mut self) -> &mut Vec<Element<'t>> {
+        &mut self.value
+    }
+
+    pub fn to_owned(&self) -> DefinitionListItem<'static> {
+        DefinitionListItem {
+            key: elements_to_owned(&self.key),
+            value: elements_to_owned(&self.value),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+/*
+ * tree/list_pages.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Representation of the query of a `ListPages` module.
+//!
+//! The query describes which pages are listed, and in what order.
+//! Finding the matching pages is up to the host, see `Handle::get_list_pages()`.
+
+use super::clone::strings_to_owned;
+use std::borrow::Cow;
+use std::num::NonZeroU32;
+use strum_macros::IntoStaticStr;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListPagesQuery<'t> {
+    /// The categories pages are listed from, or every category if empty.
+    ///
+    /// As in Wikidot, `.` is the category of the current page,
+    /// which is used if no category is given. All are listed with `*`.
+    pub categories: Vec<Cow<'t, str>>,
+
+    /// The categories pages are not listed from, given as `-category`.
+    pub excluded_categories: Vec<Cow<'t, str>>,
+
+    /// The tags a page must all have, given as `+tag`.
+    pub required_tags: Vec<Cow<'t, str>>,
+
+    /// The tags a page must have at least one of, given as `tag`.
+    pub any_tags: Vec<Cow<'t, str>>,
+
+    /// The tags a page must not have, given as `-tag`.
+    pub excluded_tags: Vec<Cow<'t, str>>,
+
+    /// How the pages are sorted.
+    pub order: ListPagesOrder,
+
+    /// The most pages listed, across all pages of results.
+    pub limit: Option<NonZeroU32>,
+
+    /// The most pages listed at once, with the rest on further pages of results.
+    pub per_page: Option<NonZeroU32>,
+
+    /// How many matching pages are skipped before listing.
+    pub offset: u32,
+}
+
+impl ListPagesQuery<'_> {
+    /// The most pages listed at once, from both the limit and page size.
+    ///
+    /// As in Wikidot, there are 20 pages per page of results by default.
+    pub fn page_size(&self) -> u32 {
+        let per_page = self.per_page.map(NonZeroU32::get).unwrap_or(20);
+
+        match self.limit {
+            Some(limit) => per_page.min(limit.get()),
+            None => per_page,
+        }
+    }
+
+    pub fn to_owned(&self) -> ListPagesQuery<'static> {
+        ListPagesQuery {
+            categories: strings_to_owned(&self.categories),
+            excluded_categories: strings_to_owned(&self.excluded_categories),
+            required_tags: strings_to_owned(&self.required_tags),
+            any_tags: strings_to_owned(&self.any_tags),
+            excluded_tags: strings_to_owned(&self.excluded_tags),
+            order: self.order,
+            limit: self.limit,
+            per_page: self.per_page,
+            offset: self.offset,
+        }
+    }
+}
+
+/// How the pages of a `ListPages` module are sorted, such as `rating desc`.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListPagesOrder {
+    pub property: PageProperty,
+    pub descending: bool,
+}
+
+impl ListPagesOrder {
+    /// Reads an order, which is a property optionally followed by `asc` or `desc`.
+    ///
+    /// Returns `None` if the order is malformed.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut words = value.split_whitespace();
+        let property = PageProperty::parse(words.next()?)?;
+        let descending = match words.next() {
+            None => false,
+            Some(word) if word.eq_ignore_ascii_case("asc") => false,
+            Some(word) if word.eq_ignore_ascii_case("desc") => true,
+            Some(_) => return None,
+        };
+
+        if words.next().is_some() {
+            return None;
+        }
+
+        Some(ListPagesOrder {
+            property,
+            descending,
+        })
+    }
+}
+
+/// As in Wikidot, the newest pages are listed first.
+impl Default for ListPagesOrder {
+    #[inline]
+    fn default() -> Self {
+        ListPagesOrder {
+            property: PageProperty::CreatedAt,
+            descending: true,
+        }
+    }
+}
+
+/// A property of a page, which listed pages can be sorted by.
+#[derive(
+    Serialize, Deserialize, IntoStaticStr, Debug, Copy, Clone, Hash, PartialEq, Eq,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum PageProperty {
+    /// The name of the page, without its category.
+    Name,
+
+    /// The name of the page, including its category.
+    Fullname,
+
+    Title,
+    CreatedBy,
+    CreatedAt,
+    UpdatedAt,
+
+    /// The length of the page's source.
+    Size,
+
+    Rating,
+    Votes,
+    Revisions,
+    Comments,
+
+    /// A random order, which is different each time the page is rendered.
+    Random,
+}
+
+impl PageProperty {
+    /// Reads a property by its name in Wikidot, such as `created_at`.
+    pub fn parse(name: &str) -> Option<Self> {
+        const PROPERTIES: [(&str, PageProperty); 12] = [
+            ("name", PageProperty::Name),
+            ("fullname", PageProperty::Fullname),
+            ("title", PageProperty::Title),
+            ("created_by", PageProperty::CreatedBy),
+            ("created_at", PageProperty::CreatedAt),
+            ("updated_at", PageProperty::UpdatedAt),
+            ("size", PageProperty::Size),
+            ("rating", PageProperty::Rating),
+            ("votes", PageProperty::Votes),
+            ("revisions", PageProperty::Revisions),
+            ("comments", PageProperty::Comments),
+            ("random", PageProperty::Random),
+        ];
+
+        PROPERTIES
+            .iter()
+            .find(|(property_name, _)| property_name.eq_ignore_ascii_case(name))
+            .map(|(_, property)| *property)
+    }
+
+    #[inline]
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
+}
+
+#[test]
+fn list_pages_order() {
+    macro_rules! check {
+        ($input:expr, $expected:expr $(,)?) => {
+            assert_eq!(
+                ListPagesOrder::parse($input),
+                $expected,
+                "Parsed order doesn't match expected",
+            );
+        };
+    }
+
+    let order = |property, descending| {
+        Some(ListPagesOrder {
+            property,
+            descending,
+        })
+    };
+
+    check!("title", order(PageProperty::Title, false));
+    check!("rating desc", order(PageProperty::Rating, true));
+    check!(" Created_At  ASC ", order(PageProperty::CreatedAt, false));
+    check!("", None);
+    check!("apple", None);
+    check!("size descending", None);
+    check!("votes desc desc", None);
+}
@@ -18,15 +18,72 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+mod bibliography;
+mod clone;
 mod container;
+mod counter;
+mod depth;
+mod diff;
+mod display;
 mod element;
+mod footnote;
+mod form;
+mod glossary;
+
+#[cfg(feature = "json")]
+mod hash;
+
+mod image;
+mod index;
+mod list;
+mod list_pages;
 mod module;
+mod normalize;
+mod outline;
+mod partial;
+mod section;
+mod tab;
+mod table;
+mod visit;
 
+pub use self::bibliography::{
+    collect_bibliography, resolve_bibliography, BibliographyEntry,
+};
 pub use self::container::*;
+pub use self::counter::*;
+pub use self::depth::{element_depth, limit_depth, MAX_SERIALIZED_DEPTH};
+pub use self::diff::{diff_elements, diff_trees};
 pub use self::element::*;
+#[cfg(feature = "html")]
+pub(crate) use self::footnote::footnotes_before;
+pub use self::footnote::{collect_footnotes, has_footnote_block, Footnotes};
+pub use self::form::*;
+pub use self::glossary::{apply_glossary, Glossary, GlossaryTarget};
+#[cfg(feature = "json")]
+pub use self::hash::ContentHash;
+pub use self::image::{Image, ImageSource};
+pub use self::index::{ElementIndex, ElementPath, PathStep};
+pub use self::list::*;
+pub use self::list_pages::*;
 pub use self::module::*;
+pub use self::normalize::normalize_elements;
+#[cfg(feature = "html")]
+pub(crate) use self::outline::headings_before;
+pub use self::outline::{collect_headings, number_headings, OutlineHeading};
+pub use self::partial::*;
+pub(crate) use self::section::section_spans;
+pub use self::section::Section;
+pub use self::tab::*;
+pub use self::table::*;
+pub(crate) use self::visit::for_each_child_list;
+#[cfg(feature = "html")]
+pub(crate) use self::visit::walk_elements;
+#[cfg(feature = "markdown")]
+pub(crate) use self::visit::walk_elements_mut;
 
+use self::clone::{elements_to_owned, strings_to_owned};
 use crate::parsing::{ParseOutcome, ParseWarning};
+use crate::span::Span;
 use std::borrow::Cow;
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
@@ -45,6 +102,14 @@ pub struct SyntaxTree<'t> {
     /// however the recommendation is to combine them all into one large style
     /// rule list.
     pub styles: Vec<Cow<'t, str>>,
+
+    /// The spans of the page's sections within the preprocessed text
+    /// which was parsed, in order.
+    ///
+    /// There is one for each heading directly on the page,
+    /// see `SyntaxTree::section()`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<Span>,
 }
 
 impl<'t> SyntaxTree<'t> {
@@ -53,7 +118,21 @@ impl<'t> SyntaxTree<'t> {
         warnings: Vec<ParseWarning>,
         styles: Vec<Cow<'t, str>>,
     ) -> ParseOutcome<Self> {
-        let tree = SyntaxTree { elements, styles };
+        let tree = SyntaxTree {
+            elements,
+            styles,
+            sections: Vec::new(),
+        };
+
         ParseOutcome::new(tree, warnings)
     }
+
+    /// Deep-clones the tree, making it owned and using `'static`.
+    pub fn to_owned(&self) -> SyntaxTree<'static> {
+        SyntaxTree {
+            elements: elements_to_owned(&self.elements),
+            styles: strings_to_owned(&self.styles),
+            sections: self.sections.clone(),
+        }
+    }
 }
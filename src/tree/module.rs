@@ -20,11 +20,13 @@
 
 //! Representation of Wikidot modules, along with their context.
 
+use super::clone::{option_string_to_owned, string_to_owned};
+use super::{FormField, ListPagesQuery};
 use std::borrow::Cow;
 use std::num::NonZeroU32;
 use strum_macros::IntoStaticStr;
 
-#[derive(Serialize, Deserialize, IntoStaticStr, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, IntoStaticStr, Debug, Clone, Hash, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case", tag = "module", content = "data")]
 pub enum Module<'t> {
     /// Lists all the backlinks on the given page.
@@ -36,6 +38,16 @@ pub enum Module<'t> {
     #[serde(rename_all = "kebab-case")]
     Categories { include_hidden: bool },
 
+    /// Lists the items of an RSS or Atom feed.
+    ///
+    /// The host fetches the feed. If a template is given, it is wikitext
+    /// which is rendered once per item, with placeholders like `%%title%%`.
+    Feed {
+        src: Cow<'t, str>,
+        limit: Option<NonZeroU32>,
+        template: Option<Cow<'t, str>>,
+    },
+
     /// Allows a user to join a site.
     #[serde(rename_all = "kebab-case")]
     Join {
@@ -45,6 +57,29 @@ pub enum Module<'t> {
         style: Option<Cow<'t, str>>,
     },
 
+    /// Lists the pages on the site which match a query.
+    ///
+    /// The host finds the pages, see `Handle::get_list_pages()`. If a template
+    /// is given, it is wikitext which is rendered once per page, with
+    /// placeholders like `%%title%%`.
+    ListPages {
+        query: ListPagesQuery<'t>,
+        template: Option<Cow<'t, str>>,
+    },
+
+    /// A form whose submissions are sent by email.
+    ///
+    /// Sending is up to the host, see `Handle::get_form_action()`.
+    /// Without it, the form is rendered but cannot be submitted.
+    #[serde(rename_all = "kebab-case")]
+    MailForm {
+        to: Cow<'t, str>,
+        title: Option<Cow<'t, str>>,
+        button_text: Option<Cow<'t, str>>,
+        success_page: Option<Cow<'t, str>>,
+        fields: Vec<FormField<'t>>,
+    },
+
     /// Meta-element for modules which perform no action.
     Null,
 
@@ -68,4 +103,63 @@ impl Module<'_> {
     pub fn name(&self) -> &'static str {
         self.into()
     }
+
+    pub fn to_owned(&self) -> Module<'static> {
+        match self {
+            Module::Backlinks { page } => Module::Backlinks {
+                page: option_string_to_owned(page),
+            },
+            Module::Categories { include_hidden } => Module::Categories {
+                include_hidden: *include_hidden,
+            },
+            Module::Feed {
+                src,
+                limit,
+                template,
+            } => Module::Feed {
+                src: string_to_owned(src),
+                limit: *limit,
+                template: option_string_to_owned(template),
+            },
+            Module::Join {
+                button_text,
+                id,
+                class,
+                style,
+            } => Module::Join {
+                button_text: option_string_to_owned(button_text),
+                id: option_string_to_owned(id),
+                class: option_string_to_owned(class),
+                style: option_string_to_owned(style),
+            },
+            Module::ListPages { query, template } => Module::ListPages {
+                query: query.to_owned(),
+                template: option_string_to_owned(template),
+            },
+            Module::MailForm {
+                to,
+                title,
+                button_text,
+                success_page,
+                fields,
+            } => Module::MailForm {
+                to: string_to_owned(to),
+                title: option_string_to_owned(title),
+                button_text: option_string_to_owned(button_text),
+                success_page: option_string_to_owned(success_page),
+                fields: fields.iter().map(FormField::to_owned).collect(),
+            },
+            Module::Null => Module::Null,
+            Module::PageTree {
+                root,
+                show_root,
+                depth,
+            } => Module::PageTree {
+                root: option_string_to_owned(root),
+                show_root: *show_root,
+                depth: *depth,
+            },
+            Module::Rate => Module::Rate,
+        }
+    }
 }
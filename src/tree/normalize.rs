@@ -0,0 +1,379 @@
+/*
+ * tree/normalize.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Pass to normalize formatting nesting in a syntax tree.
+//!
+//! Though the tree structure guarantees tags are balanced, it does not
+//! prevent nestings which are invalid in HTML, such as a block element within
+//! an inline one. This corrects such cases:
+//!
+//! * Block elements within inline containers are moved out, splitting the
+//!   inline container around them. For instance, `<strong>A<div>B</div>C</strong>`
+//!   becomes `<strong>A</strong><div>B</div><strong>C</strong>`.
+//! * Likewise, block elements within paragraphs, such as those just moved
+//!   out of an inline container, are moved out of the paragraph.
+//! * Redundant inline containers directly within one of the same type are
+//!   flattened, such as `<strong><strong>A</strong></strong>` becoming
+//!   `<strong>A</strong>`. Superscripts and subscripts are left alone,
+//!   since each level of nesting raises or lowers the text further.
+
+use super::{Container, ContainerType, Element, StyledContainer};
+use crate::enums::LinkLabel;
+use std::mem;
+
+/// Normalizes the nesting of the given elements, and all their descendants.
+pub fn normalize_elements(elements: &mut Vec<Element>) {
+    let old_elements = mem::take(elements);
+
+    for mut element in old_elements {
+        normalize_children(&mut element);
+
+        match element {
+            Element::Container(container)
+                if container.ctype() == ContainerType::Paragraph =>
+            {
+                split_paragraph(elements, container);
+            }
+            Element::Container(container) if container.ctype().is_inline() => {
+                split_container(elements, container);
+            }
+            Element::StyledContainer(container) if container.ctype().is_inline() => {
+                split_styled_container(elements, container);
+            }
+            _ => elements.push(element),
+        }
+    }
+}
+
+/// Recursively normalize each list of elements within this one.
+fn normalize_children(element: &mut Element) {
+    match element {
+        Element::Container(container) => {
+            let ctype = container.ctype();
+            let elements = container.elements_mut();
+
+            normalize_elements(elements);
+
+            if is_redundant_when_nested(ctype) {
+                flatten_container(elements, |element| match element {
+                    Element::Container(inner) if inner.ctype() == ctype => {
+                        Some(inner.elements_mut())
+                    }
+                    _ => None,
+                });
+            }
+        }
+        Element::StyledContainer(container) => {
+            normalize_elements(container.elements_mut());
+        }
+        Element::Include { elements, .. }
+        | Element::Anchor { elements, .. }
+        | Element::Collapsible { elements, .. }
+        | Element::Abbreviation { elements, .. }
+        | Element::Footnote { elements }
+        | Element::NoTypography { elements }
+        | Element::Poem { elements }
+        | Element::Color { elements, .. } => normalize_elements(elements),
+        Element::BlockQuote {
+            elements, source, ..
+        } => {
+            normalize_elements(elements);
+
+            if let Some(source) = source {
+                normalize_elements(source);
+            }
+        }
+        Element::Image(image) => {
+            if let Some(caption) = &mut image.caption {
+                normalize_elements(caption);
+            }
+        }
+        Element::Link {
+            label: LinkLabel::Elements(elements),
+            ..
+        } => normalize_elements(elements),
+        Element::TabView(tabs) => {
+            for tab in tabs {
+                normalize_elements(tab.label_mut());
+                normalize_elements(tab.elements_mut());
+            }
+        }
+        Element::Table(table) => {
+            for row in table.rows_mut() {
+                for cell in row.cells_mut() {
+                    normalize_elements(cell.elements_mut());
+                }
+            }
+        }
+        Element::List(list) => {
+            for item in list.items_mut() {
+                normalize_elements(item.elements_mut());
+            }
+        }
+        Element::DefinitionList(items) => {
+            for item in items {
+                normalize_elements(item.key_mut());
+                normalize_elements(item.value_mut());
+            }
+        }
+        Element::Bibliography { entries, .. } => {
+            for entry in entries {
+                normalize_elements(entry.elements_mut());
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Whether this container has no further effect when nested within itself.
+fn is_redundant_when_nested(ctype: ContainerType) -> bool {
+    match ctype {
+        ContainerType::Strong
+        | ContainerType::Emphasis
+        | ContainerType::Underline
+        | ContainerType::Strikethrough
+        | ContainerType::Monospace => true,
+        ContainerType::Superscript
+        | ContainerType::Subscript
+        | ContainerType::Paragraph
+        | ContainerType::Header(_)
+        | ContainerType::UnlistedHeader(_) => false,
+    }
+}
+
+/// Replaces any child matched by `get_inner` with that child's own elements.
+fn flatten_container<'t, F>(elements: &mut Vec<Element<'t>>, mut get_inner: F)
+where
+    F: for<'e> FnMut(&'e mut Element<'t>) -> Option<&'e mut Vec<Element<'t>>>,
+{
+    let old_elements = mem::take(elements);
+
+    for mut element in old_elements {
+        match get_inner(&mut element) {
+            Some(inner) => elements.append(inner),
+            None => elements.push(element),
+        }
+    }
+}
+
+/// Splits an inline container around any block elements it contains.
+///
+/// The resultant elements are appended to `output`.
+fn split_container<'t>(output: &mut Vec<Element<'t>>, container: Container<'t>) {
+    if !container.elements().iter().any(Element::is_block) {
+        output.push(Element::Container(container));
+        return;
+    }
+
+    let ctype = container.ctype();
+    split_elements(output, container.into(), |elements| {
+        Element::Container(Container::new(ctype, elements))
+    });
+}
+
+/// Splits an inline styled container around any block elements it contains.
+///
+/// The resultant elements are appended to `output`.
+fn split_styled_container<'t>(
+    output: &mut Vec<Element<'t>>,
+    mut container: StyledContainer<'t>,
+) {
+    if !container.elements().iter().any(Element::is_block) {
+        output.push(Element::StyledContainer(container));
+        return;
+    }
+
+    let elements = mem::take(container.elements_mut());
+    let template = container.clone_with_elements(Vec::new());
+    let mut first = Some(container);
+
+    split_elements(output, elements, |elements| {
+        // Only the first fragment keeps the original ID
+        let container = match first.take() {
+            Some(mut container) => {
+                *container.elements_mut() = elements;
+                container
+            }
+            None => template.clone_with_elements(elements),
+        };
+
+        Element::StyledContainer(container)
+    });
+}
+
+/// Splits a paragraph around any block elements it contains.
+///
+/// As when paragraphs are gathered, the whitespace separating each block
+/// from the surrounding text is dropped, as are paragraphs left empty.
+///
+/// The resultant elements are appended to `output`.
+fn split_paragraph<'t>(output: &mut Vec<Element<'t>>, container: Container<'t>) {
+    if !container.elements().iter().any(Element::is_block) {
+        output.push(Element::Container(container));
+        return;
+    }
+
+    let mut run = Vec::new();
+
+    for element in Vec::from(container) {
+        if element.is_block() {
+            push_paragraph(output, mem::take(&mut run));
+            output.push(element);
+        } else {
+            run.push(element);
+        }
+    }
+
+    push_paragraph(output, run);
+}
+
+/// Appends a paragraph of these elements, without surrounding whitespace, if any remain.
+fn push_paragraph<'t>(output: &mut Vec<Element<'t>>, mut elements: Vec<Element<'t>>) {
+    while elements.last().map(is_whitespace) == Some(true) {
+        elements.pop();
+    }
+
+    let start = elements
+        .iter()
+        .position(|element| !is_whitespace(element))
+        .unwrap_or(elements.len());
+
+    elements.drain(..start);
+
+    if !elements.is_empty() {
+        let container = Container::new(ContainerType::Paragraph, elements);
+        output.push(Element::Container(container));
+    }
+}
+
+fn is_whitespace(element: &Element) -> bool {
+    match element {
+        Element::Text(text) => text.trim().is_empty(),
+        Element::LineBreak => true,
+        _ => false,
+    }
+}
+
+/// Moves block elements out, wrapping each run of inline elements with `wrap`.
+fn split_elements<'t, F>(
+    output: &mut Vec<Element<'t>>,
+    elements: Vec<Element<'t>>,
+    mut wrap: F,
+) where
+    F: FnMut(Vec<Element<'t>>) -> Element<'t>,
+{
+    let mut run = Vec::new();
+
+    for element in elements {
+        if element.is_block() {
+            if !run.is_empty() {
+                output.push(wrap(mem::take(&mut run)));
+            }
+
+            output.push(element);
+        } else {
+            run.push(element);
+        }
+    }
+
+    if !run.is_empty() {
+        output.push(wrap(run));
+    }
+}
+
+#[test]
+fn normalize() {
+    use super::StyledContainerType;
+
+    let strong =
+        |elements| Element::Container(Container::new(ContainerType::Strong, elements));
+    let div = |elements| {
+        Element::StyledContainer(StyledContainer::new(
+            StyledContainerType::Div,
+            elements,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ))
+    };
+
+    // Block within inline
+    let mut elements = vec![strong(vec![text!("A"), div(vec![text!("B")]), text!("C")])];
+    normalize_elements(&mut elements);
+
+    assert_eq!(
+        elements,
+        vec![
+            strong(vec![text!("A")]),
+            div(vec![text!("B")]),
+            strong(vec![text!("C")]),
+        ],
+        "Block element was not moved out of inline container",
+    );
+
+    // Same container nested
+    let mut elements = vec![strong(vec![text!("A"), strong(vec![text!("B")])])];
+    normalize_elements(&mut elements);
+
+    assert_eq!(
+        elements,
+        vec![strong(vec![text!("A"), text!("B")])],
+        "Nested containers of the same type were not flattened",
+    );
+
+    // Nested superscripts are meaningful
+    let superscript = |elements| {
+        Element::Container(Container::new(ContainerType::Superscript, elements))
+    };
+    let mut elements = vec![superscript(vec![text!("A"), superscript(vec![text!("B")])])];
+    let expected = elements.clone();
+    normalize_elements(&mut elements);
+
+    assert_eq!(elements, expected, "Nested superscripts were flattened");
+
+    // Block moved out of a paragraph
+    let paragraph =
+        |elements| Element::Container(Container::new(ContainerType::Paragraph, elements));
+    let mut elements = vec![paragraph(vec![
+        strong(vec![text!("A"), div(vec![text!("B")])]),
+        Element::LineBreak,
+        text!("C"),
+    ])];
+    normalize_elements(&mut elements);
+
+    assert_eq!(
+        elements,
+        vec![
+            paragraph(vec![strong(vec![text!("A")])]),
+            div(vec![text!("B")]),
+            paragraph(vec![text!("C")]),
+        ],
+        "Block element was not moved out of paragraph",
+    );
+
+    // Already valid
+    let mut elements = vec![div(vec![strong(vec![text!("A")])])];
+    let expected = elements.clone();
+    normalize_elements(&mut elements);
+
+    assert_eq!(elements, expected, "Valid nesting was modified");
+}
@@ -0,0 +1,278 @@
+/*
+ * tree/outline.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! The outline of a document, made of its headings in order.
+//!
+//! This is what tables of contents list.
+
+use super::visit::for_each_child_list;
+use super::{ContainerType, Element, SyntaxTree};
+use crate::enums::HeadingLevel;
+use std::ptr;
+
+/// A heading within the document.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OutlineHeading<'a, 't> {
+    pub level: HeadingLevel,
+
+    /// Whether this heading appears in the table of contents.
+    ///
+    /// This is false for headings written as `+* Title`.
+    pub listed: bool,
+
+    pub elements: &'a [Element<'t>],
+}
+
+/// Gets every heading within the elements, in document order.
+pub fn collect_headings<'a, 't>(
+    elements: &'a [Element<'t>],
+) -> Vec<OutlineHeading<'a, 't>> {
+    let mut headings = Vec::new();
+    collect_headings_internal(elements, &mut headings);
+    headings
+}
+
+fn collect_headings_internal<'a, 't>(
+    elements: &'a [Element<'t>],
+    headings: &mut Vec<OutlineHeading<'a, 't>>,
+) {
+    for element in elements {
+        if let Element::Container(container) = element {
+            if let Some(level) = container.ctype().heading_level() {
+                headings.push(OutlineHeading {
+                    level,
+                    listed: container.ctype() == ContainerType::Header(level),
+                    elements: container.elements(),
+                });
+            }
+        }
+
+        for_each_child_list(element, &mut |children| {
+            collect_headings_internal(children, headings)
+        });
+    }
+}
+
+/// Assigns hierarchical section numbers to the headings, such as `1.` and `1.2`.
+///
+/// A heading's depth in the numbering is how many headings enclose it, rather
+/// than its level, so skipping a level does not produce an empty component.
+/// Unlisted headings are only numbered if `include_unlisted` is set,
+/// otherwise they get `None` and don't affect the numbers of the others.
+pub fn number_headings(
+    headings: &[OutlineHeading],
+    include_unlisted: bool,
+) -> Vec<Option<String>> {
+    // The level and current number of each enclosing heading
+    let mut stack: Vec<(HeadingLevel, usize)> = Vec::new();
+
+    headings
+        .iter()
+        .map(|heading| {
+            if !heading.listed && !include_unlisted {
+                return None;
+            }
+
+            let level = heading.level.value();
+
+            // Close any deeper sections, remembering the number of the last
+            // closed at this depth, so the count continues from it.
+            let mut closed = None;
+            while let Some(&(top_level, number)) = stack.last() {
+                if top_level.value() <= level {
+                    break;
+                }
+
+                closed = Some(number);
+                stack.pop();
+            }
+
+            match stack.last_mut() {
+                Some((top_level, number)) if top_level.value() == level => *number += 1,
+                _ => stack.push((heading.level, closed.map_or(1, |number| number + 1))),
+            }
+
+            let parts: Vec<String> =
+                stack.iter().map(|(_, number)| number.to_string()).collect();
+
+            match parts.len() {
+                1 => Some(format!("{}.", parts[0])),
+                _ => Some(parts.join(".")),
+            }
+        })
+        .collect()
+}
+
+/// Counts the headings which come before the given element, in document order.
+///
+/// Returns `None` if the element is not within the elements.
+#[cfg(feature = "html")]
+pub(crate) fn headings_before(elements: &[Element], target: &Element) -> Option<usize> {
+    let mut count = 0;
+
+    if headings_before_internal(elements, target as *const _ as *const (), &mut count) {
+        Some(count)
+    } else {
+        None
+    }
+}
+
+#[cfg(feature = "html")]
+fn headings_before_internal(
+    elements: &[Element],
+    target: *const (),
+    count: &mut usize,
+) -> bool {
+    for element in elements {
+        if ptr::eq(element as *const _ as *const (), target) {
+            return true;
+        }
+
+        if let Element::Container(container) = element {
+            if container.ctype().heading_level().is_some() {
+                *count += 1;
+            }
+        }
+
+        let mut found = false;
+        for_each_child_list(element, &mut |children| {
+            if !found {
+                found = headings_before_internal(children, target, count);
+            }
+        });
+
+        if found {
+            return true;
+        }
+    }
+
+    false
+}
+
+impl<'t> SyntaxTree<'t> {
+    /// Gets every heading in the tree, in document order.
+    #[inline]
+    pub fn headings(&self) -> Vec<OutlineHeading<'_, 't>> {
+        collect_headings(&self.elements)
+    }
+}
+
+#[test]
+fn outline() {
+    use super::Container;
+
+    let heading = |level, text| {
+        Element::Container(Container::new(
+            ContainerType::Header(level),
+            vec![Element::Text(cow!(text))],
+        ))
+    };
+
+    let elements = vec![
+        heading(HeadingLevel::One, "Apple"),
+        Element::Collapsible {
+            elements: vec![heading(HeadingLevel::Two, "Banana")],
+            id: None,
+            class: None,
+            style: None,
+            start_open: false,
+            show_text: None,
+            hide_text: None,
+            show_top: true,
+            show_bottom: false,
+        },
+        Element::Container(Container::new(
+            ContainerType::UnlistedHeader(HeadingLevel::Three),
+            vec![Element::Text(cow!("Cherry"))],
+        )),
+    ];
+
+    let headings: Vec<_> = collect_headings(&elements)
+        .into_iter()
+        .map(|heading| (heading.level, heading.listed))
+        .collect();
+
+    assert_eq!(
+        headings,
+        vec![
+            (HeadingLevel::One, true),
+            (HeadingLevel::Two, true),
+            (HeadingLevel::Three, false),
+        ],
+        "Headings don't match expected",
+    );
+}
+
+#[test]
+fn numbering() {
+    let heading = |level, listed| OutlineHeading {
+        level,
+        listed,
+        elements: &[],
+    };
+
+    let headings = [
+        heading(HeadingLevel::One, true),
+        heading(HeadingLevel::Two, true),
+        heading(HeadingLevel::Two, false),
+        heading(HeadingLevel::Two, true),
+        heading(HeadingLevel::One, true),
+        heading(HeadingLevel::Four, true),
+        heading(HeadingLevel::Three, true),
+    ];
+
+    macro_rules! check {
+        ($include_unlisted:expr, $expected:expr $(,)?) => {{
+            let numbers = number_headings(&headings, $include_unlisted);
+            let expected: Vec<Option<String>> = $expected
+                .iter()
+                .map(|number| number.map(str::to_string))
+                .collect();
+
+            assert_eq!(numbers, expected, "Heading numbers don't match expected");
+        }};
+    }
+
+    check!(
+        true,
+        [
+            Some("1."),
+            Some("1.1"),
+            Some("1.2"),
+            Some("1.3"),
+            Some("2."),
+            Some("2.1"),
+            Some("2.2"),
+        ],
+    );
+
+    check!(
+        false,
+        [
+            Some("1."),
+            Some("1.1"),
+            None,
+            Some("1.2"),
+            Some("2."),
+            Some("2.1"),
+            Some("2.2"),
+        ],
+    );
+}
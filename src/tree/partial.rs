@@ -0,0 +1,74 @@
+/*
+ * tree/partial.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Representation of elements which are only valid within a particular parent.
+//!
+//! For instance, a `[[tab]]` has no meaning outside of a `[[tabview]]`,
+//! nor a `[[cell]]` outside of a `[[row]]`.
+//! These are produced during parsing, then collected by their parent
+//! into the final element. They should never appear in a finished syntax tree.
+
+use super::{Tab, TableCell, TableRow};
+use strum_macros::IntoStaticStr;
+
+#[derive(Serialize, Deserialize, IntoStaticStr, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "partial", content = "data")]
+pub enum PartialElement<'t> {
+    /// A tab, which is collected by `Element::TabView`.
+    Tab(Tab<'t>),
+
+    /// A table row, which is collected by `Element::Table`.
+    TableRow(TableRow<'t>),
+
+    /// A table cell, which is collected by a `TableRow`.
+    TableCell(TableCell<'t>),
+}
+
+impl PartialElement<'_> {
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.into()
+    }
+
+    pub fn to_owned(&self) -> PartialElement<'static> {
+        match self {
+            PartialElement::Tab(tab) => PartialElement::Tab(tab.to_owned()),
+            PartialElement::TableRow(row) => PartialElement::TableRow(row.to_owned()),
+            PartialElement::TableCell(cell) => PartialElement::TableCell(cell.to_owned()),
+        }
+    }
+}
+
+/// Which kind of partial element, if any, the parser currently accepts.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AcceptsPartial {
+    None,
+    Tab,
+    TableRow,
+    TableCell,
+}
+
+impl Default for AcceptsPartial {
+    #[inline]
+    fn default() -> Self {
+        AcceptsPartial::None
+    }
+}
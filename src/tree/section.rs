@@ -0,0 +1,198 @@
+/*
+ * tree/section.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Sections of a page, for editing one part of it at a time.
+//!
+//! A section begins at a heading and runs until the next heading of the same
+//! level or above, so it includes the sections of any headings below it.
+//! Only headings directly on the page begin sections. Those within blocks,
+//! such as a collapsible, don't, since editing them alone would split the block.
+
+use super::{Element, SyntaxTree};
+use crate::enums::HeadingLevel;
+use crate::span::Span;
+
+/// A section of the page, found with `SyntaxTree::section()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section<'a, 't> {
+    /// The level of the heading which begins this section.
+    pub level: HeadingLevel,
+
+    /// The span of this section within the preprocessed text which was parsed,
+    /// including its heading and all of its subsections.
+    pub span: Span,
+
+    /// The elements in this section, starting with its heading.
+    pub elements: Vec<&'a Element<'t>>,
+}
+
+impl Section<'_, '_> {
+    /// Gets the source text of this section.
+    ///
+    /// # Panics
+    /// If `text` is not the preprocessed text this tree was parsed from,
+    /// the span may be out of bounds.
+    #[inline]
+    pub fn source<'s>(&self, text: &'s str) -> &'s str {
+        self.span.slice(text)
+    }
+
+    /// Produces a copy of the text with this section replaced.
+    ///
+    /// If the section is followed by another, a newline is added to the end
+    /// of the replacement if needed, so that the next heading remains on
+    /// its own line.
+    pub fn replace(&self, text: &str, replacement: &str) -> String {
+        let before = &text[..self.span.start];
+        let after = &text[self.span.end..];
+
+        let mut output =
+            String::with_capacity(before.len() + replacement.len() + after.len() + 1);
+        output.push_str(before);
+        output.push_str(replacement);
+
+        if !after.is_empty() && !replacement.is_empty() && !replacement.ends_with('\n') {
+            output.push('\n');
+        }
+
+        output.push_str(after);
+        output
+    }
+}
+
+/// Determines the span of each section from the headings which begin them.
+///
+/// The headings are those directly on the page, in order, and `end`
+/// is the length of the parsed text.
+pub(crate) fn section_spans(headings: &[(Span, HeadingLevel)], end: usize) -> Vec<Span> {
+    headings
+        .iter()
+        .enumerate()
+        .map(|(i, &(span, level))| {
+            let next = headings[i + 1..]
+                .iter()
+                .find(|(_, next_level)| next_level.value() <= level.value())
+                .map_or(end, |(next_span, _)| next_span.start);
+
+            Span::new(span.start, next)
+        })
+        .collect()
+}
+
+fn heading_level(element: &Element) -> Option<HeadingLevel> {
+    match element {
+        Element::Container(container) => container.ctype().heading_level(),
+        _ => None,
+    }
+}
+
+impl<'t> SyntaxTree<'t> {
+    /// Gets a section of the page by its heading path.
+    ///
+    /// Each index in the path selects a section among those within the
+    /// previous one. For instance, `[1]` is the second top-level section,
+    /// and `[1, 0]` is the first section within it. Which level a heading
+    /// has doesn't matter, only how the headings nest.
+    ///
+    /// Returns `None` if there is no such section, or if the tree was not
+    /// produced by parsing, as only then are the sections' spans known.
+    pub fn section(&self, path: &[usize]) -> Option<Section<'_, 't>> {
+        let elements = &self.elements;
+
+        // Each heading's position in the elements, and the section containing it
+        let mut headings = Vec::new();
+        let mut stack: Vec<(usize, HeadingLevel)> = Vec::new();
+
+        for (position, element) in elements.iter().enumerate() {
+            if let Some(level) = heading_level(element) {
+                while let Some(&(_, top_level)) = stack.last() {
+                    if top_level.value() < level.value() {
+                        break;
+                    }
+
+                    stack.pop();
+                }
+
+                let parent = stack.last().map(|&(index, _)| index);
+                stack.push((headings.len(), level));
+                headings.push((position, level, parent));
+            }
+        }
+
+        // Follow the path down through the nested sections
+        let mut current = None;
+
+        for &step in path {
+            let (index, _) = headings
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, _, parent))| *parent == current)
+                .nth(step)?;
+
+            current = Some(index);
+        }
+
+        let index = current?;
+        let span = *self.sections.get(index)?;
+        let (start, level, _) = headings[index];
+
+        // The section ends at the next heading which isn't within it
+        let end = headings[index + 1..]
+            .iter()
+            .find(|(_, next_level, _)| next_level.value() <= level.value())
+            .map_or(elements.len(), |&(position, _, _)| position);
+
+        Some(Section {
+            level,
+            span,
+            elements: elements[start..end].iter().collect(),
+        })
+    }
+}
+
+#[test]
+fn section() {
+    let log = crate::build_logger();
+    let text = "Intro\n\n+ Apple\nA\n++ Banana\nB\n++ Cherry\nC\n+ Durian\nD";
+    let tokenization = crate::tokenize(&log, text);
+    let (tree, _) = crate::parse(&log, &tokenization).into();
+
+    let section = tree.section(&[0, 1]).expect("No section found");
+    assert_eq!(section.level, HeadingLevel::Two);
+    assert_eq!(section.source(text), "++ Cherry\nC\n");
+    assert_eq!(
+        section.replace(text, "++ Cherry\nE"),
+        "Intro\n\n+ Apple\nA\n++ Banana\nB\n++ Cherry\nE\n+ Durian\nD",
+    );
+
+    let section = tree.section(&[0]).expect("No section found");
+    assert_eq!(
+        section.source(text),
+        "+ Apple\nA\n++ Banana\nB\n++ Cherry\nC\n"
+    );
+    assert_eq!(section.elements.len(), 6);
+
+    let section = tree.section(&[1]).expect("No section found");
+    assert_eq!(section.source(text), "+ Durian\nD");
+
+    assert!(tree.section(&[]).is_none());
+    assert!(tree.section(&[2]).is_none());
+    assert!(tree.section(&[1, 0]).is_none());
+}
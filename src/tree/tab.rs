@@ -0,0 +1,92 @@
+/*
+ * tree/tab.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::clone::{elements_to_owned, option_string_to_owned};
+use super::Element;
+use ref_map::*;
+use std::borrow::Cow;
+
+/// A single tab within a tab view.
+///
+/// The label is parsed as wikitext, so it can contain formatting.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Tab<'t> {
+    label: Vec<Element<'t>>,
+    elements: Vec<Element<'t>>,
+    id: Option<Cow<'t, str>>,
+    class: Option<Cow<'t, str>>,
+}
+
+impl<'t> Tab<'t> {
+    #[inline]
+    pub fn new(
+        label: Vec<Element<'t>>,
+        elements: Vec<Element<'t>>,
+        id: Option<Cow<'t, str>>,
+        class: Option<Cow<'t, str>>,
+    ) -> Self {
+        Tab {
+            label,
+            elements,
+            id,
+            class,
+        }
+    }
+
+    #[inline]
+    pub fn label(&self) -> &[Element<'t>] {
+        &self.label
+    }
+
+    #[inline]
+    pub fn elements(&self) -> &[Element<'t>] {
+        &self.elements
+    }
+
+    #[inline]
+    pub(crate) fn label_mut(&mut self) -> &mut Vec<Element<'t>> {
+        &mut self.label
+    }
+
+    #[inline]
+    pub(crate) fn elements_mut(&mut self) -> &mut Vec<Element<'t>> {
+        &mut self.elements
+    }
+
+    #[inline]
+    pub fn id(&self) -> Option<&str> {
+        self.id.ref_map(|s| s.as_ref())
+    }
+
+    #[inline]
+    pub fn class(&self) -> Option<&str> {
+        self.class.ref_map(|s| s.as_ref())
+    }
+
+    pub fn to_owned(&self) -> Tab<'static> {
+        Tab {
+            label: elements_to_owned(&self.label),
+            elements: elements_to_owned(&self.elements),
+            id: option_string_to_owned(&self.id),
+            class: option_string_to_owned(&self.class),
+        }
+    }
+}
@@ -0,0 +1,288 @@
+/*
+ * tree/table.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Representation of tables, such as those produced by `||` syntax.
+//!
+//! Tables, rows, and cells may also have an id, class, and style,
+//! which are given with the `[[table]]`, `[[row]]`, and `[[cell]]` blocks.
+
+use super::clone::{elements_to_owned, option_string_to_owned};
+use super::Element;
+use crate::data::Alignment;
+use std::borrow::Cow;
+use std::num::NonZeroU32;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Table<'t> {
+    rows: Vec<TableRow<'t>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<Cow<'t, str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    class: Option<Cow<'t, str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    style: Option<Cow<'t, str>>,
+}
+
+impl<'t> Table<'t> {
+    #[inline]
+    pub fn new(rows: Vec<TableRow<'t>>) -> Self {
+        Table {
+            rows,
+            id: None,
+            class: None,
+            style: None,
+        }
+    }
+
+    /// Sets the HTML attributes given in this table's block.
+    #[inline]
+    pub fn with_attributes(
+        mut self,
+        id: Option<Cow<'t, str>>,
+        class: Option<Cow<'t, str>>,
+        style: Option<Cow<'t, str>>,
+    ) -> Self {
+        self.id = id;
+        self.class = class;
+        self.style = style;
+        self
+    }
+
+    #[inline]
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    #[inline]
+    pub fn class(&self) -> Option<&str> {
+        self.class.as_deref()
+    }
+
+    #[inline]
+    pub fn style(&self) -> Option<&str> {
+        self.style.as_deref()
+    }
+
+    #[inline]
+    pub fn rows(&self) -> &[TableRow<'t>] {
+        &self.rows
+    }
+
+    #[inline]
+    pub(crate) fn rows_mut(&mut self) -> &mut [TableRow<'t>] {
+        &mut self.rows
+    }
+
+    pub fn to_owned(&self) -> Table<'static> {
+        Table {
+            rows: self.rows.iter().map(TableRow::to_owned).collect(),
+            id: option_string_to_owned(&self.id),
+            class: option_string_to_owned(&self.class),
+            style: option_string_to_owned(&self.style),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TableRow<'t> {
+    cells: Vec<TableCell<'t>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<Cow<'t, str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    class: Option<Cow<'t, str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    style: Option<Cow<'t, str>>,
+}
+
+impl<'t> TableRow<'t> {
+    #[inline]
+    pub fn new(cells: Vec<TableCell<'t>>) -> Self {
+        TableRow {
+            cells,
+            id: None,
+            class: None,
+            style: None,
+        }
+    }
+
+    /// Sets the HTML attributes given in this row's block.
+    #[inline]
+    pub fn with_attributes(
+        mut self,
+        id: Option<Cow<'t, str>>,
+        class: Option<Cow<'t, str>>,
+        style: Option<Cow<'t, str>>,
+    ) -> Self {
+        self.id = id;
+        self.class = class;
+        self.style = style;
+        self
+    }
+
+    #[inline]
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    #[inline]
+    pub fn class(&self) -> Option<&str> {
+        self.class.as_deref()
+    }
+
+    #[inline]
+    pub fn style(&self) -> Option<&str> {
+        self.style.as_deref()
+    }
+
+    #[inline]
+    pub fn cells(&self) -> &[TableCell<'t>] {
+        &self.cells
+    }
+
+    #[inline]
+    pub(crate) fn cells_mut(&mut self) -> &mut [TableCell<'t>] {
+        &mut self.cells
+    }
+
+    pub fn to_owned(&self) -> TableRow<'static> {
+        TableRow {
+            cells: self.cells.iter().map(TableCell::to_owned).collect(),
+            id: option_string_to_owned(&self.id),
+            class: option_string_to_owned(&self.class),
+            style: option_string_to_owned(&self.style),
+        }
+    }
+}
+
+/// A single cell within a table row.
+///
+/// The "header" field denotes a title cell (`||~`), and "column-span"
+/// is the number of columns this cell spans (e.g. `||||` is two).
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TableCell<'t> {
+    header: bool,
+    column_span: NonZeroU32,
+    align: Option<Alignment>,
+    elements: Vec<Element<'t>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<Cow<'t, str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    class: Option<Cow<'t, str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    style: Option<Cow<'t, str>>,
+}
+
+impl<'t> TableCell<'t> {
+    #[inline]
+    pub fn new(
+        header: bool,
+        column_span: NonZeroU32,
+        align: Option<Alignment>,
+        elements: Vec<Element<'t>>,
+    ) -> Self {
+        TableCell {
+            header,
+            column_span,
+            align,
+            elements,
+            id: None,
+            class: None,
+            style: None,
+        }
+    }
+
+    /// Sets the HTML attributes given in this cell's block.
+    #[inline]
+    pub fn with_attributes(
+        mut self,
+        id: Option<Cow<'t, str>>,
+        class: Option<Cow<'t, str>>,
+        style: Option<Cow<'t, str>>,
+    ) -> Self {
+        self.id = id;
+        self.class = class;
+        self.style = style;
+        self
+    }
+
+    #[inline]
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    #[inline]
+    pub fn class(&self) -> Option<&str> {
+        self.class.as_deref()
+    }
+
+    #[inline]
+    pub fn style(&self) -> Option<&str> {
+        self.style.as_deref()
+    }
+
+    #[inline]
+    pub fn header(&self) -> bool {
+        self.header
+    }
+
+    #[inline]
+    pub fn column_span(&self) -> NonZeroU32 {
+        self.column_span
+    }
+
+    #[inline]
+    pub fn align(&self) -> Option<Alignment> {
+        self.align
+    }
+
+    #[inline]
+    pub fn elements(&self) -> &[Element<'t>] {
+        &self.elements
+    }
+
+    #[inline]
+    pub(crate) fn elements_mut(&mut self) -> &mut Vec<Element<'t>> {
+        &mut self.elements
+    }
+
+    pub fn to_owned(&self) -> TableCell<'static> {
+        TableCell {
+            header: self.header,
+            column_span: self.column_span,
+            align: self.align,
+            elements: elements_to_owned(&self.elements),
+            id: option_string_to_owned(&self.id),
+            class: option_string_to_owned(&self.class),
+            style: option_string_to_owned(&self.style),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+/*
+ * tree/visit.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2021 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Traversal of every element in a syntax tree, in document order.
+
+use super::Element;
+use crate::enums::LinkLabel;
+
+/// Calls `f` on each element, then on each of its descendants.
+pub fn walk_elements<'a, 't, F>(elements: &'a [Element<'t>], f: &mut F)
+where
+    F: FnMut(&'a Element<'t>),
+{
+    for element in elements {
+        f(element);
+        for_each_child_list(element, &mut |children| walk_elements(children, f));
+    }
+}
+
+/// Calls `f` on each element, then on each of its descendants.
+pub fn walk_elements_mut<'t, F>(elements: &mut [Element<'t>], f: &mut F)
+where
+    F: FnMut(&mut Element<'t>),
+{
+    for element in elements {
+        f(element);
+        for_each_child_list_mut(element, &mut |children| walk_elements_mut(children, f));
+    }
+}
+
+/// Calls `f` on each list of elements directly within this one.
+///
+/// The lists are visited in the same order as `for_each_child_list_mut()`.
+pub fn for_each_child_list<'a, 't, F>(element: &'a Element<'t>, f: &mut F)
+where
+    F: FnMut(&'a [Element<'t>]),
+{
+    match element {
+        Element::Container(container) => f(container.elements()),
+        Element::StyledContainer(container) => f(container.elements()),
+        Element::Include { elements, .. }
+        | Element::Anchor { elements, .. }
+        | Element::Collapsible { elements, .. }
+        | Element::Abbreviation { elements, .. }
+        | Element::Footnote { elements }
+        | Element::NoTypography { elements }
+        | Element::Poem { elements }
+        | Element::Color { elements, .. } => f(elements),
+        Element::BlockQuote {
+            elements, source, ..
+        } => {
+            f(elements);
+
+            if let Some(source) = source {
+                f(source);
+            }
+        }
+        Element::Image(image) => {
+            if let Some(caption) = &image.caption {
+                f(caption);
+            }
+        }
+        Element::Link {
+            label: LinkLabel::Elements(elements),
+            ..
+        } => f(elements),
+        Element::TabView(tabs) => {
+            for tab in tabs {
+                f(tab.label());
+                f(tab.elements());
+            }
+        }
+        Element::Table(table) => {
+            for row in table.rows() {
+                for cell in row.cells() {
+                    f(cell.elements());
+                }
+            }
+        }
+        Element::List(list) => {
+            for item in list.items() {
+                f(item.elements());
+            }
+        }
+        Element::DefinitionList(items) => {
+            for item in items {
+                f(item.key());
+                f(item.value());
+            }
+        }
+        Element::Bibliography { entries, .. } => {
+            for entry in entries {
+                f(entry.elements());
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Calls `f` on each list of elements directly within this one.
+pub fn for_each_child_list_mut<'t, F>(element: &mut Element<'t>, f: &mut F)
+where
+    F: FnMut(&mut Vec<Element<'t>>),
+{
+    match element {
+        Element::Container(container) => f(container.elements_mut()),
+        Element::StyledContainer(container) => f(container.elements_mut()),
+        Element::Include { elements, .. }
+        | Element::Anchor { elements, .. }
+        | Element::Collapsible { elements, .. }
+        | Element::Abbreviation { elements, .. }
+        | Element::Footnote { elements }
+        | Element::NoTypography { elements }
+        | Element::Poem { elements }
+        | Element::Color { elements, .. } => f(elements),
+        Element::BlockQuote {
+            elements, source, ..
+        } => {
+            f(elements);
+
+            if let Some(source) = source {
+                f(source);
+            }
+        }
+        Element::Image(image) => {
+            if let Some(caption) = &mut image.caption {
+                f(caption);
+            }
+        }
+        Element::Link {
+            label: LinkLabel::Elements(elements),
+            ..
+        } => f(elements),
+        Element::TabView(tabs) => {
+            for tab in tabs {
+                f(tab.label_mut());
+                f(tab.elements_mut());
+            }
+        }
+        Element::Table(table) => {
+            for row in table.rows_mut() {
+                for cell in row.cells_mut() {
+                    f(cell.elements_mut());
+                }
+            }
+        }
+        Element::List(list) => {
+            for item in list.items_mut() {
+                f(item.elements_mut());
+            }
+        }
+        Element::DefinitionList(items) => {
+            for item in items {
+                f(item.key_mut());
+                f(item.value_mut());
+            }
+        }
+        Element::Bibliography { entries, .. } => {
+            for entry in entries {
+                f(entry.elements_mut());
+            }
+        }
+        _ => (),
+    }
+}